@@ -1,7 +1,7 @@
 use engine::config::input_config::{InputConfig, KeyHandler};
 use engine::config::move_config::MoveConfig;
-use engine::config::renderer_config::RendererConfig;
-use engine::config::window_config::{WindowConfig, WindowDimensions};
+use engine::config::renderer_config::{CaptureConfig, RendererConfig};
+use engine::config::window_config::{WindowBackend, WindowConfig, WindowDimensions};
 use engine::config::EngineConfig;
 use engine::graphics::subsystem::{GraphicsSubSystem, OpenGLPipeline};
 use std::sync::Arc;
@@ -19,6 +19,7 @@ pub fn create_engine_config<T>(core: Arc<T>) -> EngineConfig
             },
             title: Some(String::from("Demo1 - MsWin/OpenGL")),
             window_id: Some(String::from("PAINSAW-DEMO1")),
+            backend: WindowBackend::Native,
         },
         RendererConfig {
             graphics: GraphicsSubSystem::OpenGL {
@@ -28,6 +29,7 @@ pub fn create_engine_config<T>(core: Arc<T>) -> EngineConfig
             show_cam_coords: true,
             show_screen_stats: true,
             fps_cap: Some(60),
+            capture: Some(CaptureConfig::default()),
         },
         InputConfig {
             key_handler: core.clone(),
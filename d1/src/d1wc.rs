@@ -55,3 +55,46 @@ impl WorldController for Demo1 {
         context.frame_count += 1;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::d1::Demo1;
+    use crate::d1ec::create_engine_config;
+    use engine::config::window_config::{WindowBackend, WindowDimensions};
+    use engine::graphics::subsystem::GraphicsSubSystem;
+    use engine::support::image::RawImage;
+    use engine::window::api::cw::create_window;
+    use engine::PainsawContext;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_headless_software_frame() {
+        let core = Arc::new(Demo1::new());
+        let frame: Arc<Mutex<Option<RawImage>>> = Arc::new(Mutex::new(None));
+        let slot = frame.clone();
+
+        let mut cfg = create_engine_config(core.clone());
+        cfg.window.dimensions = WindowDimensions::Dimensional { width: 320, height: 180 };
+        cfg.window.backend = WindowBackend::Headless {
+            frames: Some(10),
+            until: Some(Arc::new(move |context: &PainsawContext| {
+                context.frame_count >= 1 && { *slot.lock().unwrap() = context.capture_frame(); true }
+            })),
+        };
+        cfg.renderer.graphics = GraphicsSubSystem::Software;
+
+        create_window(&cfg).unwrap().begin_event_handling(core, cfg).unwrap();
+        let image = frame.lock().unwrap().take().unwrap();
+        let pixel = |x: u32, y: u32| {
+            let offset = ((y * image.width + x) * 4) as usize;
+            (image.data[offset], image.data[offset + 1], image.data[offset + 2])
+        };
+
+        /* 3d axes, drawn from the default camera position */
+        assert_eq!((320, 180), (image.width, image.height));
+        assert_eq!((255, 255, 255), pixel(160, 90));
+        assert_eq!((255, 0, 0), pixel(200, 90));
+        assert_eq!((0, 255, 0), pixel(160, 50));
+        assert_eq!((0, 0, 0), pixel(100, 160));
+    }
+}
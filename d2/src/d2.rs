@@ -1,9 +1,14 @@
+use crate::d2m2d::ImageHandles;
+use std::sync::Mutex;
+
 pub struct Demo2 {
-    // todo: add fields specific to demo2
+    pub(crate) images: Mutex<Option<ImageHandles>>,                                             // keeps the images' assets alive while they're shown
 }
 
 impl Demo2 {
     pub(crate) fn new() -> Self {
-        Self {}
+        Self {
+            images: Mutex::new(None),
+        }
     }
 }
@@ -1,8 +1,8 @@
 use engine::config::input_config::mc::MouseHandler;
 use engine::config::input_config::{InputConfig, KeyHandler};
 use engine::config::move_config::MoveConfig;
-use engine::config::renderer_config::RendererConfig;
-use engine::config::window_config::{WindowConfig, WindowDimensions};
+use engine::config::renderer_config::{CaptureConfig, RendererConfig};
+use engine::config::window_config::{WindowBackend, WindowConfig, WindowDimensions};
 use engine::config::EngineConfig;
 use engine::graphics::subsystem::{GraphicsSubSystem, OpenGLPipeline};
 use std::sync::Arc;
@@ -19,6 +19,7 @@ where
             },
             title: Some(String::from("Demo2 - MsWin/OpenGL")),
             window_id: Some(String::from("PAINSAW-DEMO2")),
+            backend: WindowBackend::Native,
         },
         RendererConfig {
             graphics: GraphicsSubSystem::OpenGL {
@@ -28,6 +29,7 @@ where
             show_cam_coords: false,
             show_screen_stats: false,
             fps_cap: Some(240),
+            capture: Some(CaptureConfig::default()),
         },
         InputConfig {
             key_handler: core.clone(),
@@ -3,44 +3,87 @@ use engine::geometry::primitive::v2d::Vertex2D;
 use engine::geometry::primitive::PrimitiveType;
 use engine::graphics::color::Color;
 use engine::graphics::storage::m2d::{Model2D, Model2DBuilder};
-use engine::graphics::texture::t2d::Texture2D;
-use engine::support::image::bitmap::Bitmap;
-use engine::support::image::Image;
-use engine::support::image::targa::Targa;
-
-const B24_BMP: &[u8] = include_bytes!("../assets/24b.bmp");
-const TGA_32B_B2T_L2R: &[u8] = include_bytes!("../assets/tga/32b_b2t_l2r_footer.tga");
-
-pub(super) fn create_2d_bmp_24b() -> Model2D {
-    let bmp_24b_x_zero = 15.0;
-    let bmp_24b_y_zero = 50.0;
-    let bmp_24b = Bitmap::load_from_bytes(B24_BMP).expect("failed to load ../assets/24b.bmp");
-    let bmp_24b_width = bmp_24b.width as f32;
-    let bmp_24b_height = bmp_24b.height as f32;
-
-    Model2DBuilder::new()
-        .with_texture(Texture2D::new(bmp_24b, bmp_24b_x_zero, bmp_24b_y_zero, 1.0))
-        .with_primitive(Primitive2DBuilder::new()
-            .with_type(PrimitiveType::Point { point_size: 12.0 })
-            .with_color(Color::BLUE)
-            .with_vertex(Vertex2D::new(bmp_24b_x_zero + bmp_24b_width, bmp_24b_y_zero + bmp_24b_height))
-            .build())
-        .build()
+use engine::graphics::texture::atlas::{TextureAtlas, TextureAtlasBuilder};
+use engine::support::asset::handle::Handle;
+use engine::support::asset::manager::AssetManager;
+use engine::support::image::RawImage;
+use engine::support::resource::restype::memory::MemoryResource;
+
+///
+/// an image asset to display; the format is detected from the contents.
+///
+pub(super) struct ImageAsset {
+    pub name: &'static str,
+    pub path: &'static str,
+    pub bytes: &'static [u8],
+    pub x: f32,
+    pub y: f32,
+    pub marker: Color,
+}
+
+pub(super) static IMAGE_ASSETS: [ImageAsset; 4] = [
+    ImageAsset { name: "bmp-24b", path: "../assets/24b.bmp", bytes: include_bytes!("../assets/24b.bmp"), x: 15.0, y: 50.0, marker: Color::BLUE },
+    ImageAsset { name: "tga-32b-b2t-l2r", path: "../assets/tga/32b_b2t_l2r_footer.tga", bytes: include_bytes!("../assets/tga/32b_b2t_l2r_footer.tga"), x: 200.0, y: 50.0, marker: Color::RED },
+    ImageAsset { name: "png-rgba-8b", path: "../assets/png/rgba_8b.png", bytes: include_bytes!("../assets/png/rgba_8b.png"), x: 400.0, y: 50.0, marker: Color::GREEN },
+    ImageAsset { name: "tiff-control", path: "../assets/control.tif", bytes: include_bytes!("../assets/control.tif"), x: 15.0, y: 200.0, marker: Color::WHITE },
+];
+
+static ATLAS_KEY: &str = "d2/images-atlas";
+
+///
+/// the assets behind the images model; holding them keeps them loaded, and shared, while it's shown.
+///
+pub(super) struct ImageHandles {
+    pub images: Vec<Handle<RawImage>>,
+    pub atlas: Handle<TextureAtlas>,
 }
 
-pub(super) fn create_2d_tga_32b_b2t_l2r() -> Model2D {
-    let tga_x_zero = 200.0;
-    let tga_y_zero = 50.0;
-    let tga = Targa::load_from_bytes(TGA_32B_B2T_L2R).expect("failed to load ../assets/tga/32b_b2t_l2r_footer.tga");
-    let tga_width = tga.width as f32;
-    let tga_height = tga.height as f32;
-
-    Model2DBuilder::new()
-        .with_texture(Texture2D::new(tga, tga_x_zero, tga_y_zero, 1.0))
-        .with_primitive(Primitive2DBuilder::new()
-            .with_type(PrimitiveType::Point { point_size: 12.0 })
-            .with_color(Color::RED)
-            .with_vertex(Vertex2D::new(tga_x_zero + tga_width, tga_y_zero + tga_height))
-            .build())
-        .build()
+///
+/// every image packed into one atlas, drawn as sprites; each with a point marking its far corner.
+///
+/// images are loaded through the asset manager, keyed by path, so each is only decoded once; the
+/// atlas is an asset too, so it's only packed once.
+///
+pub(super) fn create_2d_images(assets: &[ImageAsset], manager: &mut AssetManager) -> (Model2D, ImageHandles) {
+    /* load */
+    let images: Vec<Handle<RawImage>> = assets.iter()
+        .map(|asset| manager.load::<RawImage>(asset.path, &MemoryResource::from_static(asset.bytes).with_path(asset.path))
+            .unwrap_or_else(|e| panic!("failed to load {}: {}", asset.path, e)))
+        .collect();
+
+    /* pack */
+    let handle = match manager.get::<TextureAtlas>(ATLAS_KEY) {
+        Ok(Some(handle)) => handle,
+        _ => {
+            let atlas = assets.iter().zip(&images)
+                .fold(TextureAtlasBuilder::new(), |builder, (asset, image)| builder.with_image(asset.name, image.get().as_ref().clone()))
+                .build()
+                .unwrap_or_else(|e| panic!("failed to pack images: {}", e));
+            manager.insert(ATLAS_KEY, atlas)
+        }
+    };
+    let atlas = handle.get();
+
+    /* one texture per page, with a sprite per image on that page */
+    let mut builder = Model2DBuilder::new();
+    for page in 0..atlas.pages.len() {
+        let sprites = assets.iter()
+            .filter_map(|asset| atlas.region(asset.name).map(|region| (asset, region)))
+            .filter(|(_, region)| region.page == page)
+            .map(|(asset, region)| region.sprite(asset.x, asset.y, 1.0))
+            .collect();
+        builder = builder.with_texture(atlas.texture(page, sprites));
+    }
+
+    /* markers */
+    for asset in assets {
+        if let Some(region) = atlas.region(asset.name) {
+            builder = builder.with_primitive(Primitive2DBuilder::new()
+                .with_type(PrimitiveType::Point { point_size: 12.0 })
+                .with_color(asset.marker)
+                .with_vertex(Vertex2D::new(asset.x + region.width as f32, asset.y + region.height as f32))
+                .build());
+        }
+    }
+    (builder.build(), ImageHandles { images, atlas: handle })
 }
@@ -1,15 +1,22 @@
-use crate::d2m2d::{create_2d_bmp_24b, create_2d_tga_32b_b2t_l2r};
+use crate::d2m2d::{create_2d_images, IMAGE_ASSETS};
+use engine::support::logger::log;
+use engine::support::logger::log_level::LogLevel;
 use engine::PainsawContext;
 use engine::WorldController;
 use crate::d2::Demo2;
 
-static M2D_BMP_24B: &str = "bmp-24b";
-static M2D_TGA_32B_B2T_L2R: &str = "tga-32b-b2t-l2r";
-
 impl WorldController for Demo2 {
     fn initialize_world_helper(&self, context: &mut PainsawContext) {
-        context.g2d.attach(M2D_BMP_24B, create_2d_bmp_24b());
-        context.g2d.attach(M2D_TGA_32B_B2T_L2R, create_2d_tga_32b_b2t_l2r());
+        let (images, handles) = create_2d_images(&IMAGE_ASSETS, &mut context.assets);
+        context.g2d.attach("images", images);
+
+        /* the demo holds a handle to every image and the atlas, so none of them is released */
+        context.assets.release_unused();
+        let keys = handles.images.iter().map(|handle| handle.key()).chain([handles.atlas.key()]);
+        for key in keys {
+            log(LogLevel::Debug, &|| format!("asset [{}]: {} reference(s)", key, context.assets.references(key)));
+        }
+        *self.images.lock().unwrap() = Some(handles);
     }
 
     fn update_world_helper(&self, context: &mut PainsawContext) {
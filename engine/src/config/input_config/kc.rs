@@ -18,6 +18,7 @@ pub(crate) fn handle_key_change(handler: Arc<dyn KeyHandler>, name: &KeyInputNam
         KeyInputName::KeyM => handler.handle_m_key_change(name, state, camera, config, timing),
         KeyInputName::KeyS => handler.handle_s_key_change(name, state, camera, config, timing),
         KeyInputName::KeyW => handler.handle_w_key_change(name, state, camera, config, timing),
+        KeyInputName::KeyF12 => handler.handle_f12_key_change(name, state, camera, config, timing),
     }
 }
 
@@ -39,6 +40,7 @@ pub trait KeyHandler {
     fn handle_m_key_change(&self, _name: &KeyInputName, _state: &mut KeyState, _camera: &mut Camera, _config: &EngineConfig, _timing: &EngineTiming) {}
     fn handle_s_key_change(&self, _name: &KeyInputName, _state: &mut KeyState, _camera: &mut Camera, _config: &EngineConfig, _timing: &EngineTiming) {}
     fn handle_w_key_change(&self, _name: &KeyInputName, _state: &mut KeyState, _camera: &mut Camera, _config: &EngineConfig, _timing: &EngineTiming) {}
+    fn handle_f12_key_change(&self, _name: &KeyInputName, _state: &mut KeyState, _camera: &mut Camera, _config: &EngineConfig, _timing: &EngineTiming) {}
 }
 
 ///
@@ -1,4 +1,7 @@
 use crate::graphics::subsystem::{GraphicsSubSystem, OpenGLPipeline};
+use crate::input::keyboard::kin::KeyInputName;
+use crate::support::image::ImageFormat;
+use std::path::PathBuf;
 
 pub struct RendererConfig {
     pub graphics: GraphicsSubSystem,
@@ -6,6 +9,16 @@ pub struct RendererConfig {
     pub show_cam_coords: bool,
     pub show_screen_stats: bool,
     pub fps_cap: Option<u16>,
+    pub capture: Option<CaptureConfig>,
+}
+
+///
+/// key binding for frame capture; frames are written to the directory, one file per key press.
+///
+pub struct CaptureConfig {
+    pub key: KeyInputName,
+    pub directory: PathBuf,
+    pub format: ImageFormat,
 }
 
 impl RendererConfig {
@@ -18,6 +31,7 @@ impl RendererConfig {
             show_screen_stats: false,
 
             fps_cap: None,
+            capture: None,
         }
     }
 }
@@ -34,6 +48,35 @@ impl Default for RendererConfig {
             show_screen_stats: false,
 
             fps_cap: None,
+            capture: None,
+        }
+    }
+}
+
+impl CaptureConfig {
+    pub fn new<P: Into<PathBuf>>(key: KeyInputName, directory: P, format: ImageFormat) -> CaptureConfig {
+        CaptureConfig {
+            key,
+            directory: directory.into(),
+            format,
+        }
+    }
+
+    ///
+    /// build a unique file name for the given frame.
+    ///
+    pub fn next_path(&self, frame: u128) -> PathBuf {
+        let timestamp = chrono::Local::now().format("%Y%m%d-%H%M%S%3f");
+        self.directory.join(format!("frame-{}-{}.{}", timestamp, frame, self.format.extension()))
+    }
+}
+
+impl Default for CaptureConfig {
+    fn default() -> CaptureConfig {
+        CaptureConfig {
+            key: KeyInputName::KeyF12,
+            directory: PathBuf::from("captures"),
+            format: ImageFormat::Png,
         }
     }
 }
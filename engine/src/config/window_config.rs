@@ -1,7 +1,11 @@
+use crate::PainsawContext;
+use std::sync::Arc;
+
 pub struct WindowConfig {
     pub dimensions: WindowDimensions,
     pub title: Option<String>,
     pub window_id: Option<String>,
+    pub backend: WindowBackend,
 }
 
 pub enum WindowDimensions {
@@ -9,12 +13,32 @@ pub enum WindowDimensions {
     Dimensional { width: i32, height: i32 },
 }
 
+///
+/// condition that ends a headless run, checked before each frame.
+///
+pub type StopPredicate = Arc<dyn Fn(&PainsawContext) -> bool>;
+
+///
+/// which kind of window to create.
+///
+/// native windows are provided by the host operating system; headless windows have no
+/// os resources at all, and run the world loop until one of the stop conditions is met.
+///
+pub enum WindowBackend {
+    Native,
+    Headless {
+        frames: Option<u128>,
+        until: Option<StopPredicate>,
+    },
+}
+
 impl WindowConfig {
     pub fn new(dimensions: WindowDimensions, title: &str, wndclass: &str) -> WindowConfig {
         WindowConfig {
             dimensions,
             title: Some(title.to_owned()),
             window_id: Some(wndclass.to_owned()),
+            backend: WindowBackend::Native,
         }
     }
 }
@@ -25,6 +49,7 @@ impl Default for WindowConfig {
             title: Some("window title".to_owned()),
             dimensions: WindowDimensions::Dimensional { width: 800, height: 600 },
             window_id: Some("PAINSAW".to_owned()),
+            backend: WindowBackend::Native,
         }
     }
 }
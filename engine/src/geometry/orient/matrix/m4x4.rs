@@ -1,6 +1,6 @@
 pub(crate) mod invert;
 pub mod new;
-mod mult;
+pub(crate) mod mult;
 mod eq;
 
 use crate::geometry::primitive::v3d::Vertex3D;
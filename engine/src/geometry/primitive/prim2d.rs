@@ -1,6 +1,7 @@
 use crate::graphics::color::Color;
 use crate::geometry::primitive::v2d::Vertex2D;
 use crate::geometry::primitive::PrimitiveType;
+use crate::graphics::texture::atlas::UvRect;
 use crate::support::logger::log;
 use crate::support::logger::log_level::LogLevel;
 
@@ -8,12 +9,25 @@ pub struct Primitive2D {
     pub p_type: PrimitiveType,
     pub vertices: Vec<Vertex2D>,
     pub color: Color,
+    pub texture: Option<PrimitiveTexture>,                                                      // quads only; the color tints it
+}
+
+///
+/// the part of one of the model's textures (usually an atlas page) that each quad shows.
+///
+/// a quad's vertices take the rectangle's corners in the order top-left, bottom-left, bottom-right, top-right.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PrimitiveTexture {
+    pub index: usize,                                                                           // into the model's textures
+    pub uv: UvRect,
 }
 
 pub struct Primitive2DBuilder {
     the_p_type: Option<PrimitiveType>,
     the_vertices: Vec<Vertex2D>,
     the_color: Option<Color>,
+    the_texture: Option<PrimitiveTexture>,
 }
 
 impl Primitive2D {
@@ -40,6 +54,7 @@ impl Primitive2D {
             p_type,
             vertices,
             color,
+            texture: None,
         }
     }
 }
@@ -50,6 +65,7 @@ impl Primitive2DBuilder {
             the_p_type: None,
             the_vertices: vec!(),
             the_color: None,
+            the_texture: None,
         }
     }
 
@@ -73,11 +89,17 @@ impl Primitive2DBuilder {
         self
     }
 
+    pub fn with_texture(mut self, texture: PrimitiveTexture) -> Self {
+        self.the_texture = Some(texture);
+        self
+    }
+
     pub fn build(self) -> Primitive2D {
         Primitive2D {
             p_type: self.the_p_type.unwrap_or_else(|| PrimitiveType::Point {point_size: 1.0}),
             vertices: self.the_vertices,
             color: self.the_color.unwrap_or_else(|| Color::WHITE),
+            texture: self.the_texture,
         }
     }
 }
@@ -1,3 +1,4 @@
+#[cfg(target_os="windows")]
 use windows::Win32::Foundation::RECT;
 use crate::geometry::primitive::v2d::Vertex2D;
 
@@ -16,6 +16,7 @@ use crate::input::mouse::min::MouseInputName;
 use crate::input::screen::ScreenState;
 use crate::input::UserInput;
 use crate::support::stats::screen::show_screen_stats;
+use crate::support::image::RawImage;
 
 pub mod camera;
 pub mod color;
@@ -95,4 +96,8 @@ impl GraphicsIntermediary {
     pub(crate) fn after_3d(&self, context: &PainsawContext) {
         self.subsystem.after_3d(context);
     }
+
+    pub(crate) fn capture(&self, camera: &Camera) -> Option<RawImage> {
+        self.subsystem.capture(camera)
+    }
 }
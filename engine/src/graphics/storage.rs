@@ -1,4 +1,5 @@
 pub mod g2d;
 pub mod g3d;
+pub mod label;
 pub mod m2d;
 pub mod m3d;
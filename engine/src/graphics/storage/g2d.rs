@@ -4,30 +4,53 @@ use std::collections::BTreeMap;
 // todo: better organization; quad tree?
 pub struct Graph2D {
     models: BTreeMap<String, Model2D>,
+    pub(crate) released: Vec<u32>,                                                              // texture ids for the subsystem to free
 }
 
 impl Graph2D {
     pub fn new() -> Graph2D {
         Graph2D {
             models: BTreeMap::new(),
+            released: vec![],
         }
     }
 }
 
 impl Graph2D {
+    ///
+    /// add a model; one already under the name is replaced, and its textures freed as for [Graph2D::detach].
+    ///
     pub fn attach(&mut self, name: &str, model: Model2D) {
-        self.models.insert(name.to_string(), model);
+        if let Some(mut replaced) = self.models.insert(name.to_string(), model) {
+            self.release(&mut replaced);
+        }
     }
 
     pub fn attach_or_update<IF, MF>(&mut self, name: &str, insert: IF, modify: MF)
     where
-        IF: Fn() -> Model2D,
+        IF: FnOnce() -> Model2D,
         MF: FnOnce(&mut Model2D),
     {
         self.models
             .entry(name.to_string())
             .and_modify(modify)
-            .or_insert(insert());
+            .or_insert_with(insert);
+    }
+
+    ///
+    /// remove a model; its textures are freed by the rendering subsystem before the next frame.
+    ///
+    pub fn detach(&mut self, name: &str) -> Option<Model2D> {
+        let mut model = self.models.remove(name)?;
+        self.release(&mut model);
+        Some(model)
+    }
+
+    fn release(&mut self, model: &mut Model2D) {
+        for texture in model.all_textures_mut().filter(|texture| texture.initialized) {
+            self.released.push(texture.id);
+            texture.initialized = false;
+        }
     }
 
     pub fn update<FN>(&mut self, name: &str, fx: FN)
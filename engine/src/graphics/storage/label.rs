@@ -0,0 +1,118 @@
+//!
+//! retained text: a string drawn once into a texture, and only redrawn when it changes.
+//!
+
+use crate::geometry::primitive::v2d::Vertex2D;
+use crate::geometry::rect::Rectangle2D;
+use crate::graphics::color::Color;
+use crate::graphics::texture::t2d::{Texture2D, Texture2DBuilder};
+use crate::support::text::{measure_text, text_2d_image, TextConfig};
+
+pub struct TextLabel {
+    text: String,
+    config: TextConfig,
+    width: f32,
+    height: f32,
+    pub texture: Texture2D,
+}
+
+impl TextLabel {
+    pub fn new(text: &str, config: TextConfig, x: f32, y: f32) -> TextLabel {
+        let (width, height) = measure_text(&config, text);
+        let image = text_2d_image(config.clone(), || text.to_string());
+        TextLabel {
+            text: text.to_string(),
+            config,
+            width,
+            height,
+            texture: Texture2DBuilder::new()
+                .with_x(x)
+                .with_y(y)
+                .with_image(image)
+                .build(),
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn config(&self) -> &TextConfig {
+        &self.config
+    }
+
+    ///
+    /// change the text; it's only rasterized again if it's different. returns whether it was.
+    ///
+    pub fn set_text(&mut self, text: &str) -> bool {
+        if self.text == text {
+            return false;
+        }
+        self.text = text.to_string();
+        self.rasterize();
+        true
+    }
+
+    ///
+    /// change the text colour; it's only rasterized again if it's different. returns whether it was.
+    ///
+    pub fn set_color(&mut self, foreground: Color) -> bool {
+        if self.config.foreground == foreground {
+            return false;
+        }
+        self.config.foreground = foreground;
+        self.rasterize();
+        true
+    }
+
+    pub fn set_position(&mut self, x: f32, y: f32) {
+        self.texture.x = x;
+        self.texture.y = y;
+    }
+
+    ///
+    /// where the text is drawn, in pixels, as laid out; the texture is never smaller than 1x1.
+    ///
+    pub fn bounds(&self) -> Rectangle2D {
+        let scale = self.texture.scale;
+        Rectangle2D {
+            top_left: Vertex2D::new(self.texture.x, self.texture.y),
+            bottom_right: Vertex2D::new(self.texture.x + self.width * scale, self.texture.y + self.height * scale),
+        }
+    }
+
+    fn rasterize(&mut self) {
+        (self.width, self.height) = measure_text(&self.config, &self.text);
+        self.texture.replacement = Some(text_2d_image(self.config.clone(), || self.text.clone()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graphics::color::Color;
+    use crate::graphics::storage::label::TextLabel;
+    use crate::support::text::TextConfig;
+
+    #[test]
+    fn test_redrawn_only_on_change() {
+        let mut label = TextLabel::new("FPS: 60", TextConfig::default(), 10.0, 5.0);
+        assert_eq!((91, 15), (label.texture.image.width, label.texture.image.height));
+
+        assert!(!label.set_text("FPS: 60"));
+        assert!(!label.set_color(Color::WHITE));
+        assert!(label.texture.replacement.is_none());
+
+        assert!(label.set_text("FPS: 120"));
+        assert_eq!(Some(104), label.texture.replacement.as_ref().map(|image| image.width));
+        assert_eq!("FPS: 120", label.text());
+    }
+
+    #[test]
+    fn test_bounds() {
+        let mut label = TextLabel::new("ab", TextConfig::default(), 10.0, 5.0);
+        label.set_position(20.0, 30.0);
+        let bounds = label.bounds();
+        assert_eq!((20.0, 30.0), (bounds.top_left.x, bounds.top_left.y));
+        assert_eq!((46.0, 45.0), (bounds.bottom_right.x, bounds.bottom_right.y));
+    }
+}
@@ -1,14 +1,17 @@
 use crate::geometry::primitive::prim2d::Primitive2D;
+use crate::graphics::storage::label::TextLabel;
 use crate::graphics::texture::t2d::Texture2D;
 
 pub struct Model2D {
     pub primitives: Vec<Primitive2D>,
     pub textures: Vec<Texture2D>,
+    pub labels: Vec<TextLabel>,
 }
 
 pub struct Model2DBuilder {
     the_primitives: Vec<Primitive2D>,
     the_textures: Vec<Texture2D>,
+    the_labels: Vec<TextLabel>,
 }
 
 impl Model2D {
@@ -16,6 +19,7 @@ impl Model2D {
         Model2D {
             primitives,
             textures,
+            labels: vec![],
         }
     }
 }
@@ -28,6 +32,32 @@ impl Model2D {
     pub fn attach_texture(&mut self, texture: Texture2D) {
         self.textures.push(texture);
     }
+
+    pub fn attach_label(&mut self, label: TextLabel) {
+        self.labels.push(label);
+    }
+}
+
+impl Model2D {
+    ///
+    /// every texture to draw: the model's own, then each label's.
+    ///
+    pub(crate) fn all_textures(&self) -> impl Iterator<Item = &Texture2D> {
+        self.textures.iter().chain(self.labels.iter().map(|label| &label.texture))
+    }
+
+    pub(crate) fn all_textures_mut(&mut self) -> impl Iterator<Item = &mut Texture2D> {
+        self.textures.iter_mut().chain(self.labels.iter_mut().map(|label| &mut label.texture))
+    }
+
+    ///
+    /// the texture a primitive samples, once it's ready; none if it has none, or names one the model lacks.
+    ///
+    pub(crate) fn primitive_texture(&self, primitive: &Primitive2D) -> Option<&Texture2D> {
+        primitive.texture
+            .and_then(|texture| self.textures.get(texture.index))
+            .filter(|texture| texture.initialized)
+    }
 }
 
 impl Model2DBuilder {
@@ -35,6 +65,7 @@ impl Model2DBuilder {
         Model2DBuilder {
             the_primitives: Vec::new(),
             the_textures: Vec::new(),
+            the_labels: Vec::new(),
         }
     }
 
@@ -48,10 +79,16 @@ impl Model2DBuilder {
         self
     }
 
+    pub fn with_label(mut self, label: TextLabel) -> Self {
+        self.the_labels.push(label);
+        self
+    }
+
     pub fn build(self) -> Model2D {
         Model2D {
             primitives: self.the_primitives,
             textures: self.the_textures,
+            labels: self.the_labels,
         }
     }
 }
@@ -7,14 +7,24 @@
 use crate::graphics::camera::Camera;
 use crate::graphics::storage::g2d::Graph2D;
 use crate::graphics::storage::g3d::Graph3D;
+#[cfg(target_os="windows")]
 use crate::graphics::subsystem::opengl::OpenGLHandle;
+use crate::graphics::subsystem::software::SoftwareHandle;
+use crate::support::image::RawImage;
+#[cfg(not(target_os="windows"))]
+use crate::support::logger::log;
+#[cfg(not(target_os="windows"))]
+use crate::support::logger::log_level::LogLevel;
 use crate::PainsawContext;
 
+#[cfg(target_os="windows")]
 pub mod opengl;
+pub mod software;
 
 #[derive(Clone)]
 pub enum GraphicsSubSystem {
     OpenGL { pipeline: OpenGLPipeline },
+    Software,
 }
 
 #[derive(Clone)]
@@ -47,10 +57,30 @@ pub trait RenderingSubSystemHandle {
     fn prepare_3d(&self, context: &PainsawContext);
     fn render_3d(&self, g3d: &mut Graph3D);
     fn after_3d(&self, context: &PainsawContext);
+
+    ///
+    /// read back the most recently rendered frame, if supported.
+    ///
+    fn capture(&self, camera: &Camera) -> Option<RawImage>;
 }
 
 pub fn grss_factory(gss: GraphicsSubSystem) -> Box<dyn RenderingSubSystemHandle> {
     match gss {
-        GraphicsSubSystem::OpenGL { pipeline: pl } => Box::new(OpenGLHandle { pipeline: pl }),
+        GraphicsSubSystem::OpenGL { pipeline: pl } => opengl_factory(pl),
+        GraphicsSubSystem::Software => Box::new(SoftwareHandle::new()),
     }
 }
+
+#[cfg(target_os="windows")]
+fn opengl_factory(pipeline: OpenGLPipeline) -> Box<dyn RenderingSubSystemHandle> {
+    Box::new(OpenGLHandle { pipeline })
+}
+
+///
+/// opengl is only wired up for microsoft windows; everywhere else, fall back to the software rasterizer.
+///
+#[cfg(not(target_os="windows"))]
+fn opengl_factory(_pipeline: OpenGLPipeline) -> Box<dyn RenderingSubSystemHandle> {
+    log(LogLevel::Warning, &|| String::from("opengl is not available on this platform; using software rasterizer"));
+    Box::new(SoftwareHandle::new())
+}
@@ -4,9 +4,10 @@ use crate::graphics::storage::g2d::Graph2D;
 use crate::graphics::storage::g3d::Graph3D;
 use crate::graphics::subsystem::opengl::ffp::ffp2d::{ffp_2d_initialize_textures, ffp_2d_update_textures, ffp_render_2d_line_strip, ffp_render_2d_quads};
 use crate::graphics::subsystem::opengl::ffp::ffp3d::{ffp_3d_lines, ffp_3d_points, ffp_3d_quads, ffp_3d_setup, ffp_3d_teardown};
-use crate::graphics::subsystem::opengl::ffp::{ffp_before_scene, ffp_resize};
+use crate::graphics::subsystem::opengl::ffp::{ffp_before_scene, ffp_capture, ffp_resize};
 use crate::graphics::subsystem::RendererInfo;
 use crate::graphics::subsystem::{OpenGLPipeline, RenderingSubSystemHandle};
+use crate::support::image::RawImage;
 use crate::PainsawContext;
 use ffp::api::gl_get_string;
 use ffp::ffp2d::{ffp_2d_setup, ffp_2d_teardown, ffp_render_2d_lines, ffp_render_2d_points, ffp_render_2d_texture};
@@ -75,14 +76,13 @@ impl RenderingSubSystemHandle for OpenGLHandle {
                         match primitive.p_type {
                             PrimitiveType::Point{point_size} => { ffp_render_2d_points(primitive, point_size)},
                             PrimitiveType::Line{thickness} => { ffp_render_2d_lines(primitive, thickness)},
-                            PrimitiveType::Quad {} => {ffp_render_2d_quads(primitive)},
+                            PrimitiveType::Quad {} => {ffp_render_2d_quads(primitive, model.primitive_texture(primitive))},
                             PrimitiveType::LineStrip {thickness} => {ffp_render_2d_line_strip(primitive, thickness)},
                         }
                     }
 
-                    model.textures
-                        .iter()
-                        .filter(|x| x.initialized)
+                    model.all_textures()
+                        .filter(|x| x.initialized && x.visible)
                         .for_each(|x| ffp_render_2d_texture(x));
                 }
             },
@@ -143,4 +143,11 @@ impl RenderingSubSystemHandle for OpenGLHandle {
             OpenGLPipeline::ProgrammableShader => {},
         }
     }
+
+    fn capture(&self, camera: &Camera) -> Option<RawImage> {
+        match self.pipeline {
+            OpenGLPipeline::FixedFunction => Some(ffp_capture(camera)),
+            OpenGLPipeline::ProgrammableShader => None,
+        }
+    }
 }
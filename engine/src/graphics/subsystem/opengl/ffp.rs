@@ -1,8 +1,9 @@
 use crate::graphics::camera::Camera;
-use crate::graphics::subsystem::opengl::ffp::api::{gl_clear, gl_clear_color, gl_viewport};
+use crate::graphics::subsystem::opengl::ffp::api::{gl_clear, gl_clear_color, gl_pixel_store_i, gl_read_pixels, gl_viewport};
+use crate::support::image::RawImage;
 use crate::support::logger::log;
 use crate::support::logger::log_level::LogLevel;
-use windows::Win32::Graphics::OpenGL::{GL_COLOR_BUFFER_BIT, GL_DEPTH_BUFFER_BIT};
+use windows::Win32::Graphics::OpenGL::{GL_COLOR_BUFFER_BIT, GL_DEPTH_BUFFER_BIT, GL_PACK_ALIGNMENT, GL_RGBA, GL_UNSIGNED_BYTE};
 
 pub(crate) mod api;
 pub mod ffp2d;
@@ -20,3 +21,24 @@ pub(crate) fn ffp_resize(camera: &Camera) {
     /* observe and report */
     log(LogLevel::Debug, &|| String::from(format!("resize(): w=[{}],h=[{}]", camera.projection.width, camera.projection.height)));
 }
+
+///
+/// read back the color buffer; opengl returns rows bottom-to-top, so they're flipped here.
+///
+pub(crate) fn ffp_capture(camera: &Camera) -> RawImage {
+    /* read pixels, tightly packed */
+    let width = camera.projection.width as u32;
+    let height = camera.projection.height as u32;
+    let mut data = vec![0u8; width as usize * height as usize * 4];
+    gl_pixel_store_i(GL_PACK_ALIGNMENT, 1);
+    gl_read_pixels(0, 0, width as i32, height as i32, GL_RGBA, GL_UNSIGNED_BYTE, data.as_mut_ptr() as *mut core::ffi::c_void);
+
+    /* flip rows */
+    let flipped = data.chunks_exact((width as usize * 4).max(1))
+        .rev()
+        .flatten()
+        .copied()
+        .collect();
+
+    RawImage::new(width, height, flipped)
+}
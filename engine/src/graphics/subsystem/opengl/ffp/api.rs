@@ -2,7 +2,7 @@ use crate::graphics::subsystem::opengl::errors::check_errors_gl;
 use crate::support::logger::log;
 use crate::support::logger::log_level::LogLevel;
 use std::ffi::{c_char, CStr};
-use windows::Win32::Graphics::OpenGL::{glBegin, glBindTexture, glBlendFunc, glClear, glClearColor, glColor4f, glDisable, glEnable, glEnd, glFrustum, glGenTextures, glGetString, glLineWidth, glLoadIdentity, glMatrixMode, glOrtho, glPointSize, glPolygonMode, glPopAttrib, glPopMatrix, glPushAttrib, glPushMatrix, glRotatef, glScalef, glTexCoord2f, glTexEnvf, glTexImage2D, glTexParameteri, glTexSubImage2D, glTranslatef, glVertex2f, glVertex3f, glViewport, gluPerspective, GL_LINES, GL_POINTS, GL_QUADS};
+use windows::Win32::Graphics::OpenGL::{glAlphaFunc, glBegin, glBindTexture, glBlendFunc, glClear, glClearColor, glColor4f, glDeleteTextures, glDisable, glEnable, glEnd, glFrustum, glGenTextures, glGetString, glLineWidth, glLoadIdentity, glMatrixMode, glOrtho, glPointSize, glPixelStorei, glPolygonMode, glPopAttrib, glPopMatrix, glPushAttrib, glPushMatrix, glReadPixels, glRotatef, glScalef, glTexCoord2f, glTexEnvf, glTexImage2D, glTexParameteri, glTexSubImage2D, glTranslatef, glVertex2f, glVertex3f, glViewport, gluPerspective, GL_LINES, GL_POINTS, GL_QUADS};
 
 pub(crate) fn gl_clear(mask: u32) {
     unsafe { glClear(mask); }
@@ -134,6 +134,11 @@ pub(crate) fn gl_gen_textures(n: i32, textures: *mut u32) {
     check_errors_gl("glGenTextures");
 }
 
+pub(crate) fn gl_delete_textures(n: i32, textures: *const u32) {
+    unsafe { glDeleteTextures(n, textures) }
+    check_errors_gl("glDeleteTextures");
+}
+
 pub(crate) fn gl_bind_texture(target: u32, texture: u32) {
     unsafe { glBindTexture(target, texture); }
     check_errors_gl("glBindTexture");
@@ -182,6 +187,11 @@ pub(crate) fn gl_blend_func(sfactor: u32, dfactor: u32) {
     check_errors_gl("glBlendFunc");
 }
 
+pub(crate) fn gl_alpha_func(func: u32, reference: f32) {
+    unsafe { glAlphaFunc(func, reference) }
+    check_errors_gl("glAlphaFunc");
+}
+
 pub(crate) fn glu_perspective(fovy: f64, aspect: f64, znear: f64, zfar: f64) {
     unsafe { gluPerspective(fovy, aspect, znear, zfar) }
     check_errors_gl("gluPerspective");
@@ -208,3 +218,13 @@ pub(crate) fn gl_polygon_mode(face: u32, mode: u32) {
     unsafe { glPolygonMode(face, mode) }
     check_errors_gl("glPolygonMode");
 }
+
+pub(crate) fn gl_pixel_store_i(pname: u32, param: i32) {
+    unsafe { glPixelStorei(pname, param) }
+    check_errors_gl("glPixelStorei");
+}
+
+pub(crate) fn gl_read_pixels(x: i32, y: i32, width: i32, height: i32, format: u32, r#type: u32, pixels: *mut core::ffi::c_void) {
+    unsafe { glReadPixels(x, y, width, height, format, r#type, pixels) }
+    check_errors_gl("glReadPixels");
+}
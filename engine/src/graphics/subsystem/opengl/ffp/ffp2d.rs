@@ -4,14 +4,17 @@
 
 use crate::graphics::camera::Camera;
 use crate::geometry::primitive::prim2d::Primitive2D;
+use crate::graphics::color::Color;
+use crate::graphics::texture::filter::FilterMode;
+use crate::graphics::texture::sdf::DistanceField;
 use crate::graphics::texture::t2d::Texture2D;
 use crate::graphics::storage::g2d::Graph2D;
-use crate::graphics::subsystem::opengl::ffp::api::{gl_begin, gl_begin_lines, gl_begin_points, gl_begin_quads, gl_bind_texture, gl_blend_func, gl_color_4f, gl_disable, gl_enable, gl_end, gl_gen_textures, gl_line_width, gl_load_identity, gl_matrix_mode, gl_ortho, gl_point_size, gl_pop_attrib, gl_pop_matrix, gl_push_attrib, gl_push_matrix, gl_tex_coord_2f, gl_tex_env_f, gl_tex_image_2d, gl_tex_parameter_i, gl_tex_sub_image_2d, gl_vertex_2f};
+use crate::graphics::subsystem::opengl::ffp::api::{gl_alpha_func, gl_begin, gl_begin_lines, gl_begin_points, gl_begin_quads, gl_bind_texture, gl_blend_func, gl_color_4f, gl_delete_textures, gl_disable, gl_enable, gl_end, gl_gen_textures, gl_line_width, gl_load_identity, gl_matrix_mode, gl_ortho, gl_point_size, gl_pop_attrib, gl_pop_matrix, gl_push_attrib, gl_push_matrix, gl_tex_coord_2f, gl_tex_env_f, gl_tex_image_2d, gl_tex_parameter_i, gl_tex_sub_image_2d, gl_vertex_2f};
 use crate::support::logger::log;
 use crate::support::logger::log_level::LogLevel;
 use glcore::GL_LINE_STRIP;
 use std::ffi::c_void;
-use windows::Win32::Graphics::OpenGL::{GL_ALL_ATTRIB_BITS, GL_BLEND, GL_MODELVIEW, GL_NEAREST, GL_ONE_MINUS_SRC_ALPHA, GL_PROJECTION, GL_REPLACE, GL_RGBA, GL_SRC_ALPHA, GL_TEXTURE_2D, GL_TEXTURE_ENV, GL_TEXTURE_ENV_MODE, GL_TEXTURE_MAG_FILTER, GL_TEXTURE_MIN_FILTER, GL_UNSIGNED_BYTE};
+use windows::Win32::Graphics::OpenGL::{GL_ALL_ATTRIB_BITS, GL_ALPHA_TEST, GL_BLEND, GL_GEQUAL, GL_LINEAR, GL_LINEAR_MIPMAP_LINEAR, GL_LINEAR_MIPMAP_NEAREST, GL_MODELVIEW, GL_MODULATE, GL_NEAREST, GL_NEAREST_MIPMAP_LINEAR, GL_NEAREST_MIPMAP_NEAREST, GL_ONE_MINUS_SRC_ALPHA, GL_PROJECTION, GL_REPLACE, GL_RGBA, GL_SRC_ALPHA, GL_TEXTURE_2D, GL_TEXTURE_ENV, GL_TEXTURE_ENV_MODE, GL_TEXTURE_MAG_FILTER, GL_TEXTURE_MIN_FILTER, GL_UNSIGNED_BYTE};
 
 pub(crate) fn ffp_2d_setup(camera: &Camera) {
     /* save prior state before 2d rendering */
@@ -38,7 +41,7 @@ pub(crate) fn ffp_2d_initialize_textures(g2d: &mut Graph2D) {
     /* initialize textures */
     gl_enable(GL_TEXTURE_2D);
     for (_, value) in g2d.iter_mut() {
-        for tex in &mut value.textures {
+        for tex in value.all_textures_mut() {
             ffp_2d_initialize_texture(tex);
         }
     }
@@ -48,8 +51,15 @@ pub(crate) fn ffp_2d_initialize_textures(g2d: &mut Graph2D) {
 }
 
 pub(crate) fn ffp_2d_update_textures(g2d: &mut Graph2D) {
+    /* free textures of detached models */
+    if !g2d.released.is_empty() {
+        gl_delete_textures(g2d.released.len() as i32, g2d.released.as_ptr());
+        log(LogLevel::Info, &|| format!("deleted {} texture(s)", g2d.released.len()));
+        g2d.released.clear();
+    }
+
     for (_, model) in &mut g2d.iter_mut() {
-        for texture in &mut model.textures {
+        for texture in model.all_textures_mut() {
             if !texture.initialized {
                 ffp_2d_initialize_texture(texture);
             }
@@ -65,46 +75,80 @@ fn ffp_2d_initialize_texture(texture: &mut Texture2D) {
     gl_bind_texture(GL_TEXTURE_2D, texture.id);
 
     /* set texture params */
-    gl_tex_parameter_i(GL_TEXTURE_2D, GL_TEXTURE_MAG_FILTER, GL_NEAREST as i32);
-    gl_tex_parameter_i(GL_TEXTURE_2D, GL_TEXTURE_MIN_FILTER, GL_NEAREST as i32);
-
-    /* inform opengl of the texture data; see https://registry.khronos.org/OpenGL-Refpages/gl4/html/glTexImage2D.xhtml */
-    gl_tex_image_2d(
-        GL_TEXTURE_2D,                                  // target
-        0,                                              // level
-        GL_RGBA as i32,                                 // internal format; the number of color components in the texture data
-        texture.image.width as i32,                     // width
-        texture.image.height as i32,                    // height
-        0,                                              // border
-        GL_RGBA,                                        // format: (or order) of pixel data (r,g,b,a)
-        GL_UNSIGNED_BYTE,                               // r-type: the data type of the pixel data
-        texture.image.data.as_ptr() as *const c_void,   // pixels
-    );
+    let (min, mag) = gl_filters(texture);
+    gl_tex_parameter_i(GL_TEXTURE_2D, GL_TEXTURE_MAG_FILTER, mag as i32);
+    gl_tex_parameter_i(GL_TEXTURE_2D, GL_TEXTURE_MIN_FILTER, min as i32);
+
+    /* inform opengl of the texture data, one call per mip level; see https://registry.khronos.org/OpenGL-Refpages/gl4/html/glTexImage2D.xhtml */
+    for (level, image) in texture.levels().enumerate() {
+        gl_tex_image_2d(
+            GL_TEXTURE_2D,                              // target
+            level as i32,                               // level
+            GL_RGBA as i32,                             // internal format; the number of color components in the texture data
+            image.width as i32,                         // width
+            image.height as i32,                        // height
+            0,                                          // border
+            GL_RGBA,                                    // format: (or order) of pixel data (r,g,b,a)
+            GL_UNSIGNED_BYTE,                           // r-type: the data type of the pixel data
+            image.data.as_ptr() as *const c_void,       // pixels
+        );
+    }
 
     /* mark the texture as initialized (ready) */
     texture.initialized = true;
 
     /* done */
-    log(LogLevel::Info, &|| String::from(format!("created texture, id=[{}]", texture.id)));
+    log(LogLevel::Info, &|| format!("created texture, id=[{}], levels=[{}]", texture.id, texture.mipmaps.len() + 1));
 }
 
-fn ffp_2d_update_texture(texture: &mut Texture2D) {
-    if texture.replacement.is_some() {
-        let repl = texture.replacement.take().unwrap();
+pub(crate) fn ffp_2d_update_texture(texture: &mut Texture2D) {
+    if let Some(repl) = texture.replacement.take() {
+        /* a different size (e.g. longer text) needs the storage reallocated, not just overwritten */
+        let resized = (repl.width, repl.height) != (texture.image.width, texture.image.height);
+        texture.replace_image(repl);
         gl_bind_texture(GL_TEXTURE_2D, texture.id);
-        gl_tex_sub_image_2d(
-            GL_TEXTURE_2D,                              // target
-            0,                                          // level
-            0,                                          // x-offset
-            0,                                          // y-offset
-            repl.width as i32,                          // width
-            repl.height as i32,                         // height
-            GL_RGBA,                                    // format: (order) of pixel data (r, g, b, a)
-            GL_UNSIGNED_BYTE,                           // r-type: the dat type of the pixel data
-            repl.data.as_ptr() as *const c_void,        // pixels
-        );
-        texture.image = repl;
+        for (level, image) in texture.levels().enumerate() {
+            if resized {
+                gl_tex_image_2d(GL_TEXTURE_2D, level as i32, GL_RGBA as i32, image.width as i32, image.height as i32, 0, GL_RGBA, GL_UNSIGNED_BYTE, image.data.as_ptr() as *const c_void);
+                continue;
+            }
+            gl_tex_sub_image_2d(
+                GL_TEXTURE_2D,                          // target
+                level as i32,                           // level
+                0,                                      // x-offset
+                0,                                      // y-offset
+                image.width as i32,                     // width
+                image.height as i32,                    // height
+                GL_RGBA,                                // format: (order) of pixel data (r, g, b, a)
+                GL_UNSIGNED_BYTE,                       // r-type: the dat type of the pixel data
+                image.data.as_ptr() as *const c_void,   // pixels
+            );
+        }
+    }
+}
+
+///
+/// (min, mag) filter enums for the texture; mipmapped min filters only when there's a chain to use.
+/// distance fields are always bilinear, since their edges come from interpolated distances.
+///
+fn gl_filters(texture: &Texture2D) -> (u32, u32) {
+    if texture.distance_field.is_some() {
+        return (GL_LINEAR, GL_LINEAR);
     }
+    let mip = if texture.mipmaps.is_empty() { None } else { texture.filter.mip };
+    let min = match (texture.filter.min, mip) {
+        (FilterMode::Nearest, None) => GL_NEAREST,
+        (FilterMode::Linear, None) => GL_LINEAR,
+        (FilterMode::Nearest, Some(FilterMode::Nearest)) => GL_NEAREST_MIPMAP_NEAREST,
+        (FilterMode::Linear, Some(FilterMode::Nearest)) => GL_LINEAR_MIPMAP_NEAREST,
+        (FilterMode::Nearest, Some(FilterMode::Linear)) => GL_NEAREST_MIPMAP_LINEAR,
+        (FilterMode::Linear, Some(FilterMode::Linear)) => GL_LINEAR_MIPMAP_LINEAR,
+    };
+    let mag = match texture.filter.mag {
+        FilterMode::Nearest => GL_NEAREST,
+        FilterMode::Linear => GL_LINEAR,
+    };
+    (min, mag)
 }
 
 pub(crate) fn ffp_render_2d_points(primitive: &Primitive2D, point_size: f32) {
@@ -158,18 +202,44 @@ pub(crate) fn ffp_render_2d_line_strip(primitive: &Primitive2D, thickness: f32)
     gl_pop_matrix();
 }
 
-pub(crate) fn ffp_render_2d_quads(primitive: &Primitive2D) {
+///
+/// plain quads, or (with a texture) quads showing the primitive's uv rectangle, tinted by its color.
+///
+pub(crate) fn ffp_render_2d_quads(primitive: &Primitive2D, texture: Option<&Texture2D>) {
     gl_push_matrix();
     gl_push_attrib(GL_ALL_ATTRIB_BITS);
 
     gl_color_4f(primitive.color.red, primitive.color.green, primitive.color.blue, primitive.color.alpha);
 
+    /* texture coordinates, in vertex order: top left, bottom left, bottom right, top right */
+    let corners = match (texture, primitive.texture) {
+        (Some(texture), Some(region)) => {
+            gl_enable(GL_TEXTURE_2D);
+            gl_bind_texture(GL_TEXTURE_2D, texture.id);
+            gl_enable(GL_BLEND);
+            gl_blend_func(GL_SRC_ALPHA, GL_ONE_MINUS_SRC_ALPHA);
+            gl_tex_env_f(GL_TEXTURE_ENV, GL_TEXTURE_ENV_MODE, GL_MODULATE as f32);
+            let uv = region.uv;
+            Some([(uv.u0, uv.v0), (uv.u0, uv.v1), (uv.u1, uv.v1), (uv.u1, uv.v0)])
+        }
+        _ => None,
+    };
+
     gl_begin_quads();
-    for vertex in primitive.vertices.iter() {
+    for (index, vertex) in primitive.vertices.iter().enumerate() {
+        if let Some(corners) = corners {
+            let (u, v) = corners[index % 4];
+            gl_tex_coord_2f(u, v);
+        }
         gl_vertex_2f(vertex.x, vertex.y);
     }
     gl_end();
 
+    if corners.is_some() {
+        gl_disable(GL_TEXTURE_2D);
+        gl_disable(GL_BLEND);
+    }
+
     gl_pop_attrib();
     gl_pop_matrix();
 }
@@ -179,49 +249,102 @@ pub(crate) fn ffp_render_2d_texture(texture: &Texture2D) {
     gl_push_matrix();
     gl_push_attrib(GL_ALL_ATTRIB_BITS);
 
-    /* gather variables */
-    let scale = texture.scale;
-    let x = texture.x;
-    let y = texture.y;
-    let width = texture.image.width as f32;
-    let height = texture.image.height as f32;
-
     /* prepare to render textures */
     gl_enable(GL_TEXTURE_2D);
     gl_bind_texture(GL_TEXTURE_2D, texture.id);
-    gl_tex_env_f(GL_TEXTURE_ENV, GL_TEXTURE_ENV_MODE, GL_REPLACE as f32);
 
     /* enable transparency in textures */
     gl_enable(GL_BLEND);
     gl_blend_func(GL_SRC_ALPHA, GL_ONE_MINUS_SRC_ALPHA);
 
+    /* distance fields are drawn in layers; everything else is one pass */
+    match texture.distance_field {
+        Some(ref field) => ffp_render_2d_distance_field(texture, field),
+        None => {
+            gl_tex_env_f(GL_TEXTURE_ENV, GL_TEXTURE_ENV_MODE, GL_REPLACE as f32);
+            ffp_render_2d_quads_textured(texture, 0.0, 0.0);
+        }
+    }
+
+    /* turn off the stuff we enabled (specifically for texturing) */
+    gl_disable(GL_TEXTURE_2D);
+    gl_disable(GL_BLEND);
+
+    /* restore prior state */
+    gl_pop_attrib();
+    gl_pop_matrix();
+}
+
+///
+/// draw a distance field one layer at a time, with the texture's alpha scaled by the layer colour.
+///
+/// alpha testing at a layer's edge gives hard edges at any scale (unblended, so those layers are opaque);
+/// glow, and soft shadows, blend the raw field instead, so they fade over the whole spread.
+///
+fn ffp_render_2d_distance_field(texture: &Texture2D, field: &DistanceField) {
+    let style = &field.style;
+    let edge = style.outline.map(|outline| outline.width).unwrap_or(0.0);
+    gl_tex_env_f(GL_TEXTURE_ENV, GL_TEXTURE_ENV_MODE, GL_MODULATE as f32);
+
+    let soft = |color: Color, offset_x: f32, offset_y: f32| {
+        gl_disable(GL_ALPHA_TEST);
+        gl_enable(GL_BLEND);
+        gl_color_4f(color.red, color.green, color.blue, color.alpha);
+        ffp_render_2d_quads_textured(texture, offset_x, offset_y);
+    };
+    let hard = |color: Color, distance: f32, offset_x: f32, offset_y: f32| {
+        gl_enable(GL_ALPHA_TEST);
+        gl_alpha_func(GL_GEQUAL, field.value(distance));
+        gl_disable(GL_BLEND);
+        gl_color_4f(color.red, color.green, color.blue, 1.0);
+        ffp_render_2d_quads_textured(texture, offset_x, offset_y);
+    };
+
+    /* bottom up: shadow, glow, outline, fill */
+    if let Some(shadow) = style.shadow {
+        if shadow.softness > 0.0 {
+            soft(shadow.color, shadow.offset_x, shadow.offset_y);
+        } else {
+            hard(shadow.color, edge, shadow.offset_x, shadow.offset_y);
+        }
+    }
+    if let Some(glow) = style.glow {
+        soft(glow.color, 0.0, 0.0);
+    }
+    if let Some(outline) = style.outline {
+        hard(outline.color, outline.width, 0.0, 0.0);
+    }
+    hard(style.fill, 0.0, 0.0, 0.0);
+    gl_disable(GL_ALPHA_TEST);
+}
+
+///
+/// one quad per sprite; the offset is in texels, so it scales with each sprite.
+///
+fn ffp_render_2d_quads_textured(texture: &Texture2D, offset_x: f32, offset_y: f32) {
     /* start drawing texture; only text coords and vertexes until gl-end! */
     gl_begin_quads();
+    for quad in texture.quads() {
+        let scale = quad.width / ((quad.uv.u1 - quad.uv.u0) * texture.image.width as f32);
+        let (x, y, uv) = (quad.x + offset_x * scale, quad.y + offset_y * scale, quad.uv);
 
-    /* top left */
-    gl_tex_coord_2f(0.0, 0.0);
-    gl_vertex_2f(x, y);
+        /* top left */
+        gl_tex_coord_2f(uv.u0, uv.v0);
+        gl_vertex_2f(x, y);
 
-    /* bottom left */
-    gl_tex_coord_2f(0.0, 1.0);
-    gl_vertex_2f(x, y + height * scale);
+        /* bottom left */
+        gl_tex_coord_2f(uv.u0, uv.v1);
+        gl_vertex_2f(x, y + quad.height);
 
-    /* bottom right */
-    gl_tex_coord_2f(1.0, 1.0);
-    gl_vertex_2f(x + width * scale, y +  height * scale);
+        /* bottom right */
+        gl_tex_coord_2f(uv.u1, uv.v1);
+        gl_vertex_2f(x + quad.width, y + quad.height);
 
-    /* top right */
-    gl_tex_coord_2f(1.0, 0.0);
-    gl_vertex_2f(x + width * scale, y);
+        /* top right */
+        gl_tex_coord_2f(uv.u1, uv.v0);
+        gl_vertex_2f(x + quad.width, y);
+    }
 
     /* done */
     gl_end();
-
-    /* turn off the stuff we enabled (specifically for texturing) */
-    gl_disable(GL_TEXTURE_2D);
-    gl_disable(GL_BLEND);
-
-    /* restore prior state */
-    gl_pop_attrib();
-    gl_pop_matrix();
 }
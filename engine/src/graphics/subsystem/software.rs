@@ -0,0 +1,327 @@
+//!
+//! cpu-only rendering subsystem.
+//!
+//! rasterizes the 2d and 3d graphs into an in-memory frame buffer, so that frames can be
+//! produced and inspected on machines without a gpu (or without opengl at all).
+//!
+
+use crate::geometry::orient::matrix::m4x4::Matrix4x4;
+use crate::geometry::primitive::PrimitiveType;
+use crate::graphics::camera::Camera;
+use crate::graphics::storage::g2d::Graph2D;
+use crate::graphics::storage::g3d::Graph3D;
+use crate::graphics::subsystem::software::framebuffer::FrameBuffer;
+use crate::graphics::subsystem::software::sw2d::{sw_2d_update_textures, sw_render_2d_line_strip, sw_render_2d_lines, sw_render_2d_points, sw_render_2d_quads, sw_render_2d_texture};
+use crate::graphics::subsystem::software::sw3d::{sw_3d_lines, sw_3d_points, sw_3d_quads, sw_3d_setup};
+use crate::graphics::subsystem::{RendererInfo, RenderingSubSystemHandle};
+use crate::support::image::RawImage;
+use crate::support::logger::log;
+use crate::support::logger::log_level::LogLevel;
+use crate::PainsawContext;
+use std::sync::Mutex;
+
+pub mod framebuffer;
+pub(crate) mod raster;
+pub(crate) mod sw2d;
+pub(crate) mod sw3d;
+
+pub struct SoftwareHandle {
+    target: Mutex<FrameBuffer>,
+    view: Mutex<Matrix4x4>,
+}
+
+impl SoftwareHandle {
+    pub fn new() -> SoftwareHandle {
+        SoftwareHandle {
+            target: Mutex::new(FrameBuffer::new(0, 0)),
+            view: Mutex::new(Matrix4x4::identity()),
+        }
+    }
+
+    ///
+    /// copy the current contents of the frame buffer.
+    ///
+    pub fn snapshot(&self) -> RawImage {
+        self.target.lock().unwrap().to_image()
+    }
+}
+
+impl Default for SoftwareHandle {
+    fn default() -> SoftwareHandle {
+        SoftwareHandle::new()
+    }
+}
+
+impl RenderingSubSystemHandle for SoftwareHandle {
+    fn identify(&self) -> Option<RendererInfo> {
+        Some(RendererInfo {
+            name: Some(String::from("Software")),
+            version: Some(String::from(env!("CARGO_PKG_VERSION"))),
+            vendor: Some(String::from("painsaw")),
+            device: Some(String::from("cpu")),
+        })
+    }
+
+    fn initialize(&self, g2d: &mut Graph2D, _g3d: &mut Graph3D) {
+        sw_2d_update_textures(g2d);
+    }
+
+    fn resize(&self, context: &PainsawContext) {
+        let projection = &context.camera.projection;
+        self.target.lock().unwrap().resize(projection.width as u32, projection.height as u32);
+        log(LogLevel::Debug, &|| format!("resize(): w=[{}],h=[{}]", projection.width, projection.height));
+    }
+
+    fn before_scene(&self, camera: &Camera) {
+        let mut fb = self.target.lock().unwrap();
+        fb.resize(camera.projection.width as u32, camera.projection.height as u32);
+        fb.clear((0, 0, 0, 255));
+    }
+
+    fn prepare_2d(&self, _camera: &Camera, g2d: &mut Graph2D) {
+        sw_2d_update_textures(g2d);
+    }
+
+    fn render_2d(&self, g2d: &mut Graph2D) {
+        let mut fb = self.target.lock().unwrap();
+        for (_, model) in g2d.iter() {
+            for primitive in model.primitives.iter() {
+                match primitive.p_type {
+                    PrimitiveType::Point { point_size } => sw_render_2d_points(&mut fb, primitive, point_size),
+                    PrimitiveType::Line { thickness } => sw_render_2d_lines(&mut fb, primitive, thickness),
+                    PrimitiveType::Quad {} => sw_render_2d_quads(&mut fb, primitive, model.primitive_texture(primitive)),
+                    PrimitiveType::LineStrip { thickness } => sw_render_2d_line_strip(&mut fb, primitive, thickness),
+                }
+            }
+
+            model.all_textures()
+                .filter(|x| x.initialized && x.visible)
+                .for_each(|x| sw_render_2d_texture(&mut fb, x));
+        }
+    }
+
+    fn after_2d(&self) {}
+
+    fn prepare_3d(&self, context: &PainsawContext) {
+        *self.view.lock().unwrap() = sw_3d_setup(&context.camera);
+    }
+
+    fn render_3d(&self, g3d: &mut Graph3D) {
+        let mut fb = self.target.lock().unwrap();
+        let view = self.view.lock().unwrap();
+        for (_, model) in g3d.iter() {
+            for primitive in model.primitives.iter() {
+                match primitive.ptype {
+                    PrimitiveType::Point { point_size } => sw_3d_points(&mut fb, &view, primitive, point_size),
+                    PrimitiveType::Line { thickness } => sw_3d_lines(&mut fb, &view, primitive, thickness),
+                    PrimitiveType::Quad {} => sw_3d_quads(&mut fb, &view, primitive),
+                    PrimitiveType::LineStrip { .. } => {},
+                }
+            }
+        }
+    }
+
+    fn after_3d(&self, _context: &PainsawContext) {}
+
+    fn capture(&self, _camera: &Camera) -> Option<RawImage> {
+        Some(self.snapshot())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geometry::dim::Dimension2D;
+    use crate::geometry::primitive::prim2d::Primitive2DBuilder;
+    use crate::geometry::primitive::prim3d::Primitive3DBuilder;
+    use crate::geometry::primitive::v2d::Vertex2D;
+    use crate::geometry::primitive::v3d::Vertex3D;
+    use crate::geometry::primitive::PrimitiveType;
+    use crate::graphics::camera::Camera;
+    use crate::graphics::color::Color;
+    use crate::graphics::storage::g2d::Graph2D;
+    use crate::graphics::storage::g3d::Graph3D;
+    use crate::graphics::storage::m2d::Model2DBuilder;
+    use crate::graphics::storage::m3d::Model3DBuilder;
+    use crate::graphics::subsystem::software::sw3d::sw_3d_setup;
+    use crate::graphics::subsystem::software::SoftwareHandle;
+    use crate::graphics::subsystem::RenderingSubSystemHandle;
+    use crate::graphics::texture::atlas::TextureAtlas;
+    use crate::graphics::texture::mipmap::MipmapFilter;
+    use crate::graphics::texture::sdf::{distance_field, DistanceField, SdfEffect, SdfStyle};
+    use crate::graphics::texture::t2d::{Texture2D, Texture2DBuilder};
+    use crate::support::image::pixel::PixelFormat;
+    use crate::support::image::RawImage;
+
+    fn pixel(image: &RawImage, x: u32, y: u32) -> (u8, u8, u8, u8) {
+        let offset = ((y * image.width + x) * 4) as usize;
+        (image.data[offset], image.data[offset + 1], image.data[offset + 2], image.data[offset + 3])
+    }
+
+    #[test]
+    fn test_2d_point_and_texture() {
+        let camera = Camera::new(&Dimension2D::new(20.0, 40.0));
+        let handle = SoftwareHandle::new();
+        let mut g2d = Graph2D::new();
+        g2d.attach("points", Model2DBuilder::new()
+            .with_primitive(Primitive2DBuilder::new()
+                .with_type(PrimitiveType::Point { point_size: 3.0 })
+                .with_color(Color::GREEN)
+                .with_vertex(Vertex2D::new(5.0, 5.0))
+                .build())
+            .with_texture(Texture2D::new(RawImage::new(1, 1, vec![255, 0, 0, 128]), 30.0, 10.0, 2.0))
+            .build());
+
+        handle.before_scene(&camera);
+        handle.prepare_2d(&camera, &mut g2d);
+        handle.render_2d(&mut g2d);
+        let image = handle.snapshot();
+
+        assert_eq!(40, image.width);
+        assert_eq!(20, image.height);
+        assert_eq!((0, 0, 0, 255), pixel(&image, 0, 0));
+        assert_eq!((0, 255, 0, 255), pixel(&image, 4, 4));
+        assert_eq!((0, 255, 0, 255), pixel(&image, 6, 6));
+        assert_eq!((0, 0, 0, 255), pixel(&image, 8, 8));
+        assert_eq!((128, 0, 0, 191), pixel(&image, 31, 11));
+        assert_eq!((0, 0, 0, 255), pixel(&image, 32, 12));
+    }
+
+    #[test]
+    fn test_2d_detach_releases_textures() {
+        let camera = Camera::new(&Dimension2D::new(10.0, 10.0));
+        let handle = SoftwareHandle::new();
+        let mut g2d = Graph2D::new();
+        g2d.attach("texture", Model2DBuilder::new()
+            .with_texture(Texture2D::new(RawImage::new(1, 1, vec![255, 0, 0, 255]), 0.0, 0.0, 1.0))
+            .build());
+        handle.prepare_2d(&camera, &mut g2d);
+
+        let model = g2d.detach("texture").unwrap();
+        assert!(!model.textures[0].initialized);
+        assert_eq!((0, 1), (g2d.count(), g2d.released.len()));
+        handle.prepare_2d(&camera, &mut g2d);
+        assert!(g2d.released.is_empty());
+
+        /* replacing a model releases it too */
+        let texture = || Texture2D::new(RawImage::new(1, 1, vec![255, 0, 0, 255]), 0.0, 0.0, 1.0);
+        g2d.attach("texture", Model2DBuilder::new().with_texture(texture()).build());
+        handle.prepare_2d(&camera, &mut g2d);
+        g2d.attach("texture", Model2DBuilder::new().with_texture(texture()).build());
+        assert_eq!((1, 1), (g2d.count(), g2d.released.len()));
+    }
+
+    #[test]
+    fn test_2d_minified_texture_uses_mipmaps() {
+        let camera = Camera::new(&Dimension2D::new(10.0, 10.0));
+        let handle = SoftwareHandle::new();
+        let checker: Vec<u8> = (0..16).flat_map(|i| if (i + i / 4) % 2 == 0 { [0, 0, 0, 255] } else { [255, 255, 255, 255] }).collect();
+        let mut g2d = Graph2D::new();
+        g2d.attach("checker", Model2DBuilder::new()
+            .with_texture(Texture2D::new(RawImage::new(4, 4, checker.clone()), 0.0, 0.0, 0.25))
+            .with_texture(Texture2DBuilder::new()
+                .with_image(RawImage::new(4, 4, checker))
+                .with_x(5.0)
+                .with_scale(0.25)
+                .with_mipmaps(MipmapFilter::Box)
+                .build())
+            .build());
+
+        handle.before_scene(&camera);
+        handle.prepare_2d(&camera, &mut g2d);
+        handle.render_2d(&mut g2d);
+        let image = handle.snapshot();
+
+        assert_eq!((0, 0, 0, 255), pixel(&image, 0, 0));                                      // nearest: one texel, no averaging
+        assert_eq!((188, 188, 188, 255), pixel(&image, 5, 0));
+    }
+
+    #[test]
+    fn test_2d_quad_from_atlas_page() {
+        let camera = Camera::new(&Dimension2D::new(16.0, 16.0));
+        let handle = SoftwareHandle::new();
+        let mut atlas = TextureAtlas::new(2, 1, 0);
+        atlas.insert("red", &RawImage::new(1, 1, vec![255, 0, 0, 255]));
+        let blue = atlas.insert("blue", &RawImage::new(1, 1, vec![0, 0, 255, 255])).unwrap();
+        let mut g2d = Graph2D::new();
+        g2d.attach("quad", Model2DBuilder::new()
+            .with_primitive(Primitive2DBuilder::new()
+                .with_type(PrimitiveType::Quad {})
+                .with_color(Color::from_rgba(1.0, 1.0, 1.0, 0.5))
+                .with_vertex(Vertex2D::new(4.0, 4.0))
+                .with_vertex(Vertex2D::new(4.0, 12.0))
+                .with_vertex(Vertex2D::new(12.0, 12.0))
+                .with_vertex(Vertex2D::new(12.0, 4.0))
+                .with_texture(blue.primitive_texture(0))
+                .build())
+            .with_texture(atlas.page_texture(0))
+            .build());
+
+        handle.before_scene(&camera);
+        handle.prepare_2d(&camera, &mut g2d);
+        handle.render_2d(&mut g2d);
+        let image = handle.snapshot();
+
+        assert_eq!((0, 0, 128, 191), pixel(&image, 4, 4));                                     // the color tints the region
+        assert_eq!((0, 0, 128, 191), pixel(&image, 11, 11));
+        assert_eq!((0, 0, 0, 255), pixel(&image, 0, 0));                                       // the hidden page isn't drawn
+        assert_eq!((0, 0, 0, 255), pixel(&image, 12, 12));
+    }
+
+    #[test]
+    fn test_2d_distance_field_outline() {
+        /* a 4x4 square, padded to an 8x8 field; drawn 4x, it covers 8..24 with a 4 pixel outline */
+        let camera = Camera::new(&Dimension2D::new(32.0, 32.0));
+        let handle = SoftwareHandle::new();
+        let field = distance_field(&RawImage::with_format(4, 4, PixelFormat::L8, vec![255; 16]), 2.0);
+        let style = SdfStyle { outline: Some(SdfEffect { color: Color::RED, width: 1.0 }), ..SdfStyle::fill(Color::WHITE) };
+        let mut g2d = Graph2D::new();
+        g2d.attach("square", Model2DBuilder::new()
+            .with_texture(Texture2DBuilder::new()
+                .with_image(field)
+                .with_scale(4.0)
+                .with_distance_field(DistanceField { spread: 2.0, style })
+                .build())
+            .build());
+
+        handle.before_scene(&camera);
+        handle.prepare_2d(&camera, &mut g2d);
+        handle.render_2d(&mut g2d);
+        let image = handle.snapshot();
+
+        assert_eq!((255, 255, 255, 255), pixel(&image, 16, 16));
+        assert_eq!((255, 255, 255, 255), pixel(&image, 9, 16));
+        assert_eq!((255, 0, 0, 255), pixel(&image, 6, 16));
+        assert_eq!((0, 0, 0, 255), pixel(&image, 1, 16));
+    }
+
+    #[test]
+    fn test_3d_depth() {
+        let camera = Camera::new(&Dimension2D::new(100.0, 100.0));
+        let handle = SoftwareHandle::new();
+        let mut g3d = Graph3D::new();
+        g3d.attach("far", Model3DBuilder::new()
+            .with_primitive(Primitive3DBuilder::new()
+                .with_type(PrimitiveType::Point { point_size: 5.0 })
+                .with_color(Color::RED)
+                .with_vertex(Vertex3D::new(0.0, 0.0, -1.0))
+                .build())
+            .build());
+        g3d.attach("near", Model3DBuilder::new()
+            .with_primitive(Primitive3DBuilder::new()
+                .with_type(PrimitiveType::Point { point_size: 1.0 })
+                .with_color(Color::BLUE)
+                .with_vertex(Vertex3D::origin())
+                .build())
+            .build());
+
+        handle.before_scene(&camera);
+        *handle.view.lock().unwrap() = sw_3d_setup(&camera);
+        handle.render_3d(&mut g3d);
+        let image = handle.snapshot();
+
+        /* the camera sits at z=1.5, looking down -z; both points project onto the center */
+        assert_eq!((0, 0, 255, 255), pixel(&image, 50, 50));
+        assert_eq!((255, 0, 0, 255), pixel(&image, 49, 49));
+        assert_eq!((0, 0, 0, 255), pixel(&image, 10, 10));
+    }
+}
@@ -0,0 +1,118 @@
+use crate::support::image::RawImage;
+
+///
+/// in-memory render target for the software rasterizer.
+///
+/// color is stored as rgba, 4 bytes per pixel, rows top-to-bottom; this matches the
+/// layout of [RawImage].  depth is stored per pixel, in window space (0.0=near, 1.0=far).
+///
+pub struct FrameBuffer {
+    pub width: u32,
+    pub height: u32,
+    pub color: Vec<u8>,
+    pub depth: Vec<f32>,
+}
+
+impl FrameBuffer {
+    pub fn new(width: u32, height: u32) -> FrameBuffer {
+        let pixels = width as usize * height as usize;
+        FrameBuffer {
+            width,
+            height,
+            color: vec![0; pixels * 4],
+            depth: vec![1.0; pixels],
+        }
+    }
+}
+
+impl FrameBuffer {
+    ///
+    /// reallocate the buffers, if the requested size differs from the current size.
+    ///
+    pub fn resize(&mut self, width: u32, height: u32) {
+        if self.width != width || self.height != height {
+            *self = FrameBuffer::new(width, height);
+        }
+    }
+
+    ///
+    /// clear color to the given rgba value, and depth to the far plane.
+    ///
+    pub fn clear(&mut self, rgba: (u8, u8, u8, u8)) {
+        for pixel in self.color.chunks_exact_mut(4) {
+            pixel[0] = rgba.0;
+            pixel[1] = rgba.1;
+            pixel[2] = rgba.2;
+            pixel[3] = rgba.3;
+        }
+        self.depth.fill(1.0);
+    }
+
+    ///
+    /// write a pixel, ignoring depth; out-of-bounds writes are discarded.
+    ///
+    pub fn put(&mut self, x: i32, y: i32, rgba: (u8, u8, u8, u8)) {
+        if let Some(idx) = self.index(x, y) {
+            self.write(idx, rgba);
+        }
+    }
+
+    ///
+    /// write a pixel if it passes the depth test (less-than); out-of-bounds writes are discarded.
+    ///
+    pub fn put_depth(&mut self, x: i32, y: i32, z: f32, rgba: (u8, u8, u8, u8)) {
+        if let Some(idx) = self.index(x, y) && z < self.depth[idx] {
+            self.depth[idx] = z;
+            self.write(idx, rgba);
+        }
+    }
+
+    ///
+    /// blend a pixel over the existing value, using (src_alpha, one_minus_src_alpha).
+    ///
+    pub fn blend(&mut self, x: i32, y: i32, rgba: (u8, u8, u8, u8)) {
+        if let Some(idx) = self.index(x, y) {
+            let offset = idx * 4;
+            let alpha = rgba.3 as u32;
+            let inverse = 255 - alpha;
+            let dst = &mut self.color[offset..offset + 4];
+            dst[0] = ((rgba.0 as u32 * alpha + dst[0] as u32 * inverse) / 255) as u8;
+            dst[1] = ((rgba.1 as u32 * alpha + dst[1] as u32 * inverse) / 255) as u8;
+            dst[2] = ((rgba.2 as u32 * alpha + dst[2] as u32 * inverse) / 255) as u8;
+            dst[3] = ((alpha * alpha + dst[3] as u32 * inverse) / 255) as u8;
+        }
+    }
+
+    ///
+    /// read a single pixel.
+    ///
+    pub fn get(&self, x: i32, y: i32) -> Option<(u8, u8, u8, u8)> {
+        self.index(x, y).map(|idx| {
+            let offset = idx * 4;
+            (self.color[offset], self.color[offset + 1], self.color[offset + 2], self.color[offset + 3])
+        })
+    }
+
+    ///
+    /// copy the color buffer into an image.
+    ///
+    pub fn to_image(&self) -> RawImage {
+        RawImage::new(self.width, self.height, self.color.clone())
+    }
+
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        if x < 0 || y < 0 || x as u32 >= self.width || y as u32 >= self.height {
+            None
+        } else {
+            Some(y as usize * self.width as usize + x as usize)
+        }
+    }
+
+    fn write(&mut self, idx: usize, rgba: (u8, u8, u8, u8)) {
+        let offset = idx * 4;
+        self.color[offset] = rgba.0;
+        self.color[offset + 1] = rgba.1;
+        self.color[offset + 2] = rgba.2;
+        self.color[offset + 3] = rgba.3;
+    }
+}
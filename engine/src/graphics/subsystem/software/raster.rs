@@ -0,0 +1,137 @@
+//!
+//! low-level rasterization of points, lines and triangles in window coordinates.
+//!
+//! window coordinates put the origin at the top-left of the frame buffer, with y increasing
+//! downward; pixel (i,j) covers the area [i,i+1) x [j,j+1).  z is window-space depth.
+//!
+
+use crate::graphics::subsystem::software::framebuffer::FrameBuffer;
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct WindowVertex {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl WindowVertex {
+    pub(crate) fn new(x: f32, y: f32, z: f32) -> WindowVertex {
+        WindowVertex { x, y, z }
+    }
+}
+
+///
+/// draw a square point, centered on the vertex.
+///
+pub(crate) fn raster_point(fb: &mut FrameBuffer, vertex: &WindowVertex, size: f32, rgba: (u8, u8, u8, u8), depth_test: bool) {
+    let size = size.round().max(1.0) as i32;
+    let left = (vertex.x - size as f32 / 2.0 + 0.5).floor() as i32;
+    let top = (vertex.y - size as f32 / 2.0 + 0.5).floor() as i32;
+
+    for y in top..top + size {
+        for x in left..left + size {
+            plot(fb, x, y, vertex.z, rgba, depth_test);
+        }
+    }
+}
+
+///
+/// draw a line between two vertices.
+///
+/// wide lines are drawn the same way as (non-antialiased) opengl: each step along the major
+/// axis fills a span of `thickness` pixels along the minor axis.
+///
+pub(crate) fn raster_line(fb: &mut FrameBuffer, from: &WindowVertex, to: &WindowVertex, thickness: f32, rgba: (u8, u8, u8, u8), depth_test: bool) {
+    let dx = to.x - from.x;
+    let dy = to.y - from.y;
+    let dz = to.z - from.z;
+    let x_major = dx.abs() >= dy.abs();
+    let steps = dx.abs().max(dy.abs()).ceil().max(1.0) as i32;
+    let width = thickness.round().max(1.0) as i32;
+
+    for step in 0..=steps {
+        let t = step as f32 / steps as f32;
+        let x = (from.x + dx * t).floor() as i32;
+        let y = (from.y + dy * t).floor() as i32;
+        let z = from.z + dz * t;
+
+        for k in 0..width {
+            let offset = k - (width - 1) / 2;
+            if x_major {
+                plot(fb, x, y + offset, z, rgba, depth_test);
+            } else {
+                plot(fb, x + offset, y, z, rgba, depth_test);
+            }
+        }
+    }
+}
+
+///
+/// fill a triangle; a pixel is covered when its center lies inside all three edges.
+///
+pub(crate) fn raster_triangle(fb: &mut FrameBuffer, a: &WindowVertex, b: &WindowVertex, c: &WindowVertex, rgba: (u8, u8, u8, u8), depth_test: bool) {
+    cover_triangle(fb, a, b, c, &mut |fb, x, y, [wa, wb, wc]| {
+        let z = a.z * wa + b.z * wb + c.z * wc;
+        plot(fb, x, y, z, rgba, depth_test);
+    });
+}
+
+///
+/// fill a triangle, blending the colour `shade` returns for each covered pixel's barycentric weights (a, b, c);
+/// none skips the pixel.
+///
+pub(crate) fn raster_triangle_shaded(fb: &mut FrameBuffer, a: &WindowVertex, b: &WindowVertex, c: &WindowVertex, shade: &dyn Fn([f32; 3]) -> Option<(u8, u8, u8, u8)>) {
+    cover_triangle(fb, a, b, c, &mut |fb, x, y, weights| {
+        if let Some(rgba) = shade(weights) {
+            fb.blend(x, y, rgba);
+        }
+    });
+}
+
+///
+/// called with each pixel a triangle covers, and that pixel's barycentric weights.
+///
+type CoverVisitor<'a> = dyn FnMut(&mut FrameBuffer, i32, i32, [f32; 3]) + 'a;
+
+fn cover_triangle(fb: &mut FrameBuffer, a: &WindowVertex, b: &WindowVertex, c: &WindowVertex, visit: &mut CoverVisitor) {
+    /* twice the signed area; skip degenerate triangles */
+    let area = edge(a, b, c.x, c.y);
+    if area == 0.0 {
+        return;
+    }
+
+    /* bounding box, clamped to the frame buffer */
+    let min_x = a.x.min(b.x).min(c.x).floor().max(0.0) as i32;
+    let min_y = a.y.min(b.y).min(c.y).floor().max(0.0) as i32;
+    let max_x = a.x.max(b.x).max(c.x).ceil().min(fb.width as f32) as i32;
+    let max_y = a.y.max(b.y).max(c.y).ceil().min(fb.height as f32) as i32;
+
+    for y in min_y..max_y {
+        for x in min_x..max_x {
+            let px = x as f32 + 0.5;
+            let py = y as f32 + 0.5;
+
+            /* barycentric weights; normalized so that winding order doesn't matter */
+            let wa = edge(b, c, px, py) / area;
+            let wb = edge(c, a, px, py) / area;
+            let wc = edge(a, b, px, py) / area;
+            if wa < 0.0 || wb < 0.0 || wc < 0.0 {
+                continue;
+            }
+
+            visit(fb, x, y, [wa, wb, wc]);
+        }
+    }
+}
+
+fn edge(from: &WindowVertex, to: &WindowVertex, x: f32, y: f32) -> f32 {
+    (to.x - from.x) * (y - from.y) - (to.y - from.y) * (x - from.x)
+}
+
+fn plot(fb: &mut FrameBuffer, x: i32, y: i32, z: f32, rgba: (u8, u8, u8, u8), depth_test: bool) {
+    if depth_test {
+        fb.put_depth(x, y, z, rgba);
+    } else {
+        fb.put(x, y, rgba);
+    }
+}
@@ -0,0 +1,228 @@
+//!
+//! software rasterizer: 2d rendering.
+//!
+//! 2d coordinates are already in pixels (origin top-left, y down), matching the
+//! orthographic projection used by the opengl fixed-function pipeline.
+//!
+
+use crate::geometry::primitive::prim2d::Primitive2D;
+use crate::geometry::primitive::v2d::Vertex2D;
+use crate::graphics::storage::g2d::Graph2D;
+use crate::graphics::subsystem::software::framebuffer::FrameBuffer;
+use crate::graphics::subsystem::software::raster::{raster_line, raster_point, raster_triangle, raster_triangle_shaded, WindowVertex};
+use crate::graphics::texture::filter::FilterMode;
+use crate::graphics::texture::sdf::DistanceField;
+use crate::graphics::texture::t2d::{TexturedQuad, Texture2D};
+use crate::support::image::RawImage;
+use crate::support::logger::log;
+use crate::support::logger::log_level::LogLevel;
+
+pub(crate) fn sw_2d_update_textures(g2d: &mut Graph2D) {
+    g2d.released.clear();                                                                       // nothing held outside the textures
+    for (_, model) in g2d.iter_mut() {
+        for texture in model.all_textures_mut() {
+            if !texture.initialized {
+                texture.initialized = true;
+                log(LogLevel::Info, &|| format!("created software texture, {}x{}", texture.image.width, texture.image.height));
+            }
+            if let Some(repl) = texture.replacement.take() {
+                texture.replace_image(repl);
+            }
+        }
+    }
+}
+
+pub(crate) fn sw_render_2d_points(fb: &mut FrameBuffer, primitive: &Primitive2D, point_size: f32) {
+    let rgba = primitive.color.to_u8();
+    for point in primitive.vertices.iter() {
+        raster_point(fb, &to_window(point), point_size, rgba, false);
+    }
+}
+
+pub(crate) fn sw_render_2d_lines(fb: &mut FrameBuffer, primitive: &Primitive2D, thickness: f32) {
+    let rgba = primitive.color.to_u8();
+    for pair in primitive.vertices.chunks_exact(2) {
+        raster_line(fb, &to_window(&pair[0]), &to_window(&pair[1]), thickness, rgba, false);
+    }
+}
+
+pub(crate) fn sw_render_2d_line_strip(fb: &mut FrameBuffer, primitive: &Primitive2D, thickness: f32) {
+    let rgba = primitive.color.to_u8();
+    for pair in primitive.vertices.windows(2) {
+        raster_line(fb, &to_window(&pair[0]), &to_window(&pair[1]), thickness, rgba, false);
+    }
+}
+
+///
+/// fill each quad with the primitive's color or, when it has a texture, with its uv rectangle tinted by that color.
+///
+pub(crate) fn sw_render_2d_quads(fb: &mut FrameBuffer, primitive: &Primitive2D, texture: Option<&Texture2D>) {
+    let rgba = primitive.color.to_u8();
+    let corners = primitive.texture.map(|texture| {
+        let uv = texture.uv;
+        [(uv.u0, uv.v0), (uv.u0, uv.v1), (uv.u1, uv.v1), (uv.u1, uv.v0)]                        // top-left, bottom-left, bottom-right, top-right
+    });
+    for quad in primitive.vertices.chunks_exact(4) {
+        let (a, b, c, d) = (to_window(&quad[0]), to_window(&quad[1]), to_window(&quad[2]), to_window(&quad[3]));
+        match (texture, corners) {
+            (Some(texture), Some(uv)) if texture.image.width > 0 && texture.image.height > 0 => {
+                let tint = [primitive.color.red, primitive.color.green, primitive.color.blue, primitive.color.alpha];
+                for (second, a, b, c, uv) in [(false, &a, &b, &c, [uv[0], uv[1], uv[2]]), (true, &a, &c, &d, [uv[0], uv[2], uv[3]])] {
+                    raster_triangle_shaded(fb, a, b, c, &|[wa, wb, wc]| {
+                        if second && wc == 0.0 {
+                            return None;                                                        // on the diagonal, already blended once
+                        }
+                        let u = uv[0].0 * wa + uv[1].0 * wb + uv[2].0 * wc;
+                        let v = uv[0].1 * wa + uv[1].1 * wb + uv[2].1 * wc;
+                        let texel = sample(&texture.image, u, v, texture.filter.mag);
+                        let [r, g, b, a] = std::array::from_fn(|i| (texel[i] * tint[i]).round().clamp(0.0, 255.0) as u8);
+                        Some((r, g, b, a))
+                    });
+                }
+            }
+            _ => {
+                raster_triangle(fb, &a, &b, &c, rgba, false);
+                raster_triangle(fb, &a, &c, &d, rgba, false);
+            }
+        }
+    }
+}
+
+///
+/// draw a texture (each of its sprites, or the whole image) using its filter settings, blended over the frame buffer.
+///
+pub(crate) fn sw_render_2d_texture(fb: &mut FrameBuffer, texture: &Texture2D) {
+    let image = &texture.image;
+    if image.width == 0 || image.height == 0 {
+        return;
+    }
+    let levels: Vec<&RawImage> = texture.levels().collect();
+    for quad in texture.quads() {
+        match texture.distance_field {
+            Some(field) => sw_render_2d_distance_field(fb, image, &field, &quad),
+            None => sw_render_2d_textured_quad(fb, texture, &levels, &quad),
+        }
+    }
+}
+
+///
+/// shade each pixel from the distance field (and its shadow), sampled bilinearly from the top level.
+///
+fn sw_render_2d_distance_field(fb: &mut FrameBuffer, image: &RawImage, field: &DistanceField, quad: &TexturedQuad) {
+    if quad.width <= 0.0 || quad.height <= 0.0 {
+        return;
+    }
+    let uv = quad.uv;
+    let scale = quad.width / ((uv.u1 - uv.u0) * image.width as f32);
+    let (shadow_u, shadow_v) = field.style.shadow
+        .map(|shadow| (shadow.offset_x / image.width as f32, shadow.offset_y / image.height as f32))
+        .unwrap_or((0.0, 0.0));
+    let distance = |u: f32, v: f32| field.distance(sample(image, u, v, FilterMode::Linear)[3] / 255.0);
+
+    let min_x = (quad.x - 0.5).ceil().max(0.0) as i32;
+    let min_y = (quad.y - 0.5).ceil().max(0.0) as i32;
+    let max_x = (quad.x + quad.width - 0.5).ceil().min(fb.width as f32) as i32;
+    let max_y = (quad.y + quad.height - 0.5).ceil().min(fb.height as f32) as i32;
+
+    for y in min_y..max_y {
+        let v = uv.v0 + (y as f32 + 0.5 - quad.y) / quad.height * (uv.v1 - uv.v0);
+        for x in min_x..max_x {
+            let u = uv.u0 + (x as f32 + 0.5 - quad.x) / quad.width * (uv.u1 - uv.u0);
+            let shadow = if field.style.shadow.is_some() { distance(u - shadow_u, v - shadow_v) } else { f32::MAX };
+            let [r, g, b, a] = field.shade(distance(u, v), shadow, scale);
+            if a > 0.0 {
+                let straight = |c: f32| (c / a * 255.0).round().clamp(0.0, 255.0) as u8;
+                fb.blend(x, y, (straight(r), straight(g), straight(b), (a * 255.0).round() as u8));
+            }
+        }
+    }
+}
+
+fn sw_render_2d_textured_quad(fb: &mut FrameBuffer, texture: &Texture2D, levels: &[&RawImage], quad: &TexturedQuad) {
+    /* gather variables */
+    if quad.width <= 0.0 || quad.height <= 0.0 {
+        return;
+    }
+    let uv = quad.uv;
+    let right = quad.x + quad.width;
+    let bottom = quad.y + quad.height;
+
+    /* texels per pixel decides minification vs magnification, and which mip levels to use */
+    let lod = ((uv.u1 - uv.u0) * texture.image.width as f32 / quad.width).log2();
+    let mode = if lod > 0.0 { texture.filter.min } else { texture.filter.mag };
+    let (level, next, fraction) = match texture.filter.mip {
+        Some(mip) if lod > 0.0 && levels.len() > 1 => {
+            let lod = lod.min((levels.len() - 1) as f32);
+            match mip {
+                FilterMode::Linear => (lod.floor() as usize, lod.ceil() as usize, lod.fract()),
+                FilterMode::Nearest => (lod.round() as usize, lod.round() as usize, 0.0),
+            }
+        }
+        _ => (0, 0, 0.0),
+    };
+
+    /* visit every pixel whose center falls inside the destination rectangle */
+    let min_x = (quad.x - 0.5).ceil().max(0.0) as i32;
+    let min_y = (quad.y - 0.5).ceil().max(0.0) as i32;
+    let max_x = (right - 0.5).ceil().min(fb.width as f32) as i32;
+    let max_y = (bottom - 0.5).ceil().min(fb.height as f32) as i32;
+
+    for y in min_y..max_y {
+        let v = uv.v0 + (y as f32 + 0.5 - quad.y) / quad.height * (uv.v1 - uv.v0);
+        for x in min_x..max_x {
+            let u = uv.u0 + (x as f32 + 0.5 - quad.x) / quad.width * (uv.u1 - uv.u0);
+            let mut texel = sample(levels[level], u, v, mode);
+            if next != level {
+                let other = sample(levels[next], u, v, mode);
+                texel = std::array::from_fn(|c| texel[c] + (other[c] - texel[c]) * fraction);
+            }
+            let [r, g, b, a] = texel.map(|c| c.round().clamp(0.0, 255.0) as u8);
+            fb.blend(x, y, (r, g, b, a));
+        }
+    }
+}
+
+///
+/// sample an rgba8 image at normalized coordinates, clamped to the edges; returns 0..255 channels.
+///
+/// linear sampling interpolates premultiplied colour, so transparent texels don't darken edges.
+///
+fn sample(image: &RawImage, u: f32, v: f32, mode: FilterMode) -> [f32; 4] {
+    let texel = |tx: i64, ty: i64| -> [f32; 4] {
+        let tx = tx.clamp(0, image.width as i64 - 1) as usize;
+        let ty = ty.clamp(0, image.height as i64 - 1) as usize;
+        let offset = (ty * image.width as usize + tx) * 4;
+        match image.data.get(offset..offset + 4) {
+            Some(p) => [p[0] as f32, p[1] as f32, p[2] as f32, p[3] as f32],
+            None => [0.0; 4],
+        }
+    };
+    let (fx, fy) = (u * image.width as f32, v * image.height as f32);
+    match mode {
+        FilterMode::Nearest => texel(fx.floor() as i64, fy.floor() as i64),
+        FilterMode::Linear => {
+            let (fx, fy) = (fx - 0.5, fy - 0.5);
+            let (x0, y0) = (fx.floor() as i64, fy.floor() as i64);
+            let (tx, ty) = (fx - fx.floor(), fy - fy.floor());
+            let mut sum = [0.0f32; 4];
+            for (sx, sy, weight) in [(x0, y0, (1.0 - tx) * (1.0 - ty)), (x0 + 1, y0, tx * (1.0 - ty)), (x0, y0 + 1, (1.0 - tx) * ty), (x0 + 1, y0 + 1, tx * ty)] {
+                let [r, g, b, a] = texel(sx, sy);
+                let premultiplied = a / 255.0 * weight;
+                sum[0] += r * premultiplied;
+                sum[1] += g * premultiplied;
+                sum[2] += b * premultiplied;
+                sum[3] += a * weight;
+            }
+            if sum[3] > 0.0 {
+                let alpha = sum[3] / 255.0;
+                [sum[0] / alpha, sum[1] / alpha, sum[2] / alpha, sum[3]]
+            } else {
+                [0.0; 4]
+            }
+        }
+    }
+}
+
+fn to_window(vertex: &Vertex2D) -> WindowVertex {
+    WindowVertex::new(vertex.x, vertex.y, 0.0)
+}
@@ -0,0 +1,220 @@
+//!
+//! software rasterizer: 3d rendering.
+//!
+//! mirrors the transforms of the opengl fixed-function pipeline (gluPerspective, camera
+//! rotate/translate, per-primitive translate/rotate/scale), then clips in homogeneous
+//! coordinates before mapping to window coordinates.
+//!
+
+use crate::geometry::angle::degrees::Degrees;
+use crate::geometry::orient::matrix::m4x4::mult::multiply;
+use crate::geometry::orient::matrix::m4x4::Matrix4x4;
+use crate::geometry::orient::Orientation;
+use crate::geometry::primitive::prim3d::Primitive3D;
+use crate::geometry::primitive::v3d::Vertex3D;
+use crate::graphics::camera::Camera;
+use crate::graphics::subsystem::software::framebuffer::FrameBuffer;
+use crate::graphics::subsystem::software::raster::{raster_line, raster_point, WindowVertex};
+
+///
+/// compute the combined projection and camera (view) matrix.
+///
+pub(crate) fn sw_3d_setup(camera: &Camera) -> Matrix4x4 {
+    let position = camera.orientation.position.column_major_position();
+    let projection = perspective(camera.projection.fov, camera.projection.to_aspect(), camera.projection.near, camera.projection.far);
+    let view = multiply(
+        &multiply(&rotate_x(-camera.orientation.pitch), &rotate_y(-camera.orientation.yaw)),
+        &translate(-position.x, -position.y, -position.z),
+    );
+    multiply(&projection, &view)
+}
+
+pub(crate) fn sw_3d_points(fb: &mut FrameBuffer, view: &Matrix4x4, primitive: &Primitive3D, point_size: f32) {
+    let mvp = model_view_projection(view, &primitive.orientation);
+    let rgba = primitive.color.to_u8();
+    for vertex in &primitive.vertices {
+        let clip = transform(&mvp, vertex);
+        if inside(&clip) {
+            raster_point(fb, &to_window(fb, &clip), point_size, rgba, true);
+        }
+    }
+}
+
+pub(crate) fn sw_3d_lines(fb: &mut FrameBuffer, view: &Matrix4x4, primitive: &Primitive3D, thickness: f32) {
+    let mvp = model_view_projection(view, &primitive.orientation);
+    let rgba = primitive.color.to_u8();
+    for pair in primitive.vertices.chunks_exact(2) {
+        draw_clipped_line(fb, &transform(&mvp, &pair[0]), &transform(&mvp, &pair[1]), thickness, rgba);
+    }
+}
+
+///
+/// quads are drawn as wireframe outlines, matching the fixed-function polygon mode.
+///
+pub(crate) fn sw_3d_quads(fb: &mut FrameBuffer, view: &Matrix4x4, primitive: &Primitive3D) {
+    let mvp = model_view_projection(view, &primitive.orientation);
+    let rgba = primitive.color.to_u8();
+    for quad in primitive.vertices.chunks_exact(4) {
+        let corners: Vec<[f32; 4]> = quad.iter().map(|v| transform(&mvp, v)).collect();
+        for idx in 0..4 {
+            draw_clipped_line(fb, &corners[idx], &corners[(idx + 1) % 4], 1.0, rgba);
+        }
+    }
+}
+
+fn model_view_projection(view: &Matrix4x4, orientation: &Orientation) -> Matrix4x4 {
+    let position = orientation.position.column_major_position();
+    let model = multiply(
+        &multiply(&translate(position.x, position.y, position.z), &rotate_x(orientation.pitch)),
+        &multiply(&rotate_y(orientation.yaw), &scale(orientation.x_scale, orientation.y_scale, orientation.z_scale)),
+    );
+    multiply(view, &model)
+}
+
+fn draw_clipped_line(fb: &mut FrameBuffer, from: &[f32; 4], to: &[f32; 4], thickness: f32, rgba: (u8, u8, u8, u8)) {
+    if let Some((a, b)) = clip_line(from, to) {
+        raster_line(fb, &to_window(fb, &a), &to_window(fb, &b), thickness, rgba, true);
+    }
+}
+
+///
+/// clip a line segment against the view volume (-w <= x,y,z <= w); liang-barsky in clip space.
+///
+fn clip_line(from: &[f32; 4], to: &[f32; 4]) -> Option<([f32; 4], [f32; 4])> {
+    let mut t0: f32 = 0.0;
+    let mut t1: f32 = 1.0;
+
+    for (axis, sign) in [(0, 1.0), (0, -1.0), (1, 1.0), (1, -1.0), (2, 1.0), (2, -1.0)] {
+        /* distance to the plane; positive is inside */
+        let d0 = from[3] + sign * from[axis];
+        let d1 = to[3] + sign * to[axis];
+
+        if d0 < 0.0 && d1 < 0.0 {
+            return None;
+        }
+        if d0 < 0.0 {
+            t0 = t0.max(d0 / (d0 - d1));
+        } else if d1 < 0.0 {
+            t1 = t1.min(d0 / (d0 - d1));
+        }
+        if t0 > t1 {
+            return None;
+        }
+    }
+
+    Some((lerp(from, to, t0), lerp(from, to, t1)))
+}
+
+fn lerp(from: &[f32; 4], to: &[f32; 4], t: f32) -> [f32; 4] {
+    [
+        from[0] + (to[0] - from[0]) * t,
+        from[1] + (to[1] - from[1]) * t,
+        from[2] + (to[2] - from[2]) * t,
+        from[3] + (to[3] - from[3]) * t,
+    ]
+}
+
+fn inside(clip: &[f32; 4]) -> bool {
+    let w = clip[3];
+    w > 0.0 && clip[0].abs() <= w && clip[1].abs() <= w && clip[2].abs() <= w
+}
+
+///
+/// perspective divide and viewport transform; window y is flipped so that row 0 is the top.
+///
+fn to_window(fb: &FrameBuffer, clip: &[f32; 4]) -> WindowVertex {
+    let x = clip[0] / clip[3];
+    let y = clip[1] / clip[3];
+    let z = clip[2] / clip[3];
+    WindowVertex::new(
+        (x + 1.0) * 0.5 * fb.width as f32,
+        (1.0 - y) * 0.5 * fb.height as f32,
+        (z + 1.0) * 0.5,
+    )
+}
+
+fn transform(matrix: &Matrix4x4, vertex: &Vertex3D) -> [f32; 4] {
+    [
+        matrix.c1r1 * vertex.x + matrix.c2r1 * vertex.y + matrix.c3r1 * vertex.z + matrix.c4r1,
+        matrix.c1r2 * vertex.x + matrix.c2r2 * vertex.y + matrix.c3r2 * vertex.z + matrix.c4r2,
+        matrix.c1r3 * vertex.x + matrix.c2r3 * vertex.y + matrix.c3r3 * vertex.z + matrix.c4r3,
+        matrix.c1r4 * vertex.x + matrix.c2r4 * vertex.y + matrix.c3r4 * vertex.z + matrix.c4r4,
+    ]
+}
+
+///
+/// equivalent of gluPerspective(); fov is in degrees.
+///
+fn perspective(fov: f32, aspect: f32, near: f32, far: f32) -> Matrix4x4 {
+    let f = 1.0 / (Degrees::new(fov).to_radians().radians / 2.0).tan();
+    Matrix4x4 {
+        c1r1: f / aspect,
+        c2r2: f,
+        c3r3: (far + near) / (near - far),
+        c3r4: -1.0,
+        c4r3: (2.0 * far * near) / (near - far),
+        c4r4: 0.0,
+        ..zero()
+    }
+}
+
+///
+/// equivalent of glTranslatef().
+///
+fn translate(x: f32, y: f32, z: f32) -> Matrix4x4 {
+    Matrix4x4 {
+        c4r1: x,
+        c4r2: y,
+        c4r3: z,
+        ..Matrix4x4::identity()
+    }
+}
+
+///
+/// equivalent of glScalef().
+///
+fn scale(x: f32, y: f32, z: f32) -> Matrix4x4 {
+    Matrix4x4 {
+        c1r1: x,
+        c2r2: y,
+        c3r3: z,
+        ..Matrix4x4::identity()
+    }
+}
+
+///
+/// equivalent of glRotatef(degrees, 1, 0, 0).
+///
+fn rotate_x(degrees: f32) -> Matrix4x4 {
+    let (sin, cos) = Degrees::new(degrees).to_radians().radians.sin_cos();
+    Matrix4x4 {
+        c2r2: cos,
+        c2r3: sin,
+        c3r2: -sin,
+        c3r3: cos,
+        ..Matrix4x4::identity()
+    }
+}
+
+///
+/// equivalent of glRotatef(degrees, 0, 1, 0).
+///
+fn rotate_y(degrees: f32) -> Matrix4x4 {
+    let (sin, cos) = Degrees::new(degrees).to_radians().radians.sin_cos();
+    Matrix4x4 {
+        c1r1: cos,
+        c1r3: -sin,
+        c3r1: sin,
+        c3r3: cos,
+        ..Matrix4x4::identity()
+    }
+}
+
+fn zero() -> Matrix4x4 {
+    Matrix4x4 {
+        c1r1: 0.0, c1r2: 0.0, c1r3: 0.0, c1r4: 0.0,
+        c2r1: 0.0, c2r2: 0.0, c2r3: 0.0, c2r4: 0.0,
+        c3r1: 0.0, c3r2: 0.0, c3r3: 0.0, c3r4: 0.0,
+        c4r1: 0.0, c4r2: 0.0, c4r3: 0.0, c4r4: 0.0,
+    }
+}
@@ -2,4 +2,8 @@
 //! 2d and 3d textures.
 //!
 
+pub mod atlas;
+pub mod filter;
+pub mod mipmap;
+pub mod sdf;
 pub mod t2d;
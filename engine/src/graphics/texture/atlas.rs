@@ -0,0 +1,272 @@
+//!
+//! texture atlas: many small images packed into a few large pages.
+//!
+//! each page becomes a single texture; the images on it are drawn as [Sprite2D]s
+//! that reference their [UvRect], so a page costs one upload and one bind.
+//!
+//! quad primitives can draw from a page too: add it to the model as a (usually hidden)
+//! texture, and give each primitive a [PrimitiveTexture] with its region's uv rectangle.
+//!
+
+use crate::graphics::texture::atlas::skyline::SkylinePacker;
+use crate::geometry::primitive::prim2d::PrimitiveTexture;
+use crate::graphics::texture::t2d::{Sprite2D, Texture2D, Texture2DBuilder};
+use crate::support::image::pixel::PixelFormat;
+use crate::support::image::RawImage;
+use crate::support::logger::log;
+use crate::support::logger::log_level::LogLevel;
+use std::collections::HashMap;
+use std::io;
+use std::io::{Error, ErrorKind};
+
+pub mod skyline;
+
+///
+/// normalized texture coordinates of a rectangle; (u0, v0) is the top-left corner.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct UvRect {
+    pub u0: f32,
+    pub v0: f32,
+    pub u1: f32,
+    pub v1: f32,
+}
+
+impl UvRect {
+    pub const FULL: UvRect = UvRect { u0: 0.0, v0: 0.0, u1: 1.0, v1: 1.0 };
+}
+
+///
+/// where an image ended up: its page, pixel rectangle, and uv rectangle on that page.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AtlasRegion {
+    pub page: usize,
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    pub uv: UvRect,
+}
+
+impl AtlasRegion {
+    ///
+    /// a sprite drawing this region at the given position.
+    ///
+    pub fn sprite(&self, x: f32, y: f32, scale: f32) -> Sprite2D {
+        Sprite2D::new(self.uv, x, y, scale)
+    }
+
+    ///
+    /// a quad primitive's texture reference to this region, on the page at `index` in the model's textures.
+    ///
+    pub fn primitive_texture(&self, index: usize) -> PrimitiveTexture {
+        PrimitiveTexture { index, uv: self.uv }
+    }
+}
+
+pub struct TextureAtlas {
+    pub pages: Vec<RawImage>,
+    page_width: u32,
+    page_height: u32,
+    padding: u32,
+    packers: Vec<SkylinePacker>,
+    regions: HashMap<String, AtlasRegion>,
+}
+
+pub struct TextureAtlasBuilder {
+    the_page_width: Option<u32>,
+    the_page_height: Option<u32>,
+    the_padding: Option<u32>,
+    the_images: Vec<(String, RawImage)>,
+}
+
+impl TextureAtlas {
+    pub fn new(page_width: u32, page_height: u32, padding: u32) -> TextureAtlas {
+        TextureAtlas {
+            pages: vec![],
+            page_width,
+            page_height,
+            padding,
+            packers: vec![],
+            regions: HashMap::new(),
+        }
+    }
+
+    ///
+    /// pack an image onto the first page with room, opening a new page if necessary.
+    ///
+    /// returns none if the image (plus padding) is bigger than a page; re-inserting a name replaces its region.
+    ///
+    pub fn insert(&mut self, name: &str, image: &RawImage) -> Option<AtlasRegion> {
+        let padded = image.width.checked_add(self.padding).zip(image.height.checked_add(self.padding));
+        let (padded_width, padded_height) = match padded {
+            Some((width, height)) if width <= self.page_width && height <= self.page_height => (width, height),
+            _ => {
+                log(LogLevel::Warning, &|| format!("image [{}] ({}x{}) doesn't fit in an atlas page", name, image.width, image.height));
+                return None;
+            }
+        };
+
+        /* find room, or start a new page */
+        let placed = self.packers.iter_mut().enumerate().find_map(|(page, packer)| packer.pack(padded_width, padded_height).map(|(x, y)| (page, x, y)));
+        let (page, x, y) = match placed {
+            Some(placed) => placed,
+            None => {
+                let mut packer = SkylinePacker::new(self.page_width, self.page_height);
+                let (x, y) = packer.pack(padded_width, padded_height)?;
+                self.packers.push(packer);
+                self.pages.push(RawImage::new(self.page_width, self.page_height, vec![0; self.page_width as usize * self.page_height as usize * 4]));
+                log(LogLevel::Debug, &|| format!("created atlas page {}", self.pages.len()));
+                (self.pages.len() - 1, x, y)
+            }
+        };
+
+        /* copy pixels; padding stays transparent */
+        self.pages[page].paste(&image.convert(PixelFormat::Rgba8), x as i32, y as i32);
+        let region = AtlasRegion {
+            page,
+            x,
+            y,
+            width: image.width,
+            height: image.height,
+            uv: UvRect {
+                u0: x as f32 / self.page_width as f32,
+                v0: y as f32 / self.page_height as f32,
+                u1: (x + image.width) as f32 / self.page_width as f32,
+                v1: (y + image.height) as f32 / self.page_height as f32,
+            },
+        };
+        self.regions.insert(name.to_string(), region);
+        Some(region)
+    }
+
+    pub fn region(&self, name: &str) -> Option<&AtlasRegion> {
+        self.regions.get(name)
+    }
+
+    ///
+    /// a texture for one page, drawing the given sprites from it.
+    ///
+    pub fn texture(&self, page: usize, sprites: Vec<Sprite2D>) -> Texture2D {
+        sprites.into_iter()
+            .fold(Texture2DBuilder::new().with_image(self.pages[page].clone()), |builder, sprite| builder.with_sprite(sprite))
+            .build()
+    }
+
+    ///
+    /// a texture for one page that isn't drawn itself, only through primitives that reference it.
+    ///
+    pub fn page_texture(&self, page: usize) -> Texture2D {
+        Texture2DBuilder::new().with_image(self.pages[page].clone()).hidden().build()
+    }
+}
+
+impl TextureAtlasBuilder {
+    pub fn new() -> TextureAtlasBuilder {
+        TextureAtlasBuilder {
+            the_page_width: None,
+            the_page_height: None,
+            the_padding: None,
+            the_images: vec![],
+        }
+    }
+
+    pub fn with_page_size(mut self, width: u32, height: u32) -> Self {
+        self.the_page_width = Some(width);
+        self.the_page_height = Some(height);
+        self
+    }
+
+    pub fn with_padding(mut self, padding: u32) -> Self {
+        self.the_padding = Some(padding);
+        self
+    }
+
+    pub fn with_image(mut self, name: &str, image: RawImage) -> Self {
+        self.the_images.push((name.to_string(), image));
+        self
+    }
+
+    ///
+    /// pack everything, tallest first (which packs noticeably tighter than insertion order).
+    ///
+    /// fails, naming them, if any images don't fit in a page.
+    ///
+    pub fn build(mut self) -> io::Result<TextureAtlas> {
+        let mut atlas = TextureAtlas::new(self.the_page_width.unwrap_or(1024), self.the_page_height.unwrap_or(1024), self.the_padding.unwrap_or(1));
+        self.the_images.sort_by(|a, b| b.1.height.cmp(&a.1.height).then(b.1.width.cmp(&a.1.width)));
+        let rejected: Vec<&str> = self.the_images.iter()
+            .filter(|(name, image)| atlas.insert(name, image).is_none())
+            .map(|(name, _)| name.as_str())
+            .collect();
+        if !rejected.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidInput, format!("atlas: images bigger than a page: {}", rejected.join(", "))));
+        }
+        Ok(atlas)
+    }
+}
+
+impl Default for TextureAtlasBuilder {
+    fn default() -> Self {
+        TextureAtlasBuilder::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graphics::texture::atlas::{TextureAtlas, TextureAtlasBuilder, UvRect};
+    use crate::support::image::RawImage;
+
+    fn solid(width: u32, height: u32, value: u8) -> RawImage {
+        RawImage::new(width, height, vec![value; (width * height * 4) as usize])
+    }
+
+    #[test]
+    fn test_regions_and_pixels() {
+        let atlas = TextureAtlasBuilder::new()
+            .with_page_size(16, 8)
+            .with_padding(1)
+            .with_image("small", solid(2, 2, 50))
+            .with_image("tall", solid(3, 6, 100))
+            .build()
+            .unwrap();
+        assert_eq!(1, atlas.pages.len());
+
+        /* the tall one is packed first */
+        let tall = atlas.region("tall").unwrap();
+        assert_eq!((0, 0, 0), (tall.page, tall.x, tall.y));
+        assert_eq!(UvRect { u0: 0.0, v0: 0.0, u1: 3.0 / 16.0, v1: 6.0 / 8.0 }, tall.uv);
+
+        let small = atlas.region("small").unwrap();
+        assert_eq!((4, 0), (small.x, small.y));
+        let page = &atlas.pages[0];
+        assert_eq!(50, page.data[(4 * 4) as usize]);
+        assert_eq!(0, page.data[(3 * 4) as usize]);                                                 // padding column
+        assert!(atlas.region("missing").is_none());
+
+        /* images that can't be packed fail the build */
+        let result = TextureAtlasBuilder::new()
+            .with_page_size(4, 4)
+            .with_image("fits", solid(2, 2, 1))
+            .with_image("wide", solid(5, 1, 2))
+            .with_image("tall", solid(1, 5, 3))
+            .build();
+        assert!(result.is_err_and(|e| e.to_string() == "atlas: images bigger than a page: tall, wide"));
+    }
+
+    #[test]
+    fn test_pages() {
+        let mut atlas = TextureAtlas::new(8, 8, 0);
+        assert_eq!(0, atlas.insert("a", &solid(8, 8, 1)).unwrap().page);
+        assert_eq!(1, atlas.insert("b", &solid(4, 4, 2)).unwrap().page);
+        assert_eq!(1, atlas.insert("c", &solid(4, 4, 3)).unwrap().page);
+        assert!(atlas.insert("huge", &solid(9, 1, 4)).is_none());
+        assert_eq!(2, atlas.pages.len());
+
+        /* padding can't wrap a huge size back round to something that fits */
+        let mut atlas = TextureAtlas::new(8, 8, 2);
+        assert!(atlas.insert("wraps", &RawImage::new(u32::MAX, 1, vec![])).is_none());
+        assert!(atlas.pages.is_empty());
+    }
+}
@@ -0,0 +1,147 @@
+///
+/// one horizontal run of the skyline: everything below `y` between `x` and `x + width` is taken.
+///
+#[derive(Clone, Copy, Debug)]
+struct Segment {
+    x: u32,
+    y: u32,
+    width: u32,
+}
+
+///
+/// skyline rectangle packer, bottom-left heuristic.
+///
+/// rectangles are placed as low as possible (smallest resulting top edge), ties going
+/// to the narrowest segment; space hidden under an overhang is never reclaimed.
+///
+#[derive(Clone, Debug)]
+pub struct SkylinePacker {
+    width: u32,
+    height: u32,
+    skyline: Vec<Segment>,
+}
+
+impl SkylinePacker {
+    pub fn new(width: u32, height: u32) -> SkylinePacker {
+        SkylinePacker {
+            width,
+            height,
+            skyline: vec![Segment { x: 0, y: 0, width }],
+        }
+    }
+
+    ///
+    /// reserve a rectangle; returns its top-left corner, or none if it doesn't fit.
+    ///
+    pub fn pack(&mut self, width: u32, height: u32) -> Option<(u32, u32)> {
+        if width == 0 || height == 0 {
+            return Some((0, 0));
+        }
+
+        /* find the best segment to start at */
+        let mut best: Option<(usize, u32)> = None;
+        let mut best_key = (u32::MAX, u32::MAX);
+        for idx in 0..self.skyline.len() {
+            if let Some(y) = self.fit(idx, width, height) {
+                let key = (y + height, self.skyline[idx].width);
+                if key < best_key {
+                    best_key = key;
+                    best = Some((idx, y));
+                }
+            }
+        }
+
+        /* raise the skyline over the new rectangle */
+        let (idx, y) = best?;
+        let x = self.skyline[idx].x;
+        self.add(idx, x, y, width, height);
+        Some((x, y))
+    }
+
+    ///
+    /// the y at which a rectangle starting at the given segment would rest, if it fits.
+    ///
+    fn fit(&self, idx: usize, width: u32, height: u32) -> Option<u32> {
+        let x = self.skyline[idx].x;
+        if x + width > self.width {
+            return None;
+        }
+        let mut y = 0;
+        let mut remaining = width as i64;
+        for segment in &self.skyline[idx..] {
+            if remaining <= 0 {
+                break;
+            }
+            y = y.max(segment.y);
+            if y + height > self.height {
+                return None;
+            }
+            remaining -= segment.width as i64;
+        }
+        Some(y)
+    }
+
+    fn add(&mut self, idx: usize, x: u32, y: u32, width: u32, height: u32) {
+        self.skyline.insert(idx, Segment { x, y: y + height, width });
+
+        /* trim or drop the segments now underneath */
+        let right = x + width;
+        while idx + 1 < self.skyline.len() {
+            let next = &mut self.skyline[idx + 1];
+            if next.x >= right {
+                break;
+            }
+            let covered = right - next.x;
+            if next.width <= covered {
+                self.skyline.remove(idx + 1);
+            } else {
+                next.x += covered;
+                next.width -= covered;
+                break;
+            }
+        }
+
+        /* merge neighbours at the same height */
+        let mut i = 0;
+        while i + 1 < self.skyline.len() {
+            if self.skyline[i].y == self.skyline[i + 1].y {
+                self.skyline[i].width += self.skyline[i + 1].width;
+                self.skyline.remove(i + 1);
+            } else {
+                i += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graphics::texture::atlas::skyline::SkylinePacker;
+
+    #[test]
+    fn test_no_overlap() {
+        let mut packer = SkylinePacker::new(64, 64);
+        let mut placed: Vec<(u32, u32, u32, u32)> = vec![];
+        for i in 0..40u32 {
+            let (w, h) = (3 + (i * 7) % 11, 2 + (i * 5) % 9);
+            if let Some((x, y)) = packer.pack(w, h) {
+                assert!(x + w <= 64 && y + h <= 64);
+                for &(ox, oy, ow, oh) in &placed {
+                    assert!(x >= ox + ow || ox >= x + w || y >= oy + oh || oy >= y + h, "overlap at {},{}", x, y);
+                }
+                placed.push((x, y, w, h));
+            }
+        }
+        assert!(placed.len() > 30);
+    }
+
+    #[test]
+    fn test_fills_lowest_first() {
+        let mut packer = SkylinePacker::new(10, 10);
+        assert_eq!(Some((0, 0)), packer.pack(6, 4));
+        assert_eq!(Some((6, 0)), packer.pack(4, 2));
+        assert_eq!(Some((6, 2)), packer.pack(4, 2));
+        assert_eq!(Some((0, 4)), packer.pack(10, 6));
+        assert_eq!(None, packer.pack(1, 1));
+    }
+}
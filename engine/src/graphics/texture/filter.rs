@@ -0,0 +1,33 @@
+///
+/// how a single texture level is sampled.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FilterMode {
+    Nearest,
+    Linear,
+}
+
+///
+/// sampling settings for a texture.
+///
+/// `min` applies when the texture is drawn smaller than its size, `mag` when larger;
+/// `mip` chooses between mip levels, and is ignored for textures without a mip chain.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TextureFilter {
+    pub min: FilterMode,
+    pub mag: FilterMode,
+    pub mip: Option<FilterMode>,
+}
+
+impl TextureFilter {
+    pub const NEAREST: TextureFilter = TextureFilter { min: FilterMode::Nearest, mag: FilterMode::Nearest, mip: None };
+    pub const BILINEAR: TextureFilter = TextureFilter { min: FilterMode::Linear, mag: FilterMode::Linear, mip: None };
+    pub const TRILINEAR: TextureFilter = TextureFilter { min: FilterMode::Linear, mag: FilterMode::Linear, mip: Some(FilterMode::Linear) };
+}
+
+impl Default for TextureFilter {
+    fn default() -> Self {
+        TextureFilter::NEAREST
+    }
+}
@@ -0,0 +1,203 @@
+//!
+//! mip chain generation.
+//!
+//! each level halves the previous one (rounding down, never below 1) until 1x1.
+//! filtering happens in linear light with premultiplied alpha, so that dark
+//! and transparent texels don't bleed into their neighbours as the image shrinks.
+//!
+
+use crate::support::image::pixel::PixelFormat;
+use crate::support::image::RawImage;
+use std::f32::consts::PI;
+
+///
+/// the downsampling filter used to build each mip level.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MipmapFilter {
+    Box,                                                                                        // 2x2 average; cheap, slightly blurry
+    Kaiser,                                                                                     // kaiser-windowed sinc; sharper, costs more
+}
+
+/* kaiser window parameters, in units of destination texels */
+static KAISER_WIDTH: f32 = 3.0;
+static KAISER_ALPHA: f32 = 4.0;
+
+///
+/// build every level below the base image, largest first; the base image itself is not included.
+///
+pub fn generate_mipmaps(image: &RawImage, filter: MipmapFilter) -> Vec<RawImage> {
+    let mut levels = vec![];
+    if image.width == 0 || image.height == 0 {
+        return levels;
+    }
+
+    let (mut width, mut height) = (image.width as usize, image.height as usize);
+    let mut pixels = to_linear(image);
+    while width > 1 || height > 1 {
+        let (next_width, next_height) = ((width / 2).max(1), (height / 2).max(1));
+        pixels = match filter {
+            MipmapFilter::Box => downsample_box(&pixels, width, height, next_width, next_height),
+            MipmapFilter::Kaiser => {
+                let horizontal = downsample_kaiser(&pixels, width, height, next_width, true);
+                downsample_kaiser(&horizontal, next_width, height, next_height, false)
+            }
+        };
+        (width, height) = (next_width, next_height);
+        levels.push(from_linear(&pixels, width as u32, height as u32));
+    }
+    levels
+}
+
+///
+/// average the (up to) 2x2 block of source texels under each destination texel.
+///
+fn downsample_box(pixels: &[[f32; 4]], width: usize, height: usize, next_width: usize, next_height: usize) -> Vec<[f32; 4]> {
+    let mut output = Vec::with_capacity(next_width * next_height);
+    for y in 0..next_height {
+        let rows = [(y * 2).min(height - 1), (y * 2 + 1).min(height - 1)];
+        for x in 0..next_width {
+            let columns = [(x * 2).min(width - 1), (x * 2 + 1).min(width - 1)];
+            let mut sum = [0.0f32; 4];
+            for row in rows {
+                for column in columns {
+                    let pixel = pixels[row * width + column];
+                    sum.iter_mut().zip(pixel).for_each(|(s, p)| *s += p);
+                }
+            }
+            output.push(sum.map(|s| s / 4.0));
+        }
+    }
+    output
+}
+
+///
+/// halve one axis with a kaiser-windowed sinc; edges are clamped.
+///
+fn downsample_kaiser(pixels: &[[f32; 4]], width: usize, height: usize, size: usize, horizontal: bool) -> Vec<[f32; 4]> {
+    let (source_size, lines) = if horizontal { (width, height) } else { (height, width) };
+    let ratio = source_size as f32 / size as f32;
+    let support = KAISER_WIDTH * ratio;
+
+    let (out_width, out_height) = if horizontal { (size, height) } else { (width, size) };
+    let mut output = vec![[0.0f32; 4]; out_width * out_height];
+    for dst in 0..size {
+        /* weights for this destination texel, over a clamped source range */
+        let center = (dst as f32 + 0.5) * ratio;
+        let first = (center - support).floor() as isize;
+        let last = (center + support).ceil() as isize;
+        let taps: Vec<(usize, f32)> = (first..last)
+            .map(|src| (src.clamp(0, source_size as isize - 1) as usize, kaiser((src as f32 + 0.5 - center) / ratio)))
+            .collect();
+        let total: f32 = taps.iter().map(|(_, w)| w).sum();
+
+        for line in 0..lines {
+            let mut sum = [0.0f32; 4];
+            for (src, weight) in taps.iter() {
+                let pixel = if horizontal { pixels[line * width + src] } else { pixels[src * width + line] };
+                sum.iter_mut().zip(pixel).for_each(|(s, p)| *s += p * weight / total);
+            }
+            let idx = if horizontal { line * size + dst } else { dst * width + line };
+            output[idx] = sum.map(|s| s.max(0.0));                                             // negative lobes can undershoot
+        }
+    }
+    output
+}
+
+fn kaiser(x: f32) -> f32 {
+    if x.abs() >= KAISER_WIDTH {
+        return 0.0;
+    }
+    let sinc = if x.abs() < 1e-6 { 1.0 } else { (PI * x).sin() / (PI * x) };
+    let t = x / KAISER_WIDTH;
+    sinc * bessel_i0(KAISER_ALPHA * (1.0 - t * t).sqrt()) / bessel_i0(KAISER_ALPHA)
+}
+
+///
+/// modified bessel function of the first kind, order 0; power series.
+///
+fn bessel_i0(x: f32) -> f32 {
+    let mut sum = 1.0;
+    let mut term = 1.0;
+    let half_sq = x * x / 4.0;
+    for k in 1..32 {
+        term *= half_sq / (k * k) as f32;
+        sum += term;
+        if term < sum * 1e-8 {
+            break;
+        }
+    }
+    sum
+}
+
+fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 { value / 12.92 } else { ((value + 0.055) / 1.055).powf(2.4) }
+}
+
+fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 { value * 12.92 } else { 1.055 * value.powf(1.0 / 2.4) - 0.055 }
+}
+
+///
+/// decode to linear light, with premultiplied alpha.
+///
+fn to_linear(image: &RawImage) -> Vec<[f32; 4]> {
+    image.to_rgba_f32().into_iter()
+        .map(|[r, g, b, a]| [srgb_to_linear(r) * a, srgb_to_linear(g) * a, srgb_to_linear(b) * a, a])
+        .collect()
+}
+
+fn from_linear(pixels: &[[f32; 4]], width: u32, height: u32) -> RawImage {
+    let straight: Vec<[f32; 4]> = pixels.iter()
+        .map(|&[r, g, b, a]| {
+            if a > 0.0 {
+                [linear_to_srgb(r / a), linear_to_srgb(g / a), linear_to_srgb(b / a), a]
+            } else {
+                [0.0, 0.0, 0.0, 0.0]
+            }
+        })
+        .collect();
+    RawImage::from_rgba_f32(width, height, PixelFormat::Rgba8, &straight)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graphics::texture::mipmap::{generate_mipmaps, MipmapFilter};
+    use crate::support::image::RawImage;
+
+    #[test]
+    fn test_chain_sizes() {
+        let image = RawImage::new(5, 2, vec![255; 5 * 2 * 4]);
+        let sizes: Vec<(u32, u32)> = generate_mipmaps(&image, MipmapFilter::Box).iter().map(|m| (m.width, m.height)).collect();
+        assert_eq!(vec![(2, 1), (1, 1)], sizes);
+        assert!(generate_mipmaps(&RawImage::new(1, 1, vec![0; 4]), MipmapFilter::Box).is_empty());
+    }
+
+    #[test]
+    fn test_box_is_gamma_correct() {
+        /* black and white average to ~188 in srgb, not 128 */
+        let image = RawImage::new(2, 1, vec![0, 0, 0, 255, 255, 255, 255, 255]);
+        let levels = generate_mipmaps(&image, MipmapFilter::Box);
+        assert_eq!(vec![188, 188, 188, 255], levels[0].data);
+    }
+
+    #[test]
+    fn test_transparent_texels_dont_bleed() {
+        let image = RawImage::new(2, 2, vec![
+            255, 0, 0, 255, 0, 255, 0, 0,
+            0, 255, 0, 0, 0, 255, 0, 0,
+        ]);
+        for filter in [MipmapFilter::Box, MipmapFilter::Kaiser] {
+            let level = &generate_mipmaps(&image, filter)[0];
+            assert_eq!(vec![255, 0, 0, 64], level.data, "{:?}", filter);
+        }
+    }
+
+    #[test]
+    fn test_kaiser_keeps_flat_color() {
+        let image = RawImage::new(8, 4, [30, 90, 200, 255].repeat(32));
+        let levels = generate_mipmaps(&image, MipmapFilter::Kaiser);
+        assert_eq!(3, levels.len());
+        assert!(levels.iter().all(|level| level.data.chunks_exact(4).all(|p| p == [30, 90, 200, 255])));
+    }
+}
@@ -0,0 +1,251 @@
+//!
+//! signed distance fields: textures whose alpha is the distance to a shape's edge, rather than its coverage.
+//!
+//! a field stays sharp when magnified, and one field can be drawn with any colour, outline, glow or shadow.
+//! alpha 0.5 is the edge; alpha falls to 0 at `spread` texels outside it, and rises to 1 at `spread` inside.
+//!
+
+use crate::graphics::color::Color;
+use crate::support::image::pixel::PixelFormat;
+use crate::support::image::RawImage;
+
+/* stands in for infinity in the (squared) distance transform, without producing inf - inf */
+static FAR: f32 = 1e20;
+
+///
+/// a band around the shape: an outline (its width) or a glow (how far it fades out), in field texels.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SdfEffect {
+    pub color: Color,
+    pub width: f32,
+}
+
+///
+/// a copy of the shape (and its outline) drawn underneath, offset in field texels; softness blurs its edge.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SdfShadow {
+    pub color: Color,
+    pub offset_x: f32,
+    pub offset_y: f32,
+    pub softness: f32,
+}
+
+///
+/// how to draw a distance field; effect sizes are in field texels, so they scale with the sprite.
+///
+/// effects can't reach further than the field's spread.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SdfStyle {
+    pub fill: Color,
+    pub outline: Option<SdfEffect>,
+    pub glow: Option<SdfEffect>,
+    pub shadow: Option<SdfShadow>,
+}
+
+///
+/// marks a texture as a distance field with the given spread, and how to draw it.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DistanceField {
+    pub spread: f32,
+    pub style: SdfStyle,
+}
+
+impl SdfStyle {
+    pub fn fill(color: Color) -> SdfStyle {
+        SdfStyle {
+            fill: color,
+            outline: None,
+            glow: None,
+            shadow: None,
+        }
+    }
+}
+
+impl DistanceField {
+    ///
+    /// signed distance (positive outside) in texels, from a field value in 0..1.
+    ///
+    pub(crate) fn distance(&self, value: f32) -> f32 {
+        (0.5 - value) * 2.0 * self.spread
+    }
+
+    ///
+    /// the field value at a signed distance in texels.
+    ///
+    pub(crate) fn value(&self, distance: f32) -> f32 {
+        (0.5 - distance / (2.0 * self.spread)).clamp(0.0, 1.0)
+    }
+
+    ///
+    /// the colour of one pixel, premultiplied (0..1), given distances at it and at its shadow; `scale` is pixels per texel.
+    ///
+    /// layers, bottom up: shadow, glow, outline, fill; edges are anti-aliased over one pixel.
+    ///
+    pub(crate) fn shade(&self, distance: f32, shadow_distance: f32, scale: f32) -> [f32; 4] {
+        let style = &self.style;
+        let ramp = |d: f32, edge: f32, softness: f32| (0.5 - (d - edge) / softness.max(1.0 / scale)).clamp(0.0, 1.0);
+        let edge = style.outline.map(|outline| outline.width).unwrap_or(0.0);
+
+        let mut layers: Vec<(Color, f32)> = vec![];
+        if let Some(shadow) = style.shadow {
+            layers.push((shadow.color, ramp(shadow_distance, edge, shadow.softness)));
+        }
+        if let Some(glow) = style.glow {
+            layers.push((glow.color, (1.0 - (distance - edge) / glow.width.max(f32::EPSILON)).clamp(0.0, 1.0)));
+        }
+        if let Some(outline) = style.outline {
+            layers.push((outline.color, ramp(distance, outline.width, 0.0)));
+        }
+        layers.push((style.fill, ramp(distance, 0.0, 0.0)));
+
+        layers.into_iter().fold([0.0; 4], |below, (color, coverage)| {
+            let alpha = color.alpha * coverage;
+            [
+                color.red * alpha + below[0] * (1.0 - alpha),
+                color.green * alpha + below[1] * (1.0 - alpha),
+                color.blue * alpha + below[2] * (1.0 - alpha),
+                alpha + below[3] * (1.0 - alpha),
+            ]
+        })
+    }
+}
+
+///
+/// build a distance field from a shape's coverage: an l8 image's luminance, or any other image's alpha.
+///
+/// the field is padded by the spread on every side, so effects have room; it's la8, white with the field in alpha.
+///
+pub fn distance_field(shape: &RawImage, spread: f32) -> RawImage {
+    let spread = spread.max(1.0);
+    let pad = spread.ceil() as usize;
+    let (width, height) = (shape.width as usize + pad * 2, shape.height as usize + pad * 2);
+
+    /* coverage, on the padded grid */
+    let source: Vec<u8> = match shape.format {
+        PixelFormat::L8 => shape.data.clone(),
+        _ => shape.as_rgba8().map(|rgba| rgba.data.chunks_exact(4).map(|p| p[3]).collect()).unwrap_or_default(),
+    };
+    let mut coverage = vec![0u8; width * height];
+    for (y, row) in source.chunks_exact(shape.width.max(1) as usize).enumerate() {
+        coverage[(y + pad) * width + pad..][..row.len()].copy_from_slice(row);
+    }
+
+    /* distances to the nearest pixel inside, and to the nearest outside */
+    let inside = |c: u8| c >= 128;
+    let mut to_inside: Vec<f32> = coverage.iter().map(|c| if inside(*c) { 0.0 } else { FAR }).collect();
+    let mut to_outside: Vec<f32> = coverage.iter().map(|c| if inside(*c) { FAR } else { 0.0 }).collect();
+    squared_distances(&mut to_inside, width, height);
+    squared_distances(&mut to_outside, width, height);
+
+    /* the edge runs between pixel centers; partly covered pixels know where it is more precisely */
+    let field = DistanceField { spread, style: SdfStyle::fill(Color::WHITE) };
+    let mut data = Vec::with_capacity(width * height * 2);
+    for idx in 0..width * height {
+        let distance = match coverage[idx] {
+            c if c > 0 && c < 255 => 0.5 - c as f32 / 255.0,
+            c if inside(c) => 0.5 - to_outside[idx].sqrt(),
+            _ => to_inside[idx].sqrt() - 0.5,
+        };
+        data.extend_from_slice(&[255, (field.value(distance) * 255.0).round() as u8]);
+    }
+    RawImage::with_format(width as u32, height as u32, PixelFormat::La8, data)
+}
+
+///
+/// exact euclidean distance transform (felzenszwalb & huttenlocher): zeros are the features, and every
+/// value becomes the squared distance to the nearest one; columns first, then rows.
+///
+fn squared_distances(grid: &mut [f32], width: usize, height: usize) {
+    let longest = width.max(height);
+    let mut line = vec![0.0f32; longest];
+    let mut out = vec![0.0f32; longest];
+    let mut hull = vec![0usize; longest];
+    let mut bounds = vec![0.0f32; longest + 1];
+
+    for x in 0..width {
+        (0..height).for_each(|y| line[y] = grid[y * width + x]);
+        transform_1d(&line[..height], &mut out, &mut hull, &mut bounds);
+        (0..height).for_each(|y| grid[y * width + x] = out[y]);
+    }
+    for y in 0..height {
+        line[..width].copy_from_slice(&grid[y * width..(y + 1) * width]);
+        transform_1d(&line[..width], &mut out, &mut hull, &mut bounds);
+        grid[y * width..(y + 1) * width].copy_from_slice(&out[..width]);
+    }
+}
+
+///
+/// lower envelope of the parabolas rooted at each sample.
+///
+fn transform_1d(f: &[f32], out: &mut [f32], hull: &mut [usize], bounds: &mut [f32]) {
+    let n = f.len();
+    if n == 0 {
+        return;
+    }
+    let intersect = |q: usize, p: usize| ((f[q] + (q * q) as f32) - (f[p] + (p * p) as f32)) / (2.0 * q as f32 - 2.0 * p as f32);
+
+    let mut k = 0;
+    hull[0] = 0;
+    bounds[0] = -FAR;
+    bounds[1] = FAR;
+    for q in 1..n {
+        let mut s = intersect(q, hull[k]);
+        while k > 0 && s <= bounds[k] {
+            k -= 1;
+            s = intersect(q, hull[k]);
+        }
+        k += 1;
+        hull[k] = q;
+        bounds[k] = s;
+        bounds[k + 1] = FAR;
+    }
+
+    k = 0;
+    for (q, value) in out.iter_mut().enumerate().take(n) {
+        while bounds[k + 1] < q as f32 {
+            k += 1;
+        }
+        let dq = q as f32 - hull[k] as f32;
+        *value = dq * dq + f[hull[k]];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graphics::color::Color;
+    use crate::graphics::texture::sdf::{distance_field, DistanceField, SdfEffect, SdfStyle};
+    use crate::support::image::pixel::PixelFormat;
+    use crate::support::image::RawImage;
+
+    #[test]
+    fn test_distance_field() {
+        /* a 4x4 square in the middle of an 8x8 mask */
+        let mask: Vec<u8> = (0..64).map(|i| if (2..6).contains(&(i % 8)) && (2..6).contains(&(i / 8)) { 255 } else { 0 }).collect();
+        let field = distance_field(&RawImage::with_format(8, 8, PixelFormat::L8, mask), 4.0);
+        assert_eq!((16, 16, PixelFormat::La8), (field.width, field.height, field.format));
+
+        let value = |x: usize, y: usize| field.data[(y * 16 + x) * 2 + 1];
+        assert_eq!(175, value(7, 7));                                                               // 1.5 inside: 0.5 + 1.5 / 8
+        assert_eq!(143, value(6, 7));                                                               // the square's edge pixel
+        assert_eq!(112, value(5, 7));                                                               // 0.5 outside
+        assert_eq!(16, value(2, 7));                                                                // 3.5 outside
+        assert_eq!(0, value(0, 0));
+        assert!(value(5, 5) < value(5, 7));                                                         // corners are further away
+    }
+
+    #[test]
+    fn test_shade() {
+        let field = DistanceField {
+            spread: 4.0,
+            style: SdfStyle { outline: Some(SdfEffect { color: Color::RED, width: 2.0 }), ..SdfStyle::fill(Color::WHITE) },
+        };
+        assert_eq!([1.0, 1.0, 1.0, 1.0], field.shade(-1.0, 4.0, 1.0));
+        assert_eq!([1.0, 0.0, 0.0, 1.0], field.shade(1.0, 4.0, 1.0));
+        assert_eq!([0.0, 0.0, 0.0, 0.0], field.shade(3.0, 4.0, 1.0));
+        assert_eq!([1.0, 0.5, 0.5, 1.0], field.shade(0.0, 4.0, 10.0));                               // half fill, over the outline
+    }
+}
@@ -1,22 +1,63 @@
+use crate::graphics::texture::atlas::UvRect;
+use crate::graphics::texture::filter::TextureFilter;
+use crate::graphics::texture::mipmap::{generate_mipmaps, MipmapFilter};
+use crate::graphics::texture::sdf::DistanceField;
+use crate::support::image::pixel::PixelFormat;
 use crate::support::image::RawImage;
 
 pub struct Texture2D {
     pub initialized: bool,
     pub id: u32,
-    pub image: RawImage,
+    pub image: RawImage,                                                                        // always rgba8
 
     pub x: f32,
     pub y: f32,
     pub scale: f32,
 
+    pub filter: TextureFilter,
+    pub mipmap_filter: Option<MipmapFilter>,
+    pub mipmaps: Vec<RawImage>,                                                                 // levels 1..n; empty without a mip chain
+
+    pub sprites: Vec<Sprite2D>,                                                                 // empty: draw the whole image at (x, y)
+    pub distance_field: Option<DistanceField>,                                                  // alpha is a distance field, drawn with this style
+    pub visible: bool,                                                                          // false: only drawn through primitives
+
     pub replacement: Option<RawImage>,
 }
 
+///
+/// a sub-rectangle of a texture (usually an atlas page), drawn at its own position and scale.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sprite2D {
+    pub uv: UvRect,
+    pub x: f32,
+    pub y: f32,
+    pub scale: f32,
+}
+
+///
+/// a screen rectangle and the part of the texture drawn into it.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct TexturedQuad {
+    pub uv: UvRect,
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
 pub struct Texture2DBuilder {
     the_image: Option<RawImage>,
     the_x: Option<f32>,
     the_y: Option<f32>,
     the_scale: Option<f32>,
+    the_filter: Option<TextureFilter>,
+    the_mipmap_filter: Option<MipmapFilter>,
+    the_sprites: Vec<Sprite2D>,
+    the_distance_field: Option<DistanceField>,
+    the_visible: bool,
 }
 
 impl Texture2D {
@@ -24,15 +65,78 @@ impl Texture2D {
         Texture2D {
             initialized: false,
             id: 0,
-            image,
+            image: image.into_format(PixelFormat::Rgba8),
 
             x,
             y,
             scale,
 
+            filter: TextureFilter::default(),
+            mipmap_filter: None,
+            mipmaps: vec![],
+
+            sprites: vec![],
+            distance_field: None,
+            visible: true,
+
             replacement: None,
         }
     }
+
+    ///
+    /// build (or rebuild) the mip chain from the current image.
+    ///
+    pub fn generate_mipmaps(&mut self, filter: MipmapFilter) {
+        self.mipmaps = generate_mipmaps(&self.image, filter);
+        self.mipmap_filter = Some(filter);
+    }
+
+    ///
+    /// swap in a new image, regenerating the mip chain if there is one.
+    ///
+    pub(crate) fn replace_image(&mut self, image: RawImage) {
+        self.image = image.into_format(PixelFormat::Rgba8);
+        if let Some(filter) = self.mipmap_filter {
+            self.generate_mipmaps(filter);
+        }
+    }
+
+    ///
+    /// every level, largest first, starting with the image itself.
+    ///
+    pub fn levels(&self) -> impl Iterator<Item = &RawImage> {
+        std::iter::once(&self.image).chain(self.mipmaps.iter())
+    }
+
+    ///
+    /// everything to draw from this texture: one quad per sprite, or the whole image.
+    ///
+    pub(crate) fn quads(&self) -> Vec<TexturedQuad> {
+        let (width, height) = (self.image.width as f32, self.image.height as f32);
+        if self.sprites.is_empty() {
+            return vec![TexturedQuad { uv: UvRect::FULL, x: self.x, y: self.y, width: width * self.scale, height: height * self.scale }];
+        }
+        self.sprites.iter()
+            .map(|s| TexturedQuad {
+                uv: s.uv,
+                x: s.x,
+                y: s.y,
+                width: (s.uv.u1 - s.uv.u0) * width * s.scale,
+                height: (s.uv.v1 - s.uv.v0) * height * s.scale,
+            })
+            .collect()
+    }
+}
+
+impl Sprite2D {
+    pub fn new(uv: UvRect, x: f32, y: f32, scale: f32) -> Sprite2D {
+        Sprite2D {
+            uv,
+            x,
+            y,
+            scale,
+        }
+    }
 }
 
 impl Texture2DBuilder {
@@ -42,6 +146,11 @@ impl Texture2DBuilder {
             the_x: None,
             the_y: None,
             the_scale: None,
+            the_filter: None,
+            the_mipmap_filter: None,
+            the_sprites: vec![],
+            the_distance_field: None,
+            the_visible: true,
         }
     }
 
@@ -65,14 +174,57 @@ impl Texture2DBuilder {
         self
     }
 
+    pub fn with_filter(mut self, filter: TextureFilter) -> Self {
+        self.the_filter = Some(filter);
+        self
+    }
+
+    ///
+    /// generate a mip chain; unless a filter is given, the texture then samples trilinearly.
+    ///
+    pub fn with_mipmaps(mut self, filter: MipmapFilter) -> Self {
+        self.the_mipmap_filter = Some(filter);
+        self
+    }
+
+    pub fn with_sprite(mut self, sprite: Sprite2D) -> Self {
+        self.the_sprites.push(sprite);
+        self
+    }
+
+    ///
+    /// treat the image's alpha as a distance field; it's always sampled bilinearly, without mipmaps.
+    ///
+    pub fn with_distance_field(mut self, field: DistanceField) -> Self {
+        self.the_distance_field = Some(field);
+        self
+    }
+
+    ///
+    /// don't draw the texture itself; primitives that reference it still show parts of it.
+    ///
+    pub fn hidden(mut self) -> Self {
+        self.the_visible = false;
+        self
+    }
+
     pub fn build(self) -> Texture2D {
+        let image = self.the_image.expect("can't create a texture without texture data").into_format(PixelFormat::Rgba8);
+        let mipmaps = self.the_mipmap_filter.map(|filter| generate_mipmaps(&image, filter)).unwrap_or_default();
+        let default_filter = if self.the_mipmap_filter.is_some() { TextureFilter::TRILINEAR } else { TextureFilter::default() };
         Texture2D {
             initialized: false,
             id: 0,
-            image: self.the_image.expect("can't create a texture without texture data"),
+            image,
             x: self.the_x.unwrap_or_else(|| 0.0),
             y: self.the_y.unwrap_or_else(|| 0.0),
             scale: self.the_scale.unwrap_or_else(|| 1.0),
+            filter: self.the_filter.unwrap_or(default_filter),
+            mipmap_filter: self.the_mipmap_filter,
+            mipmaps,
+            sprites: self.the_sprites,
+            distance_field: self.the_distance_field,
+            visible: self.the_visible,
             replacement: None,
         }
     }
@@ -9,6 +9,7 @@ pub enum KeyInputName {
     KeyM,
     KeyS,
     KeyW,
+    KeyF12,
 }
 
 impl Display for KeyInputName {
@@ -21,6 +22,7 @@ impl Display for KeyInputName {
             KeyInputName::KeyM => write!(f, "{}", "KeyM"),
             KeyInputName::KeyS => write!(f, "{}", "KeyS"),
             KeyInputName::KeyW => write!(f, "{}", "KeyW"),
+            KeyInputName::KeyF12 => write!(f, "KeyF12"),
         }
     }
 }
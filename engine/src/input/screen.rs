@@ -2,8 +2,12 @@ use crate::geometry::dim::Dimension2D;
 use crate::geometry::primitive::v2d::Vertex2D;
 use crate::geometry::rect::Rectangle2D;
 use crate::window::key::WindowKey;
+#[cfg(target_os="windows")]
 use crate::window::mswin::util::{get_client_rect_dim2d, get_window_rect_dim2d};
+#[cfg(target_os="windows")]
 use crate::window::mswin::winapi::{get_client_rect, get_window_rect};
+#[cfg(target_os="linux")]
+use crate::window::x11::xapi::{get_window_attributes, root_window, translate_coordinates};
 
 #[derive(Clone, Debug)]
 pub struct ScreenState {
@@ -45,7 +49,30 @@ impl ScreenState {
         }
     }
 
-    #[cfg(target_os="windows")]
+    ///
+    /// create screen state for a synthetic client area, with no os window behind it.
+    ///
+    /// window and client areas are identical, and positioned at the origin.
+    ///
+    pub fn from_dimensions(width: f32, height: f32) -> ScreenState {
+        let mut screen = ScreenState::new();
+        screen.update_synthetic(width, height);
+        screen
+    }
+
+    ///
+    /// update screen state for a synthetic client area; see [ScreenState::from_dimensions].
+    ///
+    pub fn update_synthetic(&mut self, width: f32, height: f32) {
+        let rect = Rectangle2D { top_left: Vertex2D::origin(), bottom_right: Vertex2D { x: width, y: height } };
+        self.update_client_dimensions(Dimension2D::new(height, width));
+        self.update_window_dimensions(Dimension2D::new(height, width));
+        self.update_client_rectangle(rect.clone());
+        self.update_window_rectangle(rect);
+        self.update_screen_center();
+    }
+
+    #[cfg(not(target_os="macos"))]
     pub fn from(key: &WindowKey) -> ScreenState {
         let mut screen = ScreenState::new();
         screen.update_os(key);
@@ -73,6 +100,29 @@ impl ScreenState {
         self.update_screen_center();
     }
 
+    #[cfg(target_os="linux")]
+    pub fn update_os(&mut self, key: &WindowKey) {
+        /* get the various screen stats from x11; decorations belong to the window manager, so window and client sizes match */
+        let attributes = get_window_attributes(&key.xlib, key.display, key.window);
+        let root = root_window(&key.xlib, key.display, key.screen);
+        let (x, y) = translate_coordinates(&key.xlib, key.display, key.window, root, 0, 0);
+        let (width, height) = (attributes.width as f32, attributes.height as f32);
+        let window_rect = Rectangle2D { top_left: Vertex2D::new(x as f32, y as f32), bottom_right: Vertex2D::new(x as f32 + width, y as f32 + height) };
+        let client_rect = Rectangle2D { top_left: Vertex2D::origin(), bottom_right: Vertex2D::new(width, height) };
+
+        /* make updates */
+        self.update_client_dimensions(Dimension2D::new(height, width));
+        self.update_window_dimensions(Dimension2D::new(height, width));
+        self.update_client_rectangle(client_rect);
+        self.update_window_rectangle(window_rect);
+        self.update_screen_center();
+    }
+
+    #[cfg(target_os="macos")]
+    pub fn update_os(&mut self, _key: &WindowKey) {
+        todo!("macos windowing not yet implemented")
+    }
+
     fn update_client_dimensions(&mut self, current: Dimension2D) {
         /* copy existing current into previous */
         self.previous_client_dimensions.height = self.current_client_dimensions.height;
@@ -1,3 +1,5 @@
+use std::io::ErrorKind::Unsupported;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use crate::config::EngineConfig;
 use crate::config::input_config::kc::handle_key_change;
@@ -8,10 +10,12 @@ use crate::graphics::storage::g2d::Graph2D;
 use crate::graphics::storage::g3d::Graph3D;
 use crate::input::screen::ScreenState;
 use crate::input::UserInput;
+use crate::support::asset::loader::AssetLoader;
+use crate::support::asset::manager::AssetManager;
+use crate::support::image::{ImageFormat, RawImage};
 use crate::support::logger::log;
 use crate::support::logger::log_level::LogLevel;
 use crate::support::timing::EngineTiming;
-use crate::window::key::WindowKey;
 
 pub mod config;
 pub mod graphics;
@@ -39,6 +43,10 @@ pub struct PainsawContext {
     pub g3d: Graph3D,
     pub camera: Camera,
 
+    /* loaded images, fonts and the like, shared by handle; and those still loading */
+    pub assets: AssetManager,
+    pub loader: AssetLoader,
+
     /* rendering subsystem */
     pub(crate) graphics: GraphicsIntermediary,
 
@@ -46,6 +54,9 @@ pub struct PainsawContext {
     pub input: Arc<Mutex<UserInput>>,
     pub config: EngineConfig,
     pub screen: ScreenState,
+
+    /* frame capture, written after the next scene is displayed */
+    pub(crate) pending_capture: Option<(PathBuf, ImageFormat)>,
 }
 
 impl PainsawContext {
@@ -62,15 +73,51 @@ impl PainsawContext {
             g3d: Graph3D::new(),
             camera: Camera::new(&dim),
 
+            assets: AssetManager::new(),
+            loader: AssetLoader::default(),
+
             graphics: GraphicsIntermediary::new(config.renderer.graphics.clone()),
 
             input: input.clone(),
             config,
             screen,
+
+            pending_capture: None,
         }
     }
 }
 
+impl PainsawContext {
+    ///
+    /// read back the most recently rendered frame from the rendering subsystem.
+    ///
+    /// the frame is complete after [WorldController::display_world_scene]; returns none
+    /// if the rendering subsystem doesn't support readback.
+    ///
+    pub fn capture_frame(&self) -> Option<RawImage> {
+        self.graphics.capture(&self.camera)
+    }
+
+    ///
+    /// read back the most recently rendered frame, and write it to disk.
+    ///
+    pub fn save_frame<P: AsRef<Path>>(&self, path: P, format: ImageFormat) -> std::io::Result<()> {
+        let image = self.capture_frame()
+            .ok_or_else(|| std::io::Error::new(Unsupported, "rendering subsystem doesn't support frame capture"))?;
+        if let Some(parent) = path.as_ref().parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        image.save(path, format)
+    }
+
+    ///
+    /// capture the frame at the end of the next [WorldController::display_world_scene].
+    ///
+    pub fn request_capture<P: Into<PathBuf>>(&mut self, path: P, format: ImageFormat) {
+        self.pending_capture = Some((path.into(), format));
+    }
+}
+
 ///
 /// Control various aspects of the world, as called by the windowing system.
 ///
@@ -104,7 +151,10 @@ pub trait WorldController {
     ///
     /// update the game world state - fully controlled by client.
     ///
-    fn update_world(&self, context: &mut PainsawContext, key: &WindowKey) {
+    /// the window is responsible for refreshing [PainsawContext::screen] before calling this,
+    /// whenever the screen has been resized.
+    ///
+    fn update_world(&self, context: &mut PainsawContext) {
         match context.input.clone().lock() {
             Ok(mut uin) => {
                 /* handle key changes */
@@ -112,6 +162,9 @@ pub trait WorldController {
                     let change = uin.key_changes.pop_front().unwrap();
                     let state = uin.key_states.get_mut(&change).unwrap();
                     if !state.current.is_handled() {
+                        if let Some(capture) = &context.config.renderer.capture && capture.key == change && state.current.is_active() {
+                            context.pending_capture = Some((capture.next_path(context.frame_count), capture.format));
+                        }
                         handle_key_change(context.config.input.key_handler.clone(), &change, state, &mut context.camera, &context.config, &context.timing);
                         state.current.set_handled();
                     }
@@ -122,7 +175,6 @@ pub trait WorldController {
 
                 /* handle screen resize */
                 if uin.screen_resized {
-                    context.screen.update(key);
                     context.camera.update_screen(&context.screen.current_client_dimensions);
                     context.graphics.resize(context);
                 }
@@ -146,6 +198,10 @@ pub trait WorldController {
             Err(_) => {}
         }
 
+        /* pick up assets changed on disk, if hot reload is on, and those finished loading */
+        context.assets.poll_changes(&mut context.g2d);
+        context.loader.poll(&mut context.assets, &mut context.g2d, &mut context.g3d);
+
         self.update_world_helper(context);
 
         match context.input.lock() {
@@ -182,5 +238,13 @@ pub trait WorldController {
         context.graphics.prepare_2d(&mut context.g2d, &context.camera);
         context.graphics.render_2d(&mut context.g2d, &context.timing, &context.config, &context.camera, uin, &screen);
         context.graphics.after_2d();
+
+        /* write out a requested capture, before the buffers are swapped */
+        if let Some((path, format)) = context.pending_capture.take() {
+            match context.save_frame(&path, format) {
+                Ok(()) => log(LogLevel::Info, &|| format!("saved frame capture to {}", path.display())),
+                Err(e) => log(LogLevel::Error, &|| format!("frame capture to {} failed: {}", path.display(), e)),
+            }
+        }
     }
 }
\ No newline at end of file
@@ -2,10 +2,13 @@
 //! modules that aren't a main engine component, but still useful in various arenas.
 //!
 
+pub mod asset;
 pub mod logger;
+pub mod model;
 pub mod resource;
 pub mod text;
 pub(crate) mod stats;
 pub mod timing;
 pub mod image;
-pub(crate) mod binary;
+pub mod binary;
+pub mod zlib;
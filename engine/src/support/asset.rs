@@ -0,0 +1,70 @@
+//!
+//! assets: things loaded from resources once, and shared by handle.
+//!
+
+use crate::graphics::storage::m2d::Model2D;
+use crate::graphics::storage::m3d::Model3D;
+use crate::support::image::{load_any, RawImage};
+use crate::support::model::obj::Obj;
+use std::borrow::Cow;
+use crate::support::text::bitmap::BitmapFont;
+use crate::support::text::truetype::TrueTypeFont;
+
+pub mod handle;
+pub mod loader;
+pub mod manager;
+
+///
+/// anything the asset manager can load from a resource's bytes.
+///
+/// the bytes are borrowed when the resource is already in memory; assets that keep them take ownership.
+///
+pub trait Asset: Send + Sync + Sized + 'static {
+    fn from_asset_bytes(bytes: Cow<'_, [u8]>) -> std::io::Result<Self>;
+}
+
+///
+/// images, in any registered format.
+///
+impl Asset for RawImage {
+    fn from_asset_bytes(bytes: Cow<'_, [u8]>) -> std::io::Result<Self> {
+        load_any(&bytes)
+    }
+}
+
+///
+/// bdf or psf fonts.
+///
+impl Asset for BitmapFont {
+    fn from_asset_bytes(bytes: Cow<'_, [u8]>) -> std::io::Result<Self> {
+        BitmapFont::from_bytes(&bytes)
+    }
+}
+
+///
+/// truetype fonts; the font keeps its tables and reads glyphs from them as they're drawn,
+/// so it owns a copy of the bytes, even when the resource has them in memory already.
+///
+impl Asset for TrueTypeFont {
+    fn from_asset_bytes(bytes: Cow<'_, [u8]>) -> std::io::Result<Self> {
+        TrueTypeFont::from_bytes(bytes.into_owned())
+    }
+}
+
+///
+/// wavefront obj models.
+///
+impl Asset for Model3D {
+    fn from_asset_bytes(bytes: Cow<'_, [u8]>) -> std::io::Result<Self> {
+        Obj::from_bytes(&bytes).map(|obj| obj.to_model_3d())
+    }
+}
+
+///
+/// wavefront obj models, flattened onto x and y.
+///
+impl Asset for Model2D {
+    fn from_asset_bytes(bytes: Cow<'_, [u8]>) -> std::io::Result<Self> {
+        Obj::from_bytes(&bytes).map(|obj| obj.to_model_2d())
+    }
+}
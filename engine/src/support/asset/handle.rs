@@ -0,0 +1,82 @@
+use std::fmt::{Debug, Formatter};
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering::Relaxed;
+use std::sync::{Arc, RwLock};
+
+///
+/// where a loaded asset lives; reloading swaps the asset, and every handle sees the new one.
+///
+pub(crate) struct AssetSlot<T> {
+    asset: RwLock<Arc<T>>,
+    version: AtomicU64,
+}
+
+///
+/// a shared reference to a loaded asset; the asset stays loaded while any handle to it exists.
+///
+pub struct Handle<T> {
+    key: Arc<str>,
+    slot: Arc<AssetSlot<T>>,
+}
+
+impl<T> AssetSlot<T> {
+    pub(crate) fn new(asset: T) -> AssetSlot<T> {
+        AssetSlot { asset: RwLock::new(Arc::new(asset)), version: AtomicU64::new(0) }
+    }
+
+    pub(crate) fn get(&self) -> Arc<T> {
+        self.asset.read().unwrap().clone()
+    }
+
+    pub(crate) fn replace(&self, asset: T) {
+        *self.asset.write().unwrap() = Arc::new(asset);
+        self.version.fetch_add(1, Relaxed);
+    }
+}
+
+impl<T> Handle<T> {
+    pub(crate) fn new(key: Arc<str>, slot: Arc<AssetSlot<T>>) -> Handle<T> {
+        Handle { key, slot }
+    }
+
+    ///
+    /// the key (usually the path) the asset was loaded under.
+    ///
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    ///
+    /// the asset as it is now; a reload doesn't change what's already been got.
+    ///
+    pub fn get(&self) -> Arc<T> {
+        self.slot.get()
+    }
+
+    ///
+    /// how many times the asset has been reloaded.
+    ///
+    pub fn version(&self) -> u64 {
+        self.slot.version.load(Relaxed)
+    }
+
+    pub(crate) fn slot(&self) -> &AssetSlot<T> {
+        &self.slot
+    }
+
+    pub fn ptr_eq(&self, other: &Handle<T>) -> bool {
+        Arc::ptr_eq(&self.slot, &other.slot)
+    }
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        Handle { key: self.key.clone(), slot: self.slot.clone() }
+    }
+}
+
+impl<T> Debug for Handle<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Handle({}, v{})", self.key, self.version())
+    }
+}
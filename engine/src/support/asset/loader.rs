@@ -0,0 +1,301 @@
+use crate::graphics::storage::g2d::Graph2D;
+use crate::graphics::storage::g3d::Graph3D;
+use crate::graphics::storage::m2d::Model2D;
+use crate::graphics::storage::m3d::Model3D;
+use crate::support::asset::handle::Handle;
+use crate::support::asset::manager::AssetManager;
+use crate::support::asset::Asset;
+use crate::support::logger::log;
+use crate::support::logger::log_level::LogLevel;
+use crate::support::resource::error::ResourceError;
+use crate::support::resource::Resource;
+use std::any::Any;
+use std::collections::HashMap;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+
+type Loaded = std::io::Result<Box<dyn Any + Send>>;
+type Job = (u64, Box<dyn FnOnce() -> Loaded + Send>);
+type Finish = Box<dyn FnOnce(Box<dyn Any + Send>, &mut AssetManager, &mut Graph2D, &mut Graph3D)>;
+type Then<T> = Box<dyn FnOnce(&Handle<T>, &mut Graph2D, &mut Graph3D)>;
+
+///
+/// how far through the queued loads things are, since the queue was last empty.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LoadProgress {
+    pub queued: usize,
+    pub loaded: usize,
+    pub failed: usize,
+}
+
+///
+/// a load waiting on its worker; finishing it happens on the main thread.
+///
+struct PendingLoad {
+    key: String,
+    finish: Finish,
+}
+
+///
+/// loads assets in the background: reading and decoding happen on worker threads, and
+/// [AssetLoader::poll] hands finished assets to the asset manager (and their models to the graphs).
+///
+pub struct AssetLoader {
+    threads: usize,
+    jobs: Option<Sender<Job>>,
+    workers: Vec<JoinHandle<()>>,
+    done: Sender<(u64, Loaded)>,
+    results: Receiver<(u64, Loaded)>,
+    pending: HashMap<u64, PendingLoad>,
+    next_ticket: u64,
+    progress: LoadProgress,
+}
+
+impl AssetLoader {
+    ///
+    /// a loader with this many workers; they're started with the first load.
+    ///
+    pub fn new(threads: usize) -> AssetLoader {
+        let (done, results) = channel();
+        AssetLoader {
+            threads: threads.max(1),
+            jobs: None,
+            workers: vec![],
+            done,
+            results,
+            pending: HashMap::new(),
+            next_ticket: 0,
+            progress: LoadProgress::default(),
+        }
+    }
+}
+
+impl Default for AssetLoader {
+    ///
+    /// a worker for each core but one (the main thread's).
+    ///
+    fn default() -> Self {
+        let cores = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(2);
+        AssetLoader::new(cores.saturating_sub(1))
+    }
+}
+
+impl AssetLoader {
+    ///
+    /// load an asset in the background, into the asset manager under this key.
+    ///
+    pub fn queue<T: Asset>(&mut self, key: &str, resource: impl Resource + Send + 'static) {
+        self.enqueue::<T>(key, resource, Box::new(|_, _, _| {}));
+    }
+
+    ///
+    /// load an asset in the background, then build a 2d model from it and attach it under `name`.
+    ///
+    pub fn queue_2d<T: Asset, F>(&mut self, key: &str, resource: impl Resource + Send + 'static, name: &str, build: F)
+    where
+        F: FnOnce(&Handle<T>) -> Model2D + 'static,
+    {
+        let name = name.to_string();
+        self.enqueue::<T>(key, resource, Box::new(move |handle, g2d, _| g2d.attach(&name, build(handle))));
+    }
+
+    ///
+    /// load an asset in the background, then build a 3d model from it and attach it under `name`.
+    ///
+    pub fn queue_3d<T: Asset, F>(&mut self, key: &str, resource: impl Resource + Send + 'static, name: &str, build: F)
+    where
+        F: FnOnce(&Handle<T>) -> Model3D + 'static,
+    {
+        let name = name.to_string();
+        self.enqueue::<T>(key, resource, Box::new(move |handle, _, g3d| g3d.attach(&name, build(handle))));
+    }
+
+    ///
+    /// hand over everything that's finished loading since the last poll; returns each load's key and outcome.
+    ///
+    /// assets already in the manager (loaded meanwhile by someone else) are kept, and the new copy dropped.
+    ///
+    pub fn poll(&mut self, assets: &mut AssetManager, g2d: &mut Graph2D, g3d: &mut Graph3D) -> Vec<(String, std::io::Result<()>)> {
+        let mut outcomes = vec![];
+        while let Ok((ticket, loaded)) = self.results.try_recv() {
+            let Some(pending) = self.pending.remove(&ticket) else { continue };
+            match loaded {
+                Ok(asset) => {
+                    (pending.finish)(asset, assets, g2d, g3d);
+                    self.progress.loaded += 1;
+                    log(LogLevel::Debug, &|| format!("loaded asset [{}] in the background", pending.key));
+                    outcomes.push((pending.key, Ok(())));
+                }
+                Err(e) => {
+                    self.progress.failed += 1;
+                    log(LogLevel::Error, &|| format!("loading asset [{}] failed: {}", pending.key, e));
+                    outcomes.push((pending.key, Err(e)));
+                }
+            }
+        }
+        outcomes
+    }
+
+    pub fn progress(&self) -> LoadProgress {
+        self.progress
+    }
+
+    fn enqueue<T: Asset>(&mut self, key: &str, resource: impl Resource + Send + 'static, then: Then<T>) {
+        /* a new batch, once the last one is done */
+        if self.progress.is_done() {
+            self.progress = LoadProgress::default();
+        }
+        self.progress.queued += 1;
+
+        let ticket = self.next_ticket;
+        self.next_ticket += 1;
+        let owned_key = key.to_string();
+        self.pending.insert(ticket, PendingLoad {
+            key: key.to_string(),
+            finish: Box::new(move |asset, assets, g2d, g3d| {
+                let asset = *asset.downcast::<T>().expect("a worker loaded the wrong type");
+                let handle = match assets.get::<T>(&owned_key) {
+                    Ok(Some(handle)) => handle,
+                    _ => assets.insert(&owned_key, asset),
+                };
+                then(&handle, g2d, g3d);
+            }),
+        });
+
+        let job: Job = (ticket, Box::new(move || {
+            let asset = T::from_asset_bytes(resource.data()?).map_err(|e| ResourceError::decode(&resource.logical_path(), e))?;
+            Ok(Box::new(asset) as Box<dyn Any + Send>)
+        }));
+        self.start_workers();
+        if let Some(jobs) = &self.jobs {
+            jobs.send(job).expect("asset workers stopped");
+        }
+    }
+
+    fn start_workers(&mut self) {
+        if self.jobs.is_some() {
+            return;
+        }
+        let (sender, receiver) = channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        for idx in 0..self.threads {
+            let receiver = receiver.clone();
+            let done = self.done.clone();
+            let worker = std::thread::Builder::new()
+                .name(format!("asset-loader-{}", idx))
+                .spawn(move || loop {
+                    let job = receiver.lock().unwrap().recv();
+                    let Ok((ticket, load)) = job else { break };                                // the loader's gone
+                    let loaded = catch_unwind(AssertUnwindSafe(load)).unwrap_or_else(|panic| Err(panicked(panic)));
+                    let _ = done.send((ticket, loaded));
+                })
+                .expect("failed to start an asset worker");
+            self.workers.push(worker);
+        }
+        self.jobs = Some(sender);
+        log(LogLevel::Debug, &|| format!("started {} asset worker(s)", self.threads));
+    }
+}
+
+impl Drop for AssetLoader {
+    fn drop(&mut self) {
+        self.jobs = None;
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+///
+/// a decoder that panicked on bad input fails its load, rather than taking the worker (and the load) with it.
+///
+fn panicked(panic: Box<dyn Any + Send>) -> std::io::Error {
+    let message = panic.downcast_ref::<&str>().map(|s| s.to_string())
+        .or_else(|| panic.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string());
+    std::io::Error::other(format!("loading panicked: {}", message))
+}
+
+impl LoadProgress {
+    ///
+    /// the share of queued loads that are finished, either way; 1 when nothing's queued.
+    ///
+    pub fn fraction(&self) -> f32 {
+        if self.queued == 0 { 1.0 } else { (self.loaded + self.failed) as f32 / self.queued as f32 }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.loaded + self.failed == self.queued
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graphics::storage::g2d::Graph2D;
+    use crate::graphics::storage::g3d::Graph3D;
+    use crate::graphics::storage::m2d::Model2DBuilder;
+    use crate::graphics::texture::t2d::Texture2D;
+    use crate::support::asset::loader::{AssetLoader, LoadProgress};
+    use crate::support::asset::Asset;
+    use crate::support::asset::manager::AssetManager;
+    use crate::support::image::{ImageFormat, RawImage};
+    use crate::support::resource::restype::memory::MemoryResource;
+    use std::borrow::Cow;
+    use std::time::{Duration, Instant};
+
+    struct Fragile;
+
+    impl Asset for Fragile {
+        fn from_asset_bytes(_: Cow<'_, [u8]>) -> std::io::Result<Self> {
+            panic!("bad input")
+        }
+    }
+
+    #[test]
+    fn test_background_load() {
+        let mut bmp = vec![];
+        RawImage::new(2, 1, vec![255; 8]).write_to(&mut bmp, ImageFormat::Bitmap).unwrap();
+
+        let (mut assets, mut g2d, mut g3d) = (AssetManager::new(), Graph2D::new(), Graph3D::new());
+        let mut loader = AssetLoader::new(2);
+        loader.queue_2d::<RawImage, _>("wall", MemoryResource::from_array(bmp), "wall", |image| {
+            Model2DBuilder::new().with_texture(Texture2D::new(image.get().as_ref().clone(), 0.0, 0.0, 1.0)).build()
+        });
+        loader.queue::<RawImage>("broken", MemoryResource::from_array(b"not an image".to_vec()));
+        assert_eq!(LoadProgress { queued: 2, loaded: 0, failed: 0 }, loader.progress());
+
+        let started = Instant::now();
+        let mut outcomes = vec![];
+        while !loader.progress().is_done() && started.elapsed() < Duration::from_secs(10) {
+            outcomes.extend(loader.poll(&mut assets, &mut g2d, &mut g3d));
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        outcomes.sort_by(|a, b| a.0.cmp(&b.0));
+
+        assert_eq!(LoadProgress { queued: 2, loaded: 1, failed: 1 }, loader.progress());
+        assert_eq!(1.0, loader.progress().fraction());
+        assert!(outcomes[0].0 == "broken" && outcomes[0].1.is_err());
+        assert!(outcomes[1].0 == "wall" && outcomes[1].1.is_ok());
+        assert_eq!((1, 1), (g2d.count(), assets.count()));
+        assert_eq!(Some(2), assets.get::<RawImage>("wall").unwrap().map(|image| image.get().width));
+    }
+
+    #[test]
+    fn test_panicking_load() {
+        let (mut assets, mut g2d, mut g3d) = (AssetManager::new(), Graph2D::new(), Graph3D::new());
+        let mut loader = AssetLoader::new(1);
+        loader.queue::<Fragile>("fragile", MemoryResource::from_array(vec![]));
+
+        let started = Instant::now();
+        let mut outcomes = vec![];
+        while !loader.progress().is_done() && started.elapsed() < Duration::from_secs(10) {
+            outcomes.extend(loader.poll(&mut assets, &mut g2d, &mut g3d));
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(LoadProgress { queued: 1, loaded: 0, failed: 1 }, loader.progress());
+        assert!(outcomes[0].1.as_ref().unwrap_err().to_string().contains("bad input"));
+    }
+}
@@ -0,0 +1,324 @@
+use crate::graphics::storage::g2d::Graph2D;
+use crate::support::asset::handle::{AssetSlot, Handle};
+use crate::support::asset::Asset;
+use crate::support::image::RawImage;
+use crate::support::logger::log;
+use crate::support::logger::log_level::LogLevel;
+use crate::support::resource::error::ResourceError;
+use crate::support::resource::restype::file::FileResource;
+use crate::support::resource::Resource;
+use std::any::Any;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io::Error;
+use std::io::ErrorKind::InvalidInput;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+type Reloader = fn(&(dyn Any + Send + Sync), Cow<'_, [u8]>) -> std::io::Result<()>;
+
+struct AssetEntry {
+    slot: Arc<dyn Any + Send + Sync>,                                                           // an AssetSlot<T>
+    type_name: &'static str,
+    watch: Option<FileWatch>,
+}
+
+///
+/// the file an asset was loaded from, when it was last seen to change, and how to load it again.
+///
+struct FileWatch {
+    resource: FileResource,
+    modified: Option<SystemTime>,
+    reload: Reloader,
+}
+
+///
+/// a texture that shows an image asset: texture `index` of the model `model` in the 2d graph.
+///
+struct TextureBinding {
+    key: String,
+    model: String,
+    index: usize,
+}
+
+///
+/// loads each asset once, by key, and shares it through handles.
+///
+/// assets are reference counted: once no handle refers to one, [AssetManager::release_unused] drops it.
+/// with hot reload on, assets loaded from files are loaded again when the file changes.
+///
+pub struct AssetManager {
+    assets: HashMap<Arc<str>, AssetEntry>,
+    textures: Vec<TextureBinding>,
+    poll_interval: Option<Duration>,                                                            // none: hot reload is off
+    last_poll: Option<Instant>,
+}
+
+impl AssetManager {
+    pub fn new() -> AssetManager {
+        AssetManager {
+            assets: HashMap::new(),
+            textures: vec![],
+            poll_interval: None,
+            last_poll: None,
+        }
+    }
+}
+
+impl Default for AssetManager {
+    fn default() -> Self {
+        AssetManager::new()
+    }
+}
+
+impl AssetManager {
+    ///
+    /// the asset under this key; it's only loaded from the resource if it isn't already.
+    ///
+    pub fn load<T: Asset>(&mut self, key: &str, resource: &dyn Resource) -> std::io::Result<Handle<T>> {
+        if let Some(handle) = self.get::<T>(key)? {
+            return Ok(handle);
+        }
+        let asset = T::from_asset_bytes(resource.data()?).map_err(|e| ResourceError::decode(&resource.logical_path(), e))?;
+        log(LogLevel::Debug, &|| format!("loaded asset [{}] as {}", key, std::any::type_name::<T>()));
+        Ok(self.insert(key, asset))
+    }
+
+    ///
+    /// the asset in a file, keyed by its path; it's watched for changes, for hot reload.
+    ///
+    pub fn load_file<T: Asset, P: AsRef<Path>>(&mut self, path: P) -> std::io::Result<Handle<T>> {
+        let resource = FileResource::from_path(path.as_ref().to_path_buf());
+        let key = path.as_ref().to_string_lossy();
+        let modified = resource.modified();
+        let handle = self.load::<T>(&key, &resource)?;
+        if let Some(entry) = self.assets.get_mut(&*key) && entry.watch.is_none() {
+            entry.watch = Some(FileWatch { resource, modified, reload: reload_slot::<T> });
+        }
+        Ok(handle)
+    }
+
+    ///
+    /// add an asset that was made rather than loaded (a generated model, say); an asset of the same type under
+    /// the key is replaced in place, so its handles see the new one.
+    ///
+    pub fn insert<T: Send + Sync + 'static>(&mut self, key: &str, asset: T) -> Handle<T> {
+        if let Ok(Some(handle)) = self.get::<T>(key) {
+            handle.slot().replace(asset);
+            return handle;
+        }
+        let key: Arc<str> = Arc::from(key);
+        let slot = Arc::new(AssetSlot::new(asset));
+        self.assets.insert(key.clone(), AssetEntry { slot: slot.clone(), type_name: std::any::type_name::<T>(), watch: None });
+        Handle::new(key, slot)
+    }
+
+    ///
+    /// the asset under this key, if it's loaded; an error if it's loaded as a different type.
+    ///
+    pub fn get<T: Send + Sync + 'static>(&self, key: &str) -> std::io::Result<Option<Handle<T>>> {
+        let Some((key, entry)) = self.assets.get_key_value(key) else {
+            return Ok(None);
+        };
+        match entry.slot.clone().downcast::<AssetSlot<T>>() {
+            Ok(slot) => Ok(Some(Handle::new(key.clone(), slot))),
+            Err(_) => Err(Error::new(InvalidInput, format!("asset [{}] is a {}, not a {}", key, entry.type_name, std::any::type_name::<T>()))),
+        }
+    }
+
+    ///
+    /// how many handles refer to the asset under this key; 0 if it's unused or not loaded.
+    ///
+    pub fn references(&self, key: &str) -> usize {
+        self.assets.get(key).map(|entry| Arc::strong_count(&entry.slot) - 1).unwrap_or(0)
+    }
+
+    ///
+    /// drop every asset that no handle refers to; returns their keys.
+    ///
+    pub fn release_unused(&mut self) -> Vec<String> {
+        let unused: Vec<Arc<str>> = self.assets.iter()
+            .filter(|(_, entry)| Arc::strong_count(&entry.slot) == 1)
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in &unused {
+            self.assets.remove(key);
+            log(LogLevel::Debug, &|| format!("released asset [{}]", key));
+        }
+        self.textures.retain(|binding| self.assets.contains_key(binding.key.as_str()));
+        unused.iter().map(|key| key.to_string()).collect()
+    }
+
+    pub fn count(&self) -> usize {
+        self.assets.len()
+    }
+}
+
+impl AssetManager {
+    ///
+    /// check watched files for changes at most this often; none turns hot reload off (the default).
+    ///
+    pub fn set_hot_reload(&mut self, poll_interval: Option<Duration>) {
+        self.poll_interval = poll_interval;
+        self.last_poll = None;
+    }
+
+    ///
+    /// when the image asset under `key` is reloaded, send it to texture `index` of the model `model`.
+    ///
+    /// the binding lasts until the asset is released.
+    ///
+    pub fn bind_texture(&mut self, key: &str, model: &str, index: usize) {
+        self.textures.push(TextureBinding { key: key.to_string(), model: model.to_string(), index });
+    }
+
+    ///
+    /// reload watched assets whose files have changed, if it's time to look; returns their keys.
+    ///
+    /// bound textures get the new images as their replacement. an asset that fails to load again
+    /// keeps its previous version.
+    ///
+    pub fn poll_changes(&mut self, g2d: &mut Graph2D) -> Vec<String> {
+        let Some(interval) = self.poll_interval else {
+            return vec![];
+        };
+        let now = Instant::now();
+        if self.last_poll.is_some_and(|last| now.duration_since(last) < interval) {
+            return vec![];
+        }
+        self.last_poll = Some(now);
+
+        /* reload */
+        let mut reloaded = vec![];
+        for (key, entry) in self.assets.iter_mut() {
+            let Some(watch) = entry.watch.as_mut() else { continue };
+            let modified = watch.resource.modified();
+            if modified.is_none() || modified == watch.modified {
+                continue;
+            }
+            watch.modified = modified;
+            let result = watch.resource.data()
+                .map_err(Error::from)
+                .and_then(|bytes| (watch.reload)(entry.slot.as_ref(), bytes));
+            match result {
+                Ok(()) => {
+                    log(LogLevel::Info, &|| format!("reloaded asset [{}]", key));
+                    reloaded.push(key.to_string());
+                }
+                Err(e) => log(LogLevel::Error, &|| format!("reloading asset [{}] failed, keeping the old one: {}", key, e)),
+            }
+        }
+
+        /* propagate images into their textures */
+        for binding in self.textures.iter().filter(|binding| reloaded.contains(&binding.key)) {
+            let Ok(Some(image)) = self.get::<RawImage>(&binding.key) else { continue };
+            g2d.update(&binding.model, |model| {
+                if let Some(texture) = model.textures.get_mut(binding.index) {
+                    texture.replacement = Some(image.get().as_ref().clone());
+                }
+            });
+        }
+        reloaded
+    }
+}
+
+///
+/// load an asset again into its slot; the slot is known to hold a T.
+///
+fn reload_slot<T: Asset>(slot: &(dyn Any + Send + Sync), bytes: Cow<'_, [u8]>) -> std::io::Result<()> {
+    let slot = slot.downcast_ref::<AssetSlot<T>>().ok_or_else(|| Error::new(InvalidInput, "asset changed type"))?;
+    slot.replace(T::from_asset_bytes(bytes)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graphics::storage::g2d::Graph2D;
+    use crate::graphics::storage::m2d::{Model2D, Model2DBuilder};
+    use crate::graphics::storage::m3d::Model3D;
+    use crate::graphics::texture::t2d::Texture2D;
+    use crate::support::asset::manager::AssetManager;
+    use crate::support::image::ImageFormat;
+    use crate::support::image::RawImage;
+    use crate::support::resource::restype::memory::MemoryResource;
+    use crate::support::text::bitmap::BitmapFont;
+    use std::fs::File;
+    use std::time::{Duration, SystemTime};
+
+    fn bmp(width: u32) -> Vec<u8> {
+        let mut bytes = vec![];
+        RawImage::new(width, 1, vec![255; (width * 4) as usize]).write_to(&mut bytes, ImageFormat::Bitmap).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_dedupe_and_release() {
+        let mut assets = AssetManager::new();
+        let first = assets.load::<RawImage>("red-green", &MemoryResource::from_array(bmp(2))).unwrap();
+        let second = assets.load::<RawImage>("red-green", &MemoryResource::from_array(vec![])).unwrap();
+        assert!(first.ptr_eq(&second));
+        assert_eq!((2, 1), (second.get().width, second.get().height));
+        assert_eq!((1, 2), (assets.count(), assets.references("red-green")));
+
+        drop(first);
+        assert!(assets.release_unused().is_empty());
+        drop(second);
+        assert_eq!(vec!["red-green"], assets.release_unused());
+        assert_eq!(0, assets.count());
+    }
+
+    #[test]
+    fn test_wrong_type() {
+        let mut assets = AssetManager::new();
+        let _image = assets.load::<RawImage>("image", &MemoryResource::from_array(bmp(2))).unwrap();
+        assert!(assets.load::<BitmapFont>("image", &MemoryResource::from_array(bmp(2))).is_err());
+        assert!(assets.load::<BitmapFont>("missing", &MemoryResource::from_array(bmp(2))).is_err());
+        assert_eq!(1, assets.count());
+    }
+
+    #[test]
+    fn test_models() {
+        let mut assets = AssetManager::new();
+        let obj = || MemoryResource::from_array(b"v 0 0 0\nv 1 0 0\nv 1 1 0\nf 1 2 3\n".to_vec());
+        let model = assets.load::<Model3D>("triangle", &obj()).unwrap();
+        assert_eq!(4, model.get().primitives[0].vertices.len());
+        assert!(model.ptr_eq(&assets.load::<Model3D>("triangle", &obj()).unwrap()));
+        assert_eq!(4, assets.load::<Model2D>("flat-triangle", &obj()).unwrap().get().primitives[0].vertices.len());
+    }
+
+    #[test]
+    fn test_hot_reload() {
+        let path = std::env::temp_dir().join(format!("painsaw-reload-{}.bmp", std::process::id()));
+        let touch = |bytes: Vec<u8>, age: u64| {
+            std::fs::write(&path, bytes).unwrap();
+            File::options().write(true).open(&path).unwrap().set_modified(SystemTime::now() - Duration::from_secs(age)).unwrap();
+        };
+        touch(bmp(2), 60);
+
+        let mut g2d = Graph2D::new();
+        let mut assets = AssetManager::new();
+        let handle = assets.load_file::<RawImage, _>(&path).unwrap();
+        g2d.attach("wall", Model2DBuilder::new().with_texture(Texture2D::new(handle.get().as_ref().clone(), 0.0, 0.0, 1.0)).build());
+        assets.bind_texture(handle.key(), "wall", 0);
+
+        /* off by default; then nothing to do until the file changes */
+        assert!(assets.poll_changes(&mut g2d).is_empty());
+        assets.set_hot_reload(Some(Duration::ZERO));
+        assert!(assets.poll_changes(&mut g2d).is_empty());
+
+        /* a bad edit keeps the old image */
+        touch(b"not an image".to_vec(), 30);
+        assert!(assets.poll_changes(&mut g2d).is_empty());
+        assert_eq!(2, handle.get().width);
+
+        touch(bmp(3), 0);
+        assert_eq!(vec![handle.key().to_string()], assets.poll_changes(&mut g2d));
+        assert_eq!((3, 1), (handle.get().width, handle.version()));
+        let mut replaced = None;
+        g2d.update("wall", |model| replaced = model.textures[0].replacement.take());
+        assert_eq!(Some(3), replaced.map(|image| image.width));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
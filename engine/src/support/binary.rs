@@ -1,3 +1,11 @@
+//!
+//! reading and writing binary data: typed values in either byte order, and bit streams.
+//!
+
+pub mod bits;
+pub mod endianness;
+pub mod reader;
+pub mod writer;
 
 ///
 /// read some number of bits from a byte.
@@ -7,5 +15,18 @@
 /// byte_to_bits_as_u8(byte, 2, 3);
 ///
 pub fn byte_to_bits_as_u8(byte: u8, index: u8, count: u8) -> u8 {
-    (byte >> index) & count
+    ((byte as u16 >> index) & ((1u16 << count) - 1)) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::support::binary::byte_to_bits_as_u8;
+
+    #[test]
+    fn test_byte_to_bits() {
+        assert_eq!(0b101, byte_to_bits_as_u8(0b1001_0100, 2, 3));
+        assert_eq!(1, byte_to_bits_as_u8(0b0010_0000, 5, 1));
+        assert_eq!(0b1001_0100, byte_to_bits_as_u8(0b1001_0100, 0, 8));
+        assert_eq!(0, byte_to_bits_as_u8(0b1001_0100, 0, 0));
+    }
 }
@@ -0,0 +1,142 @@
+use std::io::Error;
+use std::io::ErrorKind::UnexpectedEof;
+
+///
+/// reads bits least-significant first, as deflate requires.
+///
+pub struct LsbBitReader<'a> {
+    bytes: &'a [u8],
+    label: &'static str,                                                                        // prefixes error messages, e.g. "deflate"
+    position: usize,
+    buffer: u64,
+    count: u32,
+}
+
+///
+/// reads bits most-significant first, as tiff lzw codes are packed.
+///
+pub struct MsbBitReader<'a> {
+    bytes: &'a [u8],
+    label: &'static str,
+    position: usize,
+    buffer: u64,
+    count: u32,
+}
+
+impl<'a> LsbBitReader<'a> {
+    pub fn new(bytes: &'a [u8], label: &'static str) -> LsbBitReader<'a> {
+        LsbBitReader { bytes, label, position: 0, buffer: 0, count: 0 }
+    }
+
+    ///
+    /// the next `count` bits (at most 32), the first read in the lowest bit.
+    ///
+    pub fn bits(&mut self, count: u32) -> std::io::Result<u32> {
+        debug_assert!(count <= 32);
+        while self.count < count {
+            let byte = *self.bytes.get(self.position).ok_or_else(|| truncated(self.label, count, self.count))?;
+            self.buffer |= (byte as u64) << self.count;
+            self.position += 1;
+            self.count += 8;
+        }
+        let value = self.buffer & ((1u64 << count) - 1);
+        self.buffer >>= count;
+        self.count -= count;
+        Ok(value as u32)
+    }
+
+    ///
+    /// discard any remaining bits in the current byte.
+    ///
+    pub fn align(&mut self) {
+        self.buffer = 0;
+        self.count = 0;
+    }
+
+    ///
+    /// the next `count` whole bytes; call [LsbBitReader::align] first.
+    ///
+    pub fn bytes(&mut self, count: usize) -> std::io::Result<&'a [u8]> {
+        let slice = self.bytes.get(self.position..self.position.saturating_add(count))
+            .ok_or_else(|| Error::new(UnexpectedEof, format!("{}: truncated: {} bytes needed at offset {}, {} left",
+                                                             self.label, count, self.position, self.bytes.len() - self.position)))?;
+        self.position += count;
+        Ok(slice)
+    }
+
+    ///
+    /// bytes consumed so far, including the partially-read current byte.
+    ///
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+impl<'a> MsbBitReader<'a> {
+    pub fn new(bytes: &'a [u8], label: &'static str) -> MsbBitReader<'a> {
+        MsbBitReader { bytes, label, position: 0, buffer: 0, count: 0 }
+    }
+
+    ///
+    /// the next `count` bits (at most 32), the first read in the highest bit.
+    ///
+    pub fn bits(&mut self, count: u32) -> std::io::Result<u32> {
+        debug_assert!(count <= 32);
+        while self.count < count {
+            let byte = *self.bytes.get(self.position).ok_or_else(|| truncated(self.label, count, self.count))?;
+            self.buffer = (self.buffer << 8) | byte as u64;
+            self.position += 1;
+            self.count += 8;
+        }
+        self.count -= count;
+        Ok(((self.buffer >> self.count) & ((1u64 << count) - 1)) as u32)
+    }
+
+    ///
+    /// bits not yet read, buffered or not.
+    ///
+    pub fn remaining_bits(&self) -> usize {
+        (self.bytes.len() - self.position) * 8 + self.count as usize
+    }
+
+    ///
+    /// bytes consumed so far, including the partially-read current byte.
+    ///
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+fn truncated(label: &str, needed: u32, left: u32) -> Error {
+    Error::new(UnexpectedEof, format!("{}: truncated: {} bits needed, {} left", label, needed, left))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::support::binary::bits::{LsbBitReader, MsbBitReader};
+    use std::io::ErrorKind::UnexpectedEof;
+
+    #[test]
+    fn test_lsb_first() {
+        let mut reader = LsbBitReader::new(&[0b1010_1101, 0x34, 0x12, 0xff], "TEST");
+        assert_eq!(0b101, reader.bits(3).unwrap());
+        assert_eq!(0b10101, reader.bits(5).unwrap());
+        assert_eq!(0x1234, reader.bits(16).unwrap());
+        assert_eq!(1, reader.bits(1).unwrap());
+        reader.align();
+        assert_eq!(4, reader.position());
+        assert_eq!(UnexpectedEof, reader.bits(1).unwrap_err().kind());
+        assert_eq!(UnexpectedEof, reader.bytes(1).unwrap_err().kind());
+    }
+
+    #[test]
+    fn test_msb_first() {
+        let mut reader = MsbBitReader::new(&[0b1010_1101, 0x12, 0x34], "TEST");
+        assert_eq!(0b101, reader.bits(3).unwrap());
+        assert_eq!(0b01101, reader.bits(5).unwrap());
+        assert_eq!(0x123, reader.bits(12).unwrap());
+        assert_eq!(4, reader.remaining_bits());
+        assert_eq!(UnexpectedEof, reader.bits(5).unwrap_err().kind());
+        assert_eq!(0x4, reader.bits(4).unwrap());
+    }
+}
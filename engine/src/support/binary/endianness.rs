@@ -0,0 +1,66 @@
+use std::io::ErrorKind::UnexpectedEof;
+
+///
+/// byte order of multi-byte values in a resource.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Endianness {
+    ///
+    /// the "little end" (least-significant byte) is stored first.
+    ///
+    /// dominant in pcs (intel/amd x86, arm).
+    ///
+    LittleEndian,
+
+    ///
+    /// the "big end" (most-significant byte) is stored first.
+    ///
+    /// dominant in mainframes and network protocols (network byte order).
+    ///
+    BigEndian,
+}
+
+impl Endianness {
+    ///
+    /// read a u16 from the 2 bytes at the offset; fails if the slice is too short.
+    ///
+    pub fn read_u16(&self, bytes: &[u8], offset: usize) -> std::io::Result<u16> {
+        let bytes = self.take::<2>(bytes, offset)?;
+        Ok(match self {
+            Endianness::LittleEndian => u16::from_le_bytes(bytes),
+            Endianness::BigEndian => u16::from_be_bytes(bytes),
+        })
+    }
+
+    ///
+    /// read a u32 from the 4 bytes at the offset; fails if the slice is too short.
+    ///
+    pub fn read_u32(&self, bytes: &[u8], offset: usize) -> std::io::Result<u32> {
+        let bytes = self.take::<4>(bytes, offset)?;
+        Ok(match self {
+            Endianness::LittleEndian => u32::from_le_bytes(bytes),
+            Endianness::BigEndian => u32::from_be_bytes(bytes),
+        })
+    }
+
+    fn take<const N: usize>(&self, bytes: &[u8], offset: usize) -> std::io::Result<[u8; N]> {
+        offset.checked_add(N)
+            .and_then(|end| bytes.get(offset..end))
+            .map(|bytes| bytes.try_into().unwrap())
+            .ok_or_else(|| std::io::Error::new(UnexpectedEof, format!("{} bytes needed at offset {}, {} in all", N, offset, bytes.len())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::support::binary::endianness::Endianness::{BigEndian, LittleEndian};
+
+    #[test]
+    fn test_read() {
+        let bytes = [1, 2, 3, 4, 5];
+        assert_eq!(0x0201, LittleEndian.read_u16(&bytes, 0).unwrap());
+        assert_eq!(0x02030405, BigEndian.read_u32(&bytes, 1).unwrap());
+        assert!(LittleEndian.read_u32(&bytes, 2).is_err());
+        assert!(BigEndian.read_u16(&bytes, usize::MAX).is_err());
+    }
+}
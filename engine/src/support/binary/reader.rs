@@ -0,0 +1,176 @@
+use crate::support::binary::endianness::Endianness;
+use std::io::ErrorKind::{InvalidInput, UnexpectedEof};
+use std::io::{Error, Read, Seek, SeekFrom};
+
+///
+/// reads typed values from a seekable stream, in a chosen byte order.
+///
+/// every read is checked against the stream's length first, so truncated input is an
+/// `UnexpectedEof` error naming what was short and where, rather than a panic or a partial read.
+///
+pub struct BinaryReader<R> {
+    inner: R,
+    order: Endianness,
+    label: &'static str,                                                                        // prefixes error messages, e.g. "TGA"
+    position: u64,
+    len: u64,
+}
+
+macro_rules! read_number {
+    ($name:ident, $ty:ty) => {
+        pub fn $name(&mut self) -> std::io::Result<$ty> {
+            let bytes = self.read_array::<{ size_of::<$ty>() }>()?;
+            Ok(match self.order {
+                Endianness::LittleEndian => <$ty>::from_le_bytes(bytes),
+                Endianness::BigEndian => <$ty>::from_be_bytes(bytes),
+            })
+        }
+    };
+}
+
+impl<R: Read + Seek> BinaryReader<R> {
+    ///
+    /// read from the stream's current position.
+    ///
+    pub fn new(mut inner: R, order: Endianness) -> std::io::Result<BinaryReader<R>> {
+        let position = inner.stream_position()?;
+        let len = inner.seek(SeekFrom::End(0))?;
+        inner.seek(SeekFrom::Start(position))?;
+        Ok(BinaryReader { inner, order, label: "binary", position, len })
+    }
+
+    pub fn with_label(mut self, label: &'static str) -> Self {
+        self.label = label;
+        self
+    }
+
+    pub fn order(&self) -> Endianness {
+        self.order
+    }
+
+    ///
+    /// change the byte order for the reads that follow, e.g. once a header has said which it is.
+    ///
+    pub fn set_order(&mut self, order: Endianness) {
+        self.order = order;
+    }
+
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn remaining(&self) -> u64 {
+        self.len.saturating_sub(self.position)
+    }
+
+    pub fn seek(&mut self, position: u64) -> std::io::Result<()> {
+        if position > self.len {
+            return Err(Error::new(InvalidInput, format!("{}: offset {} is past the end ({})", self.label, position, self.len)));
+        }
+        self.position = self.inner.seek(SeekFrom::Start(position))?;
+        Ok(())
+    }
+
+    pub fn skip(&mut self, count: u64) -> std::io::Result<()> {
+        self.need(count)?;
+        self.seek(self.position + count)
+    }
+
+    read_number!(read_u16, u16);
+    read_number!(read_i16, i16);
+    read_number!(read_u32, u32);
+    read_number!(read_i32, i32);
+    read_number!(read_u64, u64);
+    read_number!(read_i64, i64);
+    read_number!(read_f32, f32);
+    read_number!(read_f64, f64);
+
+    pub fn read_u8(&mut self) -> std::io::Result<u8> {
+        Ok(self.read_array::<1>()?[0])
+    }
+
+    pub fn read_i8(&mut self) -> std::io::Result<i8> {
+        Ok(self.read_u8()? as i8)
+    }
+
+    pub fn read_array<const N: usize>(&mut self) -> std::io::Result<[u8; N]> {
+        self.need(N as u64)?;
+        let mut bytes = [0u8; N];
+        self.inner.read_exact(&mut bytes)?;
+        self.position += N as u64;
+        Ok(bytes)
+    }
+
+    ///
+    /// the next `count` bytes; checked before anything is allocated, so a corrupt length can't exhaust memory.
+    ///
+    pub fn read_bytes(&mut self, count: usize) -> std::io::Result<Vec<u8>> {
+        self.need(count as u64)?;
+        let mut bytes = vec![0u8; count];
+        self.inner.read_exact(&mut bytes)?;
+        self.position += count as u64;
+        Ok(bytes)
+    }
+
+    ///
+    /// `count` bytes starting at `offset`; reading carries on from after them.
+    ///
+    pub fn read_bytes_at(&mut self, offset: u64, count: usize) -> std::io::Result<Vec<u8>> {
+        self.seek(offset)?;
+        self.read_bytes(count)
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn need(&self, count: u64) -> std::io::Result<()> {
+        if count > self.remaining() {
+            return Err(Error::new(UnexpectedEof, format!("{}: truncated: {} bytes needed at offset {}, {} left",
+                                                         self.label, count, self.position, self.remaining())));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::support::binary::reader::BinaryReader;
+    use crate::support::binary::endianness::Endianness::{BigEndian, LittleEndian};
+    use std::io::Cursor;
+    use std::io::ErrorKind::{InvalidInput, UnexpectedEof};
+
+    #[test]
+    fn test_typed_reads() {
+        let bytes = [0x01, 0x02, 0x03, 0x04, 0xff, 0x00, 0x00, 0x80, 0x3f];
+        let mut reader = BinaryReader::new(Cursor::new(&bytes), LittleEndian).unwrap();
+        assert_eq!(0x0201, reader.read_u16().unwrap());
+        reader.set_order(BigEndian);
+        assert_eq!(0x0304, reader.read_u16().unwrap());
+        assert_eq!(-1, reader.read_i8().unwrap());
+        reader.set_order(LittleEndian);
+        assert_eq!(1.0, reader.read_f32().unwrap());
+        assert_eq!((9, 0), (reader.position(), reader.remaining()));
+    }
+
+    #[test]
+    fn test_truncated() {
+        let mut reader = BinaryReader::new(Cursor::new(vec![1, 2, 3]), LittleEndian).unwrap().with_label("TEST");
+        reader.skip(1).unwrap();
+        let error = reader.read_u32().unwrap_err();
+        assert_eq!(UnexpectedEof, error.kind());
+        assert_eq!("TEST: truncated: 4 bytes needed at offset 1, 2 left", error.to_string());
+        assert_eq!(1, reader.position());                                                       // nothing was consumed
+        assert_eq!(UnexpectedEof, reader.read_bytes(usize::MAX).unwrap_err().kind());
+        assert_eq!(InvalidInput, reader.seek(4).unwrap_err().kind());
+        assert_eq!(vec![2, 3], reader.read_bytes_at(1, 2).unwrap());
+    }
+}
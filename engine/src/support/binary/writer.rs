@@ -0,0 +1,106 @@
+use crate::support::binary::endianness::Endianness;
+use std::io::{Seek, SeekFrom, Write};
+
+///
+/// writes typed values to a seekable stream, in a chosen byte order.
+///
+/// seeking lets a size or offset be written as a placeholder, and patched once it's known.
+///
+pub struct BinaryWriter<W> {
+    inner: W,
+    order: Endianness,
+}
+
+macro_rules! write_number {
+    ($name:ident, $ty:ty) => {
+        pub fn $name(&mut self, value: $ty) -> std::io::Result<()> {
+            match self.order {
+                Endianness::LittleEndian => self.inner.write_all(&value.to_le_bytes()),
+                Endianness::BigEndian => self.inner.write_all(&value.to_be_bytes()),
+            }
+        }
+    };
+}
+
+impl<W: Write + Seek> BinaryWriter<W> {
+    pub fn new(inner: W, order: Endianness) -> BinaryWriter<W> {
+        BinaryWriter { inner, order }
+    }
+
+    pub fn order(&self) -> Endianness {
+        self.order
+    }
+
+    pub fn set_order(&mut self, order: Endianness) {
+        self.order = order;
+    }
+
+    pub fn position(&mut self) -> std::io::Result<u64> {
+        self.inner.stream_position()
+    }
+
+    write_number!(write_u16, u16);
+    write_number!(write_i16, i16);
+    write_number!(write_u32, u32);
+    write_number!(write_i32, i32);
+    write_number!(write_u64, u64);
+    write_number!(write_i64, i64);
+    write_number!(write_f32, f32);
+    write_number!(write_f64, f64);
+
+    pub fn write_u8(&mut self, value: u8) -> std::io::Result<()> {
+        self.inner.write_all(&[value])
+    }
+
+    pub fn write_i8(&mut self, value: i8) -> std::io::Result<()> {
+        self.write_u8(value as u8)
+    }
+
+    pub fn write_bytes(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+        self.inner.write_all(bytes)
+    }
+
+    ///
+    /// overwrite a u32 written earlier, then carry on from where writing was.
+    ///
+    pub fn patch_u32(&mut self, offset: u64, value: u32) -> std::io::Result<()> {
+        let position = self.inner.stream_position()?;
+        self.inner.seek(SeekFrom::Start(offset))?;
+        self.write_u32(value)?;
+        self.inner.seek(SeekFrom::Start(position))?;
+        Ok(())
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::support::binary::reader::BinaryReader;
+    use crate::support::binary::writer::BinaryWriter;
+    use crate::support::binary::endianness::Endianness::{BigEndian, LittleEndian};
+    use std::io::Cursor;
+
+    #[test]
+    fn test_round_trip() {
+        let mut writer = BinaryWriter::new(Cursor::new(vec![]), BigEndian);
+        writer.write_u32(0).unwrap();                                                           // size, patched below
+        writer.write_i16(-2).unwrap();
+        writer.set_order(LittleEndian);
+        writer.write_f64(0.5).unwrap();
+        writer.write_bytes(b"end").unwrap();
+        let size = writer.position().unwrap() as u32;
+        writer.set_order(BigEndian);
+        writer.patch_u32(0, size).unwrap();
+        let bytes = writer.into_inner().into_inner();
+        assert_eq!(&[0, 0, 0, 17, 0xff, 0xfe], &bytes[0..6]);
+
+        let mut reader = BinaryReader::new(Cursor::new(bytes), BigEndian).unwrap();
+        assert_eq!((17, -2), (reader.read_u32().unwrap(), reader.read_i16().unwrap()));
+        reader.set_order(LittleEndian);
+        assert_eq!(0.5, reader.read_f64().unwrap());
+        assert_eq!(b"end".to_vec(), reader.read_bytes(3).unwrap());
+    }
+}
@@ -1,14 +1,44 @@
+use crate::support::image::bitmap::Bitmap;
+use crate::support::image::netpbm::{Pam, Ppm};
+use crate::support::image::pixel::PixelFormat;
+use crate::support::image::png::Png;
+use crate::support::image::registry::load_registered;
+use crate::support::image::targa::{Targa, TargaRle};
+use crate::support::resource::Resource;
 use std::fs::File;
-use std::io::{BufRead, BufReader, Cursor, Seek};
+use std::io::ErrorKind::InvalidInput;
+use std::io::{BufRead, BufReader, BufWriter, Cursor, Error, Seek, Write};
 use std::path::Path;
 
 pub mod bitmap;
+pub mod netpbm;
+pub mod pixel;
+pub mod png;
+pub mod process;
+pub mod registry;
 pub mod targa;
 pub mod tiff;
 
+///
+/// load an image in any registered format, detected from its contents.
+///
+pub fn load_any(bytes: &[u8]) -> std::io::Result<RawImage> {
+    load_registered(bytes)
+}
+
+pub fn load_any_from_path<P: AsRef<Path>>(path: P) -> std::io::Result<RawImage> {
+    load_registered(&std::fs::read(path)?)
+}
+
+pub fn load_any_from_resource(resource: &dyn Resource) -> std::io::Result<RawImage> {
+    load_registered(&resource.data()?)
+}
+
+#[derive(Clone)]
 pub struct RawImage {
     pub width: u32,
     pub height: u32,
+    pub format: PixelFormat,
     pub data: Vec<u8>,
 }
 
@@ -17,9 +47,68 @@ impl RawImage {
         RawImage {
             width,
             height,
+            format: PixelFormat::Rgba8,
             data,
         }
     }
+
+    ///
+    /// encode the image in the given format.
+    ///
+    pub fn write_to<W: Write>(&self, writer: &mut W, format: ImageFormat) -> std::io::Result<()> {
+        match format {
+            ImageFormat::Bitmap => Bitmap::write_to_writer(self, writer),
+            ImageFormat::Pam => Pam::write_to_writer(self, writer),
+            ImageFormat::Png => Png::write_to_writer(self, writer),
+            ImageFormat::Ppm => Ppm::write_to_writer(self, writer),
+            ImageFormat::Targa => Targa::write_to_writer(self, writer),
+            ImageFormat::TargaRle => TargaRle::write_to_writer(self, writer),
+        }
+    }
+
+    ///
+    /// encode the image in the given format, and write it to a file.
+    ///
+    pub fn save<P: AsRef<Path>>(&self, path: P, format: ImageFormat) -> std::io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer, format)?;
+        writer.flush()
+    }
+
+    ///
+    /// encoders call this first; the data must be exactly width*height pixels of the image's format.
+    ///
+    pub(crate) fn validate(&self) -> std::io::Result<()> {
+        if self.data.len() != self.width as usize * self.height as usize * self.format.bytes_per_pixel() || self.width == 0 || self.height == 0 {
+            return Err(Error::new(InvalidInput, format!("image data doesn't match dimensions: {}x{} {:?}, {} bytes", self.width, self.height, self.format, self.data.len())));
+        }
+        Ok(())
+    }
+}
+
+///
+/// image file formats that can be written.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ImageFormat {
+    Bitmap,
+    Pam,
+    Png,
+    Ppm,
+    Targa,
+    TargaRle,
+}
+
+impl ImageFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Bitmap => "bmp",
+            ImageFormat::Pam => "pam",
+            ImageFormat::Png => "png",
+            ImageFormat::Ppm => "ppm",
+            ImageFormat::Targa | ImageFormat::TargaRle => "tga",
+        }
+    }
 }
 
 pub trait Image {
@@ -36,3 +125,81 @@ pub trait Image {
         Self::load_from_buf_read(cursor)
     }
 }
+
+///
+/// the encoding counterpart to [Image].
+///
+pub trait ImageWriter {
+    fn write_to_writer<W: Write>(image: &RawImage, writer: &mut W) -> std::io::Result<()>;
+
+    fn write_to_path<P: AsRef<Path>>(image: &RawImage, path: P) -> std::io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        Self::write_to_writer(image, &mut writer)?;
+        writer.flush()
+    }
+
+    fn write_to_bytes(image: &RawImage) -> std::io::Result<Vec<u8>> {
+        let mut bytes = vec![];
+        Self::write_to_writer(image, &mut bytes)?;
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::support::image::bitmap::Bitmap;
+    use crate::support::image::targa::Targa;
+    use crate::support::image::{Image, ImageFormat, RawImage};
+
+    fn gradient(width: u32, height: u32) -> RawImage {
+        let mut data = Vec::with_capacity((width * height * 4) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                data.extend_from_slice(&[(x * 40) as u8, (y * 60) as u8, 200, 255]);
+            }
+        }
+        RawImage::new(width, height, data)
+    }
+
+    #[test]
+    fn test_bitmap_round_trip() {
+        let image = gradient(3, 2);
+        let mut bytes = vec![];
+        image.write_to(&mut bytes, ImageFormat::Bitmap).unwrap();
+
+        let loaded = Bitmap::load_from_bytes(&bytes).unwrap();
+        assert_eq!((3, 2), (loaded.width, loaded.height));
+        assert_eq!(image.data, loaded.data);
+    }
+
+    #[test]
+    fn test_targa_round_trip() {
+        let image = gradient(4, 3);
+        let mut bytes = vec![];
+        image.write_to(&mut bytes, ImageFormat::Targa).unwrap();
+
+        let loaded = Targa::load_from_bytes(&bytes).unwrap();
+        assert_eq!((4, 3), (loaded.width, loaded.height));
+        assert_eq!(image.data, loaded.data);
+    }
+
+    #[test]
+    fn test_targa_rle_round_trip() {
+        /* long runs, short runs, and runs broken by a row boundary */
+        let mut image = gradient(150, 3);
+        image.data[0..140 * 4].fill(9);
+        let mut bytes = vec![];
+        image.write_to(&mut bytes, ImageFormat::TargaRle).unwrap();
+        assert!(bytes.len() < image.data.len());
+
+        let loaded = Targa::load_from_bytes(&bytes).unwrap();
+        assert_eq!((150, 3), (loaded.width, loaded.height));
+        assert_eq!(image.data, loaded.data);
+    }
+
+    #[test]
+    fn test_write_mismatched_dimensions() {
+        let image = RawImage::new(2, 2, vec![0; 4]);
+        assert!(image.write_to(&mut vec![], ImageFormat::Png).is_err());
+    }
+}
@@ -1,82 +1,501 @@
-use crate::support::image::{Image, RawImage};
+use crate::support::binary::reader::BinaryReader;
+use crate::support::image::{Image, ImageWriter, RawImage};
 use crate::support::logger::log;
 use crate::support::logger::log_level::LogLevel;
+use crate::support::binary::endianness::Endianness::LittleEndian;
 use std::io::ErrorKind::{InvalidData, Unsupported};
-use std::io::{BufRead, Error, Seek, SeekFrom};
+use std::io::{BufRead, Cursor, Error, Seek, SeekFrom, Write};
 
 pub struct Bitmap;
 
+const FILE_HEADER_LEN: usize = 14;
+const CORE_HEADER_LEN: u32 = 12;                                                                // os/2: BITMAPCOREHEADER
+const INFO_HEADER_LEN: u32 = 40;                                                                // win32: BITMAPINFOHEADER; V4 and V5 extend it
+
+/* compression */
+const BI_RGB: u32 = 0;
+const BI_RLE8: u32 = 1;
+const BI_RLE4: u32 = 2;
+const BI_BITFIELDS: u32 = 3;
+const BI_ALPHABITFIELDS: u32 = 6;
+
+#[repr(C)]
+#[derive(Debug)]
+struct BitmapMetaData {
+    /* file header */
+    file_sz: u32,
+    offset: u32,
+
+    /* dib */
+    dib_sz: u32,
+    width: u32,
+    height: u32,
+    top_down: bool,                                                                             // negative height; rows are stored top-to-bottom
+    bpp: u16,
+    compression: u32,
+    colors_used: u32,
+    masks: [u32; 4],                                                                            // red, green, blue, alpha
+}
+
 ///
 /// windows bitmap file format (win32: BITMAPFILEHEADER).
 ///
-/// bitmaps are 24-bit (no alpha channel), with colors BGR.
+/// supports the core, info, V4 and V5 headers; 1/4/8-bit palettized images (optionally rle
+/// compressed), and 16/24/32-bit images, with bit field masks. colors are stored BGR(A).
 ///
 impl Image for Bitmap {
     fn load_from_buf_read<R: BufRead + Seek>(mut reader: R) -> std::io::Result<RawImage> {
-        /* extract header data */
-        let mut header = [0u8;14];
-        reader.read_exact(&mut header)?;
-
-        /* extract header data */
-        let magic: [u8;2] = header[0..2].try_into().unwrap();
-        let file_sz = u32::from_le_bytes(header[2..6].try_into().unwrap());
-        let reserved = u32::from_le_bytes(header[6..10].try_into().unwrap());
-        let offset = u32::from_le_bytes(header[10..14].try_into().unwrap());
-        log(LogLevel::Debug, &||format!("BMP: Header: magic={:?}, file_sz={}, reserved={}, offset={}", magic, file_sz, reserved, offset));
+        /* bitmaps are addressed by file offset; read the whole thing */
+        reader.seek(SeekFrom::Start(0))?;
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes)?;
 
         /* first 2 bytes should be BM; otherwise, not a bitmap */
-        if magic != *b"BM" {
+        if bytes.get(0..2) != Some(b"BM") {
             return Err(Error::new(InvalidData, "not a bitmap!"));
         }
 
-        /* read dib */
-        let mut dib = [0u8; 40];
-        reader.read_exact(&mut dib)?;
-
-        /* extract image details */
-        let dib_sz = u32::from_le_bytes(dib[0..4].try_into().unwrap());
-        let width =  u32::from_le_bytes(dib[4..8].try_into().unwrap());
-        let height = u32::from_le_bytes(dib[8..12].try_into().unwrap());
-        let planes = u16::from_le_bytes(dib[12..14].try_into().unwrap());
-        let bpp = u16::from_le_bytes(dib[14..16].try_into().unwrap());
-        log(LogLevel::Debug, &||format!("BMP: DIB: dib_sz={}, width={}, height={}, planes={}, bpp={}", dib_sz, width, height, planes, bpp));
-
-        /* prepare read pixel data */
-        reader.seek(SeekFrom::Start(offset as u64))?;
-        let mut bytes = vec!();
-        reader.read_to_end(&mut bytes).expect("BMP: failed to read image data into byte array");
-        //log(LogLevel::Debug, &||format!("BMP: actual image bytes={}", bytes.len()));
-
-        /* determine if there's a color table */
-        let color_table_bytes = file_sz - (14 + dib_sz + (bytes.len() as u32));
-        log(LogLevel::Debug, &||format!("BMP: color table bytes: {}", color_table_bytes));
-
-        /* read the pixel data */
-        if bpp == 24 {
-            log(LogLevel::Debug, &||format!("BMP: 24b: bytes={}, pixels(bytes/3)={}, width*height={}", bytes.len(), bytes.len() as f32 / 3f32, width * height));
-            let pixels = parse_24_bit(width, height, bytes);
-            log(LogLevel::Debug, &|| "BMP: 24b: success".to_string());
-            Ok(RawImage::new(width, height, pixels))
+        /* extract header data */
+        let metadata = load_metadata(&bytes)?;
+        log(LogLevel::Debug, &|| format!("BMP: {:?}", metadata));
+        validate(&metadata)?;
+
+        /* the color table, if any, sits between the headers and the pixel data */
+        let palette = load_palette(&metadata, &bytes)?;
+        log(LogLevel::Debug, &|| format!("BMP: color table entries: {}", palette.len()));
+
+        /* read the pixel data; bitmaps are bottom-to-top, unless the height was negative */
+        let data = bytes.get(metadata.offset as usize..).ok_or_else(truncated)?;
+        let rows = match metadata.compression {
+            BI_RLE8 | BI_RLE4 => {
+                let indices = decode_rle(&metadata, data)?;
+                indices.iter().map(|idx| lookup(&palette, *idx as u32)).collect::<std::io::Result<Vec<_>>>()?.concat()
+            }
+            _ => parse_rows(&metadata, &palette, data)?,
+        };
+        let pixels = if metadata.top_down {
+            rows
         } else {
-            Err(Error::new(Unsupported, format!("BMP: unsupported bit encoding: {}", bpp)))
+            rows.chunks_exact(metadata.width as usize * 4).rev().flatten().copied().collect()
+        };
+
+        log(LogLevel::Debug, &|| format!("BMP: {}b: success", metadata.bpp));
+        Ok(RawImage::new(metadata.width, metadata.height, pixels))
+    }
+}
+
+///
+/// write 24-bit bitmaps; the alpha channel is discarded.
+///
+impl ImageWriter for Bitmap {
+    fn write_to_writer<W: Write>(image: &RawImage, writer: &mut W) -> std::io::Result<()> {
+        let rgba = image.as_rgba8()?;
+        let image = rgba.as_ref();
+
+        /* rows are padded to a multiple of 4 bytes */
+        let row_len = (image.width as usize * 3).div_ceil(4) * 4;
+        let data_sz = (row_len * image.height as usize) as u32;
+        let offset = 14 + 40;
+
+        /* header */
+        writer.write_all(b"BM")?;
+        writer.write_all(&(offset + data_sz).to_le_bytes())?;
+        writer.write_all(&0u32.to_le_bytes())?;
+        writer.write_all(&offset.to_le_bytes())?;
+
+        /* dib (BITMAPINFOHEADER) */
+        writer.write_all(&40u32.to_le_bytes())?;
+        writer.write_all(&image.width.to_le_bytes())?;
+        writer.write_all(&image.height.to_le_bytes())?;
+        writer.write_all(&1u16.to_le_bytes())?;                    // planes
+        writer.write_all(&24u16.to_le_bytes())?;                   // bpp
+        writer.write_all(&0u32.to_le_bytes())?;                    // compression: BI_RGB
+        writer.write_all(&data_sz.to_le_bytes())?;
+        writer.write_all(&2835u32.to_le_bytes())?;                 // 72 dpi, horizontal
+        writer.write_all(&2835u32.to_le_bytes())?;                 // 72 dpi, vertical
+        writer.write_all(&0u32.to_le_bytes())?;                    // colors in palette
+        writer.write_all(&0u32.to_le_bytes())?;                    // important colors
+
+        /* pixel data, bottom-to-top, BGR */
+        let mut row = Vec::with_capacity(row_len);
+        for pixels in image.data.chunks_exact(image.width as usize * 4).rev() {
+            row.clear();
+            for pixel in pixels.chunks_exact(4) {
+                row.extend_from_slice(&[pixel[2], pixel[1], pixel[0]]);
+            }
+            row.resize(row_len, 0);
+            writer.write_all(&row)?;
         }
+
+        Ok(())
     }
 }
 
-fn parse_24_bit(width: u32, height: u32, bytes: Vec<u8>) -> Vec<u8> {
-    let mut pixels = Vec::with_capacity((width * height * 4) as usize);
-    let row_len = bytes.len() / height as usize;
+fn truncated() -> Error {
+    Error::new(InvalidData, "BMP: file is truncated")
+}
+
+fn load_metadata(bytes: &[u8]) -> std::io::Result<BitmapMetaData> {
+    let mut reader = BinaryReader::new(Cursor::new(bytes), LittleEndian)?.with_label("BMP");
+
+    /* file header */
+    reader.skip(2)?;                                                                            // BM
+    let file_sz = reader.read_u32()?;
+    reader.skip(4)?;                                                                            // reserved
+    let offset = reader.read_u32()?;
+
+    /* dib; the size tells us which version of the header we have, and all of it must be there */
+    let dib_sz = reader.read_u32()?;
+    if dib_sz < 4 || reader.remaining() < (dib_sz - 4) as u64 {
+        return Err(truncated());
+    }
+
+    if dib_sz == CORE_HEADER_LEN {
+        let width = reader.read_u16()? as u32;
+        let height = reader.read_u16()? as u32;
+        reader.skip(2)?;                                                                        // planes
+        let bpp = reader.read_u16()?;
+        return Ok(BitmapMetaData {
+            file_sz,
+            offset,
+            dib_sz,
+            width,
+            height,
+            top_down: false,
+            bpp,
+            compression: BI_RGB,
+            colors_used: 0,
+            masks: default_masks(bpp),
+        });
+    }
+    if dib_sz < INFO_HEADER_LEN {
+        return Err(Error::new(Unsupported, format!("BMP: unsupported header size: {}", dib_sz)));
+    }
+
+    let width = reader.read_i32()?;
+    let height = reader.read_i32()?;
+    reader.skip(2)?;                                                                            // planes
+    let bpp = reader.read_u16()?;
+    let compression = reader.read_u32()?;
+    reader.skip(12)?;                                                                           // image size, resolution
+    let colors_used = reader.read_u32()?;
+
+    /* bit field masks: in the header (v2+), or just after an info header; either way, at the same offset */
+    let mut masks = default_masks(bpp);
+    if compression == BI_BITFIELDS || compression == BI_ALPHABITFIELDS {
+        let mask_count = if compression == BI_ALPHABITFIELDS { 4 } else { 3 };
+        reader.seek((FILE_HEADER_LEN + 40) as u64)?;
+        for mask in masks.iter_mut().take(mask_count) {
+            *mask = reader.read_u32()?;
+        }
+    }
+    if dib_sz >= 56 {
+        reader.seek((FILE_HEADER_LEN + 52) as u64)?;
+        let alpha = reader.read_u32()?;
+        if alpha != 0 {
+            masks[3] = alpha;
+        }
+    }
+
+    Ok(BitmapMetaData {
+        file_sz,
+        offset,
+        dib_sz,
+        width: width.unsigned_abs(),
+        height: height.unsigned_abs(),
+        top_down: height < 0,
+        bpp,
+        compression,
+        colors_used,
+        masks,
+    })
+}
+
+fn default_masks(bpp: u16) -> [u32; 4] {
+    match bpp {
+        16 => [0x7c00, 0x03e0, 0x001f, 0],                                                     // 5-5-5
+        _ => [0x00ff_0000, 0x0000_ff00, 0x0000_00ff, 0],
+    }
+}
+
+fn validate(metadata: &BitmapMetaData) -> std::io::Result<()> {
+    if metadata.width == 0 || metadata.height == 0 {
+        return Err(Error::new(InvalidData, format!("BMP: invalid dimensions: {}x{}", metadata.width, metadata.height)));
+    }
+
+    let valid = match metadata.compression {
+        BI_RGB => [1, 4, 8, 16, 24, 32].contains(&metadata.bpp),
+        BI_RLE8 => metadata.bpp == 8 && !metadata.top_down,
+        BI_RLE4 => metadata.bpp == 4 && !metadata.top_down,
+        BI_BITFIELDS | BI_ALPHABITFIELDS => [16, 32].contains(&metadata.bpp),
+        _ => false,
+    };
+    if !valid {
+        return Err(Error::new(Unsupported, format!("BMP: unsupported bit encoding: {}, compression: {}", metadata.bpp, metadata.compression)));
+    }
+
+    Ok(())
+}
+
+fn load_palette(metadata: &BitmapMetaData, bytes: &[u8]) -> std::io::Result<Vec<[u8; 4]>> {
+    if metadata.bpp > 8 {
+        return Ok(vec![]);
+    }
+
+    /* core headers use 3-byte entries (BGR), the rest use 4 (BGRX) */
+    let entry_sz = if metadata.dib_sz == CORE_HEADER_LEN { 3 } else { 4 };
+    let mut start = FILE_HEADER_LEN + metadata.dib_sz as usize;
+    if metadata.dib_sz == INFO_HEADER_LEN && metadata.compression == BI_BITFIELDS {
+        start += 12;
+    }
+    let count = if metadata.colors_used == 0 { 1 << metadata.bpp } else { metadata.colors_used as usize };
+
+    /* some writers overstate the number of colors; take what fits before the pixel data */
+    let end = (start + count * entry_sz).min(metadata.offset as usize);
+    let table = bytes.get(start..end.max(start)).ok_or_else(truncated)?;
+    Ok(table.chunks_exact(entry_sz).map(|bgr| [bgr[2], bgr[1], bgr[0], 255]).collect())
+}
+
+fn lookup(palette: &[[u8; 4]], idx: u32) -> std::io::Result<[u8; 4]> {
+    palette.get(idx as usize).copied()
+        .ok_or_else(|| Error::new(InvalidData, format!("BMP: color table index out of range: {}", idx)))
+}
 
-    for row in bytes.rchunks(row_len) {
-        for pixel in row.chunks(3) {
-            if pixel.len() == 3 {
-                pixels.push(pixel[2]);      // BGR: red
-                pixels.push(pixel[1]);      // BGR: green
-                pixels.push(pixel[0]);      // BGR: blue
-                pixels.push(255);           // we're inventing our own alpha channel; could also be programmable
+///
+/// the number of pixels, if an rgba buffer of them can be addressed at all.
+///
+fn pixel_count(metadata: &BitmapMetaData) -> std::io::Result<usize> {
+    (metadata.width as usize).checked_mul(metadata.height as usize)
+        .filter(|count| count.checked_mul(4).is_some())
+        .ok_or_else(|| Error::new(InvalidData, format!("BMP: image too large: {}x{}", metadata.width, metadata.height)))
+}
+
+fn parse_rows(metadata: &BitmapMetaData, palette: &[[u8; 4]], bytes: &[u8]) -> std::io::Result<Vec<u8>> {
+    let width = metadata.width as usize;
+    let bpp = metadata.bpp as usize;
+
+    /* rows are padded to a multiple of 4 bytes; the padding on the last row is sometimes missing */
+    let row_len = (width * bpp).div_ceil(32) * 4;
+    let used_len = (width * bpp).div_ceil(8);
+
+    /* the sizes come from the header; all the rows must be there before anything's allocated for them */
+    let needed = row_len.checked_mul(metadata.height as usize - 1).and_then(|len| len.checked_add(used_len));
+    if needed.is_none_or(|needed| needed > bytes.len()) {
+        return Err(truncated());
+    }
+    let mut pixels = Vec::with_capacity(pixel_count(metadata)? * 4);
+
+    /* 32-bit images without an alpha mask often store alpha anyway; use it, unless it's all zero */
+    let mut masks = metadata.masks;
+    if bpp == 32 && masks[3] == 0 && metadata.compression == BI_RGB
+        && bytes.chunks_exact(4).take(width * metadata.height as usize).any(|pixel| pixel[3] != 0) {
+        masks[3] = 0xff00_0000;
+    }
+
+    for y in 0..metadata.height as usize {
+        let row = bytes.get(y * row_len..y * row_len + used_len).ok_or_else(truncated)?;
+        for x in 0..width {
+            let pixel = match bpp {
+                1 | 4 | 8 => {
+                    let bit = x * bpp;
+                    let idx = (row[bit / 8] >> (8 - bpp - bit % 8)) & ((1u16 << bpp) - 1) as u8;
+                    lookup(palette, idx as u32)?
+                }
+                24 => [row[x * 3 + 2], row[x * 3 + 1], row[x * 3], 255],                       // BGR
+                16 => masked_pixel(u16::from_le_bytes([row[x * 2], row[x * 2 + 1]]) as u32, &masks),
+                _ => masked_pixel(LittleEndian.read_u32(row, x * 4)?, &masks),
+            };
+            pixels.extend_from_slice(&pixel);
+        }
+    }
+
+    Ok(pixels)
+}
+
+fn masked_pixel(value: u32, masks: &[u32; 4]) -> [u8; 4] {
+    let alpha = if masks[3] == 0 { 255 } else { masked(value, masks[3]) };
+    [masked(value, masks[0]), masked(value, masks[1]), masked(value, masks[2]), alpha]
+}
+
+/* extract the masked bits, scaled to 8 bits */
+fn masked(value: u32, mask: u32) -> u8 {
+    if mask == 0 {
+        return 0;
+    }
+    let shift = mask.trailing_zeros();
+    let bits = (mask >> shift).count_ones();
+    let channel = (value & mask) >> shift;
+    if bits >= 8 {
+        (channel >> (bits - 8)) as u8
+    } else {
+        (channel * 255 / ((1 << bits) - 1)) as u8
+    }
+}
+
+///
+/// expand rle4/rle8 data into one color table index per pixel.
+///
+/// pixels skipped by a delta, or left undefined at the end, get index 0. the output grows as pixels are
+/// decoded, rather than being sized from the header up front, so truncated data fails before it's all allocated.
+///
+fn decode_rle(metadata: &BitmapMetaData, bytes: &[u8]) -> std::io::Result<Vec<u8>> {
+    let width = metadata.width as usize;
+    let height = metadata.height as usize;
+    let count = pixel_count(metadata)?;
+    let nibbles = metadata.compression == BI_RLE4;
+    let mut indices = vec![];
+    let (mut x, mut y, mut idx) = (0usize, 0usize, 0usize);
+
+    let mut put = |x: &mut usize, y: usize, value: u8| -> std::io::Result<()> {
+        if *x < width && y < height {
+            let at = y * width + *x;
+            if at >= indices.len() {
+                grow(&mut indices, at + 1)?;
+            }
+            indices[at] = value;
+        }
+        *x += 1;
+        Ok(())
+    };
+
+    while y < height {
+        let pair = bytes.get(idx..idx + 2).ok_or_else(truncated)?;
+        idx += 2;
+        match (pair[0], pair[1]) {
+            (0, 0) => {                                                                         // end of line
+                x = 0;
+                y += 1;
             }
+            (0, 1) => break,                                                                    // end of bitmap
+            (0, 2) => {                                                                         // delta
+                let delta = bytes.get(idx..idx + 2).ok_or_else(truncated)?;
+                x += delta[0] as usize;
+                y += delta[1] as usize;
+                idx += 2;
+            }
+            (0, n) => {                                                                         // absolute run, padded to a word
+                let n = n as usize;
+                let len = if nibbles { n.div_ceil(2) } else { n };
+                let run = bytes.get(idx..idx + len).ok_or_else(truncated)?;
+                for i in 0..n {
+                    let value = if nibbles { (run[i / 2] >> (4 - (i % 2) * 4)) & 0x0f } else { run[i] };
+                    put(&mut x, y, value)?;
+                }
+                idx += len + len % 2;
+            }
+            (n, value) => {                                                                     // encoded run
+                for i in 0..n as usize {
+                    let value = if nibbles { (value >> (4 - (i % 2) * 4)) & 0x0f } else { value };
+                    put(&mut x, y, value)?;
+                }
+            }
+        }
+    }
+
+    grow(&mut indices, count)?;
+    Ok(indices)
+}
+
+///
+/// extend with index 0, failing rather than aborting if the memory isn't there.
+///
+fn grow(indices: &mut Vec<u8>, len: usize) -> std::io::Result<()> {
+    indices.try_reserve(len - indices.len())
+        .map_err(|_| Error::new(InvalidData, format!("BMP: image too large: {} pixels", len)))?;
+    indices.resize(len, 0);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::support::image::bitmap::{Bitmap, BI_BITFIELDS, BI_RGB, BI_RLE4, BI_RLE8};
+    use crate::support::image::Image;
+
+    fn dib(size: u32, width: i32, height: i32, bpp: u16, compression: u32, colors: u32) -> Vec<u8> {
+        let mut dib = vec![0u8; size as usize];
+        dib[0..4].copy_from_slice(&size.to_le_bytes());
+        dib[4..8].copy_from_slice(&width.to_le_bytes());
+        dib[8..12].copy_from_slice(&height.to_le_bytes());
+        dib[12..14].copy_from_slice(&1u16.to_le_bytes());
+        dib[14..16].copy_from_slice(&bpp.to_le_bytes());
+        dib[16..20].copy_from_slice(&compression.to_le_bytes());
+        dib[32..36].copy_from_slice(&colors.to_le_bytes());
+        dib
+    }
+
+    /* file header, dib, color table or masks, then pixels */
+    fn bitmap(dib: &[u8], table: &[u8], pixels: &[u8]) -> Vec<u8> {
+        let offset = (14 + dib.len() + table.len()) as u32;
+        let mut bytes = b"BM".to_vec();
+        bytes.extend_from_slice(&(offset + pixels.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&[0; 4]);
+        bytes.extend_from_slice(&offset.to_le_bytes());
+        [bytes, dib.to_vec(), table.to_vec(), pixels.to_vec()].concat()
+    }
+
+    #[test]
+    fn test_8_bit_palette() {
+        /* 3x2, so each row has a byte of padding; bottom row first */
+        let table = [0, 0, 255, 0, 0, 255, 0, 0, 255, 0, 255, 0];
+        let pixels = [0, 1, 2, 0, 2, 2, 2, 0];
+        let image = Bitmap::load_from_bytes(&bitmap(&dib(40, 3, 2, 8, BI_RGB, 3), &table, &pixels)).unwrap();
+        assert_eq!((3, 2), (image.width, image.height));
+        assert_eq!(vec![
+            255, 0, 255, 255, 255, 0, 255, 255, 255, 0, 255, 255,
+            255, 0, 0, 255, 0, 255, 0, 255, 255, 0, 255, 255,
+        ], image.data);
+
+        /* index 3 isn't in the table */
+        let pixels = [3, 0, 0, 0, 0, 0, 0, 0];
+        assert!(Bitmap::load_from_bytes(&bitmap(&dib(40, 3, 2, 8, BI_RGB, 3), &table, &pixels)).is_err());
+    }
+
+    #[test]
+    fn test_rle() {
+        let table = [0, 0, 0, 0, 255, 255, 255, 0];
+        let index = |image: &Vec<u8>| image.chunks_exact(4).map(|p| (p[0] == 255) as u8).collect::<Vec<u8>>();
+
+        /* rle8: bottom row is a run of 3, then a padded absolute run that overflows the row; top row is a delta, then a run */
+        let data = [3, 1, 0, 3, 0, 1, 0, 0, 0, 0, 0, 2, 2, 0, 2, 1, 0, 1];
+        let image = Bitmap::load_from_bytes(&bitmap(&dib(40, 4, 2, 8, BI_RLE8, 2), &table, &data)).unwrap();
+        assert_eq!(vec![0, 0, 1, 1, 1, 1, 1, 0], index(&image.data));
+
+        /* rle4: encoded runs alternate between the two nibbles */
+        let data = [4, 0x10, 0, 0, 0, 4, 0x01, 0x10, 0, 1];
+        let image = Bitmap::load_from_bytes(&bitmap(&dib(40, 4, 2, 4, BI_RLE4, 2), &table, &data)).unwrap();
+        assert_eq!(vec![0, 1, 1, 0, 1, 0, 1, 0], index(&image.data));
+    }
+
+    #[test]
+    fn test_16_bit_bitfields_top_down() {
+        /* 5-6-5 masks follow an info header */
+        let masks = [0x00, 0xf8, 0, 0, 0xe0, 0x07, 0, 0, 0x1f, 0, 0, 0];
+        let pixels = [0x00, 0xf8, 0xe0, 0x07, 0x1f, 0x00, 0x00, 0x00];
+        let image = Bitmap::load_from_bytes(&bitmap(&dib(40, 2, -2, 16, BI_BITFIELDS, 0), &masks, &pixels)).unwrap();
+        assert_eq!(vec![255, 0, 0, 255, 0, 255, 0, 255, 0, 0, 255, 255, 0, 0, 0, 255], image.data);
+    }
+
+    #[test]
+    fn test_32_bit_v5_alpha() {
+        /* BGRA masks, including alpha, in a V5 header */
+        let mut header = dib(124, 1, 1, 32, BI_BITFIELDS, 0);
+        for (idx, mask) in [0x00ff_0000u32, 0x0000_ff00, 0x0000_00ff, 0xff00_0000].iter().enumerate() {
+            header[40 + idx * 4..44 + idx * 4].copy_from_slice(&mask.to_le_bytes());
         }
+        let image = Bitmap::load_from_bytes(&bitmap(&header, &[], &[10, 20, 30, 40])).unwrap();
+        assert_eq!(vec![30, 20, 10, 40], image.data);
     }
 
-    pixels
+    #[test]
+    fn test_truncated() {
+        let bytes = bitmap(&dib(40, 4, 4, 24, BI_RGB, 0), &[], &[0; 20]);
+        assert!(Bitmap::load_from_bytes(&bytes).is_err());
+        assert!(Bitmap::load_from_bytes(&bytes[0..20]).is_err());
+        assert!(Bitmap::load_from_bytes(&bitmap(&dib(40, 1, 1, 2, BI_RGB, 0), &[], &[0; 4])).is_err());
+
+        /* huge dimensions with little data fail on the data, rather than allocating for the dimensions */
+        let huge = 1 << 30;
+        assert!(Bitmap::load_from_bytes(&bitmap(&dib(40, huge, huge, 24, BI_RGB, 0), &[], &[0; 4])).is_err());
+        assert!(Bitmap::load_from_bytes(&bitmap(&dib(40, huge, huge, 8, BI_RLE8, 1), &[0; 4], &[2, 0, 0, 0])).is_err());
+    }
 }
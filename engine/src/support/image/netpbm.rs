@@ -0,0 +1,50 @@
+use crate::support::image::{ImageWriter, RawImage};
+use std::io::Write;
+
+pub struct Ppm;
+pub struct Pam;
+
+///
+/// write binary portable pixmaps (P6); the alpha channel is discarded.
+///
+impl ImageWriter for Ppm {
+    fn write_to_writer<W: Write>(image: &RawImage, writer: &mut W) -> std::io::Result<()> {
+        let rgba = image.as_rgba8()?;
+        let image = rgba.as_ref();
+        write!(writer, "P6\n{} {}\n255\n", image.width, image.height)?;
+
+        let mut rgb = Vec::with_capacity(image.width as usize * image.height as usize * 3);
+        for pixel in image.data.chunks_exact(4) {
+            rgb.extend_from_slice(&pixel[0..3]);
+        }
+        writer.write_all(&rgb)
+    }
+}
+
+///
+/// write portable arbitrary maps (P7), with rgba tuples.
+///
+impl ImageWriter for Pam {
+    fn write_to_writer<W: Write>(image: &RawImage, writer: &mut W) -> std::io::Result<()> {
+        let rgba = image.as_rgba8()?;
+        let image = rgba.as_ref();
+        write!(writer, "P7\nWIDTH {}\nHEIGHT {}\nDEPTH 4\nMAXVAL 255\nTUPLTYPE RGB_ALPHA\nENDHDR\n", image.width, image.height)?;
+        writer.write_all(&image.data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::support::image::netpbm::{Pam, Ppm};
+    use crate::support::image::{ImageWriter, RawImage};
+
+    #[test]
+    fn test_write() {
+        let image = RawImage::new(2, 1, vec![1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(b"P6\n2 1\n255\n\x01\x02\x03\x05\x06\x07".to_vec(), Ppm::write_to_bytes(&image).unwrap());
+
+        let pam = Pam::write_to_bytes(&image).unwrap();
+        assert!(pam.starts_with(b"P7\nWIDTH 2\nHEIGHT 1\nDEPTH 4\nMAXVAL 255\nTUPLTYPE RGB_ALPHA\nENDHDR\n"));
+        assert_eq!(image.data, pam[pam.len() - 8..]);
+    }
+}
@@ -0,0 +1,185 @@
+use crate::support::image::RawImage;
+use std::borrow::Cow;
+
+///
+/// layout of the pixels in a [RawImage].
+///
+/// multi-byte channels (16-bit, float) are stored in native byte order, ready for upload.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PixelFormat {
+    R8,
+    Rg8,
+    Rgb8,
+    Rgba8,
+    Bgr8,
+    Bgra8,
+    L8,                                                                                         // luminance (gray)
+    La8,                                                                                        // luminance + alpha
+    Rgba16,
+    Rgba32F,
+}
+
+impl PixelFormat {
+    pub fn bytes_per_pixel(&self) -> usize {
+        match self {
+            PixelFormat::R8 | PixelFormat::L8 => 1,
+            PixelFormat::Rg8 | PixelFormat::La8 => 2,
+            PixelFormat::Rgb8 | PixelFormat::Bgr8 => 3,
+            PixelFormat::Rgba8 | PixelFormat::Bgra8 => 4,
+            PixelFormat::Rgba16 => 8,
+            PixelFormat::Rgba32F => 16,
+        }
+    }
+
+    pub fn has_alpha(&self) -> bool {
+        matches!(self, PixelFormat::Rgba8 | PixelFormat::Bgra8 | PixelFormat::La8 | PixelFormat::Rgba16 | PixelFormat::Rgba32F)
+    }
+
+    ///
+    /// read one pixel as normalized rgba; missing channels are 0, missing alpha is 1.
+    ///
+    pub fn decode(&self, bytes: &[u8]) -> [f32; 4] {
+        let n = |byte: u8| byte as f32 / 255.0;
+        match self {
+            PixelFormat::R8 => [n(bytes[0]), 0.0, 0.0, 1.0],
+            PixelFormat::Rg8 => [n(bytes[0]), n(bytes[1]), 0.0, 1.0],
+            PixelFormat::Rgb8 => [n(bytes[0]), n(bytes[1]), n(bytes[2]), 1.0],
+            PixelFormat::Rgba8 => [n(bytes[0]), n(bytes[1]), n(bytes[2]), n(bytes[3])],
+            PixelFormat::Bgr8 => [n(bytes[2]), n(bytes[1]), n(bytes[0]), 1.0],
+            PixelFormat::Bgra8 => [n(bytes[2]), n(bytes[1]), n(bytes[0]), n(bytes[3])],
+            PixelFormat::L8 => [n(bytes[0]), n(bytes[0]), n(bytes[0]), 1.0],
+            PixelFormat::La8 => [n(bytes[0]), n(bytes[0]), n(bytes[0]), n(bytes[1])],
+            PixelFormat::Rgba16 => {
+                let c = |idx: usize| u16::from_ne_bytes([bytes[idx * 2], bytes[idx * 2 + 1]]) as f32 / 65535.0;
+                [c(0), c(1), c(2), c(3)]
+            }
+            PixelFormat::Rgba32F => {
+                let c = |idx: usize| f32::from_ne_bytes(bytes[idx * 4..idx * 4 + 4].try_into().unwrap());
+                [c(0), c(1), c(2), c(3)]
+            }
+        }
+    }
+
+    ///
+    /// append one pixel, from normalized rgba; luminance uses rec. 709 weights.
+    ///
+    pub fn encode(&self, rgba: [f32; 4], out: &mut Vec<u8>) {
+        let b = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+        let [r, g, bl, a] = rgba;
+        match self {
+            PixelFormat::R8 => out.push(b(r)),
+            PixelFormat::Rg8 => out.extend_from_slice(&[b(r), b(g)]),
+            PixelFormat::Rgb8 => out.extend_from_slice(&[b(r), b(g), b(bl)]),
+            PixelFormat::Rgba8 => out.extend_from_slice(&[b(r), b(g), b(bl), b(a)]),
+            PixelFormat::Bgr8 => out.extend_from_slice(&[b(bl), b(g), b(r)]),
+            PixelFormat::Bgra8 => out.extend_from_slice(&[b(bl), b(g), b(r), b(a)]),
+            PixelFormat::L8 => out.push(b(luminance(rgba))),
+            PixelFormat::La8 => out.extend_from_slice(&[b(luminance(rgba)), b(a)]),
+            PixelFormat::Rgba16 => {
+                for channel in rgba {
+                    out.extend_from_slice(&((channel.clamp(0.0, 1.0) * 65535.0).round() as u16).to_ne_bytes());
+                }
+            }
+            PixelFormat::Rgba32F => rgba.iter().for_each(|channel| out.extend_from_slice(&channel.to_ne_bytes())),
+        }
+    }
+}
+
+fn luminance(rgba: [f32; 4]) -> f32 {
+    0.2126 * rgba[0] + 0.7152 * rgba[1] + 0.0722 * rgba[2]
+}
+
+impl RawImage {
+    ///
+    /// an image in some format other than rgba8.
+    ///
+    pub fn with_format(width: u32, height: u32, format: PixelFormat, data: Vec<u8>) -> RawImage {
+        RawImage {
+            width,
+            height,
+            format,
+            data,
+        }
+    }
+
+    ///
+    /// every pixel, as normalized rgba.
+    ///
+    pub fn to_rgba_f32(&self) -> Vec<[f32; 4]> {
+        self.data.chunks_exact(self.format.bytes_per_pixel()).map(|pixel| self.format.decode(pixel)).collect()
+    }
+
+    ///
+    /// build an image in the given format from normalized rgba pixels.
+    ///
+    pub fn from_rgba_f32(width: u32, height: u32, format: PixelFormat, pixels: &[[f32; 4]]) -> RawImage {
+        let mut data = Vec::with_capacity(pixels.len() * format.bytes_per_pixel());
+        pixels.iter().for_each(|pixel| format.encode(*pixel, &mut data));
+        RawImage::with_format(width, height, format, data)
+    }
+
+    ///
+    /// a copy of the image in another format.
+    ///
+    pub fn convert(&self, format: PixelFormat) -> RawImage {
+        match (self.format, format) {
+            (from, to) if from == to => self.clone(),
+            (PixelFormat::Rgba8, PixelFormat::Bgra8) | (PixelFormat::Bgra8, PixelFormat::Rgba8) => {
+                let mut data = self.data.clone();
+                data.chunks_exact_mut(4).for_each(|pixel| pixel.swap(0, 2));
+                RawImage::with_format(self.width, self.height, format, data)
+            }
+            _ => RawImage::from_rgba_f32(self.width, self.height, format, &self.to_rgba_f32()),
+        }
+    }
+
+    pub fn into_format(self, format: PixelFormat) -> RawImage {
+        if self.format == format { self } else { self.convert(format) }
+    }
+
+    ///
+    /// the image as rgba8, converting only if necessary; the data length is checked first.
+    ///
+    pub fn as_rgba8(&self) -> std::io::Result<Cow<'_, RawImage>> {
+        self.validate()?;
+        Ok(match self.format {
+            PixelFormat::Rgba8 => Cow::Borrowed(self),
+            _ => Cow::Owned(self.convert(PixelFormat::Rgba8)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::support::image::pixel::PixelFormat;
+    use crate::support::image::RawImage;
+
+    #[test]
+    fn test_round_trips() {
+        let image = RawImage::new(2, 1, vec![10, 20, 30, 40, 250, 128, 0, 255]);
+        for format in [PixelFormat::Rgba8, PixelFormat::Bgra8, PixelFormat::Rgba16, PixelFormat::Rgba32F] {
+            let converted = image.convert(format);
+            assert_eq!(2 * format.bytes_per_pixel(), converted.data.len());
+            assert_eq!(image.data, converted.convert(PixelFormat::Rgba8).data);
+        }
+        assert_eq!(vec![30, 20, 10, 40, 0, 128, 250, 255], image.convert(PixelFormat::Bgra8).data);
+    }
+
+    #[test]
+    fn test_lossy_conversions() {
+        let image = RawImage::new(1, 1, vec![255, 255, 255, 100]);
+        assert_eq!(vec![255, 255, 255], image.convert(PixelFormat::Rgb8).data);
+        assert_eq!(vec![255, 100], image.convert(PixelFormat::La8).data);
+        assert_eq!(vec![255, 0, 0, 255], RawImage::with_format(1, 1, PixelFormat::R8, vec![255]).convert(PixelFormat::Rgba8).data);
+        assert_eq!(vec![9, 9, 9, 255], RawImage::with_format(1, 1, PixelFormat::L8, vec![9]).convert(PixelFormat::Rgba8).data);
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(RawImage::with_format(2, 2, PixelFormat::Rgb8, vec![0; 12]).as_rgba8().is_ok());
+        assert!(RawImage::with_format(2, 2, PixelFormat::Rgb8, vec![0; 16]).as_rgba8().is_err());
+        assert!(RawImage::with_format(2, 0, PixelFormat::Rgb8, vec![]).as_rgba8().is_err());
+        assert!(RawImage::with_format(0, 2, PixelFormat::Rgb8, vec![]).as_rgba8().is_err());
+    }
+}
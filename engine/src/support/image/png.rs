@@ -0,0 +1,517 @@
+use crate::support::image::{Image, ImageWriter, RawImage};
+use crate::support::logger::log;
+use crate::support::logger::log_level::LogLevel;
+use crate::support::zlib;
+use crate::support::zlib::crc32_update;
+use std::io::ErrorKind::{InvalidData, Unsupported};
+use std::io::{BufRead, Error, Read, Seek, Write};
+
+pub struct Png;
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+/* adam7 interlacing passes: x start, y start, x step, y step */
+const ADAM7: [(usize, usize, usize, usize); 7] = [(0, 0, 8, 8), (4, 0, 8, 8), (0, 4, 4, 8), (2, 0, 4, 4), (0, 2, 2, 4), (1, 0, 2, 2), (0, 1, 1, 2)];
+
+#[derive(Debug)]
+struct PngHeader {
+    width: u32,
+    height: u32,
+    bit_depth: u8,
+    color_type: u8,                                                                             // 0=gray, 2=rgb, 3=palette, 4=gray+alpha, 6=rgba
+    interlace: u8,                                                                              // 0=none, 1=adam7
+}
+
+impl PngHeader {
+    fn channels(&self) -> usize {
+        match self.color_type {
+            2 => 3,
+            4 => 2,
+            6 => 4,
+            _ => 1,
+        }
+    }
+
+    /* bytes in one scanline of the given width, excluding the filter byte */
+    fn row_len(&self, width: usize) -> Option<usize> {
+        Some(width.checked_mul(self.channels() * self.bit_depth as usize)?.div_ceil(8))
+    }
+
+    /* distance to the corresponding byte of the previous pixel, for filtering */
+    fn filter_distance(&self) -> usize {
+        (self.channels() * self.bit_depth as usize / 8).max(1)
+    }
+}
+
+///
+/// load png image files.
+///
+/// every color type and bit depth is supported, as are palette/tRNS transparency and adam7
+/// interlacing. 16-bit samples are reduced to 8 bits.
+///
+impl Image for Png {
+    fn load_from_buf_read<R: BufRead + Seek>(mut reader: R) -> std::io::Result<RawImage> {
+        /* first 8 bytes should be the signature; otherwise, not a png */
+        let mut signature = [0u8; 8];
+        reader.read_exact(&mut signature)?;
+        if signature != SIGNATURE {
+            return Err(Error::new(InvalidData, "not a png!"));
+        }
+
+        /* read chunks until the end marker */
+        let mut header = None;
+        let mut palette: Vec<[u8; 4]> = vec![];
+        let mut key: Option<[u16; 3]> = None;
+        let mut idat = vec![];
+        loop {
+            let (kind, data) = read_chunk(&mut reader)?;
+            if header.is_none() && &kind != b"IHDR" {
+                return Err(Error::new(InvalidData, "PNG: first chunk isn't IHDR"));
+            }
+
+            match &kind {
+                b"IHDR" => {
+                    let parsed = parse_header(&data)?;
+                    log(LogLevel::Debug, &|| format!("PNG: {:?}", parsed));
+                    header = Some(parsed);
+                }
+                b"PLTE" => palette = data.chunks_exact(3).map(|rgb| [rgb[0], rgb[1], rgb[2], 255]).collect(),
+                b"tRNS" => match header.as_ref().map(|h| h.color_type) {
+                    Some(3) => palette.iter_mut().zip(data.iter()).for_each(|(color, alpha)| color[3] = *alpha),
+                    Some(0) if data.len() >= 2 => {
+                        let gray = u16::from_be_bytes([data[0], data[1]]);
+                        key = Some([gray, gray, gray]);
+                    }
+                    Some(2) if data.len() >= 6 => key = Some([
+                        u16::from_be_bytes([data[0], data[1]]),
+                        u16::from_be_bytes([data[2], data[3]]),
+                        u16::from_be_bytes([data[4], data[5]]),
+                    ]),
+                    _ => log(LogLevel::Warning, &|| "PNG: ignoring invalid tRNS chunk".to_string()),
+                },
+                b"IDAT" => idat.extend_from_slice(&data),
+                b"IEND" => break,
+                _ if kind[0] & 0x20 == 0 => {
+                    return Err(Error::new(Unsupported, format!("PNG: unsupported critical chunk: {}", String::from_utf8_lossy(&kind))));
+                }
+                _ => log(LogLevel::Debug, &|| format!("PNG: skipping chunk: {}", String::from_utf8_lossy(&kind))),
+            }
+        }
+        let header = header.unwrap();
+
+        if header.color_type == 3 && palette.is_empty() {
+            return Err(Error::new(InvalidData, "PNG: palette image without PLTE chunk"));
+        }
+
+        /* decompress, then undo filtering and interlacing */
+        let raw = zlib::decompress(&idat)?;
+        let pixels = decode(&header, &palette, key, &raw)?;
+        log(LogLevel::Debug, &|| "PNG: success".to_string());
+        Ok(RawImage::new(header.width, header.height, pixels))
+    }
+}
+
+///
+/// write 8-bit rgba pngs.
+///
+/// each scanline's filter is chosen with the usual heuristic (smallest sum of absolute values),
+/// and the result is compressed with lz77 and fixed huffman codes.
+///
+impl ImageWriter for Png {
+    fn write_to_writer<W: Write>(image: &RawImage, writer: &mut W) -> std::io::Result<()> {
+        let rgba = image.as_rgba8()?;
+        let image = rgba.as_ref();
+
+        /* header */
+        let mut ihdr = Vec::with_capacity(13);
+        ihdr.extend_from_slice(&image.width.to_be_bytes());
+        ihdr.extend_from_slice(&image.height.to_be_bytes());
+        ihdr.extend_from_slice(&[8, 6, 0, 0, 0]);                  // bit depth, color type (rgba), compression, filter, interlace
+
+        /* scanlines, each prefixed with its filter type */
+        let row_len = image.width as usize * 4;
+        let mut raw = Vec::with_capacity((row_len + 1) * image.height as usize);
+        let mut prev = vec![0u8; row_len];
+        for row in image.data.chunks_exact(row_len) {
+            raw.extend_from_slice(&filter(row, &prev));
+            prev.copy_from_slice(row);
+        }
+
+        /* write out the chunks */
+        writer.write_all(&SIGNATURE)?;
+        write_chunk(writer, b"IHDR", &ihdr)?;
+        write_chunk(writer, b"IDAT", &zlib::compress(&raw))?;
+        write_chunk(writer, b"IEND", &[])?;
+        Ok(())
+    }
+}
+
+/* filter a 4-byte-per-pixel scanline, trying every filter type and keeping the best */
+fn filter(row: &[u8], prev: &[u8]) -> Vec<u8> {
+    let mut best: Option<(u64, Vec<u8>)> = None;
+    for filter_type in 0..5u8 {
+        let mut line = Vec::with_capacity(row.len() + 1);
+        line.push(filter_type);
+        for i in 0..row.len() {
+            let a = if i >= 4 { row[i - 4] } else { 0 };
+            let b = prev[i];
+            let c = if i >= 4 { prev[i - 4] } else { 0 };
+            let predicted = match filter_type {
+                0 => 0,
+                1 => a,
+                2 => b,
+                3 => ((a as u16 + b as u16) / 2) as u8,
+                _ => paeth(a, b, c),
+            };
+            line.push(row[i].wrapping_sub(predicted));
+        }
+
+        let cost = line[1..].iter().map(|byte| (*byte as i8).unsigned_abs() as u64).sum();
+        if best.as_ref().is_none_or(|(best_cost, _)| cost < *best_cost) {
+            best = Some((cost, line));
+        }
+    }
+    best.unwrap().1
+}
+
+fn read_chunk<R: BufRead>(reader: &mut R) -> std::io::Result<([u8; 4], Vec<u8>)> {
+    /* length, then type */
+    let mut prefix = [0u8; 8];
+    reader.read_exact(&mut prefix)?;
+    let len = u32::from_be_bytes(prefix[0..4].try_into().unwrap());
+    let kind: [u8; 4] = prefix[4..8].try_into().unwrap();
+    if len > 0x7fff_ffff {
+        return Err(Error::new(InvalidData, format!("PNG: invalid chunk length: {}", len)));
+    }
+
+    /* data, then checksum over type and data */
+    let mut data = Vec::new();
+    reader.take(len as u64).read_to_end(&mut data)?;
+    let mut crc = [0u8; 4];
+    if data.len() != len as usize || reader.read_exact(&mut crc).is_err() {
+        return Err(Error::new(InvalidData, format!("PNG: truncated chunk: {}", String::from_utf8_lossy(&kind))));
+    }
+    if crc32(crc32_update(0xffff_ffff, &kind), &data) != u32::from_be_bytes(crc) {
+        return Err(Error::new(InvalidData, format!("PNG: crc mismatch in chunk: {}", String::from_utf8_lossy(&kind))));
+    }
+
+    Ok((kind, data))
+}
+
+fn parse_header(data: &[u8]) -> std::io::Result<PngHeader> {
+    if data.len() != 13 {
+        return Err(Error::new(InvalidData, format!("PNG: invalid IHDR length: {}", data.len())));
+    }
+
+    /* extract header fields */
+    let header = PngHeader {
+        width: u32::from_be_bytes(data[0..4].try_into().unwrap()),
+        height: u32::from_be_bytes(data[4..8].try_into().unwrap()),
+        bit_depth: data[8],
+        color_type: data[9],
+        interlace: data[12],
+    };
+    let compression = data[10];
+    let filter = data[11];
+
+    /* validate */
+    if header.width == 0 || header.height == 0 {
+        return Err(Error::new(InvalidData, format!("PNG: invalid dimensions: {}x{}", header.width, header.height)));
+    }
+    let valid_depth = match header.color_type {
+        0 => [1, 2, 4, 8, 16].contains(&header.bit_depth),
+        3 => [1, 2, 4, 8].contains(&header.bit_depth),
+        2 | 4 | 6 => [8, 16].contains(&header.bit_depth),
+        _ => return Err(Error::new(InvalidData, format!("PNG: invalid color type: {}", header.color_type))),
+    };
+    if !valid_depth {
+        return Err(Error::new(InvalidData, format!("PNG: invalid bit depth {} for color type {}", header.bit_depth, header.color_type)));
+    }
+    if compression != 0 || filter != 0 || header.interlace > 1 {
+        return Err(Error::new(Unsupported, format!("PNG: unsupported compression={}, filter={}, interlace={}", compression, filter, header.interlace)));
+    }
+
+    Ok(header)
+}
+
+fn decode(header: &PngHeader, palette: &[[u8; 4]], key: Option<[u16; 3]>, raw: &[u8]) -> std::io::Result<Vec<u8>> {
+    let width = header.width as usize;
+    let height = header.height as usize;
+    let too_large = || Error::new(InvalidData, format!("PNG: image too large: {}x{}", header.width, header.height));
+
+    /* a non-interlaced image is a single pass covering every pixel; each scanline is prefixed with its filter type */
+    let passes: &[(usize, usize, usize, usize)] = if header.interlace == 1 { &ADAM7 } else { &[(0, 0, 1, 1)] };
+    let mut layout = vec![];
+    let mut expected = 0usize;
+    for (x0, y0, dx, dy) in passes {
+        let pass_width = width.saturating_sub(*x0).div_ceil(*dx);
+        let pass_height = height.saturating_sub(*y0).div_ceil(*dy);
+        if pass_width == 0 || pass_height == 0 {
+            continue;
+        }
+        let row_len = header.row_len(pass_width).ok_or_else(too_large)?;
+        let pass_len = (row_len + 1).checked_mul(pass_height).ok_or_else(too_large)?;
+        expected = expected.checked_add(pass_len).ok_or_else(too_large)?;
+        layout.push((*x0, *y0, *dx, *dy, pass_width, row_len, pass_len));
+    }
+
+    /* the scanlines must all be there before the image is allocated */
+    if raw.len() < expected {
+        return Err(Error::new(InvalidData, format!("PNG: image data too short: {} bytes, expected {}", raw.len(), expected)));
+    }
+    let mut pixels = vec![0u8; width.checked_mul(height).and_then(|n| n.checked_mul(4)).ok_or_else(too_large)?];
+
+    let mut offset = 0;
+    for (x0, y0, dx, dy, pass_width, row_len, pass_len) in layout {
+        let data = &raw[offset..offset + pass_len];
+        offset += pass_len;
+
+        let rows = unfilter(data, row_len, header.filter_distance())?;
+        for (py, row) in rows.chunks_exact(row_len).enumerate() {
+            for px in 0..pass_width {
+                let idx = ((y0 + py * dy) * width + x0 + px * dx) * 4;
+                pixels[idx..idx + 4].copy_from_slice(&pixel(header, palette, key, row, px)?);
+            }
+        }
+    }
+
+    Ok(pixels)
+}
+
+fn unfilter(data: &[u8], row_len: usize, distance: usize) -> std::io::Result<Vec<u8>> {
+    let rows = data.len() / (row_len + 1);
+    let mut out = vec![0u8; row_len * rows];
+
+    for (y, line) in data.chunks_exact(row_len + 1).enumerate() {
+        let (done, rest) = out.split_at_mut(y * row_len);
+        let prev = if y > 0 { &done[(y - 1) * row_len..] } else { &[][..] };
+        let cur = &mut rest[..row_len];
+
+        for i in 0..row_len {
+            let a = if i >= distance { cur[i - distance] } else { 0 };
+            let b = prev.get(i).copied().unwrap_or(0);
+            let c = if i >= distance { prev.get(i - distance).copied().unwrap_or(0) } else { 0 };
+            let predicted = match line[0] {
+                0 => 0,
+                1 => a,
+                2 => b,
+                3 => ((a as u16 + b as u16) / 2) as u8,
+                4 => paeth(a, b, c),
+                other => return Err(Error::new(InvalidData, format!("PNG: invalid filter type: {}", other))),
+            };
+            cur[i] = line[i + 1].wrapping_add(predicted);
+        }
+    }
+
+    Ok(out)
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let pa = (p - a as i16).abs();
+    let pb = (p - b as i16).abs();
+    let pc = (p - c as i16).abs();
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+fn pixel(header: &PngHeader, palette: &[[u8; 4]], key: Option<[u16; 3]>, row: &[u8], x: usize) -> std::io::Result<[u8; 4]> {
+    let depth = header.bit_depth;
+    let channels = header.channels();
+    let sample = |channel: usize| sample(row, depth, x * channels + channel);
+    let scale = |value: u16| scale(value, depth);
+
+    match header.color_type {
+        0 | 4 => {
+            let gray = sample(0);
+            let alpha = if header.color_type == 4 {
+                scale(sample(1))
+            } else if key == Some([gray, gray, gray]) { 0 } else { 255 };
+            Ok([scale(gray), scale(gray), scale(gray), alpha])
+        }
+        2 | 6 => {
+            let rgb = [sample(0), sample(1), sample(2)];
+            let alpha = if header.color_type == 6 {
+                scale(sample(3))
+            } else if key == Some(rgb) { 0 } else { 255 };
+            Ok([scale(rgb[0]), scale(rgb[1]), scale(rgb[2]), alpha])
+        }
+        _ => {
+            let idx = sample(0) as usize;
+            palette.get(idx).copied()
+                .ok_or_else(|| Error::new(InvalidData, format!("PNG: palette index out of range: {}", idx)))
+        }
+    }
+}
+
+/* the index'th sample in a scanline, unscaled */
+fn sample(row: &[u8], depth: u8, index: usize) -> u16 {
+    match depth {
+        16 => u16::from_be_bytes([row[index * 2], row[index * 2 + 1]]),
+        8 => row[index] as u16,
+        _ => {
+            let bit = index * depth as usize;
+            let shift = 8 - depth as usize - bit % 8;
+            ((row[bit / 8] >> shift) & ((1u8 << depth) - 1)) as u16
+        }
+    }
+}
+
+/* scale a sample of the given depth to 8 bits */
+fn scale(value: u16, depth: u8) -> u8 {
+    match depth {
+        16 => (value >> 8) as u8,
+        8 => value as u8,
+        _ => (value as u32 * 255 / ((1u32 << depth) - 1)) as u8,
+    }
+}
+
+fn write_chunk<W: Write>(writer: &mut W, kind: &[u8; 4], data: &[u8]) -> std::io::Result<()> {
+    writer.write_all(&(data.len() as u32).to_be_bytes())?;
+    writer.write_all(kind)?;
+    writer.write_all(data)?;
+
+    let crc = crc32(crc32_update(0xffff_ffff, kind), data);
+    writer.write_all(&crc.to_be_bytes())
+}
+
+fn crc32(state: u32, bytes: &[u8]) -> u32 {
+    !crc32_update(state, bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::support::image::png::{crc32, paeth, write_chunk, Png, ADAM7, SIGNATURE};
+    use crate::support::image::{Image, ImageWriter, RawImage};
+    use crate::support::zlib;
+
+    /* build a png from an IHDR, extra chunks, and already-filtered scanlines */
+    fn encode(width: u32, height: u32, depth: u8, color_type: u8, interlace: u8, chunks: &[(&[u8; 4], &[u8])], raw: &[u8]) -> Vec<u8> {
+        let mut ihdr = vec![];
+        ihdr.extend_from_slice(&width.to_be_bytes());
+        ihdr.extend_from_slice(&height.to_be_bytes());
+        ihdr.extend_from_slice(&[depth, color_type, 0, 0, interlace]);
+
+        let mut bytes = SIGNATURE.to_vec();
+        write_chunk(&mut bytes, b"IHDR", &ihdr).unwrap();
+        for (kind, data) in chunks {
+            write_chunk(&mut bytes, kind, data).unwrap();
+        }
+        write_chunk(&mut bytes, b"IDAT", &zlib::compress_stored(raw)).unwrap();
+        write_chunk(&mut bytes, b"IEND", &[]).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_checksums() {
+        assert_eq!(0xcbf4_3926, crc32(0xffff_ffff, b"123456789"));
+    }
+
+    #[test]
+    fn test_write() {
+        let data: Vec<u8> = (0..64 * 48).flat_map(|i| [(i % 64) as u8 * 4, (i / 64) as u8 * 5, 200, (i % 7) as u8 * 36]).collect();
+        let image = RawImage::new(64, 48, data);
+        let bytes = Png::write_to_bytes(&image).unwrap();
+
+        assert_eq!(b"\x89PNG\r\n\x1a\n", &bytes[0..8]);
+        assert_eq!(b"IHDR", &bytes[12..16]);
+        assert_eq!([0, 0, 0, 64, 0, 0, 0, 48, 8, 6, 0, 0, 0], bytes[16..29]);
+        assert_eq!(b"IEND", &bytes[bytes.len() - 8..bytes.len() - 4]);
+        assert!(bytes.len() < image.data.len() / 4);
+
+        let loaded = Png::load_from_bytes(&bytes).unwrap();
+        assert_eq!((64, 48), (loaded.width, loaded.height));
+        assert_eq!(image.data, loaded.data);
+    }
+
+    #[test]
+    fn test_low_depth_gray() {
+        /* 2-bit gray: 0, 1, 3, 2; transparent where gray == 1 */
+        let png = encode(4, 1, 2, 0, 0, &[(b"tRNS", &[0, 1])], &[0, 0b00_01_11_10]);
+        let image = Png::load_from_bytes(&png).unwrap();
+        assert_eq!(vec![0, 0, 0, 255, 85, 85, 85, 0, 255, 255, 255, 255, 170, 170, 170, 255], image.data);
+    }
+
+    #[test]
+    fn test_palette() {
+        /* 1-bit indices into a two-color palette; the first color is half-transparent */
+        let plte = [255, 0, 0, 0, 0, 255];
+        let png = encode(3, 1, 1, 3, 0, &[(b"PLTE", &plte), (b"tRNS", &[128])], &[0, 0b0100_0000]);
+        let image = Png::load_from_bytes(&png).unwrap();
+        assert_eq!(vec![255, 0, 0, 128, 0, 0, 255, 255, 255, 0, 0, 128], image.data);
+
+        /* index 2 isn't in the palette */
+        let png = encode(1, 1, 2, 3, 0, &[(b"PLTE", &plte)], &[0, 0b1000_0000]);
+        assert!(Png::load_from_bytes(&png).is_err());
+    }
+
+    #[test]
+    fn test_filters() {
+        /* 16-bit rgb, one scanline per filter type */
+        let (width, height, bpp) = (3usize, 5usize, 6usize);
+        let rows: Vec<Vec<u8>> = (0..height)
+            .map(|y| (0..width * bpp).map(|i| (i * 37 + y * 91 + i * y * 13) as u8).collect())
+            .collect();
+
+        let mut raw = vec![];
+        for (y, row) in rows.iter().enumerate() {
+            raw.push(y as u8);
+            for i in 0..row.len() {
+                let a = if i >= bpp { row[i - bpp] } else { 0 };
+                let b = if y > 0 { rows[y - 1][i] } else { 0 };
+                let c = if y > 0 && i >= bpp { rows[y - 1][i - bpp] } else { 0 };
+                let predicted = [0, a, b, ((a as u16 + b as u16) / 2) as u8, paeth(a, b, c)][y];
+                raw.push(row[i].wrapping_sub(predicted));
+            }
+        }
+
+        let image = Png::load_from_bytes(&encode(width as u32, height as u32, 16, 2, 0, &[], &raw)).unwrap();
+        for (y, row) in rows.iter().enumerate() {
+            for x in 0..width {
+                let idx = (y * width + x) * 4;
+                assert_eq!([row[x * bpp], row[x * bpp + 2], row[x * bpp + 4], 255], image.data[idx..idx + 4]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_adam7() {
+        /* 8-bit gray+alpha, where each pixel's value is its index */
+        let (width, height) = (10usize, 9usize);
+        let mut raw = vec![];
+        for (x0, y0, dx, dy) in ADAM7 {
+            for y in (y0..height).step_by(dy) {
+                if x0 >= width {
+                    continue;
+                }
+                raw.push(0);
+                for x in (x0..width).step_by(dx) {
+                    raw.extend_from_slice(&[(y * width + x) as u8, 255]);
+                }
+            }
+        }
+
+        let image = Png::load_from_bytes(&encode(width as u32, height as u32, 8, 4, 1, &[], &raw)).unwrap();
+        let expected: Vec<u8> = (0..width * height).flat_map(|i| [i as u8, i as u8, i as u8, 255]).collect();
+        assert_eq!(expected, image.data);
+    }
+
+    #[test]
+    fn test_corrupt() {
+        let mut png = encode(1, 1, 8, 0, 0, &[], &[0, 7]);
+        assert!(Png::load_from_bytes(&png).is_ok());
+
+        png[20] ^= 1;
+        assert!(Png::load_from_bytes(&png).is_err());
+        assert!(Png::load_from_bytes(&encode(1, 1, 4, 2, 0, &[], &[0, 7])).is_err());
+
+        /* huge dimensions, with one scanline's worth of data */
+        assert!(Png::load_from_bytes(&encode(0x7fff0000, 0x7fff0000, 8, 6, 0, &[], &[0, 7])).is_err());
+        assert!(Png::load_from_bytes(&encode(0x7fff0000, 0x7fff0000, 8, 6, 1, &[], &[0, 7])).is_err());
+    }
+}
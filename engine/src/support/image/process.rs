@@ -0,0 +1,348 @@
+//!
+//! pixel operations on [RawImage]s.
+//!
+//! geometric operations (crop, flip, rotate, paste) move whole pixels and work in any format;
+//! the rest decode to normalized rgba and encode back to the image's own format.
+//!
+
+use crate::support::image::pixel::PixelFormat;
+use crate::support::image::RawImage;
+use std::f32::consts::PI;
+
+///
+/// reconstruction filter used by [RawImage::resize].
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ResizeFilter {
+    Box,
+    Bilinear,
+    Lanczos3,
+}
+
+impl ResizeFilter {
+    fn support(&self) -> f32 {
+        match self {
+            ResizeFilter::Box => 0.5,
+            ResizeFilter::Bilinear => 1.0,
+            ResizeFilter::Lanczos3 => 3.0,
+        }
+    }
+
+    fn weight(&self, x: f32) -> f32 {
+        let x = x.abs();
+        match self {
+            ResizeFilter::Box => if x <= 0.5 { 1.0 } else { 0.0 },
+            ResizeFilter::Bilinear => (1.0 - x).max(0.0),
+            ResizeFilter::Lanczos3 => {
+                if x < 1e-6 {
+                    1.0
+                } else if x < 3.0 {
+                    let px = PI * x;
+                    3.0 * px.sin() * (px / 3.0).sin() / (px * px)
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+impl RawImage {
+    fn pixel_offset(&self, x: u32, y: u32) -> usize {
+        (y as usize * self.width as usize + x as usize) * self.format.bytes_per_pixel()
+    }
+
+    ///
+    /// a copy of the given rectangle, or none if it doesn't fit inside the image.
+    ///
+    pub fn crop(&self, x: u32, y: u32, width: u32, height: u32) -> Option<RawImage> {
+        if x.checked_add(width)? > self.width || y.checked_add(height)? > self.height {
+            return None;
+        }
+        let row_len = width as usize * self.format.bytes_per_pixel();
+        let mut data = Vec::with_capacity(row_len * height as usize);
+        for row in y..y + height {
+            let start = self.pixel_offset(x, row);
+            data.extend_from_slice(&self.data[start..start + row_len]);
+        }
+        Some(RawImage::with_format(width, height, self.format, data))
+    }
+
+    pub fn flip_horizontal(&self) -> RawImage {
+        if self.width == 0 || self.height == 0 {
+            return self.clone();
+        }
+        let bpp = self.format.bytes_per_pixel();
+        let mut data = Vec::with_capacity(self.data.len());
+        for row in self.data.chunks_exact(self.width as usize * bpp) {
+            row.chunks_exact(bpp).rev().for_each(|pixel| data.extend_from_slice(pixel));
+        }
+        RawImage::with_format(self.width, self.height, self.format, data)
+    }
+
+    pub fn flip_vertical(&self) -> RawImage {
+        if self.width == 0 || self.height == 0 {
+            return self.clone();
+        }
+        let row_len = self.width as usize * self.format.bytes_per_pixel();
+        let data = self.data.chunks_exact(row_len).rev().flatten().copied().collect();
+        RawImage::with_format(self.width, self.height, self.format, data)
+    }
+
+    ///
+    /// rotate a quarter turn clockwise.
+    ///
+    pub fn rotate_90(&self) -> RawImage {
+        self.rotated(|image, x, y| (y, image.height - 1 - x), self.height, self.width)
+    }
+
+    pub fn rotate_180(&self) -> RawImage {
+        self.rotated(|image, x, y| (image.width - 1 - x, image.height - 1 - y), self.width, self.height)
+    }
+
+    ///
+    /// rotate a quarter turn counter-clockwise.
+    ///
+    pub fn rotate_270(&self) -> RawImage {
+        self.rotated(|image, x, y| (image.width - 1 - y, x), self.height, self.width)
+    }
+
+    ///
+    /// build a new image by looking up, for each destination pixel, the source pixel it came from.
+    ///
+    fn rotated(&self, source: impl Fn(&RawImage, u32, u32) -> (u32, u32), width: u32, height: u32) -> RawImage {
+        let bpp = self.format.bytes_per_pixel();
+        let mut data = Vec::with_capacity(self.data.len());
+        for y in 0..height {
+            for x in 0..width {
+                let (sx, sy) = source(self, x, y);
+                let start = self.pixel_offset(sx, sy);
+                data.extend_from_slice(&self.data[start..start + bpp]);
+            }
+        }
+        RawImage::with_format(width, height, self.format, data)
+    }
+
+    ///
+    /// copy another image's pixels over this one, unblended; anything outside is clipped.
+    ///
+    pub fn paste(&mut self, source: &RawImage, x: i32, y: i32) {
+        let source = source.convert(self.format);
+        self.visit_overlap(&source, x, y, |target, dst, src| {
+            target.data[dst..dst + src.len()].copy_from_slice(src);
+        });
+    }
+
+    ///
+    /// draw another image over this one using its alpha ("source over"); anything outside is clipped.
+    ///
+    pub fn blit(&mut self, source: &RawImage, x: i32, y: i32) {
+        let format = self.format;
+        let source_format = source.format;
+        let mut blended = Vec::with_capacity(format.bytes_per_pixel());
+        self.visit_overlap(source, x, y, |target, dst, src| {
+            let bpp = format.bytes_per_pixel();
+            let [sr, sg, sb, sa] = source_format.decode(src);
+            let [dr, dg, db, da] = format.decode(&target.data[dst..dst + bpp]);
+            let alpha = sa + da * (1.0 - sa);
+            let mix = |s: f32, d: f32| if alpha > 0.0 { (s * sa + d * da * (1.0 - sa)) / alpha } else { 0.0 };
+
+            blended.clear();
+            format.encode([mix(sr, dr), mix(sg, dg), mix(sb, db), alpha], &mut blended);
+            target.data[dst..dst + bpp].copy_from_slice(&blended);
+        });
+    }
+
+    ///
+    /// call visitor(self, target byte offset, source pixel) for every overlapping pixel.
+    ///
+    fn visit_overlap(&mut self, source: &RawImage, x: i32, y: i32, mut visitor: impl FnMut(&mut RawImage, usize, &[u8])) {
+        let source_bpp = source.format.bytes_per_pixel();
+        for sy in 0..source.height {
+            let ty = y as i64 + sy as i64;
+            if ty < 0 || ty >= self.height as i64 {
+                continue;
+            }
+            for sx in 0..source.width {
+                let tx = x as i64 + sx as i64;
+                if tx < 0 || tx >= self.width as i64 {
+                    continue;
+                }
+                let src = source.pixel_offset(sx, sy);
+                let dst = self.pixel_offset(tx as u32, ty as u32);
+                visitor(self, dst, &source.data[src..src + source_bpp]);
+            }
+        }
+    }
+
+    pub fn premultiply_alpha(&mut self) {
+        self.map_pixels(|[r, g, b, a]| [r * a, g * a, b * a, a]);
+    }
+
+    pub fn unpremultiply_alpha(&mut self) {
+        self.map_pixels(|[r, g, b, a]| if a > 0.0 { [r / a, g / a, b / a, a] } else { [0.0, 0.0, 0.0, 0.0] });
+    }
+
+    ///
+    /// make every pixel of the given colour fully transparent; formats without alpha become rgba8.
+    ///
+    pub fn color_key(&mut self, key: (u8, u8, u8)) {
+        if !self.format.has_alpha() {
+            *self = self.convert(PixelFormat::Rgba8);
+        }
+        let key = [key.0, key.1, key.2];
+        self.map_pixels(|rgba| {
+            let mut bytes = Vec::with_capacity(3);
+            PixelFormat::Rgb8.encode(rgba, &mut bytes);
+            if bytes == key { [rgba[0], rgba[1], rgba[2], 0.0] } else { rgba }
+        });
+    }
+
+    fn map_pixels(&mut self, op: impl Fn([f32; 4]) -> [f32; 4]) {
+        let pixels: Vec<[f32; 4]> = self.to_rgba_f32().into_iter().map(op).collect();
+        *self = RawImage::from_rgba_f32(self.width, self.height, self.format, &pixels);
+    }
+
+    ///
+    /// resample to a new size; colour is filtered premultiplied so transparent pixels don't bleed.
+    ///
+    pub fn resize(&self, width: u32, height: u32, filter: ResizeFilter) -> RawImage {
+        if width == 0 || height == 0 || self.width == 0 || self.height == 0 {
+            return RawImage::with_format(width, height, self.format, vec![0; width as usize * height as usize * self.format.bytes_per_pixel()]);
+        }
+
+        /* premultiply, then filter separably: rows first, then columns */
+        let pixels: Vec<[f32; 4]> = self.to_rgba_f32().into_iter().map(|[r, g, b, a]| [r * a, g * a, b * a, a]).collect();
+        let horizontal = resample(&pixels, self.width as usize, self.height as usize, width as usize, true, filter);
+        let both = resample(&horizontal, width as usize, self.height as usize, height as usize, false, filter);
+
+        /* back to straight alpha, in the source format */
+        let output: Vec<[f32; 4]> = both.into_iter()
+            .map(|[r, g, b, a]| if a > 0.0 { [r / a, g / a, b / a, a.min(1.0)] } else { [0.0, 0.0, 0.0, 0.0] })
+            .collect();
+        RawImage::from_rgba_f32(width, height, self.format, &output)
+    }
+}
+
+///
+/// resample along one axis; when shrinking, the filter is stretched to cover every source pixel.
+///
+fn resample(pixels: &[[f32; 4]], width: usize, height: usize, size: usize, horizontal: bool, filter: ResizeFilter) -> Vec<[f32; 4]> {
+    let (source_size, lines) = if horizontal { (width, height) } else { (height, width) };
+    let ratio = source_size as f32 / size as f32;
+    let scale = ratio.max(1.0);
+    let support = filter.support() * scale;
+
+    /* weights depend only on the destination position, so compute them once per axis */
+    let mut taps: Vec<(usize, Vec<f32>)> = Vec::with_capacity(size);
+    for dst in 0..size {
+        let center = (dst as f32 + 0.5) * ratio;
+        let first = (center - support).floor().max(0.0) as usize;
+        let last = ((center + support).ceil() as usize).min(source_size);
+        let mut weights: Vec<f32> = (first..last).map(|src| filter.weight((src as f32 + 0.5 - center) / scale)).collect();
+        let total: f32 = weights.iter().sum();
+        if total.abs() > f32::EPSILON {
+            weights.iter_mut().for_each(|w| *w /= total);
+        } else {
+            /* no tap landed under the filter; fall back to the nearest pixel */
+            let nearest = (center as usize).min(source_size - 1);
+            taps.push((nearest, vec![1.0]));
+            continue;
+        }
+        taps.push((first, weights));
+    }
+
+    let (out_width, out_height) = if horizontal { (size, height) } else { (width, size) };
+    let mut output = vec![[0.0f32; 4]; out_width * out_height];
+    for line in 0..lines {
+        for (dst, (first, weights)) in taps.iter().enumerate() {
+            let mut sum = [0.0f32; 4];
+            for (i, weight) in weights.iter().enumerate() {
+                let src = first + i;
+                let pixel = if horizontal { pixels[line * width + src] } else { pixels[src * width + line] };
+                for c in 0..4 {
+                    sum[c] += pixel[c] * weight;
+                }
+            }
+            let idx = if horizontal { line * size + dst } else { dst * width + line };
+            output[idx] = sum.map(|v| v.max(0.0));
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::support::image::pixel::PixelFormat;
+    use crate::support::image::process::ResizeFilter;
+    use crate::support::image::RawImage;
+
+    /// 3x2, each pixel's red channel is its index
+    fn numbered() -> RawImage {
+        RawImage::new(3, 2, (0..6).flat_map(|i| [i, 0, 0, 255]).collect())
+    }
+
+    fn reds(image: &RawImage) -> Vec<u8> {
+        image.data.chunks_exact(4).map(|p| p[0]).collect()
+    }
+
+    #[test]
+    fn test_geometry() {
+        let image = numbered();
+        assert_eq!(vec![1, 2, 4, 5], reds(&image.crop(1, 0, 2, 2).unwrap()));
+        assert!(image.crop(2, 0, 2, 2).is_none());
+        assert_eq!(vec![2, 1, 0, 5, 4, 3], reds(&image.flip_horizontal()));
+        assert_eq!(vec![3, 4, 5, 0, 1, 2], reds(&image.flip_vertical()));
+
+        let cw = image.rotate_90();
+        assert_eq!((2, 3), (cw.width, cw.height));
+        assert_eq!(vec![3, 0, 4, 1, 5, 2], reds(&cw));
+        assert_eq!(vec![2, 5, 1, 4, 0, 3], reds(&image.rotate_270()));
+        assert_eq!(vec![5, 4, 3, 2, 1, 0], reds(&image.rotate_180()));
+        assert_eq!(image.data, cw.rotate_270().data);
+
+        let empty = RawImage::new(0, 3, vec![]);
+        assert_eq!((0, 3), (empty.flip_horizontal().width, empty.flip_vertical().height));
+    }
+
+    #[test]
+    fn test_blit_and_paste() {
+        let mut target = RawImage::new(2, 1, vec![0, 0, 255, 255, 0, 0, 255, 255]);
+        target.blit(&RawImage::new(1, 1, vec![255, 0, 0, 128]), 1, 0);
+        assert_eq!(vec![0, 0, 255, 255, 128, 0, 127, 255], target.data);
+
+        target.paste(&RawImage::with_format(2, 1, PixelFormat::L8, vec![7, 9]), -1, 0);
+        assert_eq!(vec![9, 9, 9, 255, 128, 0, 127, 255], target.data);
+    }
+
+    #[test]
+    fn test_alpha_ops() {
+        let mut image = RawImage::new(2, 1, vec![200, 100, 50, 128, 10, 20, 30, 255]);
+        image.premultiply_alpha();
+        assert_eq!(vec![100, 50, 25, 128, 10, 20, 30, 255], image.data);
+
+        let mut keyed = RawImage::with_format(2, 1, PixelFormat::Rgb8, vec![255, 0, 255, 1, 2, 3]);
+        keyed.color_key((255, 0, 255));
+        assert_eq!(PixelFormat::Rgba8, keyed.format);
+        assert_eq!(vec![255, 0, 255, 0, 1, 2, 3, 255], keyed.data);
+    }
+
+    #[test]
+    fn test_resize() {
+        let image = RawImage::new(4, 1, vec![0, 0, 0, 255, 0, 0, 0, 255, 255, 255, 255, 255, 255, 255, 255, 255]);
+        assert_eq!(vec![0, 255], reds(&image.resize(2, 1, ResizeFilter::Box)));
+
+        /* a flat colour stays flat under every filter, including lanczos' negative lobes */
+        let flat = RawImage::new(3, 3, [40, 80, 120, 255].repeat(9));
+        for filter in [ResizeFilter::Box, ResizeFilter::Bilinear, ResizeFilter::Lanczos3] {
+            let resized = flat.resize(7, 5, filter);
+            assert_eq!((7, 5), (resized.width, resized.height));
+            assert!(resized.data.chunks_exact(4).all(|p| p == [40, 80, 120, 255]), "{:?}", filter);
+        }
+
+        /* fully transparent pixels don't tint their neighbours */
+        let edge = RawImage::new(2, 1, vec![255, 0, 0, 255, 0, 255, 0, 0]);
+        let up = edge.resize(4, 1, ResizeFilter::Bilinear);
+        assert!(up.data.chunks_exact(4).filter(|p| p[3] > 0).all(|p| p[1] == 0));
+    }
+}
@@ -0,0 +1,184 @@
+use crate::support::image::bitmap::Bitmap;
+use crate::support::image::png::Png;
+use crate::support::image::targa::Targa;
+use crate::support::image::tiff::Tiff;
+use crate::support::image::{Image, RawImage};
+use crate::support::logger::log;
+use crate::support::logger::log_level::LogLevel;
+use std::io::Error;
+use std::io::ErrorKind::Unsupported;
+use std::sync::{LazyLock, Mutex, MutexGuard, PoisonError};
+
+static REGISTRY: LazyLock<Mutex<ImageRegistry>> = LazyLock::new(|| Mutex::new(ImageRegistry::default()));
+
+///
+/// decide, from the file contents, whether a decoder can handle them.
+///
+pub type DetectFn = fn(&[u8]) -> bool;
+pub type LoadFn = fn(&[u8]) -> std::io::Result<RawImage>;
+
+///
+/// an image decoder, and how to recognize its files.
+///
+#[derive(Clone, Copy)]
+pub struct ImageDecoder {
+    pub name: &'static str,
+    pub detect: DetectFn,
+    pub load: LoadFn,
+}
+
+///
+/// decoders, in the order they're tried.
+///
+/// the default registry has the built-in formats; targa has no magic number, so it's tried last.
+///
+pub struct ImageRegistry {
+    decoders: Vec<ImageDecoder>,
+}
+
+impl ImageRegistry {
+    pub fn empty() -> ImageRegistry {
+        ImageRegistry { decoders: vec![] }
+    }
+
+    ///
+    /// add a decoder; it's tried before any registered earlier, including the built-ins.
+    ///
+    pub fn register(&mut self, decoder: ImageDecoder) {
+        self.decoders.insert(0, decoder);
+    }
+
+    ///
+    /// find the first decoder that recognizes the bytes.
+    ///
+    pub fn detect(&self, bytes: &[u8]) -> Option<ImageDecoder> {
+        self.decoders.iter().find(|decoder| (decoder.detect)(bytes)).copied()
+    }
+
+    pub fn load(&self, bytes: &[u8]) -> std::io::Result<RawImage> {
+        detected(self.detect(bytes))?.decode(bytes)
+    }
+}
+
+impl Default for ImageRegistry {
+    fn default() -> Self {
+        let mut registry = ImageRegistry::empty();
+        registry.register(decoder::<Targa>("tga", is_targa));
+        registry.register(decoder::<Tiff>("tiff", |bytes| bytes.starts_with(b"II*\0") || bytes.starts_with(b"MM\0*")));
+        registry.register(decoder::<Bitmap>("bmp", |bytes| bytes.starts_with(b"BM")));
+        registry.register(decoder::<Png>("png", |bytes| bytes.starts_with(b"\x89PNG\r\n\x1a\n")));
+        registry
+    }
+}
+
+impl ImageDecoder {
+    pub fn decode(&self, bytes: &[u8]) -> std::io::Result<RawImage> {
+        log(LogLevel::Debug, &|| format!("IMG: detected format: {}", self.name));
+        (self.load)(bytes)
+    }
+}
+
+///
+/// a decoder for any [Image] implementation.
+///
+pub fn decoder<I: Image>(name: &'static str, detect: DetectFn) -> ImageDecoder {
+    ImageDecoder { name, detect, load: I::load_from_bytes }
+}
+
+///
+/// register a decoder with the registry used by [crate::support::image::load_any].
+///
+pub fn register_image_decoder(decoder: ImageDecoder) {
+    registry().register(decoder);
+}
+
+///
+/// the registry is only locked to pick a decoder; decoding happens after, so loads on several threads run at once.
+///
+pub(crate) fn load_registered(bytes: &[u8]) -> std::io::Result<RawImage> {
+    let decoder = registry().detect(bytes);
+    detected(decoder)?.decode(bytes)
+}
+
+///
+/// the registry is never left half-changed, so one poisoned by a panic elsewhere is still good to use.
+///
+fn registry() -> MutexGuard<'static, ImageRegistry> {
+    REGISTRY.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn detected(decoder: Option<ImageDecoder>) -> std::io::Result<ImageDecoder> {
+    decoder.ok_or_else(|| Error::new(Unsupported, "unrecognized image format"))
+}
+
+///
+/// targa files may end with a footer; otherwise, check that the header is plausible.
+///
+fn is_targa(bytes: &[u8]) -> bool {
+    if bytes.len() >= 26 && bytes[bytes.len() - 18..bytes.len() - 2] == *b"TRUEVISION-XFILE" {
+        return true;
+    }
+    if bytes.len() < 18 {
+        return false;
+    }
+
+    let color_map_type = bytes[1];
+    let image_type = bytes[2];
+    let color_map_depth = bytes[7];
+    let width = u16::from_le_bytes([bytes[12], bytes[13]]);
+    let height = u16::from_le_bytes([bytes[14], bytes[15]]);
+    let pixel_depth = bytes[16];
+
+    color_map_type <= 1
+        && [1, 2, 3, 9, 10, 11].contains(&image_type)
+        && (color_map_type == 0 || [15, 16, 24, 32].contains(&color_map_depth))
+        && [8, 15, 16, 24, 32].contains(&pixel_depth)
+        && width > 0
+        && height > 0
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::support::image::registry::{decoder, ImageRegistry};
+    use crate::support::image::targa::TargaRle;
+    use crate::support::image::{Image, ImageWriter, RawImage};
+    use crate::support::image::{ImageFormat, bitmap::Bitmap, png::Png};
+    use std::io::{BufRead, Seek};
+
+    #[test]
+    fn test_detect() {
+        let image = RawImage::new(3, 2, (0..24).map(|i| if i % 4 == 3 { 255 } else { i }).collect());
+        let registry = ImageRegistry::default();
+        for (format, name) in [(ImageFormat::Bitmap, "bmp"), (ImageFormat::Png, "png"), (ImageFormat::Targa, "tga")] {
+            let mut bytes = vec![];
+            image.write_to(&mut bytes, format).unwrap();
+            assert_eq!(name, registry.detect(&bytes).unwrap().name);
+            assert_eq!(image.data, registry.load(&bytes).unwrap().data);
+        }
+
+        let rle = TargaRle::write_to_bytes(&image).unwrap();
+        assert_eq!("tga", registry.detect(&rle).unwrap().name);
+        assert!(registry.load(b"GIF89a").is_err());
+        assert!(registry.detect(&[0; 4]).is_none());
+    }
+
+    #[test]
+    fn test_register() {
+        struct Solid;
+        impl Image for Solid {
+            fn load_from_buf_read<R: BufRead + Seek>(_reader: R) -> std::io::Result<RawImage> {
+                Ok(RawImage::new(1, 1, vec![1, 2, 3, 4]))
+            }
+        }
+
+        /* custom decoders are tried first, so they can claim built-in formats too */
+        let mut registry = ImageRegistry::default();
+        registry.register(decoder::<Solid>("solid", |bytes| bytes.starts_with(b"SOLID") || bytes.starts_with(b"BM")));
+        assert_eq!(vec![1, 2, 3, 4], registry.load(b"SOLID").unwrap().data);
+
+        let bmp = Bitmap::write_to_bytes(&RawImage::new(1, 1, vec![9, 9, 9, 255])).unwrap();
+        assert_eq!("solid", registry.detect(&bmp).unwrap().name);
+        assert_eq!("bmp", ImageRegistry::default().detect(&bmp).unwrap().name);
+        assert_eq!("png", ImageRegistry::default().detect(&Png::write_to_bytes(&RawImage::new(1, 1, vec![0; 4])).unwrap()).unwrap().name);
+    }
+}
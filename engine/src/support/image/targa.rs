@@ -1,14 +1,18 @@
-use crate::support::image::{Image, RawImage};
-use std::io::{BufRead, Error, Seek, SeekFrom};
-use std::io::ErrorKind::Unsupported;
 use crate::support::binary::byte_to_bits_as_u8;
+use crate::support::binary::reader::BinaryReader;
+use crate::support::image::{Image, ImageWriter, RawImage};
 use crate::support::logger::log;
 use crate::support::logger::log_level::LogLevel;
+use crate::support::binary::endianness::Endianness::LittleEndian;
+use std::io::ErrorKind::{InvalidData, InvalidInput, Unsupported};
+use std::io::{BufRead, Error, Read, Seek, Write};
 
 pub struct Targa;
+pub struct TargaRle;
 
 const HEADER_LEN : usize = 18;
 const FOOTER_IDX : usize = 26;
+const EXTENSION_LEN : usize = 495;
 
 #[repr(C)]
 #[derive(Debug)]
@@ -18,7 +22,9 @@ struct TargaMetaData {
     id_len: u8,
     color_map_type: u8,
     image_type: u8,
-    color_map_spec: [u8;5],
+    color_map_first: u16,
+    color_map_len: u16,
+    color_map_depth: u8,
     x_origin: u16,
     y_origin: u16,
     width: u16,
@@ -30,9 +36,11 @@ struct TargaMetaData {
     alpha_channel_depth: u8,
     left_to_right: bool,
     top_to_bottom: bool,
-    reserved: u8,
 
-    /* image data */
+    /* tga 2.0 extension area, if present */
+    extension: Option<TargaExtension>,
+
+    /* image data; includes the color map, if any */
     data_start: usize,
     data_size: u64,
 }
@@ -45,129 +53,344 @@ struct TargaFooter {
     marker: [u8;16],
     reserved: u8,
     terminator: u8,
-    dev_sz: u32,
-    ext_sz: u32,
+}
+
+#[repr(C)]
+#[derive(Debug)]
+struct TargaExtension {
+    author: String,
+    software: String,
+    gamma: Option<f32>,                                                                         // numerator / denominator; none if not specified
+    attribute_type: u8,                                                                         // 0=no alpha, 1/2=undefined (ignore), 3=alpha, 4=premultiplied alpha
+}
+
+impl TargaMetaData {
+    fn is_rle(&self) -> bool {
+        self.image_type & 8 != 0
+    }
+
+    fn bytes_per_pixel(&self) -> usize {
+        (self.pixel_depth as usize).div_ceil(8)
+    }
+
+    fn color_map_bytes(&self) -> usize {
+        self.color_map_len as usize * (self.color_map_depth as usize).div_ceil(8)
+    }
 }
 
 ///
 /// load targa image files.
 ///
+/// supports color-mapped, truecolor and black/white images (image types 1, 2 and 3), either
+/// uncompressed or run-length encoded (9, 10 and 11), in any origin orientation.
+///
 impl Image for Targa {
-    fn load_from_buf_read<R: BufRead + Seek>(mut reader: R) -> std::io::Result<RawImage> {
+    fn load_from_buf_read<R: BufRead + Seek>(reader: R) -> std::io::Result<RawImage> {
         /* load the metadata */
         log(LogLevel::Debug, &|| "TGA: begin----------------".to_string());
-        let metadata = load_metadata(&mut reader);
+        let mut reader = BinaryReader::new(reader, LittleEndian)?.with_label("TGA");
+        let metadata = load_metadata(&mut reader)?;
         log(LogLevel::Debug, &|| format!("TGA: {:?}", metadata));
-        validate(&metadata);
+        validate(&metadata)?;
 
-        /* load the image data, stored in bgr/bgra */
-        let mut data = vec![0u8; metadata.data_size as usize];
-        reader.read_exact(&mut data)?;
+        /* load the image data: color map, then pixels */
+        let data = reader.read_bytes_at(metadata.data_start as u64, metadata.data_size as usize)?;
+        let (color_map, data) = data.split_at(metadata.color_map_bytes().min(data.len()));
+        let palette = parse_color_map(&metadata, color_map)?;
 
-        /* parse the image data based on characteristics of the image */
-        if metadata.pixel_depth == 32 {
-            let pixels = parse_32_bit(&metadata, data);
-            Ok(RawImage::new(metadata.width as u32, metadata.height as u32, pixels))
+        /* expand run-length encoding; either way, we end up with pixels in file order */
+        let count = (metadata.width as usize).checked_mul(metadata.height as usize).ok_or_else(truncated)?;
+        let bpp = metadata.bytes_per_pixel();
+        let data = if metadata.is_rle() {
+            decode_rle(data, count, bpp)?
         } else {
-            Err(Error::new(Unsupported, format!("TGA: unsupported pixel depth: {}", metadata.pixel_depth)))
+            data.get(..count.checked_mul(bpp).ok_or_else(truncated)?).ok_or_else(truncated)?.to_vec()
+        };
+
+        /* convert to rgba, then flip into top-to-bottom, left-to-right order */
+        let mut pixels = Vec::with_capacity(data.len() / bpp * 4);
+        for pixel in data.chunks_exact(bpp) {
+            pixels.extend_from_slice(&parse_pixel(&metadata, &palette, pixel)?);
         }
+        apply_attribute_type(&metadata, &mut pixels);
+        let pixels = orient(&metadata, pixels);
+
+        log(LogLevel::Debug, &|| "TGA: success".to_string());
+        Ok(RawImage::new(metadata.width as u32, metadata.height as u32, pixels))
     }
 }
 
-fn parse_32_bit(metadata: &TargaMetaData, bytes: Vec<u8>) -> Vec<u8> {
-    let mut pixels = Vec::with_capacity(metadata.width as usize * metadata.height as usize * 4);
-    let chunk_sz = 4;
-    let row_sz = bytes.len() / metadata.height as usize;
+///
+/// write uncompressed, 32-bit truecolor targas; rows are stored bottom-to-top.
+///
+impl ImageWriter for Targa {
+    fn write_to_writer<W: Write>(image: &RawImage, writer: &mut W) -> std::io::Result<()> {
+        let rgba = image.as_rgba8()?;
+        let image = rgba.as_ref();
+        writer.write_all(&write_header(image, 2)?)?;
 
-    if metadata.top_to_bottom {
-        for _pixel in bytes.chunks(chunk_sz) {
-            if metadata.left_to_right {
-                todo!("TGA: top-to-bottom: implement left-to-right")
-            } else {
-                todo!("TGA: top-to-bottom: implement right-to-left")
-            }
+        /* pixel data, BGRA */
+        let mut row = Vec::with_capacity(image.width as usize * 4);
+        for pixels in image.data.chunks_exact(image.width as usize * 4).rev() {
+            row.clear();
+            pixels.chunks_exact(4).for_each(|pixel| push_bgra(&mut row, pixel));
+            writer.write_all(&row)?;
         }
-    } else {
-        for row in bytes.rchunks(row_sz) {
-            if metadata.left_to_right {
-                for pixel in row.chunks(chunk_sz) {
-                    if pixel.len() == chunk_sz {
-                        pixels.push(pixel[2]);      // BGRA: red
-                        pixels.push(pixel[1]);      // BGRA: green
-                        pixels.push(pixel[0]);      // BGRA: blue
-                        pixels.push(pixel[3]);
-                    } else {
-                        log(LogLevel::Warning, &|| format!("TGA: bottom-to-top: chunk_length={}", pixel.len()));
+
+        Ok(())
+    }
+}
+
+///
+/// write run-length encoded, 32-bit truecolor targas; packets never cross a row.
+///
+impl ImageWriter for TargaRle {
+    fn write_to_writer<W: Write>(image: &RawImage, writer: &mut W) -> std::io::Result<()> {
+        let rgba = image.as_rgba8()?;
+        let image = rgba.as_ref();
+        writer.write_all(&write_header(image, 10)?)?;
+
+        let mut packets = Vec::with_capacity(image.data.len());
+        for pixels in image.data.chunks_exact(image.width as usize * 4).rev() {
+            let pixels: Vec<&[u8]> = pixels.chunks_exact(4).collect();
+            let mut x = 0;
+            while x < pixels.len() {
+                let run = pixels[x..].iter().take(128).take_while(|pixel| **pixel == pixels[x]).count();
+                let end = if run > 1 {
+                    /* run of identical pixels: stored once */
+                    packets.push(0x80 | (run - 1) as u8);
+                    push_bgra(&mut packets, pixels[x]);
+                    x + run
+                } else {
+                    /* raw pixels, up to where the next run starts */
+                    let mut end = x + 1;
+                    while end < pixels.len() && end - x < 128 && (end + 1 == pixels.len() || pixels[end] != pixels[end + 1]) {
+                        end += 1;
                     }
+                    packets.push((end - x - 1) as u8);
+                    pixels[x..end].iter().for_each(|pixel| push_bgra(&mut packets, pixel));
+                    end
+                };
+                x = end;
+            }
+        }
+
+        writer.write_all(&packets)
+    }
+}
+
+fn push_bgra(bytes: &mut Vec<u8>, pixel: &[u8]) {
+    bytes.extend_from_slice(&[pixel[2], pixel[1], pixel[0], pixel[3]]);
+}
+
+fn write_header(image: &RawImage, image_type: u8) -> std::io::Result<[u8; HEADER_LEN]> {
+    let (Ok(width), Ok(height)) = (u16::try_from(image.width), u16::try_from(image.height)) else {
+        return Err(Error::new(InvalidInput, format!("TGA: {}x{} is too big, at most 65535x65535", image.width, image.height)));
+    };
+    let mut header = [0u8; HEADER_LEN];
+    header[2] = image_type;                                                                     // 2=uncompressed truecolor, 10=rle truecolor
+    header[12..14].copy_from_slice(&width.to_le_bytes());
+    header[14..16].copy_from_slice(&height.to_le_bytes());
+    header[16] = 32;
+    header[17] = 8;                                                                             // 8 alpha bits, bottom-left origin
+    Ok(header)
+}
+
+fn truncated() -> Error {
+    Error::new(InvalidData, "TGA: image data is truncated")
+}
+
+fn parse_color_map(metadata: &TargaMetaData, bytes: &[u8]) -> std::io::Result<Vec<[u8;4]>> {
+    if bytes.len() < metadata.color_map_bytes() {
+        return Err(truncated());
+    }
+
+    let entry_sz = (metadata.color_map_depth as usize).div_ceil(8).max(1);
+    Ok(bytes.chunks_exact(entry_sz)
+        .map(|entry| parse_color(entry, metadata.alpha_channel_depth))
+        .collect())
+}
+
+fn decode_rle(bytes: &[u8], count: usize, bpp: usize) -> std::io::Result<Vec<u8>> {
+    /* every packet takes at least a header and a pixel, and makes at most 128 pixels */
+    let len = count.checked_mul(bpp).filter(|_| count / 128 <= bytes.len() / (1 + bpp)).ok_or_else(truncated)?;
+    let mut out = vec![];
+    let mut idx = 0;
+
+    while out.len() < len {
+        /* high bit set: one pixel, repeated; otherwise, raw pixels. either way, 1-128 of them */
+        let packet = *bytes.get(idx).ok_or_else(truncated)?;
+        let n = (packet & 0x7f) as usize + 1;
+        idx += 1;
+        out.try_reserve(n * bpp)
+            .map_err(|_| Error::new(InvalidData, format!("TGA: image too large: {} pixels", count)))?;
+
+        if packet & 0x80 != 0 {
+            let pixel = bytes.get(idx..idx + bpp).ok_or_else(truncated)?;
+            (0..n).for_each(|_| out.extend_from_slice(pixel));
+            idx += bpp;
+        } else {
+            out.extend_from_slice(bytes.get(idx..idx + n * bpp).ok_or_else(truncated)?);
+            idx += n * bpp;
+        }
+    }
+
+    /* a packet may cross the end of the image */
+    out.truncate(len);
+    Ok(out)
+}
+
+fn parse_pixel(metadata: &TargaMetaData, palette: &[[u8;4]], pixel: &[u8]) -> std::io::Result<[u8;4]> {
+    match metadata.image_type & !8 {
+        1 => {
+            let idx = if pixel.len() == 2 { u16::from_le_bytes([pixel[0], pixel[1]]) } else { pixel[0] as u16 };
+            idx.checked_sub(metadata.color_map_first)
+                .and_then(|idx| palette.get(idx as usize).copied())
+                .ok_or_else(|| Error::new(InvalidData, format!("TGA: color map index out of range: {}", idx)))
+        }
+        3 => Ok([pixel[0], pixel[0], pixel[0], if pixel.len() == 2 { pixel[1] } else { 255 }]),
+        _ => Ok(parse_color(pixel, metadata.alpha_channel_depth)),
+    }
+}
+
+fn parse_color(bytes: &[u8], alpha_channel_depth: u8) -> [u8;4] {
+    match bytes.len() {
+        2 => {
+            /* ARRRRRGG GGGBBBBB, little-endian; 5-bit channels are expanded to 8 */
+            let value = u16::from_le_bytes([bytes[0], bytes[1]]);
+            let expand = |shift: u16| {
+                let channel = ((value >> shift) & 0x1f) as u8;
+                (channel << 3) | (channel >> 2)
+            };
+            let alpha = if alpha_channel_depth > 0 && value & 0x8000 == 0 { 0 } else { 255 };
+            [expand(10), expand(5), expand(0), alpha]
+        }
+        3 => [bytes[2], bytes[1], bytes[0], 255],                                               // BGR
+        4 => [bytes[2], bytes[1], bytes[0], bytes[3]],                                          // BGRA
+        _ => [0, 0, 0, 255],
+    }
+}
+
+///
+/// the extension area says how the alpha channel (if any) should be interpreted.
+///
+fn apply_attribute_type(metadata: &TargaMetaData, pixels: &mut [u8]) {
+    let attribute_type = match &metadata.extension {
+        Some(extension) => extension.attribute_type,
+        None => return,
+    };
+
+    for pixel in pixels.chunks_exact_mut(4) {
+        match attribute_type {
+            0..=2 => pixel[3] = 255,
+            4 if pixel[3] > 0 => {
+                let alpha = pixel[3] as u32;
+                for channel in &mut pixel[0..3] {
+                    *channel = (*channel as u32 * 255 / alpha).min(255) as u8;
                 }
-            } else {
-                todo!("TGA: bottom-to-top: implement right-to-left")
             }
+            _ => {}
         }
     }
+}
 
-    pixels
+fn orient(metadata: &TargaMetaData, pixels: Vec<u8>) -> Vec<u8> {
+    if metadata.top_to_bottom && metadata.left_to_right {
+        return pixels;
+    }
+
+    let row_sz = metadata.width as usize * 4;
+    let mut oriented = Vec::with_capacity(pixels.len());
+    for y in 0..metadata.height as usize {
+        let src_y = if metadata.top_to_bottom { y } else { metadata.height as usize - 1 - y };
+        let row = &pixels[src_y * row_sz..(src_y + 1) * row_sz];
+        if metadata.left_to_right {
+            oriented.extend_from_slice(row);
+        } else {
+            row.chunks_exact(4).rev().for_each(|pixel| oriented.extend_from_slice(pixel));
+        }
+    }
+
+    oriented
 }
 
-fn validate(metadata: &TargaMetaData) {
-    /* color map type:  */
-    if metadata.color_map_type > 0 {
-        panic!("TGA: unsupported color map type {}", metadata.color_map_type);
+fn validate(metadata: &TargaMetaData) -> std::io::Result<()> {
+    /* color map type: 0=none, 1=present */
+    if metadata.color_map_type > 1 {
+        return Err(Error::new(Unsupported, format!("TGA: unsupported color map type {}", metadata.color_map_type)));
     }
 
-    /* currently only support 2, uncompressed truecolor */
-    if metadata.image_type != 2 {
-        panic!("TGA: unsupported image type {}", metadata.image_type);
+    /* image type, and the pixel depths each allows */
+    let valid_depth = match metadata.image_type {
+        0 => return Err(Error::new(InvalidData, "TGA: no image data")),
+        1 | 9 => [8, 16].contains(&metadata.pixel_depth)
+            && metadata.color_map_type == 1
+            && [15, 16, 24, 32].contains(&metadata.color_map_depth),
+        2 | 10 => [15, 16, 24, 32].contains(&metadata.pixel_depth),
+        3 | 11 => [8, 16].contains(&metadata.pixel_depth),
+        _ => return Err(Error::new(Unsupported, format!("TGA: unsupported image type {}", metadata.image_type))),
+    };
+    if !valid_depth {
+        return Err(Error::new(Unsupported, format!("TGA: unsupported pixel depth {} (color map depth {}) for image type {}",
+                                                   metadata.pixel_depth, metadata.color_map_depth, metadata.image_type)));
     }
+
+    Ok(())
 }
 
-fn load_metadata<R: BufRead + Seek>(mut reader: R) -> TargaMetaData {
-    /* determine the file size; reset the stream to start */
-    let file_size = reader.seek(SeekFrom::End(0)).expect("TODO: panic message");
+fn load_metadata<R: Read + Seek>(reader: &mut BinaryReader<R>) -> std::io::Result<TargaMetaData> {
+    /* determine the file size */
+    let file_size = reader.len();
+    if file_size < HEADER_LEN as u64 {
+        return Err(Error::new(InvalidData, "TGA: file too short"));
+    }
 
-    /* load the footer */
-    let maybe_footer = load_footer(&mut reader);
-    let mut footer_bytes_consumed = 0;
-    if maybe_footer.is_some() {
-        footer_bytes_consumed = FOOTER_IDX;
-        let footer = maybe_footer.unwrap();
-        footer_bytes_consumed += footer.dev_sz as usize;
-        footer_bytes_consumed += footer.ext_sz as usize;
+    /* load the footer and extension area; image data ends where they begin */
+    let mut data_end = file_size;
+    let mut extension = None;
+    if let Some(footer) = load_footer(reader)? {
+        data_end -= FOOTER_IDX as u64;
+        for offset in [footer.extension_offset, footer.developer_offset] {
+            if offset as u64 > HEADER_LEN as u64 {
+                data_end = data_end.min(offset as u64);
+            }
+        }
+        extension = load_extension(reader, footer.extension_offset)?;
     }
-    reader.seek(SeekFrom::Start(0)).expect("TODO: panic message");
+    reader.seek(0)?;
 
-    /* load the header */
-    let mut header = [0u8; HEADER_LEN];
-    reader.read_exact(&mut header).expect("TODO: panic message");
-
-    /* extract header fields */
-    let id_len = header[0];
-    let color_map_type = header[1];                                                             // 0=no color map, 1=color map
-    let image_type = header[2];                                                                 // 0=empty, 1=uncompressed color-mapped, 2=uncompressed truecolor, 3=uncompressed b/w, 9=rle colormapped, 10=rletruecolor (compressed), 11=rle b/w
-    let color_map_spec: [u8;5] = header[3..8].try_into().unwrap();                              // 2b=color map origin, 2b=color map length, 1b=color map size
-    let x_origin = u16::from_le_bytes(header[8..10].try_into().unwrap());
-    let y_origin = u16::from_le_bytes(header[10..12].try_into().unwrap());
-    let width = u16::from_le_bytes(header[12..14].try_into().unwrap());
-    let height = u16::from_le_bytes(header[14..16].try_into().unwrap());
-    let pixel_depth = header[16];
-    let image_desc = header[17];
-    let alpha_channel_depth = byte_to_bits_as_u8(image_desc, 0, 4);                    // 0=no alpha, 8=32-bit/8-bit alpha, 1=16-bit/1-bit alpha
-    let left_to_right = byte_to_bits_as_u8(image_desc, 4, 1);                          // 0=left-to-right, 1=right-to-left
-    let bottom_to_top = byte_to_bits_as_u8(image_desc, 5, 1);                          // 0=bottom-to-top, 1=top-to-bottom
-    let reserved = byte_to_bits_as_u8(image_desc, 6, 2);
+    /* read header fields */
+    let id_len = reader.read_u8()?;
+    let color_map_type = reader.read_u8()?;                                                     // 0=no color map, 1=color map
+    let image_type = reader.read_u8()?;                                                         // 0=empty, 1=uncompressed color-mapped, 2=uncompressed truecolor, 3=uncompressed b/w, 9=rle colormapped, 10=rletruecolor (compressed), 11=rle b/w
+    let color_map_first = reader.read_u16()?;                                                   // index of the first color map entry
+    let color_map_len = reader.read_u16()?;
+    let color_map_depth = reader.read_u8()?;                                                    // bits per color map entry
+    let x_origin = reader.read_u16()?;
+    let y_origin = reader.read_u16()?;
+    let width = reader.read_u16()?;
+    let height = reader.read_u16()?;
+    let pixel_depth = reader.read_u8()?;
+    let image_desc = reader.read_u8()?;
+    let alpha_channel_depth = image_desc & 0x0f;                                                // 0=no alpha, 8=32-bit/8-bit alpha, 1=16-bit/1-bit alpha
+    let right_to_left = byte_to_bits_as_u8(image_desc, 4, 1);                          // 0=left-to-right, 1=right-to-left
+    let top_to_bottom = byte_to_bits_as_u8(image_desc, 5, 1);                          // 0=bottom-to-top, 1=top-to-bottom
 
     /* calculate where image data starts */
     let data_start = HEADER_LEN + id_len as usize;
-    let data_size = (file_size - data_start as u64) - footer_bytes_consumed as u64;
+    let data_size = data_end.checked_sub(data_start as u64)
+        .ok_or_else(|| Error::new(InvalidData, "TGA: image id runs past the end of the file"))?;
 
     /* done */
-    TargaMetaData {
+    Ok(TargaMetaData {
         file_size,
 
         id_len,
         color_map_type,
         image_type,
-        color_map_spec,
+        color_map_first,
+        color_map_len,
+        color_map_depth,
         x_origin,
         y_origin,
         width,
@@ -176,64 +399,161 @@ fn load_metadata<R: BufRead + Seek>(mut reader: R) -> TargaMetaData {
 
         image_desc,
         alpha_channel_depth,
-        left_to_right: left_to_right == 0,
-        top_to_bottom: bottom_to_top != 0,
-        reserved,
+        left_to_right: right_to_left == 0,
+        top_to_bottom: top_to_bottom != 0,
+
+        extension,
 
         data_start,
         data_size,
-    }
+    })
 }
 
-fn load_footer<R: BufRead + Seek>(mut reader: R) -> Option<TargaFooter> {
-    /* load footer bytes */
-    reader.seek(SeekFrom::End(-26)).expect("TODO: panic message");
-    let footer_begin = reader.stream_position().unwrap();
-
-    let mut footer = [0u8; FOOTER_IDX];
-    reader.read_exact(&mut footer).expect("TODO: panic message");
+fn load_footer<R: Read + Seek>(reader: &mut BinaryReader<R>) -> std::io::Result<Option<TargaFooter>> {
+    if reader.len() < (HEADER_LEN + FOOTER_IDX) as u64 {
+        return Ok(None);
+    }
 
-    /* read the marker */
-    let marker: [u8;16] = footer[8..24].try_into().unwrap();
+    /* read the footer fields */
+    reader.seek(reader.len() - FOOTER_IDX as u64)?;
+    let extension_offset = reader.read_u32()?;
+    let developer_offset = reader.read_u32()?;
+    let marker = reader.read_array::<16>()?;
 
     /* if footer marker matches, fill fields and return */
     if marker == *b"TRUEVISION-XFILE" {
-        /* read additional data fields */
-        let extension_offset = u32::from_le_bytes(footer[0..4].try_into().unwrap());
-        let developer_offset = u32::from_le_bytes(footer[4..8].try_into().unwrap());
-        let reserved: u8 = footer[24].try_into().unwrap();
-        let terminator: u8 = footer[25].try_into().unwrap();
-
-        /* load up the developer and extension data, if present */
-        let ext_sz: u32 = if extension_offset != 0 {
-            if developer_offset != 0 {
-                footer_begin as u32 - developer_offset.abs_diff(extension_offset)
-            } else {
-                footer_begin as u32 - extension_offset
-            }
-        } else { 0 };
-        let dev_sz: u32 = if developer_offset != 0 {
-            if extension_offset != 0 {
-                footer_begin as u32 - extension_offset.abs_diff(developer_offset)
-            } else {
-                footer_begin as u32 - developer_offset
-            }
-        } else { 0 };
-
-        /* done */
         let footer = TargaFooter {
             extension_offset,
             developer_offset,
             marker,
-            reserved,
-            terminator,
-            dev_sz,
-            ext_sz,
+            reserved: reader.read_u8()?,
+            terminator: reader.read_u8()?,
         };
         log(LogLevel::Debug, &|| format!("TGA: {:?}", footer));
-        return Some(footer);
+        return Ok(Some(footer));
     }
 
     /* no footer */
-    None
+    Ok(None)
+}
+
+fn load_extension<R: Read + Seek>(reader: &mut BinaryReader<R>, offset: u32) -> std::io::Result<Option<TargaExtension>> {
+    if offset == 0 {
+        return Ok(None);
+    }
+
+    /* the first field is the size of the area */
+    let offset = offset as u64;
+    reader.seek(offset)?;
+    let size = reader.read_u16()?;
+    if size as usize != EXTENSION_LEN {
+        log(LogLevel::Warning, &|| format!("TGA: ignoring extension area with unexpected size: {}", size));
+        return Ok(None);
+    }
+
+    /* read the fields we care about */
+    let text = |bytes: Vec<u8>| String::from_utf8_lossy(bytes.split(|b| *b == 0).next().unwrap_or_default()).trim().to_string();
+    let author = text(reader.read_bytes_at(offset + 2, 41)?);
+    let software = text(reader.read_bytes_at(offset + 426, 41)?);
+    reader.seek(offset + 478)?;
+    let gamma_numerator = reader.read_u16()?;
+    let gamma_denominator = reader.read_u16()?;
+    reader.seek(offset + 494)?;
+    let extension = TargaExtension {
+        author,
+        software,
+        gamma: if gamma_denominator == 0 { None } else { Some(gamma_numerator as f32 / gamma_denominator as f32) },
+        attribute_type: reader.read_u8()?,
+    };
+    log(LogLevel::Debug, &|| format!("TGA: {:?}", extension));
+    Ok(Some(extension))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::support::image::targa::{Targa, TargaRle, EXTENSION_LEN, HEADER_LEN};
+    use crate::support::image::{Image, ImageWriter, RawImage};
+
+    fn header(image_type: u8, color_map: (u16, u16, u8), width: u16, height: u16, depth: u8, desc: u8) -> Vec<u8> {
+        let mut header = vec![0u8; HEADER_LEN];
+        header[1] = (color_map.1 > 0) as u8;
+        header[2] = image_type;
+        header[3..5].copy_from_slice(&color_map.0.to_le_bytes());
+        header[5..7].copy_from_slice(&color_map.1.to_le_bytes());
+        header[7] = color_map.2;
+        header[12..14].copy_from_slice(&width.to_le_bytes());
+        header[14..16].copy_from_slice(&height.to_le_bytes());
+        header[16] = depth;
+        header[17] = desc;
+        header
+    }
+
+    #[test]
+    fn test_rle_truecolor_24_bit() {
+        /* 3x2, bottom-to-top: a run of 2 blue pixels, then 4 raw pixels */
+        let mut tga = header(10, (0, 0, 0), 3, 2, 24, 0);
+        tga.extend_from_slice(&[0x81, 255, 0, 0]);
+        tga.extend_from_slice(&[0x03, 0, 255, 0, 0, 0, 255, 1, 2, 3, 4, 5, 6]);
+
+        let image = Targa::load_from_bytes(&tga).unwrap();
+        assert_eq!(vec![
+            255, 0, 0, 255, 3, 2, 1, 255, 6, 5, 4, 255,
+            0, 0, 255, 255, 0, 0, 255, 255, 0, 255, 0, 255,
+        ], image.data);
+    }
+
+    #[test]
+    fn test_color_mapped_16_bit_entries() {
+        /* map starts at index 1; entries are A1R5G5B5 with a 1-bit alpha; top-to-bottom, right-to-left */
+        let mut tga = header(1, (1, 2, 16), 2, 1, 8, 0x31);
+        tga.extend_from_slice(&0xfc00u16.to_le_bytes());                           // opaque red
+        tga.extend_from_slice(&0x03e0u16.to_le_bytes());                           // transparent green
+        tga.extend_from_slice(&[1, 2]);
+
+        let image = Targa::load_from_bytes(&tga).unwrap();
+        assert_eq!(vec![0, 255, 0, 0, 255, 0, 0, 255], image.data);
+
+        /* index 0 is before the start of the map */
+        let len = tga.len();
+        tga[len - 1] = 0;
+        assert!(Targa::load_from_bytes(&tga).is_err());
+    }
+
+    #[test]
+    fn test_rle_grayscale_with_extension() {
+        /* gray+alpha, top-to-bottom, with an extension area that says there's no useful alpha */
+        let mut tga = header(11, (0, 0, 0), 2, 2, 16, 0x28);
+        tga.extend_from_slice(&[0x83, 77, 0]);
+        let extension_offset = tga.len() as u32;
+        let mut extension = vec![0u8; EXTENSION_LEN];
+        extension[0..2].copy_from_slice(&(EXTENSION_LEN as u16).to_le_bytes());
+        extension[478..482].copy_from_slice(&[22, 0, 10, 0]);
+        tga.extend_from_slice(&extension);
+        tga.extend_from_slice(&extension_offset.to_le_bytes());
+        tga.extend_from_slice(&[0; 4]);
+        tga.extend_from_slice(b"TRUEVISION-XFILE.\0");
+
+        let image = Targa::load_from_bytes(&tga).unwrap();
+        assert_eq!([77, 77, 77, 255].repeat(4), image.data);
+    }
+
+    #[test]
+    fn test_invalid() {
+        /* truncated pixel data, and an unsupported depth */
+        let mut tga = header(2, (0, 0, 0), 2, 2, 32, 8);
+        tga.extend_from_slice(&[0; 12]);
+        assert!(Targa::load_from_bytes(&tga).is_err());
+        assert!(Targa::load_from_bytes(&header(3, (0, 0, 0), 1, 1, 24, 0)).is_err());
+        assert!(Targa::load_from_bytes(&[0; 4]).is_err());
+
+        /* a huge rle image with almost no data */
+        let mut tga = header(10, (0, 0, 0), 65535, 65535, 24, 0);
+        tga.extend_from_slice(&[0xff, 0, 0, 0]);
+        assert!(Targa::load_from_bytes(&tga).is_err_and(|e| e.to_string() == "TGA: image data is truncated"));
+
+        /* too wide for the header's 16-bit dimensions */
+        let wide = RawImage::new(65536, 1, vec![0; 65536 * 4]);
+        assert_eq!(std::io::ErrorKind::InvalidInput, Targa::write_to_bytes(&wide).unwrap_err().kind());
+        assert_eq!(std::io::ErrorKind::InvalidInput, TargaRle::write_to_bytes(&wide).unwrap_err().kind());
+    }
 }
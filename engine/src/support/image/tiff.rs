@@ -1,13 +1,480 @@
+use crate::support::image::tiff::compression::{lzw, packbits};
 use crate::support::image::{Image, RawImage};
-use std::io::{BufRead, Seek};
+use crate::support::logger::log;
+use crate::support::logger::log_level::LogLevel;
+use crate::support::binary::endianness::Endianness;
+use std::collections::HashMap;
+use std::io::ErrorKind::{InvalidData, Unsupported};
+use std::io::{BufRead, Error, Seek, SeekFrom};
+
+mod compression;
 
 pub struct Tiff;
 
+/* baseline tags */
+const TAG_WIDTH: u16 = 256;
+const TAG_HEIGHT: u16 = 257;
+const TAG_BITS_PER_SAMPLE: u16 = 258;
+const TAG_COMPRESSION: u16 = 259;
+const TAG_PHOTOMETRIC: u16 = 262;
+const TAG_STRIP_OFFSETS: u16 = 273;
+const TAG_SAMPLES_PER_PIXEL: u16 = 277;
+const TAG_ROWS_PER_STRIP: u16 = 278;
+const TAG_STRIP_BYTE_COUNTS: u16 = 279;
+const TAG_PLANAR_CONFIG: u16 = 284;
+const TAG_PREDICTOR: u16 = 317;
+const TAG_COLOR_MAP: u16 = 320;
+const TAG_TILE_WIDTH: u16 = 322;
+const TAG_TILE_LENGTH: u16 = 323;
+const TAG_TILE_OFFSETS: u16 = 324;
+const TAG_TILE_BYTE_COUNTS: u16 = 325;
+const TAG_EXTRA_SAMPLES: u16 = 338;
+
+#[derive(Debug)]
+struct TiffMetaData {
+    /* image info */
+    order: Endianness,
+    width: u32,
+    height: u32,
+    bits_per_sample: u32,
+    samples_per_pixel: u32,
+    compression: u32,                                                                           // 1=none, 5=lzw, 32773=packbits
+    photometric: u32,                                                                           // 0=white is zero, 1=black is zero, 2=rgb, 3=palette
+    predictor: u32,                                                                             // 1=none, 2=horizontal differencing
+    associated_alpha: bool,
+    color_map: Vec<u32>,
+
+    /* image data, as strips (full width) or tiles */
+    block_width: u32,
+    block_height: u32,
+    offsets: Vec<u32>,
+    byte_counts: Vec<u32>,
+}
+
+impl TiffMetaData {
+    /* color channels, not counting extra samples */
+    fn color_channels(&self) -> u32 {
+        if self.photometric == 2 { 3 } else { 1 }
+    }
+
+    fn has_alpha(&self) -> bool {
+        self.samples_per_pixel > self.color_channels()
+    }
+
+    /* bytes in one row of a strip or tile */
+    fn block_row_len(&self) -> Option<usize> {
+        let bits = (self.block_width as usize).checked_mul(self.samples_per_pixel as usize)?.checked_mul(self.bits_per_sample as usize)?;
+        Some(bits.div_ceil(8))
+    }
+
+    /* bytes in a whole strip or tile, once decompressed */
+    fn block_len(&self) -> Option<usize> {
+        self.block_row_len()?.checked_mul(self.block_height as usize)
+    }
+
+    /* strips or tiles needed to cover the image */
+    fn block_count(&self) -> Option<usize> {
+        (self.width.div_ceil(self.block_width) as usize).checked_mul(self.height.div_ceil(self.block_height) as usize)
+    }
+}
+
 ///
 /// load tiff image files.
 ///
+/// supports baseline tiff in either byte order: strips or tiles; no, lzw or packbits compression;
+/// and grayscale, rgb(a) or palette images. only the first image in the file is read.
+///
 impl Image for Tiff {
-    fn load_from_buf_read<R: BufRead + Seek>(_reader: R) -> std::io::Result<RawImage> {
-        todo!()
+    fn load_from_buf_read<R: BufRead + Seek>(mut reader: R) -> std::io::Result<RawImage> {
+        /* tiff uses absolute offsets throughout; read the whole file */
+        reader.seek(SeekFrom::Start(0))?;
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes)?;
+
+        /* header: byte order, magic number, offset of the first ifd */
+        let order = match bytes.get(0..2) {
+            Some(b"II") => Endianness::LittleEndian,
+            Some(b"MM") => Endianness::BigEndian,
+            _ => return Err(Error::new(InvalidData, "not a tiff!")),
+        };
+        let header = bytes.get(0..8).ok_or_else(|| Error::new(InvalidData, "TIFF: header too short"))?;
+        if order.read_u16(header, 2)? != 42 {
+            return Err(Error::new(InvalidData, "TIFF: bad magic number"));
+        }
+        let ifd_offset = order.read_u32(header, 4)? as usize;
+
+        /* read and validate the image description */
+        let tags = read_ifd(&bytes, order, ifd_offset)?;
+        let metadata = load_metadata(order, &tags)?;
+        log(LogLevel::Debug, &|| format!("TIFF: {:?}", metadata));
+        validate(&metadata)?;
+
+        /* decode each strip or tile into place */
+        let mut pixels = allocate(&metadata)?;
+        for (idx, (offset, count)) in metadata.offsets.iter().zip(metadata.byte_counts.iter()).enumerate() {
+            let raw = bytes.get(*offset as usize..*offset as usize + *count as usize)
+                .ok_or_else(|| Error::new(InvalidData, format!("TIFF: block {} is past the end of the file", idx)))?;
+            decode_block(&metadata, idx, raw, &mut pixels)?;
+        }
+
+        log(LogLevel::Debug, &|| "TIFF: success".to_string());
+        Ok(RawImage::new(metadata.width, metadata.height, pixels))
+    }
+}
+
+///
+/// read an image file directory; only byte, short and long values are kept.
+///
+fn read_ifd(bytes: &[u8], order: Endianness, offset: usize) -> std::io::Result<HashMap<u16, Vec<u32>>> {
+    let truncated = || Error::new(InvalidData, "TIFF: image file directory is truncated");
+    let count = order.read_u16(bytes, offset).map_err(|_| truncated())? as usize;
+    let entries = bytes.get(offset + 2..offset + 2 + count * 12).ok_or_else(truncated)?;
+
+    let mut tags = HashMap::new();
+    for entry in entries.chunks_exact(12) {
+        let tag = order.read_u16(entry, 0)?;
+        let size = match order.read_u16(entry, 2)? {
+            1 => 1,                                                                             // byte
+            3 => 2,                                                                             // short
+            4 => 4,                                                                             // long
+            _ => continue,
+        };
+        let n = order.read_u32(entry, 4)? as usize;
+
+        /* values are stored inline when they fit in 4 bytes */
+        let len = n.checked_mul(size).ok_or_else(truncated)?;
+        let data = if len <= 4 {
+            &entry[8..8 + len]
+        } else {
+            let start = order.read_u32(entry, 8)? as usize;
+            bytes.get(start..start + len).ok_or_else(truncated)?
+        };
+
+        let values = data.chunks_exact(size).map(|value| match size {
+            1 => Ok(value[0] as u32),
+            2 => order.read_u16(value, 0).map(|value| value as u32),
+            _ => order.read_u32(value, 0),
+        }).collect::<std::io::Result<_>>()?;
+        tags.insert(tag, values);
+    }
+
+    Ok(tags)
+}
+
+fn load_metadata(order: Endianness, tags: &HashMap<u16, Vec<u32>>) -> std::io::Result<TiffMetaData> {
+    let field = |tag: u16| tags.get(&tag).and_then(|values| values.first().copied());
+    let required = |tag: u16| field(tag).ok_or_else(|| Error::new(InvalidData, format!("TIFF: missing required tag: {}", tag)));
+    let list = |tag: u16| tags.get(&tag).cloned().unwrap_or_default();
+
+    /* image info */
+    let width = required(TAG_WIDTH)?;
+    let height = required(TAG_HEIGHT)?;
+    let samples_per_pixel = field(TAG_SAMPLES_PER_PIXEL).unwrap_or(1);
+    let bits = list(TAG_BITS_PER_SAMPLE);
+    if bits.iter().any(|b| *b != bits[0]) {
+        return Err(Error::new(Unsupported, format!("TIFF: mixed bits per sample: {:?}", bits)));
+    }
+
+    /* image data: tiles if present, otherwise strips */
+    let tiled = tags.contains_key(&TAG_TILE_OFFSETS);
+    let (block_width, block_height, offsets, byte_counts) = if tiled {
+        (required(TAG_TILE_WIDTH)?, required(TAG_TILE_LENGTH)?, list(TAG_TILE_OFFSETS), list(TAG_TILE_BYTE_COUNTS))
+    } else {
+        (width, field(TAG_ROWS_PER_STRIP).unwrap_or(height).min(height), list(TAG_STRIP_OFFSETS), list(TAG_STRIP_BYTE_COUNTS))
+    };
+
+    /* planar configuration is checked here, since it changes the meaning of the offsets */
+    if field(TAG_PLANAR_CONFIG).unwrap_or(1) != 1 {
+        return Err(Error::new(Unsupported, "TIFF: only chunky (interleaved) planar configuration is supported"));
+    }
+
+    Ok(TiffMetaData {
+        order,
+        width,
+        height,
+        bits_per_sample: bits.first().copied().unwrap_or(1),
+        samples_per_pixel,
+        compression: field(TAG_COMPRESSION).unwrap_or(1),
+        photometric: required(TAG_PHOTOMETRIC)?,
+        predictor: field(TAG_PREDICTOR).unwrap_or(1),
+        associated_alpha: field(TAG_EXTRA_SAMPLES) == Some(1),
+        color_map: list(TAG_COLOR_MAP),
+
+        block_width,
+        block_height,
+        offsets,
+        byte_counts,
+    })
+}
+
+fn validate(metadata: &TiffMetaData) -> std::io::Result<()> {
+    if metadata.width == 0 || metadata.height == 0 || metadata.block_width == 0 || metadata.block_height == 0 {
+        return Err(Error::new(InvalidData, format!("TIFF: invalid dimensions: {}x{}", metadata.width, metadata.height)));
+    }
+
+    /* sample layout */
+    if ![1, 2, 4, 8, 16].contains(&metadata.bits_per_sample) {
+        return Err(Error::new(Unsupported, format!("TIFF: unsupported bits per sample: {}", metadata.bits_per_sample)));
+    }
+    if metadata.photometric > 3 {
+        return Err(Error::new(Unsupported, format!("TIFF: unsupported photometric interpretation: {}", metadata.photometric)));
+    }
+    if metadata.samples_per_pixel < metadata.color_channels() {
+        return Err(Error::new(InvalidData, format!("TIFF: too few samples per pixel: {}", metadata.samples_per_pixel)));
+    }
+    if metadata.photometric == 3 && metadata.color_map.len() != 3 << metadata.bits_per_sample {
+        return Err(Error::new(InvalidData, format!("TIFF: color map has {} entries", metadata.color_map.len())));
+    }
+
+    /* encoding */
+    if ![1, 5, 32773].contains(&metadata.compression) {
+        return Err(Error::new(Unsupported, format!("TIFF: unsupported compression: {}", metadata.compression)));
+    }
+    if metadata.predictor > 2 || (metadata.predictor == 2 && metadata.bits_per_sample < 8) {
+        return Err(Error::new(Unsupported, format!("TIFF: unsupported predictor {} for {} bits per sample", metadata.predictor, metadata.bits_per_sample)));
+    }
+    if metadata.offsets.is_empty() || metadata.offsets.len() != metadata.byte_counts.len() {
+        return Err(Error::new(InvalidData, format!("TIFF: {} data offsets, but {} byte counts", metadata.offsets.len(), metadata.byte_counts.len())));
+    }
+
+    /* sizes; every strip or tile must be present */
+    if metadata.block_len().is_none() {
+        return Err(Error::new(InvalidData, format!("TIFF: strip or tile too large: {}x{}", metadata.block_width, metadata.block_height)));
+    }
+    if metadata.block_count().is_none_or(|count| count > metadata.offsets.len()) {
+        return Err(Error::new(InvalidData, format!("TIFF: {} strips or tiles can't cover {}x{}", metadata.offsets.len(), metadata.width, metadata.height)));
+    }
+
+    Ok(())
+}
+
+///
+/// the rgba pixels for the whole image, failing rather than aborting if the memory isn't there.
+///
+fn allocate(metadata: &TiffMetaData) -> std::io::Result<Vec<u8>> {
+    let too_large = || Error::new(InvalidData, format!("TIFF: image too large: {}x{}", metadata.width, metadata.height));
+    let len = (metadata.width as usize).checked_mul(metadata.height as usize)
+        .and_then(|count| count.checked_mul(4))
+        .ok_or_else(too_large)?;
+    let mut pixels = vec![];
+    pixels.try_reserve_exact(len).map_err(|_| too_large())?;
+    pixels.resize(len, 0);
+    Ok(pixels)
+}
+
+fn decode_block(metadata: &TiffMetaData, idx: usize, raw: &[u8], pixels: &mut [u8]) -> std::io::Result<()> {
+    /* position of the block in the image */
+    let across = metadata.width.div_ceil(metadata.block_width) as usize;
+    let x0 = (idx % across) * metadata.block_width as usize;
+    let y0 = (idx / across) * metadata.block_height as usize;
+
+    /* decompress; the last strip may be short */
+    let too_large = || Error::new(InvalidData, "TIFF: strip or tile too large");
+    let row_len = metadata.block_row_len().ok_or_else(too_large)?;
+    let expected = metadata.block_len().ok_or_else(too_large)?;
+    let mut data = match metadata.compression {
+        5 => lzw(raw, expected)?,
+        32773 => packbits(raw, expected)?,
+        _ => raw.to_vec(),
+    };
+
+    let width = metadata.width as usize;
+    let height = metadata.height as usize;
+    for (y, row) in data.chunks_exact_mut(row_len).take(metadata.block_height as usize).enumerate() {
+        if y0 + y >= height {
+            break;
+        }
+        if metadata.predictor == 2 {
+            undo_horizontal_differencing(metadata, row)?;
+        }
+
+        for x in 0..(metadata.block_width as usize).min(width.saturating_sub(x0)) {
+            let idx = ((y0 + y) * width + x0 + x) * 4;
+            pixels[idx..idx + 4].copy_from_slice(&pixel(metadata, row, x)?);
+        }
+    }
+
+    Ok(())
+}
+
+fn undo_horizontal_differencing(metadata: &TiffMetaData, row: &mut [u8]) -> std::io::Result<()> {
+    let stride = metadata.samples_per_pixel as usize;
+    if metadata.bits_per_sample == 16 {
+        for i in stride..row.len() / 2 {
+            let sum = metadata.order.read_u16(row, i * 2)?.wrapping_add(metadata.order.read_u16(row, (i - stride) * 2)?);
+            let bytes = match metadata.order {
+                Endianness::LittleEndian => sum.to_le_bytes(),
+                Endianness::BigEndian => sum.to_be_bytes(),
+            };
+            row[i * 2..i * 2 + 2].copy_from_slice(&bytes);
+        }
+    } else {
+        for i in stride..row.len() {
+            row[i] = row[i].wrapping_add(row[i - stride]);
+        }
+    }
+    Ok(())
+}
+
+fn pixel(metadata: &TiffMetaData, row: &[u8], x: usize) -> std::io::Result<[u8; 4]> {
+    let depth = metadata.bits_per_sample;
+    let channels = metadata.samples_per_pixel as usize;
+    let sample = |channel: usize| sample(metadata, row, x * channels + channel);
+    let scale = |value: u32| scale(value, depth);
+    let alpha = if metadata.has_alpha() { scale(sample(metadata.color_channels() as usize)?) } else { 255 };
+
+    let rgb = match metadata.photometric {
+        0 => [255 - scale(sample(0)?); 3],
+        1 => [scale(sample(0)?); 3],
+        2 => [scale(sample(0)?), scale(sample(1)?), scale(sample(2)?)],
+        _ => {
+            /* color map holds all reds, then all greens, then all blues, as 16-bit values */
+            let idx = sample(0)? as usize;
+            let n = 1 << depth;
+            [(metadata.color_map[idx] >> 8) as u8, (metadata.color_map[n + idx] >> 8) as u8, (metadata.color_map[2 * n + idx] >> 8) as u8]
+        }
+    };
+
+    /* associated alpha is premultiplied into the colors */
+    if metadata.associated_alpha && alpha > 0 {
+        let unmultiply = |c: u8| (c as u32 * 255 / alpha as u32).min(255) as u8;
+        return Ok([unmultiply(rgb[0]), unmultiply(rgb[1]), unmultiply(rgb[2]), alpha]);
+    }
+    Ok([rgb[0], rgb[1], rgb[2], alpha])
+}
+
+/* the index'th sample in a row, unscaled */
+fn sample(metadata: &TiffMetaData, row: &[u8], index: usize) -> std::io::Result<u32> {
+    match metadata.bits_per_sample {
+        16 => metadata.order.read_u16(row, index * 2).map(|value| value as u32),
+        8 => Ok(row[index] as u32),
+        depth => {
+            let bit = index * depth as usize;
+            let shift = 8 - depth as usize - bit % 8;
+            Ok(((row[bit / 8] >> shift) & ((1u8 << depth) - 1)) as u32)
+        }
+    }
+}
+
+/* scale a sample of the given depth to 8 bits */
+fn scale(value: u32, depth: u32) -> u8 {
+    match depth {
+        16 => (value >> 8) as u8,
+        8 => value as u8,
+        _ => (value * 255 / ((1 << depth) - 1)) as u8,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::support::image::tiff::*;
+
+    /* build a tiff from tags (all stored as longs) and strips or tiles */
+    fn encode(order: Endianness, tags: &[(u16, Vec<u32>)], blocks: &[Vec<u8>], tiled: bool) -> Vec<u8> {
+        let u16_bytes = |v: u16| if order == Endianness::LittleEndian { v.to_le_bytes() } else { v.to_be_bytes() };
+        let u32_bytes = |v: u32| if order == Endianness::LittleEndian { v.to_le_bytes() } else { v.to_be_bytes() };
+
+        /* header, then image data */
+        let mut bytes = if order == Endianness::LittleEndian { b"II".to_vec() } else { b"MM".to_vec() };
+        bytes.extend_from_slice(&u16_bytes(42));
+        bytes.extend_from_slice(&[0; 4]);
+        let mut offsets = vec![];
+        for block in blocks {
+            offsets.push(bytes.len() as u32);
+            bytes.extend_from_slice(block);
+        }
+
+        let mut tags = tags.to_vec();
+        let (offset_tag, count_tag) = if tiled { (TAG_TILE_OFFSETS, TAG_TILE_BYTE_COUNTS) } else { (TAG_STRIP_OFFSETS, TAG_STRIP_BYTE_COUNTS) };
+        tags.push((offset_tag, offsets));
+        tags.push((count_tag, blocks.iter().map(|b| b.len() as u32).collect()));
+        tags.sort_by_key(|(tag, _)| *tag);
+
+        /* values that don't fit inline, then the ifd */
+        let mut inline = vec![];
+        for (_, values) in &tags {
+            if values.len() > 1 {
+                inline.push(bytes.len() as u32);
+                values.iter().for_each(|v| bytes.extend_from_slice(&u32_bytes(*v)));
+            } else {
+                inline.push(values[0]);
+            }
+        }
+        let ifd = bytes.len() as u32;
+        bytes[4..8].copy_from_slice(&u32_bytes(ifd));
+        bytes.extend_from_slice(&u16_bytes(tags.len() as u16));
+        for ((tag, values), value) in tags.iter().zip(inline) {
+            bytes.extend_from_slice(&u16_bytes(*tag));
+            bytes.extend_from_slice(&u16_bytes(4));
+            bytes.extend_from_slice(&u32_bytes(values.len() as u32));
+            bytes.extend_from_slice(&u32_bytes(value));
+        }
+        bytes.extend_from_slice(&[0; 4]);
+        bytes
+    }
+
+    #[test]
+    fn test_rgb_strips() {
+        for order in [Endianness::LittleEndian, Endianness::BigEndian] {
+            let tags = [
+                (TAG_WIDTH, vec![2]), (TAG_HEIGHT, vec![2]), (TAG_BITS_PER_SAMPLE, vec![8, 8, 8]),
+                (TAG_PHOTOMETRIC, vec![2]), (TAG_SAMPLES_PER_PIXEL, vec![3]), (TAG_ROWS_PER_STRIP, vec![1]),
+            ];
+            let strips = [vec![255, 0, 0, 0, 255, 0], vec![0, 0, 255, 10, 20, 30]];
+            let image = Tiff::load_from_bytes(&encode(order, &tags, &strips, false)).unwrap();
+            assert_eq!((2, 2), (image.width, image.height));
+            assert_eq!(vec![255, 0, 0, 255, 0, 255, 0, 255, 0, 0, 255, 255, 10, 20, 30, 255], image.data);
+        }
+    }
+
+    #[test]
+    fn test_gray_tiles_packbits() {
+        /* 3x2 white-is-zero image in 2x2 tiles; each tile is a literal run, then a repeat run */
+        let tags = [
+            (TAG_WIDTH, vec![3]), (TAG_HEIGHT, vec![2]), (TAG_BITS_PER_SAMPLE, vec![8]), (TAG_COMPRESSION, vec![32773]),
+            (TAG_PHOTOMETRIC, vec![0]), (TAG_TILE_WIDTH, vec![2]), (TAG_TILE_LENGTH, vec![2]),
+        ];
+        let tiles = [vec![1, 0, 255, 0xff, 10], vec![0, 100, 0xfe, 0]];
+        let image = Tiff::load_from_bytes(&encode(Endianness::BigEndian, &tags, &tiles, true)).unwrap();
+        let gray: Vec<u8> = image.data.chunks_exact(4).map(|p| p[0]).collect();
+        assert_eq!(vec![255, 0, 155, 245, 245, 255], gray);
+    }
+
+    #[test]
+    fn test_palette() {
+        /* 4-bit indices; index 1 is pure green */
+        let mut color_map = vec![0; 48];
+        color_map[16 + 1] = 0xffff;
+        color_map[2] = 0x8000;
+        let tags = [
+            (TAG_WIDTH, vec![3]), (TAG_HEIGHT, vec![1]), (TAG_BITS_PER_SAMPLE, vec![4]),
+            (TAG_PHOTOMETRIC, vec![3]), (TAG_COLOR_MAP, color_map),
+        ];
+        let image = Tiff::load_from_bytes(&encode(Endianness::LittleEndian, &tags, &[vec![0x12, 0x00]], false)).unwrap();
+        assert_eq!(vec![0, 255, 0, 255, 128, 0, 0, 255, 0, 0, 0, 255], image.data);
+    }
+
+    #[test]
+    fn test_predictor_16_bit_alpha() {
+        /* gray + associated alpha, horizontally differenced */
+        let tags = [
+            (TAG_WIDTH, vec![2]), (TAG_HEIGHT, vec![1]), (TAG_BITS_PER_SAMPLE, vec![16, 16]), (TAG_PHOTOMETRIC, vec![1]),
+            (TAG_SAMPLES_PER_PIXEL, vec![2]), (TAG_PREDICTOR, vec![2]), (TAG_EXTRA_SAMPLES, vec![1]),
+        ];
+        let strip = vec![0x40, 0x00, 0x80, 0x00, 0x10, 0x00, 0x7f, 0xff];
+        let image = Tiff::load_from_bytes(&encode(Endianness::BigEndian, &tags, &[strip], false)).unwrap();
+        assert_eq!(vec![127, 127, 127, 128, 80, 80, 80, 255], image.data);
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(Tiff::load_from_bytes(b"II*\0").is_err());
+        assert!(Tiff::load_from_bytes(b"PK\x03\x04\0\0\0\0").is_err());
+        let tags = [(TAG_WIDTH, vec![1]), (TAG_HEIGHT, vec![1]), (TAG_COMPRESSION, vec![7]), (TAG_PHOTOMETRIC, vec![1])];
+        assert!(Tiff::load_from_bytes(&encode(Endianness::LittleEndian, &tags, &[vec![0]], false)).is_err());
+
+        /* huge dimensions, with one strip where billions are needed */
+        let tags = [(TAG_WIDTH, vec![0xffff_ffff]), (TAG_HEIGHT, vec![0xffff_ffff]), (TAG_PHOTOMETRIC, vec![1]), (TAG_ROWS_PER_STRIP, vec![1])];
+        assert!(Tiff::load_from_bytes(&encode(Endianness::LittleEndian, &tags, &[vec![0]], false)).is_err());
     }
 }
@@ -0,0 +1,131 @@
+//!
+//! decompression schemes used by tiff strips and tiles.
+//!
+
+use crate::support::binary::bits::MsbBitReader;
+use std::io::Error;
+use std::io::ErrorKind::InvalidData;
+
+const LZW_CLEAR: usize = 256;
+const LZW_END: usize = 257;
+const LZW_MAX_BITS: u32 = 12;
+
+///
+/// decode packbits (apple macintosh run-length encoding).
+///
+pub(super) fn packbits(bytes: &[u8], expected: usize) -> std::io::Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(expected.min(bytes.len() * 64));                           // 2 bytes make at most 128
+    let mut idx = 0;
+
+    while idx < bytes.len() && out.len() < expected {
+        let n = bytes[idx] as i8;
+        idx += 1;
+        match n {
+            0..=127 => {
+                /* literal run of n+1 bytes */
+                let run = bytes.get(idx..idx + n as usize + 1)
+                    .ok_or_else(|| Error::new(InvalidData, "TIFF: packbits: literal run past end of data"))?;
+                out.extend_from_slice(run);
+                idx += run.len();
+            }
+            -127..=-1 => {
+                /* next byte, repeated 1-n times */
+                let byte = *bytes.get(idx)
+                    .ok_or_else(|| Error::new(InvalidData, "TIFF: packbits: repeat run past end of data"))?;
+                out.resize(out.len() + (1 - n as isize) as usize, byte);
+                idx += 1;
+            }
+            -128 => {}                                                                          // no-op
+        }
+    }
+
+    Ok(out)
+}
+
+///
+/// decode tiff lzw: msb-first codes of 9-12 bits, widened one code early.
+///
+pub(super) fn lzw(bytes: &[u8], expected: usize) -> std::io::Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(expected.min(bytes.len() * 8));                            // a guess; it grows if needed
+    let mut table: Vec<Vec<u8>> = lzw_table();
+    let mut width = 9;
+    let mut prev: Option<usize> = None;
+
+    let mut reader = MsbBitReader::new(bytes, "TIFF: lzw");
+    loop {
+        /* next code */
+        if reader.remaining_bits() < width as usize {
+            break;                                                                              // some encoders omit the end code
+        }
+        let code = reader.bits(width)? as usize;
+
+        if code == LZW_CLEAR {
+            table.truncate(LZW_END + 1);
+            width = 9;
+            prev = None;
+            continue;
+        }
+        if code == LZW_END {
+            break;
+        }
+
+        /* a code may refer to the entry that's about to be added */
+        let entry = match (table.get(code), prev) {
+            (Some(entry), _) => entry.clone(),
+            (None, Some(prev)) if code == table.len() => {
+                let mut entry = table[prev].clone();
+                entry.push(entry[0]);
+                entry
+            }
+            _ => return Err(Error::new(InvalidData, format!("TIFF: lzw: invalid code: {}", code))),
+        };
+        out.extend_from_slice(&entry);
+
+        if let Some(prev) = prev && table.len() < 1 << LZW_MAX_BITS {
+            let mut added = table[prev].clone();
+            added.push(entry[0]);
+            table.push(added);
+        }
+        prev = Some(code);
+
+        if table.len() + 1 >= 1 << width && width < LZW_MAX_BITS {
+            width += 1;
+        }
+    }
+
+    Ok(out)
+}
+
+fn lzw_table() -> Vec<Vec<u8>> {
+    let mut table: Vec<Vec<u8>> = (0..=255u8).map(|byte| vec![byte]).collect();
+    table.push(vec![]);                                                                         // clear
+    table.push(vec![]);                                                                         // end of information
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::support::image::tiff::compression::{lzw, packbits};
+
+    #[test]
+    fn test_packbits() {
+        /* example from the tiff 6.0 specification */
+        let packed = [0xfe, 0xaa, 0x02, 0x80, 0x00, 0x2a, 0xfd, 0xaa, 0x03, 0x80, 0x00, 0x2a, 0x22, 0xf7, 0xaa];
+        let expected = [
+            0xaa, 0xaa, 0xaa, 0x80, 0x00, 0x2a, 0xaa, 0xaa, 0xaa, 0xaa, 0x80, 0x00, 0x2a, 0x22,
+            0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa,
+        ];
+        assert_eq!(expected.to_vec(), packbits(&packed, expected.len()).unwrap());
+    }
+
+    #[test]
+    fn test_lzw() {
+        let packed = [
+            0x80, 0x15, 0x09, 0xe4, 0x22, 0x29, 0x3c, 0xa4, 0x4e, 0x27, 0x95, 0x20, 0x50, 0x48, 0x34, 0x2e,
+            0x0b, 0x07, 0x84, 0xc0, 0x40,
+        ];
+        let expected = b"TOBEORNOTTOBEORTOBEORNOT";
+        assert_eq!(expected.to_vec(), lzw(&packed, expected.len()).unwrap());
+        assert!(lzw(&[0xff, 0xff], 4).is_err());
+    }
+}
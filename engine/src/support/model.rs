@@ -0,0 +1,5 @@
+//!
+//! models loaded from files, as 2d or 3d models of the engine's primitive types.
+//!
+
+pub mod obj;
@@ -0,0 +1,178 @@
+//!
+//! wavefront obj: vertices, and the points, lines and faces that join them.
+//!
+//! faces become quads (triangles repeat their last vertex; bigger polygons are fanned into
+//! triangles); each line is a line strip.  texture coordinates, normals, groups and materials
+//! are skipped, so everything is drawn in one color.
+//!
+
+use crate::geometry::primitive::prim2d::Primitive2DBuilder;
+use crate::geometry::primitive::prim3d::Primitive3DBuilder;
+use crate::geometry::primitive::v2d::Vertex2D;
+use crate::geometry::primitive::v3d::Vertex3D;
+use crate::geometry::primitive::PrimitiveType;
+use crate::graphics::storage::m2d::{Model2D, Model2DBuilder};
+use crate::graphics::storage::m3d::{Model3D, Model3DBuilder};
+use std::io::Error;
+use std::io::ErrorKind::InvalidData;
+
+///
+/// an obj file's geometry; elements are indexes into `vertices`.
+///
+pub struct Obj {
+    pub vertices: Vec<[f32; 3]>,
+    pub points: Vec<usize>,
+    pub lines: Vec<Vec<usize>>,
+    pub quads: Vec<[usize; 4]>,
+}
+
+impl Obj {
+    pub fn from_bytes(bytes: &[u8]) -> std::io::Result<Obj> {
+        let text = std::str::from_utf8(bytes).map_err(|_| Error::new(InvalidData, "obj: not utf-8 text"))?;
+        let mut obj = Obj { vertices: vec![], points: vec![], lines: vec![], quads: vec![] };
+
+        for (number, line) in text.lines().enumerate() {
+            let invalid = |what: &str| Error::new(InvalidData, format!("obj: line {}: {}", number + 1, what));
+            let mut tokens = line.split('#').next().unwrap_or_default().split_whitespace();
+            let Some(keyword) = tokens.next() else {
+                continue;
+            };
+            let arguments: Vec<&str> = tokens.collect();
+
+            /* vertex references are 1-based, or negative to count back from the last vertex so far */
+            let count = obj.vertices.len();
+            let indexes = || -> std::io::Result<Vec<usize>> {
+                arguments.iter()
+                    .map(|token| {
+                        let index = token.split('/').next().unwrap_or_default().parse::<i64>().map_err(|_| invalid("bad vertex index"))?;
+                        match index {
+                            1.. if index as u64 <= count as u64 => Ok(index as usize - 1),
+                            ..=-1 if index.unsigned_abs() <= count as u64 => Ok(count - index.unsigned_abs() as usize),
+                            _ => Err(invalid("vertex index out of range")),
+                        }
+                    })
+                    .collect()
+            };
+
+            match keyword {
+                "v" => {
+                    let coordinate = |n: usize| arguments.get(n).and_then(|token| token.parse::<f32>().ok()).ok_or_else(|| invalid("bad vertex"));
+                    obj.vertices.push([coordinate(0)?, coordinate(1)?, coordinate(2)?]);
+                }
+                "p" => obj.points.extend(indexes()?),
+                "l" => {
+                    let strip = indexes()?;
+                    if strip.len() < 2 {
+                        return Err(invalid("a line needs at least 2 vertices"));
+                    }
+                    obj.lines.push(strip);
+                }
+                "f" => {
+                    let face = indexes()?;
+                    match face.len() {
+                        0..=2 => return Err(invalid("a face needs at least 3 vertices")),
+                        4 => obj.quads.push([face[0], face[1], face[2], face[3]]),
+                        _ => obj.quads.extend(face[1..].windows(2).map(|pair| [face[0], pair[0], pair[1], pair[1]])),
+                    }
+                }
+                _ => {}                                                                         // vt, vn, o, g, s, usemtl, mtllib...
+            }
+        }
+        Ok(obj)
+    }
+
+    ///
+    /// a 3d model: one primitive for the points, one for the faces, and one per line.
+    ///
+    pub fn to_model_3d(&self) -> Model3D {
+        let vertex = |index: &usize| {
+            let [x, y, z] = self.vertices[*index];
+            Vertex3D::new(x, y, z)
+        };
+        let primitive = |p_type: PrimitiveType, indexes: &mut dyn Iterator<Item = &usize>| {
+            indexes.fold(Primitive3DBuilder::new().with_type(p_type), |builder, index| builder.with_vertex(vertex(index))).build()
+        };
+
+        let mut builder = Model3DBuilder::new();
+        if !self.points.is_empty() {
+            builder = builder.with_primitive(primitive(PrimitiveType::Point { point_size: 1.0 }, &mut self.points.iter()));
+        }
+        for line in &self.lines {
+            builder = builder.with_primitive(primitive(PrimitiveType::LineStrip { thickness: 1.0 }, &mut line.iter()));
+        }
+        if !self.quads.is_empty() {
+            builder = builder.with_primitive(primitive(PrimitiveType::Quad {}, &mut self.quads.iter().flatten()));
+        }
+        builder.build()
+    }
+
+    ///
+    /// a 2d model, the same as the 3d one with z dropped.
+    ///
+    pub fn to_model_2d(&self) -> Model2D {
+        let vertex = |index: &usize| {
+            let [x, y, _] = self.vertices[*index];
+            Vertex2D::new(x, y)
+        };
+        let primitive = |p_type: PrimitiveType, indexes: &mut dyn Iterator<Item = &usize>| {
+            indexes.fold(Primitive2DBuilder::new().with_type(p_type), |builder, index| builder.with_vertex(vertex(index))).build()
+        };
+
+        let mut builder = Model2DBuilder::new();
+        if !self.points.is_empty() {
+            builder = builder.with_primitive(primitive(PrimitiveType::Point { point_size: 1.0 }, &mut self.points.iter()));
+        }
+        for line in &self.lines {
+            builder = builder.with_primitive(primitive(PrimitiveType::LineStrip { thickness: 1.0 }, &mut line.iter()));
+        }
+        if !self.quads.is_empty() {
+            builder = builder.with_primitive(primitive(PrimitiveType::Quad {}, &mut self.quads.iter().flatten()));
+        }
+        builder.build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geometry::primitive::PrimitiveType;
+    use crate::support::model::obj::Obj;
+
+    #[test]
+    fn test_elements() {
+        let obj = Obj::from_bytes(b"# a square, a triangle and a pentagon\n\
+            v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0.5\nv 2 2 2 1.0 0.0 0.0\n\
+            vt 0 0\nvn 0 0 1\ng shapes\n\
+            f 1/1/1 2/1/1 3/1/1 4/1/1\n\
+            f 1 2 -1\n\
+            f 1//1 2//1 3//1 4//1 5//1\n\
+            l 1 3 5  # a diagonal\n\
+            p 2 4\n").unwrap();
+
+        assert_eq!(5, obj.vertices.len());
+        assert_eq!([2.0, 2.0, 2.0], obj.vertices[4]);
+        assert_eq!(vec![[0, 1, 2, 3], [0, 1, 4, 4], [0, 1, 2, 2], [0, 2, 3, 3], [0, 3, 4, 4]], obj.quads);
+        assert_eq!(vec![vec![0, 2, 4]], obj.lines);
+        assert_eq!(vec![1, 3], obj.points);
+
+        let model = obj.to_model_3d();
+        assert_eq!(3, model.primitives.len());
+        assert!(matches!(model.primitives[2].ptype, PrimitiveType::Quad {}));
+        assert_eq!(20, model.primitives[2].vertices.len());
+        assert_eq!(0.5, model.primitives[2].vertices[3].z);
+
+        let model = obj.to_model_2d();
+        assert_eq!(3, model.primitives.len());
+        assert_eq!(1.0, model.primitives[2].vertices[3].y);
+    }
+
+    #[test]
+    fn test_errors() {
+        let message = |bytes: &[u8]| Obj::from_bytes(bytes).err().map(|e| e.to_string());
+        assert_eq!(Some("obj: line 2: vertex index out of range".to_string()), message(b"v 0 0 0\nf 1 1 2\n"));
+        assert_eq!(Some("obj: line 1: vertex index out of range".to_string()), message(b"p -1\n"));
+        assert_eq!(Some("obj: line 1: bad vertex".to_string()), message(b"v 0 0\n"));
+        assert_eq!(Some("obj: line 2: bad vertex index".to_string()), message(b"v 0 0 0\np x\n"));
+        assert_eq!(Some("obj: line 2: a face needs at least 3 vertices".to_string()), message(b"v 0 0 0\nf 1 1\n"));
+        assert_eq!(Some("obj: line 2: a line needs at least 2 vertices".to_string()), message(b"v 0 0 0\nl 1\n"));
+    }
+}
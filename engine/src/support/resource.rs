@@ -2,17 +2,54 @@
 //! access to restype that are external to the engine.
 //!
 
-use std::error::Error;
+use crate::support::resource::error::ResourceError;
+use std::borrow::Cow;
+use std::io::{Cursor, Read, Seek};
+use std::time::SystemTime;
 
-pub mod endianness;
+pub mod error;
 pub mod restype;
+pub mod vfs;
 
 ///
 /// any resource that exists outside the engine.
 ///
 pub trait Resource {
     ///
-    /// load all the bytes from the resource.
+    /// where the resource is, for messages: a file path, a path in the virtual file system, etc.
     ///
-    fn bytes(&self) -> Result<Vec<u8>, Box<dyn Error>>;
+    fn logical_path(&self) -> Cow<'_, str>;
+
+    ///
+    /// all the bytes from the resource; borrowed, when they're already in memory.
+    ///
+    fn data(&self) -> Result<Cow<'_, [u8]>, ResourceError>;
+
+    ///
+    /// load all the bytes from the resource, as an owned copy.
+    ///
+    fn bytes(&self) -> Result<Vec<u8>, ResourceError> {
+        Ok(self.data()?.into_owned())
+    }
+
+    ///
+    /// read the resource as a stream, without loading it all first where that's possible.
+    ///
+    fn open(&self) -> Result<Box<dyn ResourceStream + '_>, ResourceError> {
+        Ok(Box::new(Cursor::new(self.data()?)))
+    }
+
+    ///
+    /// when the resource last changed, if that can be known.
+    ///
+    fn modified(&self) -> Option<SystemTime> {
+        None
+    }
 }
+
+///
+/// a seekable stream over a resource's bytes.
+///
+pub trait ResourceStream: Read + Seek {}
+
+impl<T: Read + Seek> ResourceStream for T {}
@@ -0,0 +1,106 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::io::ErrorKind;
+
+///
+/// why a resource couldn't be loaded; each names the resource's logical path.
+///
+#[derive(Debug)]
+pub enum ResourceError {
+    ///
+    /// nothing exists at the path.
+    ///
+    NotFound { path: String },
+
+    ///
+    /// the resource exists, but reading it failed.
+    ///
+    Io { path: String, source: std::io::Error },
+
+    ///
+    /// the resource was read, but its contents aren't what was expected.
+    ///
+    Decode { path: String, source: std::io::Error },
+}
+
+impl ResourceError {
+    ///
+    /// a failed read; a missing file becomes [ResourceError::NotFound].
+    ///
+    pub fn io(path: &str, source: std::io::Error) -> ResourceError {
+        match source.kind() {
+            ErrorKind::NotFound => ResourceError::NotFound { path: path.to_string() },
+            _ => ResourceError::Io { path: path.to_string(), source },
+        }
+    }
+
+    pub fn decode(path: &str, source: std::io::Error) -> ResourceError {
+        ResourceError::Decode { path: path.to_string(), source }
+    }
+
+    pub fn path(&self) -> &str {
+        match self {
+            ResourceError::NotFound { path } => path,
+            ResourceError::Io { path, .. } => path,
+            ResourceError::Decode { path, .. } => path,
+        }
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            ResourceError::NotFound { .. } => ErrorKind::NotFound,
+            ResourceError::Io { source, .. } => source.kind(),
+            ResourceError::Decode { source, .. } => source.kind(),
+        }
+    }
+}
+
+impl Display for ResourceError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ResourceError::NotFound { path } => write!(f, "resource [{}] not found", path),
+            ResourceError::Io { path, source } => write!(f, "reading resource [{}] failed: {}", path, source),
+            ResourceError::Decode { path, source } => write!(f, "decoding resource [{}] failed: {}", path, source),
+        }
+    }
+}
+
+impl Error for ResourceError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ResourceError::NotFound { .. } => None,
+            ResourceError::Io { source, .. } => Some(source),
+            ResourceError::Decode { source, .. } => Some(source),
+        }
+    }
+}
+
+///
+/// so resources can be loaded with `?` in code that returns io results; the kind is kept.
+///
+impl From<ResourceError> for std::io::Error {
+    fn from(error: ResourceError) -> Self {
+        std::io::Error::new(error.kind(), error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::support::resource::error::ResourceError;
+    use crate::support::resource::restype::file::FileResource;
+    use crate::support::resource::Resource;
+    use std::io::ErrorKind::{InvalidData, NotFound};
+
+    #[test]
+    fn test_errors() {
+        let missing = FileResource::from_path_str("no/such/file.png").data().unwrap_err();
+        assert!(matches!(missing, ResourceError::NotFound { .. }));
+        assert_eq!("no/such/file.png", missing.path());
+        assert_eq!("resource [no/such/file.png] not found", missing.to_string());
+
+        let decode: std::io::Error = ResourceError::decode("a.bmp", std::io::Error::new(InvalidData, "not a bitmap!")).into();
+        assert_eq!(InvalidData, decode.kind());
+        assert_eq!("decoding resource [a.bmp] failed: not a bitmap!", decode.to_string());
+        assert_eq!(NotFound, std::io::Error::from(missing).kind());
+    }
+}
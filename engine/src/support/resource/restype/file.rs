@@ -1,7 +1,11 @@
-use crate::support::resource::Resource;
-use std::error::Error;
+use crate::support::resource::error::ResourceError;
+use crate::support::resource::{Resource, ResourceStream};
+use std::borrow::Cow;
 use std::fs;
-use std::path::PathBuf;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
 ///
 /// a resource that comes from a file.
@@ -26,13 +30,32 @@ impl FileResource {
             path,
         }
     }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
 }
 
 impl Resource for FileResource {
+    fn logical_path(&self) -> Cow<'_, str> {
+        self.path.to_string_lossy()
+    }
+
     ///
     /// read a file, to eof, with automagic retries.
     ///
-    fn bytes(&self) -> Result<Vec<u8>, Box<dyn Error>> {
-        Ok(fs::read(&self.path)?)
+    fn data(&self) -> Result<Cow<'_, [u8]>, ResourceError> {
+        fs::read(&self.path)
+            .map(Cow::Owned)
+            .map_err(|e| ResourceError::io(&self.logical_path(), e))
+    }
+
+    fn open(&self) -> Result<Box<dyn ResourceStream + '_>, ResourceError> {
+        let file = File::open(&self.path).map_err(|e| ResourceError::io(&self.logical_path(), e))?;
+        Ok(Box::new(BufReader::new(file)))
+    }
+
+    fn modified(&self) -> Option<SystemTime> {
+        fs::metadata(&self.path).and_then(|metadata| metadata.modified()).ok()
     }
 }
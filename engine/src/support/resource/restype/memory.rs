@@ -1,25 +1,91 @@
+use crate::support::resource::error::ResourceError;
 use crate::support::resource::Resource;
-use std::error::Error;
+use std::borrow::Cow;
+use std::sync::Arc;
 
 ///
 /// a resource that comes from memory.
 ///
+/// the bytes are never copied: they're borrowed from a static (e.g. `include_bytes!`), or
+/// shared, so cloning the resource is cheap.
+///
+#[derive(Clone)]
 pub struct MemoryResource {
-    bytes: Vec<u8>,
+    path: Cow<'static, str>,
+    bytes: MemoryBytes,
+}
+
+#[derive(Clone)]
+enum MemoryBytes {
+    Static(&'static [u8]),
+    Shared(Arc<[u8]>),
 }
 
 impl MemoryResource {
     pub fn from_array(bytes: Vec<u8>) -> MemoryResource {
-        MemoryResource { bytes }
+        Self::from_shared(Arc::from(bytes))
+    }
+
+    pub fn from_shared(bytes: Arc<[u8]>) -> MemoryResource {
+        MemoryResource { path: Cow::Borrowed("<memory>"), bytes: MemoryBytes::Shared(bytes) }
+    }
+
+    pub fn from_static(bytes: &'static [u8]) -> MemoryResource {
+        MemoryResource { path: Cow::Borrowed("<memory>"), bytes: MemoryBytes::Static(bytes) }
+    }
+
+    ///
+    /// name the resource, e.g. by the path it was embedded from; it's used in messages.
+    ///
+    pub fn with_path(mut self, path: &str) -> MemoryResource {
+        self.path = Cow::Owned(path.to_string());
+        self
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        match &self.bytes {
+            MemoryBytes::Static(bytes) => bytes,
+            MemoryBytes::Shared(bytes) => bytes,
+        }
     }
 }
 
 impl Resource for MemoryResource {
+    fn logical_path(&self) -> Cow<'_, str> {
+        Cow::Borrowed(&self.path)
+    }
+
     ///
-    /// get all bytes from memory.
+    /// get all bytes from memory, borrowed.
     ///
-    fn bytes(&self) -> Result<Vec<u8>, Box<dyn Error>> {
-        // todo: clone here?
-        Ok(self.bytes.clone())
+    fn data(&self) -> Result<Cow<'_, [u8]>, ResourceError> {
+        Ok(Cow::Borrowed(self.as_slice()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::support::resource::restype::memory::MemoryResource;
+    use crate::support::resource::Resource;
+    use std::borrow::Cow;
+    use std::io::{Read, Seek, SeekFrom};
+
+    static EMBEDDED: &[u8] = b"embedded bytes";
+
+    #[test]
+    fn test_no_copies() {
+        let resource = MemoryResource::from_static(EMBEDDED).with_path("assets/embedded.txt");
+        let clone = resource.clone();
+        assert!(matches!(clone.data().unwrap(), Cow::Borrowed(bytes) if std::ptr::eq(bytes, EMBEDDED)));
+        assert_eq!("assets/embedded.txt", clone.logical_path());
+
+        let shared = MemoryResource::from_array(b"shared bytes".to_vec());
+        assert_eq!(shared.as_slice().as_ptr(), shared.clone().as_slice().as_ptr());
+
+        let mut stream = resource.open().unwrap();
+        stream.seek(SeekFrom::Start(9)).unwrap();
+        let mut tail = String::new();
+        stream.read_to_string(&mut tail).unwrap();
+        assert_eq!("bytes", tail);
     }
 }
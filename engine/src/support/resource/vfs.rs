@@ -0,0 +1,365 @@
+//!
+//! virtual file system: logical paths (like `textures/wall.tga`) resolved against mounted roots.
+//!
+//! roots are loose directories, `.pak` archives or zip files; higher priorities are searched first, and
+//! among equal priorities the most recent mount wins, so a mod or patch overlays what's under it.
+//!
+
+use crate::support::logger::log;
+use crate::support::logger::log_level::LogLevel;
+use crate::support::resource::vfs::directory::DirectoryMount;
+use crate::support::resource::vfs::pak::{PakArchive, PAK_MAGIC};
+use crate::support::resource::vfs::zip::ZipArchive;
+use crate::support::resource::error::ResourceError;
+use crate::support::resource::{Resource, ResourceStream};
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::ErrorKind::{InvalidData, InvalidInput, NotFound};
+use std::io::{Cursor, Read, Seek, SeekFrom, Take};
+use std::path::{Path, PathBuf};
+
+pub mod directory;
+pub mod pak;
+pub mod zip;
+
+///
+/// one root in the file system.
+///
+pub trait Mount: Send + Sync {
+    ///
+    /// what this is, for logging.
+    ///
+    fn describe(&self) -> String;
+
+    fn contains(&self, path: &str) -> bool;
+
+    ///
+    /// the file's contents; `path` is already normalized.
+    ///
+    fn read(&self, path: &str) -> std::io::Result<Vec<u8>>;
+
+    ///
+    /// the file's contents as a stream; by default, read whole first, but a root that can read
+    /// the file in place (loose or uncompressed) should.
+    ///
+    fn open(&self, path: &str) -> std::io::Result<Box<dyn ResourceStream + '_>> {
+        Ok(Box::new(Cursor::new(self.read(path)?)))
+    }
+
+    ///
+    /// every file in this root, as logical paths.
+    ///
+    fn files(&self) -> Vec<String>;
+}
+
+pub struct VirtualFileSystem {
+    mounts: Vec<(i32, Box<dyn Mount>)>,                                                         // searched in order; highest priority first
+}
+
+///
+/// a file in the virtual file system, as a resource.
+///
+pub struct VfsResource<'a> {
+    vfs: &'a VirtualFileSystem,
+    path: String,
+}
+
+///
+/// where an archive's bytes are: read from disk on demand, or already in memory.
+///
+pub(crate) enum ArchiveSource {
+    File(PathBuf),
+    Memory(Vec<u8>),
+}
+
+///
+/// part of a file, as a stream of its own: position 0 is the start of the range, and reads end at its end.
+///
+pub(crate) struct FileRange {
+    inner: Take<File>,
+    start: u64,
+    length: u64,
+}
+
+impl VirtualFileSystem {
+    pub fn new() -> VirtualFileSystem {
+        VirtualFileSystem {
+            mounts: vec![],
+        }
+    }
+}
+
+impl Default for VirtualFileSystem {
+    fn default() -> Self {
+        VirtualFileSystem::new()
+    }
+}
+
+impl VirtualFileSystem {
+    pub fn mount(&mut self, mount: Box<dyn Mount>, priority: i32) {
+        log(LogLevel::Info, &|| format!("mounted {} at priority {}", mount.describe(), priority));
+        let idx = self.mounts.iter().position(|(p, _)| *p <= priority).unwrap_or(self.mounts.len());
+        self.mounts.insert(idx, (priority, mount));
+    }
+
+    pub fn mount_directory<P: AsRef<Path>>(&mut self, root: P, priority: i32) -> std::io::Result<()> {
+        self.mount(Box::new(DirectoryMount::new(root)?), priority);
+        Ok(())
+    }
+
+    ///
+    /// mount a `.pak` or zip file, detected from its contents.
+    ///
+    pub fn mount_archive<P: AsRef<Path>>(&mut self, path: P, priority: i32) -> std::io::Result<()> {
+        let mut magic = [0u8; 4];
+        File::open(path.as_ref())?.read_exact(&mut magic)?;
+        let mount: Box<dyn Mount> = match &magic {
+            m if m == PAK_MAGIC => Box::new(PakArchive::open(path)?),
+            b"PK\x03\x04" | b"PK\x05\x06" => Box::new(ZipArchive::open(path)?),
+            _ => return Err(std::io::Error::new(InvalidData, format!("{} isn't a pak or zip archive", path.as_ref().display()))),
+        };
+        self.mount(mount, priority);
+        Ok(())
+    }
+
+    pub fn exists(&self, path: &str) -> bool {
+        normalize_path(path).is_ok_and(|path| self.mounts.iter().any(|(_, mount)| mount.contains(&path)))
+    }
+
+    ///
+    /// the file from the highest-priority root that has it.
+    ///
+    pub fn read(&self, path: &str) -> std::io::Result<Vec<u8>> {
+        let path = normalize_path(path)?;
+        self.find(&path)?.read(&path)
+    }
+
+    ///
+    /// the file from the highest-priority root that has it, as a stream.
+    ///
+    pub fn open(&self, path: &str) -> std::io::Result<Box<dyn ResourceStream + '_>> {
+        let path = normalize_path(path)?;
+        self.find(&path)?.open(&path)
+    }
+
+    fn find(&self, path: &str) -> std::io::Result<&dyn Mount> {
+        match self.mounts.iter().find(|(_, mount)| mount.contains(path)) {
+            Some((_, mount)) => Ok(mount.as_ref()),
+            None => Err(std::io::Error::new(NotFound, format!("no mounted root has [{}]", path))),
+        }
+    }
+
+    pub fn resource(&self, path: &str) -> VfsResource<'_> {
+        VfsResource { vfs: self, path: path.to_string() }
+    }
+
+    ///
+    /// every file visible through any root, sorted.
+    ///
+    pub fn files(&self) -> Vec<String> {
+        let mut files: Vec<String> = self.mounts.iter().flat_map(|(_, mount)| mount.files()).collect();
+        files.sort();
+        files.dedup();
+        files
+    }
+}
+
+impl Resource for VfsResource<'_> {
+    fn logical_path(&self) -> Cow<'_, str> {
+        Cow::Borrowed(&self.path)
+    }
+
+    fn data(&self) -> Result<Cow<'_, [u8]>, ResourceError> {
+        self.vfs.read(&self.path)
+            .map(Cow::Owned)
+            .map_err(|e| ResourceError::io(&self.path, e))
+    }
+
+    fn open(&self) -> Result<Box<dyn ResourceStream + '_>, ResourceError> {
+        self.vfs.open(&self.path).map_err(|e| ResourceError::io(&self.path, e))
+    }
+}
+
+impl ArchiveSource {
+    pub(crate) fn len(&self) -> std::io::Result<u64> {
+        match self {
+            ArchiveSource::File(path) => Ok(std::fs::metadata(path)?.len()),
+            ArchiveSource::Memory(bytes) => Ok(bytes.len() as u64),
+        }
+    }
+
+    ///
+    /// `length` bytes from `offset`; lengths come from archive headers, so they're checked before allocating.
+    ///
+    pub(crate) fn read_range(&self, offset: u64, length: usize) -> std::io::Result<Vec<u8>> {
+        let mut data = vec![];
+        self.open_range(offset, length as u64)?.read_to_end(&mut data)?;
+        Ok(data)
+    }
+
+    ///
+    /// `length` bytes from `offset`, as a stream; nothing's read until the stream is.
+    ///
+    pub(crate) fn open_range(&self, offset: u64, length: u64) -> std::io::Result<Box<dyn ResourceStream + '_>> {
+        let truncated = || std::io::Error::new(InvalidData, "archive truncated");
+        let end = offset.checked_add(length).ok_or_else(truncated)?;
+        match self {
+            ArchiveSource::File(path) => {
+                let file = File::open(path)?;
+                if end > file.metadata()?.len() {
+                    return Err(truncated());
+                }
+                Ok(Box::new(FileRange::new(file, offset, length)?))
+            }
+            ArchiveSource::Memory(bytes) => {
+                let range = usize::try_from(offset).ok().zip(usize::try_from(end).ok()).ok_or_else(truncated)?;
+                Ok(Box::new(Cursor::new(bytes.get(range.0..range.1).ok_or_else(truncated)?)))
+            }
+        }
+    }
+
+    pub(crate) fn describe(&self) -> String {
+        match self {
+            ArchiveSource::File(path) => path.display().to_string(),
+            ArchiveSource::Memory(bytes) => format!("<{} bytes in memory>", bytes.len()),
+        }
+    }
+}
+
+impl FileRange {
+    pub(crate) fn new(mut file: File, start: u64, length: u64) -> std::io::Result<FileRange> {
+        file.seek(SeekFrom::Start(start))?;
+        Ok(FileRange { inner: file.take(length), start, length })
+    }
+}
+
+impl Read for FileRange {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.inner.read(buf)
+    }
+}
+
+impl Seek for FileRange {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let current = self.length - self.inner.limit();
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => self.length.checked_add_signed(offset),
+            SeekFrom::Current(offset) => current.checked_add_signed(offset),
+        };
+        let position = position.ok_or_else(|| std::io::Error::new(InvalidInput, "seek before the start of the range"))?;
+
+        /* past the end is allowed, as for files; reads there just find nothing */
+        self.inner.get_mut().seek(SeekFrom::Start(self.start + position.min(self.length)))?;
+        self.inner.set_limit(self.length.saturating_sub(position));
+        Ok(position)
+    }
+}
+
+///
+/// a logical path in canonical form: `/`-separated, relative, without `.` or empty segments.
+///
+/// `\` is accepted as a separator; `..` is resolved, but can't climb above the root. segments with a `:`
+/// are rejected, since on windows `C:` would replace the root it's joined to.
+///
+pub fn normalize_path(path: &str) -> std::io::Result<String> {
+    let mut segments: Vec<&str> = vec![];
+    for segment in path.split(['/', '\\']) {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop().ok_or_else(|| std::io::Error::new(InvalidInput, format!("path [{}] leaves the root", path)))?;
+            }
+            s if s.contains(':') => {
+                return Err(std::io::Error::new(InvalidInput, format!("path [{}] has a drive or stream name", path)));
+            }
+            s => segments.push(s),
+        }
+    }
+    if segments.is_empty() {
+        return Err(std::io::Error::new(InvalidInput, format!("path [{}] names no file", path)));
+    }
+    Ok(segments.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::support::resource::vfs::pak::write_pak;
+    use crate::support::resource::vfs::{normalize_path, VirtualFileSystem};
+    use crate::support::resource::vfs::pak::PakArchive;
+    use crate::support::resource::Resource;
+    use std::io::{Read, SeekFrom};
+
+    #[test]
+    fn test_normalize_path() {
+        assert_eq!("textures/wall.tga", normalize_path("textures/wall.tga").unwrap());
+        assert_eq!("textures/wall.tga", normalize_path("/textures//./wall.tga").unwrap());
+        assert_eq!("textures/wall.tga", normalize_path("textures\\old\\..\\wall.tga").unwrap());
+        assert!(normalize_path("../wall.tga").is_err());
+        assert!(normalize_path("./").is_err());
+        assert!(normalize_path("C:/windows/win.ini").is_err());
+        assert!(normalize_path("textures/C:\\wall.tga").is_err());
+    }
+
+    #[test]
+    fn test_overlay() {
+        let base = write_pak(&[("textures/wall.tga", b"base wall"), ("textures/floor.tga", b"base floor")], false).unwrap();
+        let patch = write_pak(&[("textures/wall.tga", b"patched wall")], true).unwrap();
+        let late = write_pak(&[("textures/wall.tga", b"low priority wall")], false).unwrap();
+
+        let mut vfs = VirtualFileSystem::new();
+        vfs.mount(Box::new(PakArchive::from_bytes(base).unwrap()), 0);
+        vfs.mount(Box::new(PakArchive::from_bytes(patch).unwrap()), 0);
+        vfs.mount(Box::new(PakArchive::from_bytes(late).unwrap()), -1);
+
+        assert_eq!(b"patched wall".to_vec(), vfs.read("textures/wall.tga").unwrap());
+        assert_eq!(b"base floor".to_vec(), vfs.resource("/textures/floor.tga").bytes().unwrap());
+        assert!(vfs.exists("textures\\floor.tga"));
+        assert!(!vfs.exists("textures/ceiling.tga"));
+        assert_eq!(std::io::ErrorKind::NotFound, vfs.read("textures/ceiling.tga").unwrap_err().kind());
+        assert_eq!(vec!["textures/floor.tga", "textures/wall.tga"], vfs.files());
+    }
+
+    #[test]
+    fn test_mount_archive_file() {
+        let path = std::env::temp_dir().join(format!("painsaw-vfs-{}.pak", std::process::id()));
+        std::fs::write(&path, write_pak(&[("sounds/hit.wav", b"hit")], true).unwrap()).unwrap();
+
+        let mut vfs = VirtualFileSystem::new();
+        vfs.mount_archive(&path, 0).unwrap();
+        assert_eq!(b"hit".to_vec(), vfs.read("sounds/hit.wav").unwrap());
+        assert!(vfs.mount_archive(std::env::current_exe().unwrap(), 0).is_err());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_stream_stored_file() {
+        let path = std::env::temp_dir().join(format!("painsaw-vfs-stream-{}.pak", std::process::id()));
+        std::fs::write(&path, write_pak(&[("a.txt", b"first"), ("b.txt", b"second file")], false).unwrap()).unwrap();
+
+        let mut vfs = VirtualFileSystem::new();
+        vfs.mount_archive(&path, 0).unwrap();
+        let resource = vfs.resource("b.txt");
+        let mut stream = resource.open().unwrap();
+        let mut text = String::new();
+        stream.read_to_string(&mut text).unwrap();
+        assert_eq!("second file", text);
+
+        assert_eq!(7, stream.seek(SeekFrom::End(-4)).unwrap());
+        text.clear();
+        stream.read_to_string(&mut text).unwrap();
+        assert_eq!("file", text);
+        assert_eq!(1, stream.seek(SeekFrom::Start(1)).unwrap());
+        assert_eq!(3, stream.seek(SeekFrom::Current(2)).unwrap());
+        let mut word = [0u8; 3];
+        stream.read_exact(&mut word).unwrap();
+        assert_eq!(b"ond", &word);
+        assert!(stream.seek(SeekFrom::Current(-7)).is_err());
+        assert_eq!(20, stream.seek(SeekFrom::Start(20)).unwrap());
+        assert_eq!(0, stream.read(&mut word).unwrap());
+
+        drop(stream);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
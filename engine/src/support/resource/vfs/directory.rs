@@ -0,0 +1,97 @@
+use crate::support::resource::vfs::Mount;
+use crate::support::resource::ResourceStream;
+use std::fs::File;
+use std::io::{BufReader, Error};
+use std::io::ErrorKind::{InvalidInput, NotFound};
+use std::path::{Component, Path, PathBuf};
+
+///
+/// a loose directory; logical paths are relative to it.
+///
+pub struct DirectoryMount {
+    root: PathBuf,
+}
+
+impl DirectoryMount {
+    pub fn new<P: AsRef<Path>>(root: P) -> std::io::Result<DirectoryMount> {
+        let root = root.as_ref().to_path_buf();
+        if !root.is_dir() {
+            return Err(Error::new(NotFound, format!("{} isn't a directory", root.display())));
+        }
+        Ok(DirectoryMount { root })
+    }
+
+    ///
+    /// the file under the root; each segment must be a plain name, so nothing can replace the root or climb out of it.
+    ///
+    fn resolve(&self, path: &str) -> std::io::Result<PathBuf> {
+        let mut full = self.root.clone();
+        for segment in path.split('/') {
+            let mut components = Path::new(segment).components();
+            match (components.next(), components.next()) {
+                (Some(Component::Normal(name)), None) => full.push(name),
+                _ => return Err(Error::new(InvalidInput, format!("path [{}] leaves {}", path, self.root.display()))),
+            }
+        }
+        Ok(full)
+    }
+}
+
+impl Mount for DirectoryMount {
+    fn describe(&self) -> String {
+        format!("directory {}", self.root.display())
+    }
+
+    fn contains(&self, path: &str) -> bool {
+        self.resolve(path).is_ok_and(|full| full.is_file())
+    }
+
+    fn read(&self, path: &str) -> std::io::Result<Vec<u8>> {
+        std::fs::read(self.resolve(path)?)
+    }
+
+    fn open(&self, path: &str) -> std::io::Result<Box<dyn ResourceStream + '_>> {
+        Ok(Box::new(BufReader::new(File::open(self.resolve(path)?)?)))
+    }
+
+    fn files(&self) -> Vec<String> {
+        let mut files = vec![];
+        let mut pending = vec![(self.root.clone(), String::new())];
+        while let Some((dir, prefix)) = pending.pop() {
+            let Ok(entries) = std::fs::read_dir(&dir) else { continue };
+            for entry in entries.flatten() {
+                let name = format!("{}{}", prefix, entry.file_name().to_string_lossy());
+                match entry.file_type() {
+                    Ok(kind) if kind.is_dir() => pending.push((entry.path(), format!("{}/", name))),
+                    Ok(kind) if kind.is_file() => files.push(name),
+                    _ => {}
+                }
+            }
+        }
+        files
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::support::resource::vfs::directory::DirectoryMount;
+    use crate::support::resource::vfs::Mount;
+
+    #[test]
+    fn test_directory() {
+        let root = std::env::temp_dir().join(format!("painsaw-vfs-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("textures")).unwrap();
+        std::fs::write(root.join("textures").join("wall.tga"), b"wall").unwrap();
+
+        let mount = DirectoryMount::new(&root).unwrap();
+        assert!(mount.contains("textures/wall.tga"));
+        assert!(!mount.contains("textures"));
+        assert_eq!(b"wall".to_vec(), mount.read("textures/wall.tga").unwrap());
+        assert_eq!(vec!["textures/wall.tga"], mount.files());
+        assert!(mount.read("textures/../../wall.tga").is_err());
+        assert!(!mount.contains("/etc/passwd"));
+
+        std::fs::remove_dir_all(&root).unwrap();
+        assert!(DirectoryMount::new(&root).is_err());
+    }
+}
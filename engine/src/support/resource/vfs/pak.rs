@@ -0,0 +1,186 @@
+//!
+//! `.pak` archives: a flat list of files, each stored as-is or zlib-compressed.
+//!
+//! all numbers are little-endian:
+//!
+//! * header: magic `PSPK`, u32 version (1), u32 directory offset, u32 entry count
+//! * file data, in any order
+//! * directory, one entry per file: u16 path length, the logical path (utf-8), u8 method
+//!   (0 stored, 1 zlib), u32 data offset, u32 stored length, u32 original length
+//!
+
+use crate::support::binary::reader::BinaryReader;
+use crate::support::binary::endianness::Endianness::LittleEndian;
+use crate::support::resource::vfs::{normalize_path, ArchiveSource, Mount};
+use crate::support::resource::ResourceStream;
+use crate::support::zlib;
+use std::collections::HashMap;
+use std::io::{Cursor, Error};
+use std::io::ErrorKind::{InvalidData, NotFound, Unsupported};
+use std::path::Path;
+
+pub(crate) static PAK_MAGIC: &[u8; 4] = b"PSPK";
+static PAK_VERSION: u32 = 1;
+static HEADER_SIZE: usize = 16;
+static MIN_ENTRY_SIZE: usize = 15;                                                              // an empty path; real ones are longer
+
+const METHOD_STORED: u8 = 0;
+const METHOD_ZLIB: u8 = 1;
+
+struct PakEntry {
+    method: u8,
+    offset: u32,
+    stored: u32,
+    size: u32,
+}
+
+pub struct PakArchive {
+    source: ArchiveSource,
+    entries: HashMap<String, PakEntry>,
+}
+
+impl PakArchive {
+    ///
+    /// read the directory now; file data is read from disk when it's asked for.
+    ///
+    pub fn open<P: AsRef<Path>>(path: P) -> std::io::Result<PakArchive> {
+        PakArchive::new(ArchiveSource::File(path.as_ref().to_path_buf()))
+    }
+
+    pub fn from_bytes(bytes: Vec<u8>) -> std::io::Result<PakArchive> {
+        PakArchive::new(ArchiveSource::Memory(bytes))
+    }
+
+    fn new(source: ArchiveSource) -> std::io::Result<PakArchive> {
+        /* header */
+        let header = source.read_range(0, HEADER_SIZE)?;
+        let mut reader = BinaryReader::new(Cursor::new(&header), LittleEndian)?.with_label("PAK");
+        if &reader.read_array::<4>()? != PAK_MAGIC {
+            return Err(Error::new(InvalidData, "not a pak archive"));
+        }
+        let version = reader.read_u32()?;
+        if version != PAK_VERSION {
+            return Err(Error::new(Unsupported, format!("pak version {} not supported", version)));
+        }
+        let directory_offset = reader.read_u32()? as u64;
+        let count = reader.read_u32()? as usize;
+
+        /* directory */
+        let directory = source.read_range(directory_offset, (source.len()?.saturating_sub(directory_offset)) as usize)?;
+        let mut reader = BinaryReader::new(Cursor::new(&directory), LittleEndian)?.with_label("PAK");
+        let mut entries = HashMap::with_capacity(count.min(directory.len() / MIN_ENTRY_SIZE));       // the count is untrusted
+        for _ in 0..count {
+            let length = reader.read_u16()? as usize;
+            let name = reader.read_bytes(length)?;
+            let name = normalize_path(std::str::from_utf8(&name).map_err(|e| Error::new(InvalidData, e))?)?;
+            entries.insert(name, PakEntry {
+                method: reader.read_u8()?,
+                offset: reader.read_u32()?,
+                stored: reader.read_u32()?,
+                size: reader.read_u32()?,
+            });
+        }
+        Ok(PakArchive { source, entries })
+    }
+}
+
+impl Mount for PakArchive {
+    fn describe(&self) -> String {
+        format!("pak {} ({} files)", self.source.describe(), self.entries.len())
+    }
+
+    fn contains(&self, path: &str) -> bool {
+        self.entries.contains_key(path)
+    }
+
+    fn read(&self, path: &str) -> std::io::Result<Vec<u8>> {
+        let entry = self.entries.get(path).ok_or_else(|| Error::new(NotFound, format!("[{}] isn't in the pak", path)))?;
+        let stored = self.source.read_range(entry.offset as u64, entry.stored as usize)?;
+        let data = match entry.method {
+            METHOD_STORED => stored,
+            METHOD_ZLIB => zlib::decompress_limited(&stored, entry.size as usize)?,
+            m => return Err(Error::new(Unsupported, format!("pak method {} not supported", m))),
+        };
+        if data.len() != entry.size as usize {
+            return Err(Error::new(InvalidData, format!("[{}] is {} bytes, expected {}", path, data.len(), entry.size)));
+        }
+        Ok(data)
+    }
+
+    fn open(&self, path: &str) -> std::io::Result<Box<dyn ResourceStream + '_>> {
+        let entry = self.entries.get(path).ok_or_else(|| Error::new(NotFound, format!("[{}] isn't in the pak", path)))?;
+        match entry.method {
+            METHOD_STORED if entry.stored == entry.size => self.source.open_range(entry.offset as u64, entry.stored as u64),
+            _ => Ok(Box::new(Cursor::new(self.read(path)?))),
+        }
+    }
+
+    fn files(&self) -> Vec<String> {
+        self.entries.keys().cloned().collect()
+    }
+}
+
+///
+/// build a pak from (logical path, contents) pairs, optionally zlib-compressing each file.
+///
+pub fn write_pak(files: &[(&str, &[u8])], compress: bool) -> std::io::Result<Vec<u8>> {
+    let mut out = vec![0u8; HEADER_SIZE];
+    let mut directory = vec![];
+    for (path, contents) in files {
+        let path = normalize_path(path)?;
+        let (method, stored) = if compress { (METHOD_ZLIB, zlib::compress(contents)) } else { (METHOD_STORED, contents.to_vec()) };
+        directory.extend_from_slice(&(path.len() as u16).to_le_bytes());
+        directory.extend_from_slice(path.as_bytes());
+        directory.push(method);
+        directory.extend_from_slice(&(out.len() as u32).to_le_bytes());
+        directory.extend_from_slice(&(stored.len() as u32).to_le_bytes());
+        directory.extend_from_slice(&(contents.len() as u32).to_le_bytes());
+        out.extend_from_slice(&stored);
+    }
+
+    let directory_offset = out.len() as u32;
+    out[0..4].copy_from_slice(PAK_MAGIC);
+    out[4..8].copy_from_slice(&PAK_VERSION.to_le_bytes());
+    out[8..12].copy_from_slice(&directory_offset.to_le_bytes());
+    out[12..16].copy_from_slice(&(files.len() as u32).to_le_bytes());
+    out.extend_from_slice(&directory);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::support::resource::vfs::pak::{write_pak, PakArchive};
+    use crate::support::resource::vfs::Mount;
+
+    #[test]
+    fn test_round_trip() {
+        let wall = vec![7u8; 1000];
+        let bytes = write_pak(&[("textures/wall.tga", &wall), ("./readme.txt", b"hello")], true).unwrap();
+        assert!(bytes.len() < 1000);
+
+        let pak = PakArchive::from_bytes(bytes).unwrap();
+        assert_eq!(wall, pak.read("textures/wall.tga").unwrap());
+        assert_eq!(b"hello".to_vec(), pak.read("readme.txt").unwrap());
+        assert!(pak.read("missing.txt").is_err());
+    }
+
+    #[test]
+    fn test_corrupt() {
+        assert!(PakArchive::from_bytes(b"PK\x03\x04".to_vec()).is_err());
+        let mut bytes = write_pak(&[("a.txt", b"abc")], false).unwrap();
+        bytes.truncate(bytes.len() - 2);
+        assert!(PakArchive::from_bytes(bytes).is_err());
+
+        /* a huge entry count fails on the directory, rather than reserving for it */
+        let mut bytes = write_pak(&[("a.txt", b"abc")], false).unwrap();
+        bytes[12..16].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(PakArchive::from_bytes(bytes).is_err());
+
+        /* a compressed entry that expands past its declared size stops there */
+        let mut bytes = write_pak(&[("bomb.bin", &[0; 100_000])], true).unwrap();
+        let len = bytes.len();
+        bytes[len - 4..].copy_from_slice(&100u32.to_le_bytes());
+        let archive = PakArchive::from_bytes(bytes).unwrap();
+        assert!(archive.read("bomb.bin").unwrap_err().to_string().contains("more than 100 bytes"));
+    }
+}
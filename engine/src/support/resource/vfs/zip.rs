@@ -0,0 +1,248 @@
+//!
+//! zip archives, with stored or deflated entries; see https://pkware.cachefly.net/webdocs/casestudies/APPNOTE.TXT
+//!
+//! zip64, encryption and multi-disk archives aren't supported.
+//!
+
+use crate::support::binary::reader::BinaryReader;
+use crate::support::binary::endianness::Endianness::LittleEndian;
+use crate::support::resource::vfs::{normalize_path, ArchiveSource, Mount};
+use crate::support::resource::ResourceStream;
+use crate::support::zlib;
+use std::collections::HashMap;
+use std::io::{Cursor, Error};
+use std::io::ErrorKind::{InvalidData, NotFound, Unsupported};
+use std::path::Path;
+
+static END_SIGNATURE: u32 = 0x0605_4b50;
+static CENTRAL_SIGNATURE: u32 = 0x0201_4b50;
+static LOCAL_SIGNATURE: u32 = 0x0403_4b50;
+static END_SIZE: usize = 22;
+static CENTRAL_SIZE: usize = 46;                                                                // a central directory record, without its name
+static MAX_COMMENT: usize = 65535;
+
+const METHOD_STORED: u16 = 0;
+const METHOD_DEFLATE: u16 = 8;
+
+struct ZipEntry {
+    method: u16,
+    flags: u16,
+    crc: u32,
+    compressed: u32,
+    size: u32,
+    local_offset: u32,
+}
+
+pub struct ZipArchive {
+    source: ArchiveSource,
+    entries: HashMap<String, ZipEntry>,
+}
+
+impl ZipArchive {
+    ///
+    /// read the central directory now; file data is read from disk when it's asked for.
+    ///
+    pub fn open<P: AsRef<Path>>(path: P) -> std::io::Result<ZipArchive> {
+        ZipArchive::new(ArchiveSource::File(path.as_ref().to_path_buf()))
+    }
+
+    pub fn from_bytes(bytes: Vec<u8>) -> std::io::Result<ZipArchive> {
+        ZipArchive::new(ArchiveSource::Memory(bytes))
+    }
+
+    fn new(source: ArchiveSource) -> std::io::Result<ZipArchive> {
+        /* end of central directory: the last record with its signature, before a comment of up to 64k */
+        let length = source.len()?;
+        if length < END_SIZE as u64 {
+            return Err(Error::new(InvalidData, "not a zip archive: too short"));
+        }
+        let tail_length = (length as usize).min(END_SIZE + MAX_COMMENT);
+        let tail = source.read_range(length - tail_length as u64, tail_length)?;
+        let end = (0..=tail.len() - END_SIZE).rev()
+            .find(|idx| tail[*idx..*idx + 4] == END_SIGNATURE.to_le_bytes())
+            .ok_or_else(|| Error::new(InvalidData, "not a zip archive"))?;
+        let mut reader = BinaryReader::new(Cursor::new(&tail[end..]), LittleEndian)?.with_label("ZIP");
+        reader.skip(4)?;                                                                        // signature
+        let disk = reader.read_u16()?;
+        reader.skip(4)?;                                                                        // directory disk, entries on this disk
+        let count = reader.read_u16()? as usize;
+        let directory_size = reader.read_u32()?;
+        let directory_offset = reader.read_u32()?;
+        if directory_offset == u32::MAX || disk != 0 {
+            return Err(Error::new(Unsupported, "zip64 and multi-disk archives aren't supported"));
+        }
+
+        /* central directory */
+        let directory = source.read_range(directory_offset as u64, directory_size as usize)?;
+        let mut reader = BinaryReader::new(Cursor::new(&directory), LittleEndian)?.with_label("ZIP");
+        let mut entries = HashMap::with_capacity(count.min(directory.len() / CENTRAL_SIZE));       // the count is untrusted
+        for _ in 0..count {
+            if reader.read_u32()? != CENTRAL_SIGNATURE {
+                return Err(Error::new(InvalidData, "bad zip central directory record"));
+            }
+            reader.skip(4)?;                                                                    // versions
+            let flags = reader.read_u16()?;
+            let method = reader.read_u16()?;
+            reader.skip(4)?;                                                                    // time, date
+            let crc = reader.read_u32()?;
+            let compressed = reader.read_u32()?;
+            let size = reader.read_u32()?;
+            let name_length = reader.read_u16()? as usize;
+            let extra_length = reader.read_u16()? as u64;
+            let comment_length = reader.read_u16()? as u64;
+            reader.skip(8)?;                                                                    // disk, attributes
+            let local_offset = reader.read_u32()?;
+            let name = reader.read_bytes(name_length)?;
+            reader.skip(extra_length + comment_length)?;
+
+            let name = String::from_utf8_lossy(&name);
+            if !name.ends_with('/') {
+                entries.insert(normalize_path(&name)?, ZipEntry { method, flags, crc, compressed, size, local_offset });
+            }
+        }
+        Ok(ZipArchive { source, entries })
+    }
+}
+
+impl Mount for ZipArchive {
+    fn describe(&self) -> String {
+        format!("zip {} ({} files)", self.source.describe(), self.entries.len())
+    }
+
+    fn contains(&self, path: &str) -> bool {
+        self.entries.contains_key(path)
+    }
+
+    fn read(&self, path: &str) -> std::io::Result<Vec<u8>> {
+        let (entry, start) = self.locate(path)?;
+        let stored = self.source.read_range(start, entry.compressed as usize)?;
+
+        let data = match entry.method {
+            METHOD_STORED => stored,
+            METHOD_DEFLATE => zlib::inflate_limited(&stored, entry.size as usize)?,
+            m => return Err(Error::new(Unsupported, format!("zip method {} for [{}] not supported", m, path))),
+        };
+        if data.len() != entry.size as usize || zlib::crc32(&data) != entry.crc {
+            return Err(Error::new(InvalidData, format!("[{}] failed its size or crc check", path)));
+        }
+        Ok(data)
+    }
+
+    ///
+    /// a stored file is read straight from the archive; the crc can't be checked that way, so
+    /// a corrupt one is only caught by [ZipArchive::read].
+    ///
+    fn open(&self, path: &str) -> std::io::Result<Box<dyn ResourceStream + '_>> {
+        let (entry, start) = self.locate(path)?;
+        match entry.method {
+            METHOD_STORED if entry.compressed == entry.size => self.source.open_range(start, entry.compressed as u64),
+            _ => Ok(Box::new(Cursor::new(self.read(path)?))),
+        }
+    }
+
+    fn files(&self) -> Vec<String> {
+        self.entries.keys().cloned().collect()
+    }
+}
+
+impl ZipArchive {
+    ///
+    /// the entry, and where its data starts in the archive.
+    ///
+    fn locate(&self, path: &str) -> std::io::Result<(&ZipEntry, u64)> {
+        let entry = self.entries.get(path).ok_or_else(|| Error::new(NotFound, format!("[{}] isn't in the zip", path)))?;
+        if entry.flags & 1 != 0 {
+            return Err(Error::new(Unsupported, format!("[{}] is encrypted", path)));
+        }
+
+        /* the local header's name and extra field can differ from the central directory's */
+        let local = self.source.read_range(entry.local_offset as u64, 30)?;
+        let mut reader = BinaryReader::new(Cursor::new(&local), LittleEndian)?.with_label("ZIP");
+        if reader.read_u32()? != LOCAL_SIGNATURE {
+            return Err(Error::new(InvalidData, format!("bad zip local header for [{}]", path)));
+        }
+        reader.seek(26)?;
+        let start = entry.local_offset as u64 + 30 + reader.read_u16()? as u64 + reader.read_u16()? as u64;
+        Ok((entry, start))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::support::resource::vfs::zip::ZipArchive;
+    use crate::support::resource::vfs::Mount;
+    use crate::support::zlib;
+    use std::io::Read;
+
+    /* a zip of (name, contents, deflate?) entries, with a comment at the end */
+    fn zip(files: &[(&str, &[u8], bool)]) -> Vec<u8> {
+        let (mut out, mut central) = (vec![], vec![]);
+        for (name, contents, deflate) in files {
+            let stored = if *deflate {
+                let stream = zlib::compress(contents);
+                stream[2..stream.len() - 4].to_vec()                                            // raw deflate: no zlib header or adler
+            } else {
+                contents.to_vec()
+            };
+            let method: u16 = if *deflate { 8 } else { 0 };
+            let mut fields = vec![];
+            fields.extend_from_slice(&method.to_le_bytes());
+            fields.extend_from_slice(&[0, 0, 0, 0]);                                                // time, date
+            fields.extend_from_slice(&zlib::crc32(contents).to_le_bytes());
+            fields.extend_from_slice(&(stored.len() as u32).to_le_bytes());
+            fields.extend_from_slice(&(contents.len() as u32).to_le_bytes());
+            fields.extend_from_slice(&(name.len() as u16).to_le_bytes());
+
+            central.extend_from_slice(&[0x50, 0x4b, 0x01, 0x02, 20, 0, 20, 0, 0, 0]);
+            central.extend_from_slice(&fields);
+            central.extend_from_slice(&[0; 12]);                                                    // extra, comment, disk, attributes
+            central.extend_from_slice(&(out.len() as u32).to_le_bytes());
+            central.extend_from_slice(name.as_bytes());
+
+            out.extend_from_slice(&[0x50, 0x4b, 0x03, 0x04, 20, 0, 0, 0]);
+            out.extend_from_slice(&fields);
+            out.extend_from_slice(&[0, 0]);                                                         // extra
+            out.extend_from_slice(name.as_bytes());
+            out.extend_from_slice(&stored);
+        }
+        let offset = out.len() as u32;
+        out.extend_from_slice(&central);
+        out.extend_from_slice(&[0x50, 0x4b, 0x05, 0x06, 0, 0, 0, 0]);
+        out.extend_from_slice(&(files.len() as u16).to_le_bytes());
+        out.extend_from_slice(&(files.len() as u16).to_le_bytes());
+        out.extend_from_slice(&(central.len() as u32).to_le_bytes());
+        out.extend_from_slice(&offset.to_le_bytes());
+        out.extend_from_slice(&3u16.to_le_bytes());
+        out.extend_from_slice(b"PK!");
+        out
+    }
+
+    #[test]
+    fn test_stored_and_deflated() {
+        let wall = b"wall wall wall wall wall wall wall wall".to_vec();
+        let archive = ZipArchive::from_bytes(zip(&[("textures/", b"", false), ("textures/wall.tga", &wall, true), ("readme.txt", b"hello", false)])).unwrap();
+        let mut files = archive.files();
+        files.sort();
+        assert_eq!(vec!["readme.txt", "textures/wall.tga"], files);
+        assert_eq!(wall, archive.read("textures/wall.tga").unwrap());
+        assert_eq!(b"hello".to_vec(), archive.read("readme.txt").unwrap());
+
+        let mut text = String::new();
+        archive.open("readme.txt").unwrap().read_to_string(&mut text).unwrap();
+        assert_eq!("hello", text);
+        let mut tga = vec![];
+        archive.open("textures/wall.tga").unwrap().read_to_end(&mut tga).unwrap();
+        assert_eq!(wall, tga);
+    }
+
+    #[test]
+    fn test_corrupt() {
+        let mut bytes = zip(&[("readme.txt", b"hello", false)]);
+        bytes[30 + 10] = b'j';                                                                      // "hello" -> "jello"
+        let archive = ZipArchive::from_bytes(bytes).unwrap();
+        assert_eq!(std::io::ErrorKind::InvalidData, archive.read("readme.txt").unwrap_err().kind());
+        assert!(ZipArchive::from_bytes(b"not a zip".to_vec()).is_err());
+        assert!(ZipArchive::from_bytes(b"PK\x05\x06".to_vec()).is_err());
+        assert!(ZipArchive::from_bytes(b"PK".to_vec()).is_err());
+    }
+}
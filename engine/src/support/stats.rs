@@ -2,10 +2,12 @@ use crate::geometry::primitive::v2d::Vertex2D;
 use crate::geometry::primitive::v3d::Vertex3D;
 use crate::geometry::rect::Rectangle2D;
 use crate::graphics::color::Color;
-use crate::graphics::storage::m2d::{Model2D, Model2DBuilder};
-use crate::graphics::texture::t2d::Texture2DBuilder;
-use crate::support::image::RawImage;
-use crate::support::text::{text_2d_image, TextConfig, Typeface};
+use crate::graphics::storage::g2d::Graph2D;
+use crate::graphics::storage::label::TextLabel;
+use crate::graphics::storage::m2d::Model2DBuilder;
+use crate::support::text::fallback::Replacement;
+use crate::support::text::layout::LayoutOptions;
+use crate::support::text::{TextConfig, Typeface};
 
 pub mod coords;
 pub mod fps;
@@ -15,93 +17,67 @@ pub(crate) static TC: TextConfig = TextConfig {
     foreground: Color::RED,
     background: Color::TRANSPARENT,
     typeface: Typeface::Generic,
+    layout: LayoutOptions::DEFAULT,
+    replacement: Replacement::Tofu,
 };
 
 pub(crate) static HEIGHT: f32 = 13.7;
 pub(crate) static X_POS: f32 = 10.0;
 
-pub(crate) fn create_vertex2d_model(x: f32, y: f32, config: TextConfig, label: &str, pt: &Vertex2D) -> Model2D {
-    Model2DBuilder::new()
-        .with_texture(Texture2DBuilder::new()
-            .with_x(x)
-            .with_y(y)
-            .with_image(create_vertex2d_text(config, label, pt).unwrap())
-            .build())
-        .build()
+///
+/// show a line of text, attaching its label the first time; after that it's only redrawn when the text changes.
+///
+/// a model under the name that has no label (say, one attached elsewhere) gets one.
+///
+pub(crate) fn show_label(g2d: &mut Graph2D, name: &str, x: f32, y: f32, text: String) {
+    g2d.attach_or_update(
+        name,
+        || Model2DBuilder::new().with_label(TextLabel::new(&text, TC.clone(), x, y)).build(),
+        |m| match m.labels.first_mut() {
+            Some(label) => { label.set_text(&text); }
+            None => m.attach_label(TextLabel::new(&text, TC.clone(), x, y)),
+        },
+    );
 }
 
-pub(crate) fn create_vertex2d_text(config: TextConfig, label: &str, pt: &Vertex2D) -> Option<RawImage> {
-    Option::from(text_2d_image(config.clone(), || {
-        String::from(format!(
-            "{}({:+08.2},{:+08.2})",
-            label,
-            pt.x,
-            pt.y,
-        ))
-    }))
+pub(crate) fn vertex2d_text(label: &str, pt: &Vertex2D) -> String {
+    format!("{}({:+08.2},{:+08.2})", label, pt.x, pt.y)
 }
 
-pub(crate) fn create_vertex3d_model(x: f32, y: f32, label: &str, config: TextConfig, target: &Vertex3D) -> Model2D {
-    Model2DBuilder::new()
-        .with_texture(Texture2DBuilder::new()
-            .with_x(x)
-            .with_y(y)
-            .with_image(create_vertex3d_text(config, label, &target).unwrap())
-            .build())
-        .build()
+pub(crate) fn vertex3d_text(label: &str, target: &Vertex3D) -> String {
+    format!("{}({:+08.2},{:+08.2},{:+08.2})", label, target.x, target.y, target.z)
 }
 
-pub(crate) fn create_vertex3d_text(config: TextConfig, label: &str, target: &Vertex3D) -> Option<RawImage> {
-    Option::from(text_2d_image(config.clone(), || {
-        String::from(format!(
-            "{}({:+08.2},{:+08.2},{:+08.2})",
-            label,
-            target.x,
-            target.y,
-            target.z,
-        ))
-    }))
+pub(crate) fn f32_text(label: &str, value: f32) -> String {
+    format!("{}{:+08.2}", label, value)
 }
 
-pub(crate) fn create_f32_model(x: f32, y: f32, config: TextConfig, label: &str, value: f32) -> Model2D {
-    Model2DBuilder::new()
-        .with_texture(Texture2DBuilder::new()
-            .with_x(x)
-            .with_y(y)
-            .with_image(create_f32_text(config, label, value).unwrap())
-            .build())
-        .build()
+pub(crate) fn rect2d_text(label: &str, rect: &Rectangle2D) -> String {
+    format!(
+        "{}: ({:+08.2},{:+08.2}),({:+08.2},{:+08.2})",
+        label,
+        rect.top_left.x,
+        rect.top_left.y,
+        rect.bottom_right.x,
+        rect.bottom_right.y,
+    )
 }
 
-pub(crate) fn create_f32_text(config: TextConfig, label: &str, value: f32) -> Option<RawImage> {
-    Option::from(text_2d_image(config.clone(), || {
-        String::from(format!(
-            "{}{:+08.2}",
-            label,
-            value
-        ))
-    }))
-}
+#[cfg(test)]
+mod tests {
+    use crate::graphics::storage::g2d::Graph2D;
+    use crate::graphics::storage::m2d::Model2DBuilder;
+    use crate::support::stats::show_label;
 
-pub(crate) fn create_rect2d_model(x: f32, y: f32, config: TextConfig, label: &str, rect: &Rectangle2D) -> Model2D {
-    Model2DBuilder::new()
-        .with_texture(Texture2DBuilder::new()
-            .with_x(x)
-            .with_y(y)
-            .with_image(create_rect2d_text(config, label, &rect).unwrap())
-            .build())
-        .build()
-}
+    #[test]
+    fn test_show_label_on_model_without_labels() {
+        let mut g2d = Graph2D::new();
+        g2d.attach("fps", Model2DBuilder::new().build());
+        show_label(&mut g2d, "fps", 0.0, 0.0, String::from("fps: 60"));
+        show_label(&mut g2d, "fps", 0.0, 0.0, String::from("fps: 59"));
 
-pub(crate) fn create_rect2d_text(config: TextConfig, label: &str, rect: &Rectangle2D) -> Option<RawImage> {
-    Option::from(text_2d_image(config.clone(), || {
-        String::from(format!(
-            "{}: ({:+08.2},{:+08.2}),({:+08.2},{:+08.2})",
-            label,
-            rect.top_left.x,
-            rect.top_left.y,
-            rect.bottom_right.x,
-            rect.bottom_right.y,
-        ))
-    }))
+        let (_, model) = g2d.iter().find(|(name, _)| name.as_str() == "fps").unwrap();
+        assert_eq!(1, model.labels.len());
+        assert_eq!("fps: 59", model.labels[0].text());
+    }
 }
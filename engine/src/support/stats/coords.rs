@@ -1,10 +1,7 @@
 use crate::config::EngineConfig;
 use crate::graphics::camera::Camera;
 use crate::graphics::storage::g2d::Graph2D;
-use crate::support::stats::{
-    create_f32_model, create_f32_text, create_vertex3d_model, create_vertex3d_text, HEIGHT, TC,
-    X_POS,
-};
+use crate::support::stats::{f32_text, show_label, vertex3d_text, HEIGHT, X_POS};
 
 static CAM_POS: &str = "cam pos: ";
 static FORWARD: &str = "forward: ";
@@ -37,41 +34,11 @@ pub(crate) fn show_cam_coords(g2d: &mut Graph2D, config: &EngineConfig, camera:
     let y_roll = y + HEIGHT * 6.0;
 
     /* update models */
-    g2d.attach_or_update(
-        "99-2d-text-cam-pos",
-        || create_vertex3d_model(X_POS, y_cam, CAM_POS, TC.clone(), &position),
-        |m| m.textures[0].replacement = create_vertex3d_text(TC.clone(), CAM_POS, &position),
-    );
-    g2d.attach_or_update(
-        "99-2d-text-forward",
-        || create_vertex3d_model(X_POS, y_forward, FORWARD, TC.clone(), &forward),
-        |m| m.textures[0].replacement = create_vertex3d_text(TC.clone(), FORWARD, &forward),
-    );
-    g2d.attach_or_update(
-        "99-2d-text-right",
-        || create_vertex3d_model(X_POS, y_right, RIGHT, TC.clone(), &right),
-        |m| m.textures[0].replacement = create_vertex3d_text(TC.clone(), RIGHT, &right),
-    );
-    g2d.attach_or_update(
-        "99-2d-text-up",
-        || create_vertex3d_model(X_POS, y_up, UP, TC.clone(), &up),
-        |m| m.textures[0].replacement = create_vertex3d_text(TC.clone(), UP, &up),
-    );
-    g2d.attach_or_update(
-        "99-2d-pitch",
-        || create_f32_model(X_POS, y_pitch, TC.clone(), PITCH, camera.orientation.pitch),
-        |m| {
-            m.textures[0].replacement = create_f32_text(TC.clone(), PITCH, camera.orientation.pitch)
-        },
-    );
-    g2d.attach_or_update(
-        "99-2d-yaw",
-        || create_f32_model(X_POS, y_yaw, TC.clone(), YAW, camera.orientation.yaw),
-        |m| m.textures[0].replacement = create_f32_text(TC.clone(), YAW, camera.orientation.yaw),
-    );
-    g2d.attach_or_update(
-        "99-2d-roll",
-        || create_f32_model(X_POS, y_roll, TC.clone(), ROLL, camera.orientation.roll),
-        |m| m.textures[0].replacement = create_f32_text(TC.clone(), ROLL, camera.orientation.roll),
-    );
+    show_label(g2d, "99-2d-text-cam-pos", X_POS, y_cam, vertex3d_text(CAM_POS, &position));
+    show_label(g2d, "99-2d-text-forward", X_POS, y_forward, vertex3d_text(FORWARD, &forward));
+    show_label(g2d, "99-2d-text-right", X_POS, y_right, vertex3d_text(RIGHT, &right));
+    show_label(g2d, "99-2d-text-up", X_POS, y_up, vertex3d_text(UP, &up));
+    show_label(g2d, "99-2d-pitch", X_POS, y_pitch, f32_text(PITCH, camera.orientation.pitch));
+    show_label(g2d, "99-2d-yaw", X_POS, y_yaw, f32_text(YAW, camera.orientation.yaw));
+    show_label(g2d, "99-2d-roll", X_POS, y_roll, f32_text(ROLL, camera.orientation.roll));
 }
@@ -1,10 +1,6 @@
 use crate::config::EngineConfig;
 use crate::graphics::storage::g2d::Graph2D;
-use crate::graphics::storage::m2d::{Model2D, Model2DBuilder};
-use crate::graphics::texture::t2d::Texture2DBuilder;
-use crate::support::image::RawImage;
-use crate::support::stats::{TC, X_POS};
-use crate::support::text::{text_2d_image, TextConfig};
+use crate::support::stats::{show_label, X_POS};
 use crate::support::timing::EngineTiming;
 
 pub(crate) fn show_fps(g2d: &mut Graph2D, timing: &EngineTiming, config: &EngineConfig) {
@@ -18,27 +14,5 @@ pub(crate) fn show_fps(g2d: &mut Graph2D, timing: &EngineTiming, config: &Engine
     let avg = timing.compute_avg_fps();
 
     /* add or update models */
-    g2d.attach_or_update(
-        "99-builtin-fps",
-        || create_model(TC.clone(), fps, avg),
-        |m| m.textures[0].replacement = Option::from(create_text(TC.clone(), fps, avg)),
-    );
-}
-
-fn create_text(config: TextConfig, fps: u32, avg: u32) -> RawImage {
-    text_2d_image(config.clone(), || {
-        String::from(format!("FPS:{:4} ({:4} avg)", fps, avg))
-    })
-}
-
-fn create_model(config: TextConfig, fps: u32, avg: u32) -> Model2D {
-    Model2DBuilder::new()
-        .with_texture(
-            Texture2DBuilder::new()
-                .with_x(X_POS)
-                .with_y(5.0)
-                .with_image(create_text(config, fps, avg))
-                .build(),
-        )
-        .build()
+    show_label(g2d, "99-builtin-fps", X_POS, 5.0, format!("FPS:{:4} ({:4} avg)", fps, avg));
 }
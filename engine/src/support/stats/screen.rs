@@ -2,10 +2,7 @@ use crate::config::EngineConfig;
 use crate::geometry::primitive::v2d::Vertex2D;
 use crate::graphics::storage::g2d::Graph2D;
 use crate::input::screen::ScreenState;
-use crate::support::stats::{
-    create_rect2d_model, create_rect2d_text, create_vertex2d_model, create_vertex2d_text, HEIGHT, TC,
-    X_POS,
-};
+use crate::support::stats::{rect2d_text, show_label, vertex2d_text, HEIGHT, X_POS};
 
 static CLT_POS: &str = "clt pos: ";
 static WIN_POS: &str = "win pos: ";
@@ -46,29 +43,9 @@ pub(crate) fn show_screen_stats(
     let y_mp = y + HEIGHT + HEIGHT + HEIGHT + HEIGHT;
 
     /* update models */
-    g2d.attach_or_update(
-        "99-2d-screen-client-rect",
-        || create_rect2d_model(X_POS, y_cr, TC.clone(), CLT_POS, &client_rect),
-        |m| m.textures[0].replacement = create_rect2d_text(TC.clone(), CLT_POS, &client_rect),
-    );
-    g2d.attach_or_update(
-        "99-2d-screen-window-rect",
-        || create_rect2d_model(X_POS, y_wr, TC.clone(), WIN_POS, &window_rect),
-        |m| m.textures[0].replacement = create_rect2d_text(TC.clone(), WIN_POS, &window_rect),
-    );
-    g2d.attach_or_update(
-        "99-2d-screen-client-center",
-        || create_vertex2d_model(X_POS, y_cc, TC.clone(), CLT_CTR, &client_center),
-        |m| m.textures[0].replacement = create_vertex2d_text(TC.clone(), CLT_CTR, &client_center),
-    );
-    g2d.attach_or_update(
-        "99-2d-screen-window-center",
-        || create_vertex2d_model(X_POS, y_wc, TC.clone(), WIN_CTR, &window_center),
-        |m| m.textures[0].replacement = create_vertex2d_text(TC.clone(), WIN_CTR, &window_center),
-    );
-    g2d.attach_or_update(
-        "99-2d-screen-mouse-pos",
-        || create_vertex2d_model(X_POS, y_mp, TC.clone(), MOS_POS, &mouse_position),
-        |m| m.textures[0].replacement = create_vertex2d_text(TC.clone(), MOS_POS, &mouse_position),
-    );
+    show_label(g2d, "99-2d-screen-client-rect", X_POS, y_cr, rect2d_text(CLT_POS, client_rect));
+    show_label(g2d, "99-2d-screen-window-rect", X_POS, y_wr, rect2d_text(WIN_POS, window_rect));
+    show_label(g2d, "99-2d-screen-client-center", X_POS, y_cc, vertex2d_text(CLT_CTR, client_center));
+    show_label(g2d, "99-2d-screen-window-center", X_POS, y_wc, vertex2d_text(WIN_CTR, window_center));
+    show_label(g2d, "99-2d-screen-mouse-pos", X_POS, y_mp, vertex2d_text(MOS_POS, mouse_position));
 }
@@ -1,12 +1,24 @@
 use crate::graphics::color::Color;
 use crate::support::image::RawImage;
-use crate::support::text::generic::create_generic;
+use crate::support::text::bitmap::BitmapFont;
+use crate::support::text::fallback::{FallbackFont, Replacement};
+use crate::support::text::generic::generic_font;
+use crate::support::text::layout::{layout, measure, render, LayoutFont, LayoutOptions};
+use crate::support::text::truetype::TrueTypeFont;
+use std::sync::Arc;
 
+pub mod bitmap;
+pub mod fallback;
 pub mod generic;
+pub mod layout;
+pub mod sdf;
+pub mod truetype;
 
 #[derive(Clone)]
 pub enum Typeface {
     Generic,
+    Bitmap(Arc<BitmapFont>),
+    TrueType { font: Arc<TrueTypeFont>, size: f32 },                                           // size: pixels per em
 }
 
 #[derive(Clone)]
@@ -14,14 +26,56 @@ pub struct TextConfig {
     pub typeface: Typeface,
     pub foreground: Color,
     pub background: Color,
+    pub layout: LayoutOptions,
+    pub replacement: Replacement,                                                               // for characters the typeface doesn't have
 }
 
 pub fn text_2d_image<P>(config: TextConfig, provider: P) -> RawImage
 where
     P: Fn() -> String,
 {
-    match config.typeface {
-        Typeface::Generic => create_generic(&config, provider()),
+    config.typeface.with_font(|font| {
+        let font = FallbackFont::new(font, config.replacement);
+        render(&font, &layout(&font, &provider(), &config.layout), config.foreground, config.background)
+    })
+}
+
+///
+/// the size, in pixels, that text would be drawn at.
+///
+pub fn measure_text(config: &TextConfig, text: &str) -> (f32, f32) {
+    config.typeface.with_font(|font| measure(&FallbackFont::new(font, config.replacement), text, &config.layout))
+}
+
+impl Typeface {
+    ///
+    /// run something against this typeface's layout metrics and glyphs.
+    ///
+    pub fn with_font<R>(&self, f: impl FnOnce(&dyn LayoutFont) -> R) -> R {
+        match self {
+            Typeface::Generic => f(generic_font()),
+            Typeface::Bitmap(font) => f(font.as_ref()),
+            Typeface::TrueType { font, size } => f(&font.at_size(*size)),
+        }
+    }
+
+    pub fn has_glyph(&self, c: char) -> bool {
+        self.with_font(|font| font.has_glyph(c))
+    }
+
+    ///
+    /// each character in the text that this typeface can't draw, once, in order; whitespace is ignored.
+    ///
+    pub fn missing_chars(&self, text: &str) -> Vec<char> {
+        self.with_font(|font| {
+            let mut missing: Vec<char> = vec![];
+            for c in text.chars().filter(|c| !c.is_whitespace() && !font.has_glyph(*c)) {
+                if !missing.contains(&c) {
+                    missing.push(c);
+                }
+            }
+            missing
+        })
     }
 }
 
@@ -33,6 +87,8 @@ impl Default for TextConfig {
             typeface: Typeface::Generic,
             foreground: Color::WHITE,
             background: Color::TRANSPARENT,
+            layout: LayoutOptions::DEFAULT,
+            replacement: Replacement::default(),
         }
     }
 }
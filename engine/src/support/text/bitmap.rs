@@ -0,0 +1,192 @@
+//!
+//! bitmap (pixel) fonts, loaded from bdf or psf files.
+//!
+
+use crate::graphics::color::Color;
+use crate::support::image::RawImage;
+use crate::support::resource::Resource;
+use crate::support::text::bitmap::bdf::parse_bdf;
+use crate::support::text::bitmap::psf::{is_psf, parse_psf};
+use crate::support::text::layout::{layout, render, LayoutFont, LayoutOptions};
+use std::collections::HashMap;
+use std::io::Error;
+use std::io::ErrorKind::InvalidData;
+
+pub mod bdf;
+pub mod psf;
+
+///
+/// one glyph: a 1-bit bitmap, its placement, and how far it moves the pen.
+///
+/// offsets follow bdf: `x_offset` from the pen, `y_offset` from the baseline to the bottom row (y up).
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct BitmapGlyph {
+    pub width: u32,
+    pub height: u32,
+    pub x_offset: i32,
+    pub y_offset: i32,
+    pub advance: u32,
+    pub bits: Vec<bool>,                                                                        // row-major, top row first
+}
+
+impl BitmapGlyph {
+    pub fn bit(&self, x: u32, y: u32) -> bool {
+        x < self.width && y < self.height && self.bits[(y * self.width + x) as usize]
+    }
+}
+
+pub struct BitmapFont {
+    pub ascent: u32,
+    pub descent: u32,
+    pub default_char: Option<char>,
+    glyphs: HashMap<char, BitmapGlyph>,
+}
+
+impl BitmapFont {
+    pub fn new(ascent: u32, descent: u32, default_char: Option<char>, glyphs: HashMap<char, BitmapGlyph>) -> BitmapFont {
+        BitmapFont {
+            ascent,
+            descent,
+            default_char,
+            glyphs,
+        }
+    }
+
+    pub fn from_resource(resource: &dyn Resource) -> std::io::Result<BitmapFont> {
+        BitmapFont::from_bytes(&resource.data()?)
+    }
+
+    ///
+    /// load a psf (v1 or v2) or bdf font, detected from its contents.
+    ///
+    pub fn from_bytes(bytes: &[u8]) -> std::io::Result<BitmapFont> {
+        if is_psf(bytes) {
+            parse_psf(bytes)
+        } else if bytes.starts_with(b"STARTFONT") {
+            let text = std::str::from_utf8(bytes).map_err(|e| Error::new(InvalidData, e))?;
+            parse_bdf(text)
+        } else {
+            Err(Error::new(InvalidData, "not a bdf or psf font"))
+        }
+    }
+
+    pub fn line_height(&self) -> u32 {
+        self.ascent + self.descent
+    }
+
+    ///
+    /// the glyph for a character, or the font's default glyph.
+    ///
+    pub fn glyph(&self, c: char) -> Option<&BitmapGlyph> {
+        self.glyphs.get(&c).or_else(|| self.default_char.and_then(|d| self.glyphs.get(&d)))
+    }
+
+    pub fn has_glyph(&self, c: char) -> bool {
+        self.glyphs.contains_key(&c)
+    }
+
+    pub fn chars(&self) -> impl Iterator<Item = &char> {
+        self.glyphs.keys()
+    }
+
+    ///
+    /// one character cell, `advance` x `line_height`, in the given colours; none if there's no glyph.
+    ///
+    pub fn glyph_image(&self, c: char, foreground: Color, background: Color) -> Option<RawImage> {
+        let glyph = self.glyph(c)?;
+        let (width, height) = (glyph.advance, self.line_height());
+        let (fg, bg) = (foreground.to_u8(), background.to_u8());
+
+        /* the glyph's top-left corner within the cell */
+        let left = glyph.x_offset;
+        let top = self.ascent as i32 - glyph.y_offset - glyph.height as i32;
+
+        let mut data = Vec::with_capacity((width * height * 4) as usize);
+        for y in 0..height as i32 {
+            for x in 0..width as i32 {
+                let (gx, gy) = (x - left, y - top);
+                let on = gx >= 0 && gy >= 0 && glyph.bit(gx as u32, gy as u32);
+                let (r, g, b, a) = if on { fg } else { bg };
+                data.extend_from_slice(&[r, g, b, a]);
+            }
+        }
+        Some(RawImage::new(width, height, data))
+    }
+
+    ///
+    /// draw a single line of text; characters without a glyph (and no default) are skipped.
+    ///
+    pub fn render_text(&self, text: &str, foreground: Color, background: Color) -> RawImage {
+        render(self, &layout(self, text, &LayoutOptions::DEFAULT), foreground, background)
+    }
+}
+
+impl LayoutFont for BitmapFont {
+    fn ascent(&self) -> f32 {
+        self.ascent as f32
+    }
+
+    fn descent(&self) -> f32 {
+        self.descent as f32
+    }
+
+    fn has_glyph(&self, c: char) -> bool {
+        BitmapFont::has_glyph(self, c)
+    }
+
+    fn advance(&self, c: char) -> f32 {
+        self.glyph(c).map(|glyph| glyph.advance as f32).unwrap_or(0.0)
+    }
+
+    fn draw(&self, target: &mut RawImage, c: char, x: f32, y: f32, foreground: Color, background: Color) {
+        if let Some(cell) = self.glyph_image(c, foreground, background) {
+            target.paste(&cell, x.round() as i32, y.round() as i32);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graphics::color::Color;
+    use crate::support::text::bitmap::BitmapFont;
+
+    static FONT: &str = "STARTFONT 2.1
+FONTBOUNDINGBOX 4 4 0 -1
+STARTPROPERTIES 2
+FONT_ASCENT 3
+FONT_DESCENT 1
+ENDPROPERTIES
+CHARS 2
+STARTCHAR i
+ENCODING 105
+DWIDTH 2 0
+BBX 1 3 0 0
+BITMAP
+80
+00
+80
+ENDCHAR
+STARTCHAR g
+ENCODING 103
+DWIDTH 3 0
+BBX 2 2 1 -1
+BITMAP
+C0
+40
+ENDCHAR
+ENDFONT
+";
+
+    #[test]
+    fn test_render_text() {
+        let font = BitmapFont::from_bytes(FONT.as_bytes()).unwrap();
+        let image = font.render_text("ig?", Color::WHITE, Color::TRANSPARENT);
+        assert_eq!((5, 4), (image.width, image.height));                                           // '?' has no glyph, and no default
+
+        let on: Vec<String> = image.data.chunks_exact(4 * 5)
+            .map(|row| row.chunks_exact(4).map(|p| if p[3] == 255 { '#' } else { '.' }).collect())
+            .collect();
+        assert_eq!(vec!["#....", ".....", "#..##", "....#"], on);
+    }
+}
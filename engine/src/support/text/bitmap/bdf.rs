@@ -0,0 +1,128 @@
+//!
+//! glyph bitmap distribution format (bdf) 2.1, the x11 text format.
+//!
+
+use crate::support::text::bitmap::{BitmapFont, BitmapGlyph};
+use std::collections::HashMap;
+use std::io::Error;
+use std::io::ErrorKind::InvalidData;
+
+const MAX_GLYPH_SIZE: i64 = 1024;                                                               // pixels, either way
+
+///
+/// a glyph's settings, before its bitmap is read; defaults come from the font's global values.
+///
+struct PendingGlyph {
+    encoding: Option<char>,
+    advance: Option<u32>,
+    bbx: Option<(u32, u32, i32, i32)>,
+}
+
+pub fn parse_bdf(text: &str) -> std::io::Result<BitmapFont> {
+    let mut bounding_box: Option<(u32, u32, i32, i32)> = None;
+    let mut font_advance: Option<u32> = None;
+    let mut ascent: Option<u32> = None;
+    let mut descent: Option<u32> = None;
+    let mut default_char: Option<char> = None;
+    let mut glyphs = HashMap::new();
+    let mut pending: Option<PendingGlyph> = None;
+
+    let mut lines = text.lines().enumerate();
+    while let Some((number, line)) = lines.next() {
+        let mut words = line.split_whitespace();
+        let keyword = words.next().unwrap_or("");
+        let values: Vec<&str> = words.collect();
+        let int = |idx: usize| -> std::io::Result<i64> {
+            values.get(idx)
+                .and_then(|v| v.parse::<i64>().ok())
+                .ok_or_else(|| Error::new(InvalidData, format!("bdf line {}: bad {} value", number + 1, keyword)))
+        };
+        let size = |idx: usize| -> std::io::Result<u32> {
+            match int(idx)? {
+                value @ 0..=MAX_GLYPH_SIZE => Ok(value as u32),
+                value => Err(Error::new(InvalidData, format!("bdf line {}: bad {} size: {}", number + 1, keyword, value))),
+            }
+        };
+        let offset = |idx: usize| -> std::io::Result<i32> {
+            match int(idx)? {
+                value if value.abs() <= MAX_GLYPH_SIZE => Ok(value as i32),
+                value => Err(Error::new(InvalidData, format!("bdf line {}: bad {} offset: {}", number + 1, keyword, value))),
+            }
+        };
+
+        match (keyword, pending.as_mut()) {
+            ("FONTBOUNDINGBOX", None) => bounding_box = Some((size(0)?, size(1)?, offset(2)?, offset(3)?)),
+            ("DWIDTH", None) => font_advance = Some(size(0)?),
+            ("FONT_ASCENT", None) => ascent = Some(size(0)?),
+            ("FONT_DESCENT", None) => descent = Some(size(0)?),
+            ("DEFAULT_CHAR", None) => default_char = char::from_u32(int(0)? as u32),
+            ("STARTCHAR", None) => pending = Some(PendingGlyph { encoding: None, advance: None, bbx: None }),
+            ("ENCODING", Some(glyph)) => glyph.encoding = u32::try_from(int(0)?).ok().and_then(char::from_u32),
+            ("DWIDTH", Some(glyph)) => glyph.advance = Some(size(0)?),
+            ("BBX", Some(glyph)) => glyph.bbx = Some((size(0)?, size(1)?, offset(2)?, offset(3)?)),
+            ("BITMAP", Some(glyph)) => {
+                let (width, height, x_offset, y_offset) = glyph.bbx.or(bounding_box)
+                    .ok_or_else(|| Error::new(InvalidData, format!("bdf line {}: glyph has no bounding box", number + 1)))?;
+
+                /* one hex row per line, most significant bit leftmost, padded to whole bytes */
+                let mut bits = Vec::with_capacity((width * height) as usize);
+                for _ in 0..height {
+                    let (_, row) = lines.next().ok_or_else(|| Error::new(InvalidData, "bdf bitmap truncated"))?;
+                    let row = row.trim();
+                    for x in 0..width as usize {
+                        let nibble = row.get(x / 4..x / 4 + 1)
+                            .and_then(|digit| u8::from_str_radix(digit, 16).ok())
+                            .unwrap_or(0);
+                        bits.push(nibble & (0x8 >> (x % 4)) != 0);
+                    }
+                }
+
+                if let Some(c) = glyph.encoding {
+                    let advance = glyph.advance.or(font_advance).unwrap_or(width);
+                    glyphs.insert(c, BitmapGlyph { width, height, x_offset, y_offset, advance, bits });
+                }
+            }
+            ("ENDCHAR", Some(_)) => pending = None,
+            _ => {}
+        }
+    }
+
+    /* vertical metrics fall back to the font bounding box */
+    let (_, box_height, _, box_y) = bounding_box.unwrap_or((0, 0, 0, 0));
+    let descent = descent.unwrap_or((-box_y).max(0) as u32);
+    let ascent = ascent.unwrap_or((box_height as i32 + box_y).max(0) as u32);
+    if glyphs.is_empty() {
+        return Err(Error::new(InvalidData, "bdf font has no glyphs"));
+    }
+    Ok(BitmapFont::new(ascent, descent, default_char, glyphs))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::support::text::bitmap::bdf::parse_bdf;
+
+    #[test]
+    fn test_defaults_from_font() {
+        let font = parse_bdf("STARTFONT 2.1\nFONTBOUNDINGBOX 9 3 0 -1\nDWIDTH 7 0\nDEFAULT_CHAR 65\nSTARTCHAR A\nENCODING 65\nBITMAP\nFF80\n0080\n8000\nENDCHAR\nSTARTCHAR unencoded\nENCODING -1\nBBX 1 1 0 0\nBITMAP\n80\nENDCHAR\nENDFONT\n").unwrap();
+        assert_eq!((2, 1), (font.ascent, font.descent));
+
+        let glyph = font.glyph('A').unwrap();
+        assert_eq!((9, 3, 0, -1, 7), (glyph.width, glyph.height, glyph.x_offset, glyph.y_offset, glyph.advance));
+        assert!((0..9).all(|x| glyph.bit(x, 0)));
+        assert_eq!((false, true, true), (glyph.bit(7, 1), glyph.bit(8, 1), glyph.bit(0, 2)));
+        assert_eq!(Some(glyph), font.glyph('z'));                                                    // default char
+        assert_eq!(1, font.chars().count());
+    }
+
+    #[test]
+    fn test_bad_sizes() {
+        for bbx in ["BBX -1 2 0 0", "BBX 2 -1 0 0", "BBX 100000 100000 0 0", "BBX 1 1 9999999999 0", "DWIDTH -1 0"] {
+            let bdf = format!("STARTFONT 2.1\nSTARTCHAR A\nENCODING 65\nBBX 1 1 0 0\n{}\nBITMAP\n80\nENDCHAR\nENDFONT\n", bbx);
+            assert!(parse_bdf(&bdf).is_err());
+        }
+        for global in ["FONTBOUNDINGBOX -8 8 0 0", "DWIDTH -5 0", "FONT_ASCENT 99999999", "FONT_DESCENT -1"] {
+            let bdf = format!("STARTFONT 2.1\n{}\nSTARTCHAR A\nENCODING 65\nBBX 1 1 0 0\nBITMAP\n80\nENDCHAR\nENDFONT\n", global);
+            assert!(parse_bdf(&bdf).is_err());
+        }
+    }
+}
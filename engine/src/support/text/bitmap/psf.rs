@@ -0,0 +1,166 @@
+//!
+//! pc screen fonts (psf), versions 1 and 2: the linux console format.
+//!
+//! all glyphs share one size; without a unicode table, glyph n is character n.
+//!
+
+use crate::support::binary::endianness::Endianness;
+use crate::support::text::bitmap::{BitmapFont, BitmapGlyph};
+use std::collections::HashMap;
+use std::io::Error;
+use std::io::ErrorKind::InvalidData;
+
+static PSF1_MAGIC: [u8; 2] = [0x36, 0x04];
+static PSF2_MAGIC: [u8; 4] = [0x72, 0xb5, 0x4a, 0x86];
+
+static PSF1_MODE_512: u8 = 0x01;
+static PSF1_MODE_HAS_TABLE: u8 = 0x02;
+static PSF1_MODE_SEQ: u8 = 0x04;
+static PSF2_HAS_UNICODE_TABLE: u32 = 0x01;
+
+pub fn is_psf(bytes: &[u8]) -> bool {
+    bytes.starts_with(&PSF1_MAGIC) || bytes.starts_with(&PSF2_MAGIC)
+}
+
+pub fn parse_psf(bytes: &[u8]) -> std::io::Result<BitmapFont> {
+    let truncated = || Error::new(InvalidData, "psf font truncated");
+    let u32_at = |offset: usize| Endianness::LittleEndian.read_u32(bytes, offset).map_err(|_| truncated());
+
+    /* header: where the glyphs are, how many, how big, and whether there's a unicode table */
+    let (header_len, count, glyph_len, width, height, has_table, psf1) = if bytes.starts_with(&PSF2_MAGIC) {
+        let header_len = u32_at(8)? as usize;
+        let flags = u32_at(12)?;
+        (header_len, u32_at(16)? as usize, u32_at(20)? as usize, u32_at(28)?, u32_at(24)?, flags & PSF2_HAS_UNICODE_TABLE != 0, false)
+    } else if bytes.starts_with(&PSF1_MAGIC) {
+        let mode = *bytes.get(2).ok_or_else(truncated)?;
+        let height = *bytes.get(3).ok_or_else(truncated)? as u32;
+        let count = if mode & PSF1_MODE_512 != 0 { 512 } else { 256 };
+        (4, count, height as usize, 8, height, mode & (PSF1_MODE_HAS_TABLE | PSF1_MODE_SEQ) != 0, true)
+    } else {
+        return Err(Error::new(InvalidData, "not a psf font"));
+    };
+    if width == 0 || height == 0 {
+        return Err(Error::new(InvalidData, format!("psf glyphs are empty: {}x{}", width, height)));
+    }
+    let row_len = width.div_ceil(8) as usize;
+    if (row_len as u64 * height as u64) > glyph_len as u64 {
+        return Err(Error::new(InvalidData, format!("psf glyphs too small: {} bytes for {}x{}", glyph_len, width, height)));
+    }
+
+    /* the header's numbers are untrusted; with glyphs at least a byte each, a count that fits is bounded by the file */
+    let table_start = count.checked_mul(glyph_len).and_then(|len| len.checked_add(header_len));
+    let table_start = table_start.filter(|start| *start <= bytes.len()).ok_or_else(truncated)?;
+
+    /* glyph bitmaps */
+    let glyph = |index: usize| -> BitmapGlyph {
+        let data = &bytes[header_len + index * glyph_len..];
+        let bits = (0..height as usize)
+            .flat_map(|y| (0..width as usize).map(move |x| data[y * row_len + x / 8] & (0x80 >> (x % 8)) != 0))
+            .collect();
+        BitmapGlyph { width, height, x_offset: 0, y_offset: 0, advance: width, bits }
+    };
+
+    /* characters for each glyph */
+    let mut glyphs = HashMap::new();
+    if has_table {
+        let table = &bytes[table_start..];
+        let mapping = if psf1 { psf1_table(table, count) } else { psf2_table(table, count) };
+        for (index, chars) in mapping.into_iter().enumerate() {
+            for c in chars {
+                glyphs.insert(c, glyph(index));
+            }
+        }
+    } else {
+        for index in 0..count {
+            if let Some(c) = char::from_u32(index as u32) {
+                glyphs.insert(c, glyph(index));
+            }
+        }
+    }
+
+    /* there's no baseline in the format; everything sits above it */
+    let default_char = ['\u{fffd}', '?'].into_iter().find(|c| glyphs.contains_key(c));
+    Ok(BitmapFont::new(height, 0, default_char, glyphs))
+}
+
+///
+/// little-endian u16 code points per glyph, terminated by 0xffff; sequences (after 0xfffe) are skipped.
+///
+fn psf1_table(table: &[u8], count: usize) -> Vec<Vec<char>> {
+    let mut mapping = vec![vec![]; count];
+    let mut values = table.chunks_exact(2).map_while(|b| Endianness::LittleEndian.read_u16(b, 0).ok());
+    for chars in mapping.iter_mut() {
+        let mut in_sequence = false;
+        for value in values.by_ref() {
+            match value {
+                0xffff => break,
+                0xfffe => in_sequence = true,
+                _ if !in_sequence => chars.extend(char::from_u32(value as u32)),
+                _ => {}
+            }
+        }
+    }
+    mapping
+}
+
+///
+/// utf-8 characters per glyph, terminated by 0xff; sequences (after 0xfe) are skipped.
+///
+fn psf2_table(table: &[u8], count: usize) -> Vec<Vec<char>> {
+    let mut mapping = vec![vec![]; count];
+    let mut entries = table.split(|b| *b == 0xff);
+    for chars in mapping.iter_mut() {
+        let Some(entry) = entries.next() else { break };
+        let singles = entry.split(|b| *b == 0xfe).next().unwrap_or(&[]);
+        chars.extend(String::from_utf8_lossy(singles).chars().filter(|c| *c != '\u{fffd}'));
+    }
+    mapping
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::support::text::bitmap::psf::parse_psf;
+
+    #[test]
+    fn test_psf1_with_table() {
+        /* 256 glyphs of 2 rows; glyph 1 is mapped to 'x' and 'y', with a 2-character sequence after */
+        let mut bytes = vec![0x36, 0x04, 0x02, 2];
+        bytes.extend((0..256).flat_map(|i| [i as u8, 0x81]));
+        for i in 0..256u16 {
+            let entry: Vec<u16> = if i == 1 { vec![0x78, 0x79, 0xfffe, 0x61, 0x62, 0xffff] } else { vec![0xffff] };
+            entry.iter().for_each(|v| bytes.extend_from_slice(&v.to_le_bytes()));
+        }
+
+        let font = parse_psf(&bytes).unwrap();
+        assert_eq!((2, 0), (font.ascent, font.descent));
+        assert_eq!(2, font.chars().count());
+        let glyph = font.glyph('y').unwrap();
+        assert_eq!((8, 2, 8), (glyph.width, glyph.height, glyph.advance));
+        assert_eq!(vec![false, false, false, false, false, false, false, true], (0..8).map(|x| glyph.bit(x, 0)).collect::<Vec<_>>());
+        assert!(glyph.bit(0, 1) && glyph.bit(7, 1));
+        assert!(font.glyph('a').is_none());
+    }
+
+    #[test]
+    fn test_psf2_without_table() {
+        /* 3 glyphs, 10x1: two bytes per row */
+        let mut bytes = vec![0x72, 0xb5, 0x4a, 0x86];
+        [0u32, 32, 0, 3, 2, 1, 10].iter().for_each(|v| bytes.extend_from_slice(&v.to_le_bytes()));
+        bytes.extend_from_slice(&[0, 0, 0xff, 0xc0, 0x80, 0x40]);
+
+        let font = parse_psf(&bytes).unwrap();
+        let glyph = font.glyph('\u{1}').unwrap();
+        assert_eq!(10, glyph.advance);
+        assert!((0..10).all(|x| glyph.bit(x, 0)));
+        let glyph = font.glyph('\u{2}').unwrap();
+        assert_eq!((true, false, true), (glyph.bit(0, 0), glyph.bit(1, 0), glyph.bit(9, 0)));
+
+        /* glyph counts and sizes that don't fit the file, or don't fit at all */
+        for (count, glyph_len) in [(u32::MAX, 2), (u32::MAX, u32::MAX), (3, 0)] {
+            let mut header = bytes[0..32].to_vec();
+            header[16..20].copy_from_slice(&count.to_le_bytes());
+            header[20..24].copy_from_slice(&glyph_len.to_le_bytes());
+            assert!(parse_psf(&[header, bytes[32..].to_vec()].concat()).is_err());
+        }
+    }
+}
@@ -0,0 +1,149 @@
+//!
+//! what to draw for characters a typeface doesn't have.
+//!
+
+use crate::graphics::color::Color;
+use crate::support::image::RawImage;
+use crate::support::text::layout::LayoutFont;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Replacement {
+    Skip,                                                                                       // leave them out
+    Char(char),                                                                                 // draw this instead; a box if it's missing too
+    #[default]
+    Tofu,                                                                                       // draw an empty box
+}
+
+///
+/// a font that applies a replacement policy to the characters it's missing.
+///
+/// whitespace always passes through, since layout handles it.
+///
+pub struct FallbackFont<'a> {
+    font: &'a dyn LayoutFont,
+    replacement: Replacement,
+}
+
+impl<'a> FallbackFont<'a> {
+    pub fn new(font: &'a dyn LayoutFont, replacement: Replacement) -> FallbackFont<'a> {
+        FallbackFont { font, replacement }
+    }
+
+    ///
+    /// what a character is drawn as: itself, another character, a box (none), or nothing at all.
+    ///
+    fn resolve(&self, c: char) -> Option<Option<char>> {
+        if c.is_whitespace() || self.font.has_glyph(c) {
+            return Some(Some(c));
+        }
+        match self.replacement {
+            Replacement::Skip => None,
+            Replacement::Char(r) if self.font.has_glyph(r) => Some(Some(r)),
+            Replacement::Char(_) | Replacement::Tofu => Some(None),
+        }
+    }
+
+    ///
+    /// box size: most of the ascent tall, and a bit over half as wide, with a pixel of space either side.
+    ///
+    fn tofu_size(&self) -> (u32, u32) {
+        let height = ((self.font.ascent() * 0.7).round() as u32).max(3);
+        let width = ((height as f32 * 0.6).round() as u32).max(3);
+        (width, height)
+    }
+}
+
+impl LayoutFont for FallbackFont<'_> {
+    fn ascent(&self) -> f32 {
+        self.font.ascent()
+    }
+
+    fn descent(&self) -> f32 {
+        self.font.descent()
+    }
+
+    fn line_gap(&self) -> f32 {
+        self.font.line_gap()
+    }
+
+    fn has_glyph(&self, c: char) -> bool {
+        self.resolve(c).is_some()
+    }
+
+    fn advance(&self, c: char) -> f32 {
+        match self.resolve(c) {
+            Some(Some(c)) => self.font.advance(c),
+            Some(None) => (self.tofu_size().0 + 2) as f32,
+            None => 0.0,
+        }
+    }
+
+    fn kerning(&self, left: char, right: char) -> f32 {
+        match (self.resolve(left), self.resolve(right)) {
+            (Some(Some(left)), Some(Some(right))) => self.font.kerning(left, right),
+            _ => 0.0,
+        }
+    }
+
+    fn draw(&self, target: &mut RawImage, c: char, x: f32, y: f32, foreground: Color, background: Color) {
+        match self.resolve(c) {
+            Some(Some(c)) => self.font.draw(target, c, x, y, foreground, background),
+            Some(None) => {
+                /* an outline, sitting on the baseline */
+                let (width, height) = self.tofu_size();
+                let fg = foreground.to_u8();
+                let mut data = Vec::with_capacity((width * height * 4) as usize);
+                for row in 0..height {
+                    for col in 0..width {
+                        let edge = row == 0 || col == 0 || row == height - 1 || col == width - 1;
+                        data.extend_from_slice(&if edge { [fg.0, fg.1, fg.2, fg.3] } else { [0, 0, 0, 0] });
+                    }
+                }
+                let baseline = (y + self.font.ascent()).round() as i32;
+                target.blit(&RawImage::new(width, height, data), x.round() as i32 + 1, baseline - height as i32);
+            }
+            None => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graphics::color::Color;
+    use crate::support::text::fallback::Replacement;
+    use crate::support::text::generic::create_generic;
+    use crate::support::text::layout::LayoutOptions;
+    use crate::support::text::{measure_text, TextConfig, Typeface};
+
+    fn config(replacement: Replacement) -> TextConfig {
+        TextConfig { typeface: Typeface::Generic, foreground: Color::RED, background: Color::TRANSPARENT, layout: LayoutOptions::DEFAULT, replacement }
+    }
+
+    #[test]
+    fn test_replacement_policies() {
+        assert_eq!((26.0, 15.0), measure_text(&config(Replacement::Skip), "A€B"));
+        assert_eq!((39.0, 15.0), measure_text(&config(Replacement::Char('?')), "A€B"));
+        assert_eq!((35.0, 15.0), measure_text(&config(Replacement::Tofu), "A€B"));                  // 7 wide, and a pixel either side
+        assert_eq!((35.0, 15.0), measure_text(&config(Replacement::Char('€')), "A€B"));             // the replacement is missing too
+    }
+
+    #[test]
+    fn test_tofu_drawn() {
+        let image = create_generic(&config(Replacement::Tofu), String::from("€"));
+        assert_eq!((9, 15), (image.width, image.height));
+        let on: Vec<String> = image.data.chunks_exact(4 * 9)
+            .map(|row| row.chunks_exact(4).map(|p| if p[3] == 255 { '#' } else { '.' }).collect())
+            .collect();
+        assert_eq!(".........", on[3]);
+        assert_eq!(".#######.", on[4]);
+        assert_eq!(".#.....#.", on[9]);
+        assert_eq!(".#######.", on[14]);
+    }
+
+    #[test]
+    fn test_supported() {
+        let typeface = Typeface::Generic;
+        assert!(typeface.has_glyph('~') && !typeface.has_glyph('é'));
+        assert_eq!(vec!['é', '€'], typeface.missing_chars("héllo € é\n"));
+    }
+}
@@ -0,0 +1,325 @@
+//!
+//! text layout: measurement, line breaking, alignment, line spacing and tab stops, for any typeface.
+//!
+//! layout works in pixels, with y down from the top of the first line; whitespace moves the pen but isn't placed.
+//!
+
+use crate::graphics::color::Color;
+use crate::support::image::RawImage;
+use crate::support::logger::log;
+use crate::support::logger::log_level::LogLevel;
+
+/* slack when comparing a line against the wrap width, so exact fits don't wrap on rounding */
+static EPSILON: f32 = 0.001;
+
+/* largest rendered text image, either way; text past it is cut off */
+pub const MAX_RENDER_SIZE: u32 = 8192;
+
+///
+/// what layout needs from a font: vertical metrics, advances and kerning, and a way to draw one glyph.
+///
+pub trait LayoutFont {
+    fn ascent(&self) -> f32;
+    fn descent(&self) -> f32;
+
+    fn line_gap(&self) -> f32 {
+        0.0
+    }
+
+    fn has_glyph(&self, c: char) -> bool;
+
+    ///
+    /// how far the pen moves after this character; zero if the font can't draw it.
+    ///
+    fn advance(&self, c: char) -> f32;
+
+    fn kerning(&self, _left: char, _right: char) -> f32 {
+        0.0
+    }
+
+    ///
+    /// draw one glyph with its pen position at `x`, on the line whose top is at `y`.
+    ///
+    fn draw(&self, target: &mut RawImage, c: char, x: f32, y: f32, foreground: Color, background: Color);
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Alignment {
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LayoutOptions {
+    pub max_width: Option<f32>,                                                                 // wrap width in pixels; none for no wrapping
+    pub alignment: Alignment,
+    pub line_spacing: f32,                                                                      // multiple of the font's line height
+    pub tab_size: u32,                                                                          // tab stops, in spaces
+}
+
+impl LayoutOptions {
+    pub const DEFAULT: LayoutOptions = LayoutOptions { max_width: None, alignment: Alignment::Left, line_spacing: 1.0, tab_size: 4 };
+}
+
+impl Default for LayoutOptions {
+    fn default() -> Self {
+        LayoutOptions::DEFAULT
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PlacedGlyph {
+    pub c: char,
+    pub x: f32,                                                                                 // pen position, alignment included
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LayoutLine {
+    pub glyphs: Vec<PlacedGlyph>,
+    pub y: f32,                                                                                 // top of the line
+    pub width: f32,                                                                             // without trailing whitespace
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextLayout {
+    pub lines: Vec<LayoutLine>,
+    pub width: f32,
+    pub height: f32,
+}
+
+///
+/// break text into lines and place each glyph.
+///
+/// the layout is as wide as its widest line; when wrapping with center or right alignment, it's the wrap width.
+///
+pub fn layout(font: &dyn LayoutFont, text: &str, options: &LayoutOptions) -> TextLayout {
+    /* break each paragraph into lines */
+    let mut lines = vec![];
+    for paragraph in text.split('\n') {
+        break_paragraph(font, paragraph.trim_end_matches('\r'), options, &mut lines);
+    }
+
+    /* align each line in the layout's width */
+    let widest = lines.iter().map(|line| line.width).fold(0.0f32, f32::max);
+    let width = match (options.max_width, options.alignment) {
+        (Some(max_width), Alignment::Center | Alignment::Right) => max_width.max(widest),
+        _ => widest,
+    };
+    let factor = match options.alignment {
+        Alignment::Left => 0.0,
+        Alignment::Center => 0.5,
+        Alignment::Right => 1.0,
+    };
+    for line in lines.iter_mut() {
+        let offset = ((width - line.width) * factor).round();
+        line.glyphs.iter_mut().for_each(|glyph| glyph.x += offset);
+    }
+
+    /* stack the lines */
+    let line_height = font.ascent() + font.descent();
+    let step = (line_height + font.line_gap()) * options.line_spacing;
+    for (idx, line) in lines.iter_mut().enumerate() {
+        line.y = (idx as f32 * step).round();
+    }
+    let height = lines.last().map(|line| line.y).unwrap_or(0.0) + line_height;
+
+    TextLayout { lines, width, height }
+}
+
+///
+/// the size of the text, as laid out with these options.
+///
+pub fn measure(font: &dyn LayoutFont, text: &str, options: &LayoutOptions) -> (f32, f32) {
+    let layout = layout(font, text, options);
+    (layout.width, layout.height)
+}
+
+///
+/// draw a layout onto its background; the image is at most [MAX_RENDER_SIZE] either way.
+///
+pub fn render(font: &dyn LayoutFont, layout: &TextLayout, foreground: Color, background: Color) -> RawImage {
+    let width = (layout.width.ceil() as u32).clamp(1, MAX_RENDER_SIZE);
+    let height = (layout.height.ceil() as u32).clamp(1, MAX_RENDER_SIZE);
+    if layout.width > width as f32 || layout.height > height as f32 {
+        log(LogLevel::Warning, &|| format!("text is {}x{}, cut off at {}x{}", layout.width, layout.height, width, height));
+    }
+    let bg = background.to_u8();
+    let pixels = (width as usize).checked_mul(height as usize).expect("clamped render size overflows");
+    let mut output = RawImage::new(width, height, [bg.0, bg.1, bg.2, bg.3].repeat(pixels));
+    for line in &layout.lines {
+        for glyph in &line.glyphs {
+            font.draw(&mut output, glyph.c, glyph.x, line.y, foreground, background);
+        }
+    }
+    output
+}
+
+///
+/// greedy line breaking: words wrap at whitespace, and words wider than a line break between characters.
+///
+fn break_paragraph(font: &dyn LayoutFont, paragraph: &str, options: &LayoutOptions, lines: &mut Vec<LayoutLine>) {
+    let space = font.advance(' ');
+    let tab_stop = space * options.tab_size as f32;
+    let fits = |right: f32| options.max_width.is_none_or(|max_width| right <= max_width + EPSILON);
+
+    let mut line = LayoutLine { glyphs: vec![], y: 0.0, width: 0.0 };
+    let mut pen = 0.0f32;
+    let mut wrapped = false;
+    let mut chars = paragraph.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        /* whitespace moves the pen, except at the start of a wrapped line */
+        if c == ' ' || c == '\t' {
+            chars.next();
+            if !(wrapped && line.glyphs.is_empty()) {
+                pen = if c == '\t' && tab_stop > 0.0 { ((pen + EPSILON) / tab_stop).floor() * tab_stop + tab_stop } else { pen + space };
+            }
+            continue;
+        }
+
+        /* the next word moves to a new line if it doesn't fit on this one */
+        let mut word = vec![];
+        while let Some(&c) = chars.peek().filter(|c| **c != ' ' && **c != '\t') {
+            word.push(c);
+            chars.next();
+        }
+        let word_width: f32 = word.iter().map(|c| font.advance(*c)).sum::<f32>()
+            + word.windows(2).map(|pair| font.kerning(pair[0], pair[1])).sum::<f32>();
+        if !fits(pen + word_width) && !line.glyphs.is_empty() {
+            lines.push(std::mem::replace(&mut line, LayoutLine { glyphs: vec![], y: 0.0, width: 0.0 }));
+            pen = 0.0;
+            wrapped = true;
+        }
+
+        /* place it, breaking inside it only if it's wider than a whole line */
+        let mut previous: Option<char> = None;
+        for c in word {
+            let kerning = previous.map(|previous| font.kerning(previous, c)).unwrap_or(0.0);
+            let advance = font.advance(c);
+            if !fits(pen + kerning + advance) && !line.glyphs.is_empty() {
+                lines.push(std::mem::replace(&mut line, LayoutLine { glyphs: vec![], y: 0.0, width: 0.0 }));
+                pen = 0.0;
+                wrapped = true;
+            } else {
+                pen += kerning;
+            }
+            line.glyphs.push(PlacedGlyph { c, x: pen });
+            pen += advance;
+            line.width = pen;
+            previous = Some(c);
+        }
+    }
+    lines.push(line);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graphics::color::Color;
+    use crate::support::image::RawImage;
+    use crate::support::text::layout::{layout, measure, render, Alignment, LayoutFont, LayoutOptions, TextLayout, MAX_RENDER_SIZE};
+
+    ///
+    /// every character is 10 wide and 10 tall, and drawn as a solid 8x10 block; 'x' doesn't exist.
+    ///
+    struct BlockFont;
+
+    impl LayoutFont for BlockFont {
+        fn ascent(&self) -> f32 {
+            8.0
+        }
+
+        fn descent(&self) -> f32 {
+            2.0
+        }
+
+        fn has_glyph(&self, c: char) -> bool {
+            c != 'x'
+        }
+
+        fn advance(&self, c: char) -> f32 {
+            if c == 'x' { 0.0 } else { 10.0 }
+        }
+
+        fn kerning(&self, left: char, right: char) -> f32 {
+            if left == 'A' && right == 'V' { -2.0 } else { 0.0 }
+        }
+
+        fn draw(&self, target: &mut RawImage, c: char, x: f32, y: f32, foreground: Color, _background: Color) {
+            if c != 'x' {
+                let fg = foreground.to_u8();
+                target.paste(&RawImage::new(8, 10, [fg.0, fg.1, fg.2, fg.3].repeat(80)), x as i32, y as i32);
+            }
+        }
+    }
+
+    fn text(layout: &TextLayout) -> Vec<(String, Vec<f32>)> {
+        layout.lines.iter()
+            .map(|line| (line.glyphs.iter().map(|g| g.c).collect(), line.glyphs.iter().map(|g| g.x).collect()))
+            .collect()
+    }
+
+    #[test]
+    fn test_single_line() {
+        let result = layout(&BlockFont, "héllo AV", &LayoutOptions::DEFAULT);
+        assert_eq!(1, result.lines.len());
+        assert_eq!((78.0, 10.0), (result.width, result.height));                                   // 8 chars, -2 kerning; multibyte 'é' is one char
+        assert_eq!(vec![0.0, 10.0, 20.0, 30.0, 40.0, 60.0, 68.0], text(&result)[0].1);
+    }
+
+    #[test]
+    fn test_newlines_and_spacing() {
+        let options = LayoutOptions { line_spacing: 1.5, ..LayoutOptions::DEFAULT };
+        let result = layout(&BlockFont, "ab\r\n\ncde", &options);
+        assert_eq!(vec!["ab", "", "cde"], text(&result).into_iter().map(|(s, _)| s).collect::<Vec<_>>());
+        assert_eq!(vec![0.0, 15.0, 30.0], result.lines.iter().map(|l| l.y).collect::<Vec<_>>());
+        assert_eq!((30.0, 40.0), (result.width, result.height));
+    }
+
+    #[test]
+    fn test_wrapping() {
+        let options = LayoutOptions { max_width: Some(50.0), ..LayoutOptions::DEFAULT };
+        let result = layout(&BlockFont, "aa bb   cc abcdefghijkl", &options);
+        assert_eq!(
+            vec![("aabb".to_string(), vec![0.0, 10.0, 30.0, 40.0]), ("cc".to_string(), vec![0.0, 10.0]), ("abcde".to_string(), vec![0.0, 10.0, 20.0, 30.0, 40.0]), ("fghij".to_string(), vec![0.0, 10.0, 20.0, 30.0, 40.0]), ("kl".to_string(), vec![0.0, 10.0])],
+            text(&result)
+        );
+        assert_eq!(50.0, result.width);
+        assert_eq!(vec![50.0, 20.0, 50.0, 50.0, 20.0], result.lines.iter().map(|l| l.width).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_alignment() {
+        let center = LayoutOptions { max_width: Some(60.0), alignment: Alignment::Center, ..LayoutOptions::DEFAULT };
+        let result = layout(&BlockFont, "a\nabc", &center);
+        assert_eq!(60.0, result.width);
+        assert_eq!((vec![25.0], vec![15.0, 25.0, 35.0]), (text(&result)[0].1.clone(), text(&result)[1].1.clone()));
+
+        let right = LayoutOptions { alignment: Alignment::Right, ..LayoutOptions::DEFAULT };
+        let result = layout(&BlockFont, "a\nabc", &right);
+        assert_eq!(30.0, result.width);                                                             // no wrap width: aligned to the widest line
+        assert_eq!(vec![20.0], text(&result)[0].1);
+    }
+
+    #[test]
+    fn test_tab_stops() {
+        let options = LayoutOptions { tab_size: 2, ..LayoutOptions::DEFAULT };
+        let result = layout(&BlockFont, "\ta\tbcd\te", &options);
+        assert_eq!(vec![20.0, 40.0, 50.0, 60.0, 80.0], text(&result)[0].1);
+    }
+
+    #[test]
+    fn test_measure_and_render() {
+        let options = LayoutOptions { max_width: Some(25.0), ..LayoutOptions::DEFAULT };
+        assert_eq!((20.0, 20.0), measure(&BlockFont, "ab cd", &options));
+
+        let result = layout(&BlockFont, "a\nxb", &LayoutOptions::DEFAULT);
+        let image = render(&BlockFont, &result, Color::WHITE, Color::TRANSPARENT);
+        assert_eq!((10, 20), (image.width, image.height));
+        let alpha = |x: u32, y: u32| image.data[((y * image.width + x) * 4 + 3) as usize];
+        assert_eq!((255, 0, 255, 0), (alpha(7, 9), alpha(8, 9), alpha(0, 10), alpha(9, 19)));
+
+        let wide = TextLayout { lines: vec![], width: 1e12, height: 10.0 };
+        let image = render(&BlockFont, &wide, Color::WHITE, Color::TRANSPARENT);
+        assert_eq!((MAX_RENDER_SIZE, 10), (image.width, image.height));
+    }
+}
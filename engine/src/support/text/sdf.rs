@@ -0,0 +1,290 @@
+//!
+//! distance-field fonts: a typeface's glyphs as signed distance fields on one atlas page.
+//!
+//! the page is made once, in white; colour, size, outline, glow and shadow are all chosen when drawing.
+//!
+
+use crate::graphics::color::Color;
+use crate::graphics::texture::atlas::{AtlasRegion, TextureAtlas, UvRect};
+use crate::graphics::texture::sdf::{distance_field, DistanceField, SdfStyle};
+use crate::graphics::texture::t2d::{Sprite2D, Texture2D, Texture2DBuilder};
+use crate::support::image::pixel::PixelFormat;
+use crate::support::image::RawImage;
+use crate::support::logger::log;
+use crate::support::logger::log_level::LogLevel;
+use crate::support::text::bitmap::BitmapFont;
+use crate::support::text::generic::generic_font;
+use crate::support::text::layout::{layout, LayoutFont, LayoutOptions};
+use crate::support::text::Typeface;
+use std::collections::HashMap;
+
+/* page sizes to try, smallest first; the glyphs all go on one page */
+static PAGE_SIZES: [u32; 6] = [128, 256, 512, 1024, 2048, 4096];
+
+///
+/// one glyph's field on the page; the field's top-left corner is (left, top) from the pen on the baseline, y down.
+///
+/// all sizes are in field texels; blank glyphs (spaces) have no region.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SdfGlyph {
+    pub region: Option<AtlasRegion>,
+    pub left: f32,
+    pub top: f32,
+    pub advance: f32,
+}
+
+pub struct SdfFont {
+    pub page: RawImage,
+    pub spread: f32,
+    pub oversample: u32,                                                                        // field texels per pixel of the typeface
+    ascent: f32,
+    descent: f32,
+    line_gap: f32,
+    glyphs: HashMap<char, SdfGlyph>,
+    kerning: HashMap<(char, char), f32>,
+}
+
+impl SdfFont {
+    ///
+    /// rasterize each character at `oversample` times the typeface's size, and pack their fields onto one page.
+    ///
+    /// characters the typeface doesn't have are left out.
+    ///
+    pub fn new(typeface: &Typeface, chars: impl IntoIterator<Item = char>, oversample: u32, spread: f32) -> SdfFont {
+        let oversample = oversample.max(1);
+        let spread = spread.max(1.0);
+        let pad = spread.ceil();
+
+        /* coverage for each glyph, turned into a field */
+        let mut fields: Vec<(char, RawImage)> = vec![];
+        let mut glyphs = HashMap::new();
+        for c in chars {
+            if glyphs.contains_key(&c) {
+                continue;
+            }
+            let Some((coverage, left, top, advance)) = glyph_coverage(typeface, c, oversample) else {
+                continue;
+            };
+            if coverage.data.iter().any(|c| *c > 0) {
+                fields.push((c, distance_field(&coverage, spread)));
+            }
+            glyphs.insert(c, SdfGlyph { region: None, left: left - pad, top: top - pad, advance });
+        }
+
+        /* one page, as small as will hold them */
+        fields.sort_by(|a, b| b.1.height.cmp(&a.1.height).then(b.1.width.cmp(&a.1.width)));
+        let mut page = RawImage::new(1, 1, vec![0; 4]);
+        for size in PAGE_SIZES {
+            let mut atlas = TextureAtlas::new(size, size, 1);
+            let regions: Option<Vec<(char, AtlasRegion)>> = fields.iter()
+                .map(|(c, field)| atlas.insert(&c.to_string(), field).map(|region| (*c, region)))
+                .collect();
+            if let Some(regions) = regions.filter(|_| atlas.pages.len() <= 1) {
+                for (c, region) in regions {
+                    glyphs.entry(c).and_modify(|glyph| glyph.region = Some(region));
+                }
+                page = atlas.pages.pop().unwrap_or(page);
+                break;
+            }
+        }
+        if fields.iter().any(|(c, _)| glyphs[c].region.is_none()) {
+            log(LogLevel::Warning, &|| format!("distance-field glyphs don't fit a {}px page; some are missing", PAGE_SIZES[PAGE_SIZES.len() - 1]));
+        }
+
+        /* metrics and kerning, in field texels */
+        let k = oversample as f32;
+        let (ascent, descent, line_gap) = match typeface {
+            Typeface::Generic => (generic_font().ascent as f32 * k, generic_font().descent as f32 * k, 0.0),
+            Typeface::Bitmap(font) => (font.ascent as f32 * k, font.descent as f32 * k, 0.0),
+            Typeface::TrueType { font, size } => {
+                let metrics = font.line_metrics(size * k);
+                (metrics.ascent, metrics.descent, metrics.line_gap)
+            }
+        };
+        let mut kerning = HashMap::new();
+        if let Typeface::TrueType { font, size } = typeface {
+            for left in glyphs.keys() {
+                for right in glyphs.keys() {
+                    let amount = font.kerning(font.glyph_index(*left), font.glyph_index(*right), size * k);
+                    if amount != 0.0 {
+                        kerning.insert((*left, *right), amount);
+                    }
+                }
+            }
+        }
+
+        log(LogLevel::Debug, &|| format!("created distance-field font: {} glyphs on a {}x{} page", glyphs.len(), page.width, page.height));
+        SdfFont { page, spread, oversample, ascent, descent, line_gap, glyphs, kerning }
+    }
+
+    pub fn glyph(&self, c: char) -> Option<&SdfGlyph> {
+        self.glyphs.get(&c)
+    }
+
+    ///
+    /// a texture drawing the text with its top-left at (x, y); scale 1 is the typeface's own size.
+    ///
+    /// the wrap width in the options is in screen pixels, like the position.
+    ///
+    pub fn text_texture(&self, text: &str, x: f32, y: f32, scale: f32, options: &LayoutOptions, style: SdfStyle) -> Texture2D {
+        let s = scale / self.oversample as f32;
+        let options = LayoutOptions { max_width: options.max_width.map(|width| width / s), ..options.clone() };
+        let placed = layout(self, text, &options);
+
+        let mut sprites = vec![];
+        for line in &placed.lines {
+            for placed in &line.glyphs {
+                let Some(glyph) = self.glyphs.get(&placed.c) else { continue };
+                if let Some(region) = glyph.region {
+                    sprites.push(region.sprite(x + (placed.x + glyph.left) * s, y + (line.y + self.ascent + glyph.top) * s, s));
+                }
+            }
+        }
+        if sprites.is_empty() {
+            sprites.push(Sprite2D::new(UvRect::FULL, x, y, 0.0));                                   // nothing to draw, but not the whole page
+        }
+
+        sprites.into_iter()
+            .fold(Texture2DBuilder::new().with_image(self.page.clone()), |builder, sprite| builder.with_sprite(sprite))
+            .with_distance_field(DistanceField { spread: self.spread, style })
+            .build()
+    }
+}
+
+///
+/// layout in field texels; drawing fills each glyph at the field's own resolution.
+///
+impl LayoutFont for SdfFont {
+    fn ascent(&self) -> f32 {
+        self.ascent
+    }
+
+    fn descent(&self) -> f32 {
+        self.descent
+    }
+
+    fn line_gap(&self) -> f32 {
+        self.line_gap
+    }
+
+    fn has_glyph(&self, c: char) -> bool {
+        self.glyphs.contains_key(&c)
+    }
+
+    fn advance(&self, c: char) -> f32 {
+        self.glyphs.get(&c).map(|glyph| glyph.advance).unwrap_or(0.0)
+    }
+
+    fn kerning(&self, left: char, right: char) -> f32 {
+        self.kerning.get(&(left, right)).copied().unwrap_or(0.0)
+    }
+
+    fn draw(&self, target: &mut RawImage, c: char, x: f32, y: f32, foreground: Color, _background: Color) {
+        let Some((glyph, region)) = self.glyphs.get(&c).and_then(|glyph| glyph.region.map(|region| (glyph, region))) else {
+            return;
+        };
+        let field = DistanceField { spread: self.spread, style: SdfStyle::fill(foreground) };
+        let mut data = Vec::with_capacity((region.width * region.height * 4) as usize);
+        for row in region.y..region.y + region.height {
+            for col in region.x..region.x + region.width {
+                let value = self.page.data[((row * self.page.width + col) * 4 + 3) as usize] as f32 / 255.0;
+                let [r, g, b, a] = field.shade(field.distance(value), f32::MAX, 1.0);
+                let straight = |channel: f32| if a > 0.0 { (channel / a * 255.0).round() as u8 } else { 0 };
+                data.extend_from_slice(&[straight(r), straight(g), straight(b), (a * 255.0).round() as u8]);
+            }
+        }
+        let (left, top) = ((x + glyph.left).round() as i32, (y + self.ascent + glyph.top).round() as i32);
+        target.blit(&RawImage::new(region.width, region.height, data), left, top);
+    }
+}
+
+///
+/// a glyph's coverage (l8), its top-left from the pen on the baseline, and its advance, all oversampled.
+///
+fn glyph_coverage(typeface: &Typeface, c: char, oversample: u32) -> Option<(RawImage, f32, f32, f32)> {
+    match typeface {
+        Typeface::Generic => bitmap_coverage(generic_font(), c, oversample),
+        Typeface::Bitmap(font) => bitmap_coverage(font, c, oversample),
+        Typeface::TrueType { font, size } => {
+            if !font.has_glyph(c) {
+                return None;
+            }
+            let glyph = font.glyph_index(c);
+            let size = size * oversample as f32;
+            let advance = font.advance(glyph, size);
+            match font.rasterize(glyph, size, 0.0) {
+                Ok(Some(bitmap)) => Some((bitmap.coverage, bitmap.left as f32, bitmap.top as f32, advance)),
+                Ok(None) => Some((RawImage::with_format(0, 0, PixelFormat::L8, vec![]), 0.0, 0.0, advance)),
+                Err(e) => {
+                    log(LogLevel::Warning, &|| format!("skipping glyph for [{}]: {}", c, e));
+                    None
+                }
+            }
+        }
+    }
+}
+
+///
+/// a bitmap glyph, each bit blown up to an `oversample`-sized square.
+///
+fn bitmap_coverage(font: &BitmapFont, c: char, oversample: u32) -> Option<(RawImage, f32, f32, f32)> {
+    if !font.has_glyph(c) {
+        return None;
+    }
+    let glyph = font.glyph(c)?;
+    let (width, height) = (glyph.width * oversample, glyph.height * oversample);
+    let data = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .map(|(x, y)| if glyph.bit(x / oversample, y / oversample) { 255 } else { 0 })
+        .collect();
+    let k = oversample as f32;
+    let top = -(glyph.y_offset + glyph.height as i32) as f32 * k;
+    Some((RawImage::with_format(width, height, PixelFormat::L8, data), glyph.x_offset as f32 * k, top, glyph.advance as f32 * k))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graphics::color::Color;
+    use crate::graphics::texture::sdf::SdfStyle;
+    use crate::support::text::generic::generic_font;
+    use crate::support::text::layout::{layout, render, LayoutFont, LayoutOptions};
+    use crate::support::text::sdf::SdfFont;
+    use crate::support::text::Typeface;
+
+    #[test]
+    fn test_generic_font() {
+        let font = SdfFont::new(&Typeface::Generic, "AB €".chars(), 2, 4.0);
+        assert_eq!((128, 128), (font.page.width, font.page.height));
+        assert!(font.glyph('€').is_none());
+        assert!(font.glyph(' ').unwrap().region.is_none());
+        assert_eq!(26.0, font.advance(' '));
+
+        /* drawn at the field's resolution, it's the bitmap glyph, doubled */
+        let image = render(&font, &layout(&font, "A", &LayoutOptions::DEFAULT), Color::WHITE, Color::TRANSPARENT);
+        assert_eq!((26, 30), (image.width, image.height));
+        let glyph = generic_font().glyph('A').unwrap();
+        for y in 0..30 {
+            for x in 0..26 {
+                let on = image.data[((y * 26 + x) * 4 + 3) as usize] > 128;
+                assert_eq!(glyph.bit(x / 2, y / 2), on, "at ({}, {})", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn test_text_texture() {
+        let font = SdfFont::new(&Typeface::Generic, "AB".chars(), 2, 4.0);
+        let texture = font.text_texture("AB\nB", 10.0, 20.0, 3.0, &LayoutOptions::DEFAULT, SdfStyle::fill(Color::RED));
+        assert_eq!(3, texture.sprites.len());
+        assert_eq!(4.0, texture.distance_field.unwrap().spread);
+
+        /* scale 3 means 1.5 screen pixels per field texel; each field has 4 texels of padding */
+        let sprite = texture.sprites[1];
+        assert_eq!((1.5, 10.0 + (26.0 - 4.0) * 1.5, 20.0 - 4.0 * 1.5), (sprite.scale, sprite.x, sprite.y));
+        assert_eq!(20.0 + (30.0 - 4.0) * 1.5, texture.sprites[2].y);
+
+        let empty = font.text_texture("", 0.0, 0.0, 1.0, &LayoutOptions::DEFAULT, SdfStyle::fill(Color::RED));
+        assert_eq!(0.0, empty.sprites[0].scale);
+    }
+}
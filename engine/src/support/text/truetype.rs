@@ -0,0 +1,582 @@
+//!
+//! truetype / opentype fonts, with quadratic (glyf) outlines.
+//!
+//! supported tables: head, hhea, maxp, cmap (formats 4 and 12), hmtx, loca, glyf and kern (format 0).
+//! opentype fonts with cff outlines are rejected; gpos kerning and hinting are ignored.
+//!
+
+use crate::graphics::color::Color;
+use crate::support::image::pixel::PixelFormat;
+use crate::support::image::RawImage;
+use crate::support::logger::log;
+use crate::support::logger::log_level::LogLevel;
+use crate::support::binary::endianness::Endianness;
+use crate::support::resource::Resource;
+use crate::support::text::layout::{layout, render, LayoutFont, LayoutOptions};
+use crate::support::text::truetype::outline::{parse_outline, Segment};
+use crate::support::text::truetype::raster::Rasterizer;
+use std::collections::HashMap;
+use std::io::Error;
+use std::io::ErrorKind::{InvalidData, Unsupported};
+use std::ops::Range;
+
+pub mod outline;
+pub(crate) mod raster;
+
+/* every unicode code point, once; overlapping cmap groups can't make the map take longer than this */
+const MAX_MAPPED: usize = 0x110000;
+
+pub struct TrueTypeFont {
+    data: Vec<u8>,
+    glyf: Range<usize>,
+    loca: Range<usize>,
+    hmtx: Range<usize>,
+    long_loca: bool,
+    glyph_count: u16,
+    h_metric_count: u16,
+    units_per_em: u16,
+    ascender: i16,
+    descender: i16,
+    line_gap: i16,
+    cmap: HashMap<u32, u16>,
+    kerning: HashMap<(u16, u16), i16>,
+}
+
+///
+/// vertical metrics, in pixels at some size; descent is positive (below the baseline).
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LineMetrics {
+    pub ascent: f32,
+    pub descent: f32,
+    pub line_gap: f32,
+}
+
+///
+/// a rasterized glyph: an 8-bit coverage mask, positioned relative to the pen on the baseline (y down).
+///
+pub struct GlyphBitmap {
+    pub coverage: RawImage,
+    pub left: i32,
+    pub top: i32,
+}
+
+impl TrueTypeFont {
+    ///
+    /// the font owns its bytes, since glyphs are read from them as they're drawn: an embedded
+    /// font is copied once, here, rather than borrowed.
+    ///
+    pub fn from_resource(resource: &dyn Resource) -> std::io::Result<TrueTypeFont> {
+        TrueTypeFont::from_bytes(resource.bytes()?)
+    }
+
+    pub fn from_bytes(data: Vec<u8>) -> std::io::Result<TrueTypeFont> {
+        /* table directory */
+        let version = read_u32(&data, 0)?;
+        let table_count = read_u16(&data, 4)? as usize;
+        let mut tables: HashMap<[u8; 4], Range<usize>> = HashMap::new();
+        for i in 0..table_count {
+            let record = 12 + i * 16;
+            let tag: [u8; 4] = data.get(record..record + 4).ok_or_else(truncated)?.try_into().unwrap();
+            let offset = read_u32(&data, record + 8)? as usize;
+            let length = read_u32(&data, record + 12)? as usize;
+            if offset.checked_add(length).is_none_or(|end| end > data.len()) {
+                return Err(Error::new(InvalidData, format!("font table {} out of bounds", String::from_utf8_lossy(&tag))));
+            }
+            tables.insert(tag, offset..offset + length);
+        }
+        let table = |tag: &[u8; 4]| -> std::io::Result<Range<usize>> {
+            tables.get(tag).cloned().ok_or_else(|| Error::new(InvalidData, format!("font has no {} table", String::from_utf8_lossy(tag))))
+        };
+        if !tables.contains_key(b"glyf") && tables.contains_key(b"CFF ") {
+            return Err(Error::new(Unsupported, "opentype fonts with cff outlines aren't supported"));
+        }
+        if version != 0x00010000 && version != 0x74727565 && version != 0x4f54544f {
+            return Err(Error::new(InvalidData, format!("not a truetype font: version 0x{:08x}", version)));
+        }
+
+        /* global metrics */
+        let head = table(b"head")?.start;
+        let hhea = table(b"hhea")?.start;
+        let maxp = table(b"maxp")?.start;
+        let units_per_em = read_u16(&data, head + 18)?;
+        if units_per_em == 0 {
+            return Err(Error::new(InvalidData, "font has 0 units per em"));
+        }
+
+        let kerning = match tables.get(b"kern") {
+            Some(kern) => parse_kern(&data[kern.clone()])?,
+            None => HashMap::new(),
+        };
+        let cmap = parse_cmap(&data[table(b"cmap")?])?;
+        log(LogLevel::Debug, &|| format!("loaded truetype font: {} tables, {} characters, {} kerning pairs", table_count, cmap.len(), kerning.len()));
+
+        Ok(TrueTypeFont {
+            glyf: table(b"glyf")?,
+            loca: table(b"loca")?,
+            hmtx: table(b"hmtx")?,
+            long_loca: read_i16(&data, head + 50)? != 0,
+            glyph_count: read_u16(&data, maxp + 4)?,
+            h_metric_count: read_u16(&data, hhea + 34)?,
+            units_per_em,
+            ascender: read_i16(&data, hhea + 4)?,
+            descender: read_i16(&data, hhea + 6)?,
+            line_gap: read_i16(&data, hhea + 8)?,
+            cmap,
+            kerning,
+            data,
+        })
+    }
+
+    ///
+    /// the glyph for a character; 0 (the "missing" glyph) if the font doesn't have one.
+    ///
+    pub fn glyph_index(&self, c: char) -> u16 {
+        self.cmap.get(&(c as u32)).copied().unwrap_or(0)
+    }
+
+    pub fn has_glyph(&self, c: char) -> bool {
+        self.glyph_index(c) != 0
+    }
+
+    pub fn units_per_em(&self) -> u16 {
+        self.units_per_em
+    }
+
+    ///
+    /// scale from font units to pixels, for an em of `size` pixels.
+    ///
+    pub fn scale(&self, size: f32) -> f32 {
+        size / self.units_per_em as f32
+    }
+
+    pub fn line_metrics(&self, size: f32) -> LineMetrics {
+        let scale = self.scale(size);
+        LineMetrics {
+            ascent: self.ascender as f32 * scale,
+            descent: -(self.descender as f32) * scale,
+            line_gap: self.line_gap as f32 * scale,
+        }
+    }
+
+    ///
+    /// horizontal advance of a glyph, in pixels.
+    ///
+    pub fn advance(&self, glyph: u16, size: f32) -> f32 {
+        let metric = glyph.min(self.h_metric_count.saturating_sub(1)) as usize;
+        let units = read_u16(&self.data[self.hmtx.clone()], metric * 4).unwrap_or(0);
+        units as f32 * self.scale(size)
+    }
+
+    ///
+    /// adjustment between two glyphs, in pixels (usually negative).
+    ///
+    pub fn kerning(&self, left: u16, right: u16, size: f32) -> f32 {
+        self.kerning.get(&(left, right)).map(|units| *units as f32 * self.scale(size)).unwrap_or(0.0)
+    }
+
+    ///
+    /// the glyph's outline, in font units (y up).
+    ///
+    pub fn outline(&self, glyph: u16) -> std::io::Result<Vec<Segment>> {
+        parse_outline(self.glyph_data(glyph)?, &|component| self.glyph_data(component), 0)
+    }
+
+    fn glyph_data(&self, glyph: u16) -> std::io::Result<&[u8]> {
+        if glyph >= self.glyph_count {
+            return Err(Error::new(InvalidData, format!("glyph {} out of range ({} glyphs)", glyph, self.glyph_count)));
+        }
+        let loca = &self.data[self.loca.clone()];
+        let (start, end) = if self.long_loca {
+            (read_u32(loca, glyph as usize * 4)? as usize, read_u32(loca, glyph as usize * 4 + 4)? as usize)
+        } else {
+            (read_u16(loca, glyph as usize * 2)? as usize * 2, read_u16(loca, glyph as usize * 2 + 2)? as usize * 2)
+        };
+        let glyf = &self.data[self.glyf.clone()];
+        if start > end || end > glyf.len() {
+            return Err(Error::new(InvalidData, format!("glyph {} data out of bounds", glyph)));
+        }
+        Ok(&glyf[start..end])
+    }
+
+    ///
+    /// rasterize a glyph with its origin `offset_x` pixels right of a pixel boundary; none for blank glyphs.
+    ///
+    pub fn rasterize(&self, glyph: u16, size: f32, offset_x: f32) -> std::io::Result<Option<GlyphBitmap>> {
+        /* to pixels, y down */
+        let scale = self.scale(size);
+        let segments: Vec<Segment> = self.outline(glyph)?.iter()
+            .map(|s| s.map(|(x, y)| (x * scale + offset_x, -y * scale)))
+            .collect();
+        let points: Vec<(f32, f32)> = segments.iter().flat_map(|s| s.points()).collect();
+        if points.is_empty() {
+            return Ok(None);
+        }
+
+        /* bounds, with a spare column so edges never spill into the next row */
+        let left = points.iter().map(|p| p.0).fold(f32::MAX, f32::min).floor() as i32;
+        let top = points.iter().map(|p| p.1).fold(f32::MAX, f32::min).floor() as i32;
+        let right = points.iter().map(|p| p.0).fold(f32::MIN, f32::max).ceil() as i32;
+        let bottom = points.iter().map(|p| p.1).fold(f32::MIN, f32::max).ceil() as i32;
+        let (width, height) = ((right - left + 1) as usize, (bottom - top).max(1) as usize);
+
+        let mut rasterizer = Rasterizer::new(width, height);
+        let local = |(x, y): (f32, f32)| (x - left as f32, y - top as f32);
+        for segment in segments {
+            match segment.map(local) {
+                Segment::Line(a, b) => rasterizer.line(a, b),
+                Segment::Quad(a, b, c) => rasterizer.quad(a, b, c),
+            }
+        }
+
+        Ok(Some(GlyphBitmap {
+            coverage: RawImage::with_format(width as u32, height as u32, PixelFormat::L8, rasterizer.coverage()),
+            left,
+            top,
+        }))
+    }
+
+    ///
+    /// draw a single line of text: `size` pixels per em, on a background exactly as tall as the font's ascent + descent.
+    ///
+    pub fn render_text(&self, text: &str, size: f32, foreground: Color, background: Color) -> RawImage {
+        let font = self.at_size(size);
+        render(&font, &layout(&font, text, &LayoutOptions::DEFAULT), foreground, background)
+    }
+
+    ///
+    /// this font at a pixel size, for text layout.
+    ///
+    pub fn at_size(&self, size: f32) -> SizedFont<'_> {
+        SizedFont { font: self, size, metrics: self.line_metrics(size) }
+    }
+}
+
+///
+/// a font at one pixel size, with its line metrics worked out once.
+///
+pub struct SizedFont<'a> {
+    font: &'a TrueTypeFont,
+    size: f32,
+    metrics: LineMetrics,
+}
+
+impl LayoutFont for SizedFont<'_> {
+    fn ascent(&self) -> f32 {
+        self.metrics.ascent
+    }
+
+    fn descent(&self) -> f32 {
+        self.metrics.descent
+    }
+
+    fn line_gap(&self) -> f32 {
+        self.metrics.line_gap
+    }
+
+    fn has_glyph(&self, c: char) -> bool {
+        self.font.has_glyph(c)
+    }
+
+    fn advance(&self, c: char) -> f32 {
+        self.font.advance(self.font.glyph_index(c), self.size)
+    }
+
+    fn kerning(&self, left: char, right: char) -> f32 {
+        self.font.kerning(self.font.glyph_index(left), self.font.glyph_index(right), self.size)
+    }
+
+    fn draw(&self, target: &mut RawImage, c: char, x: f32, y: f32, foreground: Color, _background: Color) {
+        let glyph = self.font.glyph_index(c);
+        match self.font.rasterize(glyph, self.size, x.fract()) {
+            Ok(Some(bitmap)) => {
+                let fg = foreground.to_u8();
+                let tinted: Vec<u8> = bitmap.coverage.data.iter()
+                    .flat_map(|coverage| [fg.0, fg.1, fg.2, ((*coverage as u32 * fg.3 as u32) / 255) as u8])
+                    .collect();
+                let image = RawImage::new(bitmap.coverage.width, bitmap.coverage.height, tinted);
+                let baseline = (y + self.metrics.ascent).round() as i32;
+                target.blit(&image, x.floor() as i32 + bitmap.left, baseline + bitmap.top);
+            }
+            Ok(None) => {}
+            Err(e) => log(LogLevel::Warning, &|| format!("skipping glyph {}: {}", glyph, e)),
+        }
+    }
+}
+
+///
+/// character to glyph map, from the best unicode subtable: format 12 (full unicode) over format 4 (bmp).
+///
+fn parse_cmap(cmap: &[u8]) -> std::io::Result<HashMap<u32, u16>> {
+    let count = read_u16(cmap, 2)? as usize;
+    let mut best: Option<(u16, usize)> = None;
+    for i in 0..count {
+        let platform = read_u16(cmap, 4 + i * 8)?;
+        let encoding = read_u16(cmap, 6 + i * 8)?;
+        let offset = read_u32(cmap, 8 + i * 8)? as usize;
+        let unicode = platform == 0 || (platform == 3 && (encoding == 1 || encoding == 10));
+        if !unicode {
+            continue;
+        }
+        let format = read_u16(cmap, offset)?;
+        if (format == 4 || format == 12) && best.is_none_or(|(f, _)| format > f) {
+            best = Some((format, offset));
+        }
+    }
+
+    let mut map = HashMap::new();
+    match best {
+        Some((4, offset)) => {
+            let seg_count = read_u16(cmap, offset + 6)? as usize / 2;
+            let ends = offset + 14;
+            let starts = ends + seg_count * 2 + 2;
+            let deltas = starts + seg_count * 2;
+            let range_offsets = deltas + seg_count * 2;
+            for seg in 0..seg_count {
+                let end = read_u16(cmap, ends + seg * 2)? as u32;
+                let start = read_u16(cmap, starts + seg * 2)? as u32;
+                let delta = read_u16(cmap, deltas + seg * 2)?;
+                let range_offset = read_u16(cmap, range_offsets + seg * 2)? as usize;
+                for c in start..=end.min(0xfffe) {
+                    let glyph = if range_offset == 0 {
+                        (c as u16).wrapping_add(delta)
+                    } else {
+                        /* the offset is relative to the idRangeOffset entry itself */
+                        let address = range_offsets + seg * 2 + range_offset + (c - start) as usize * 2;
+                        match read_u16(cmap, address)? {
+                            0 => 0,
+                            glyph => glyph.wrapping_add(delta),
+                        }
+                    };
+                    if glyph != 0 {
+                        map.insert(c, glyph);
+                    }
+                }
+            }
+        }
+        Some((_, offset)) => {
+            let groups = read_u32(cmap, offset + 12)? as usize;
+            let mut mapped = 0;
+            for group in 0..groups {
+                let record = offset + 16 + group * 12;
+                let (start, end, first) = (read_u32(cmap, record)?, read_u32(cmap, record + 4)?, read_u32(cmap, record + 8)?);
+
+                /* glyph ids are 16 bits; the rest of a group that runs past them is ignored */
+                let Some(last) = 0xffffu32.checked_sub(first) else { continue };
+                let end = end.min(0x10ffff).min(start.saturating_add(last));
+                for c in start..=end {
+                    if mapped == MAX_MAPPED {
+                        log(LogLevel::Warning, &|| format!("font maps more than {} characters; the rest are ignored", MAX_MAPPED));
+                        return Ok(map);
+                    }
+                    map.insert(c, (first + (c - start)) as u16);
+                    mapped += 1;
+                }
+            }
+        }
+        None => return Err(Error::new(Unsupported, "font has no unicode character map")),
+    }
+    Ok(map)
+}
+
+///
+/// horizontal pairs from the first format 0 subtable (the original microsoft layout only).
+///
+fn parse_kern(kern: &[u8]) -> std::io::Result<HashMap<(u16, u16), i16>> {
+    let mut pairs = HashMap::new();
+    if read_u16(kern, 0)? != 0 {
+        log(LogLevel::Debug, &|| String::from("ignoring apple-format kern table"));
+        return Ok(pairs);
+    }
+    let mut offset = 4;
+    for _ in 0..read_u16(kern, 2)? {
+        let length = read_u16(kern, offset + 2)? as usize;
+        let coverage = read_u16(kern, offset + 4)?;
+        if coverage >> 8 == 0 && coverage & 0x01 != 0 {
+            let count = read_u16(kern, offset + 6)? as usize;
+            for i in 0..count {
+                let pair = offset + 14 + i * 6;
+                pairs.insert((read_u16(kern, pair)?, read_u16(kern, pair + 2)?), read_i16(kern, pair + 4)?);
+            }
+            break;
+        }
+        offset += length;
+    }
+    Ok(pairs)
+}
+
+fn truncated() -> Error {
+    Error::new(InvalidData, "font data truncated")
+}
+
+pub(crate) fn read_u8(data: &[u8], offset: usize) -> std::io::Result<u8> {
+    data.get(offset).copied().ok_or_else(truncated)
+}
+
+pub(crate) fn read_u16(data: &[u8], offset: usize) -> std::io::Result<u16> {
+    Endianness::BigEndian.read_u16(data, offset).map_err(|_| truncated())
+}
+
+pub(crate) fn read_i16(data: &[u8], offset: usize) -> std::io::Result<i16> {
+    read_u16(data, offset).map(|value| value as i16)
+}
+
+pub(crate) fn read_u32(data: &[u8], offset: usize) -> std::io::Result<u32> {
+    Endianness::BigEndian.read_u32(data, offset).map_err(|_| truncated())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use crate::graphics::color::Color;
+    use crate::support::text::truetype::{parse_cmap, TrueTypeFont};
+    use std::io::ErrorKind::Unsupported;
+
+    fn be16(out: &mut Vec<u8>, values: &[u16]) {
+        values.iter().for_each(|v| out.extend_from_slice(&v.to_be_bytes()));
+    }
+
+    ///
+    /// a simple glyph from (x, y, on-curve) contours, using only long coordinates.
+    ///
+    fn simple_glyph(contours: &[&[(i16, i16, bool)]]) -> Vec<u8> {
+        let mut out = vec![];
+        be16(&mut out, &[contours.len() as u16, 0, 0, 0, 0]);
+        let mut end = 0;
+        for contour in contours {
+            end += contour.len();
+            be16(&mut out, &[end as u16 - 1]);
+        }
+        be16(&mut out, &[0]);
+        let points: Vec<&(i16, i16, bool)> = contours.iter().flat_map(|c| c.iter()).collect();
+        points.iter().for_each(|p| out.push(if p.2 { 1 } else { 0 }));
+        let (mut x, mut y) = (0, 0);
+        for p in &points {
+            be16(&mut out, &[(p.0 - x) as u16]);
+            x = p.0;
+        }
+        for p in &points {
+            be16(&mut out, &[(p.1 - y) as u16]);
+            y = p.1;
+        }
+        out
+    }
+
+    ///
+    /// a tiny font, 1000 units/em: 'A' is a 500-unit square on the baseline, 'B' a curved diamond,
+    /// 'C' a composite of 'A' shifted right by 500; the pair A-A kerns by -100.
+    ///
+    pub(crate) fn test_font() -> Vec<u8> {
+        let square = simple_glyph(&[&[(0, 0, true), (0, 500, true), (500, 500, true), (500, 0, true)]]);
+        let diamond = simple_glyph(&[&[(250, 0, true), (0, 0, false), (0, 250, true), (0, 500, false), (250, 500, true), (500, 500, false), (500, 250, true), (500, 0, false)]]);
+        let mut composite = vec![];
+        be16(&mut composite, &[0xffff, 0, 0, 1000, 500, 0x0003, 1, 500, 0]);
+        let glyphs = [vec![], square, diamond, composite];
+
+        let mut glyf = vec![];
+        let mut loca = vec![0u8, 0];
+        for glyph in &glyphs {
+            glyf.extend_from_slice(glyph);
+            if glyf.len() % 2 == 1 {
+                glyf.push(0);
+            }
+            be16(&mut loca, &[(glyf.len() / 2) as u16]);
+        }
+
+        let mut head = vec![0u8; 54];
+        head[18..20].copy_from_slice(&1000u16.to_be_bytes());
+        let mut hhea = vec![0u8; 36];
+        hhea[4..6].copy_from_slice(&800i16.to_be_bytes());
+        hhea[6..8].copy_from_slice(&(-200i16).to_be_bytes());
+        hhea[34..36].copy_from_slice(&4u16.to_be_bytes());
+        let mut maxp = vec![0u8; 6];
+        maxp[4..6].copy_from_slice(&4u16.to_be_bytes());
+        let mut hmtx = vec![];
+        be16(&mut hmtx, &[500, 0, 600, 0, 600, 0, 1100, 0]);
+        let mut cmap = vec![];
+        be16(&mut cmap, &[0, 1, 3, 1, 0, 12, 4, 32, 0, 4, 0, 0, 0, 67, 0xffff, 0, 65, 0xffff, (-64i16) as u16, 1, 0, 0]);
+        let mut kern = vec![];
+        be16(&mut kern, &[0, 1, 0, 20, 0x0001, 1, 0, 0, 0, 1, 1, (-100i16) as u16]);
+
+        let tables: [(&[u8; 4], Vec<u8>); 8] = [(b"cmap", cmap), (b"glyf", glyf), (b"head", head), (b"hhea", hhea), (b"hmtx", hmtx), (b"kern", kern), (b"loca", loca), (b"maxp", maxp)];
+        let mut font = vec![];
+        be16(&mut font, &[1, 0, tables.len() as u16, 0, 0, 0]);
+        let mut offset = 12 + tables.len() * 16;
+        for (tag, data) in &tables {
+            font.extend_from_slice(*tag);
+            font.extend_from_slice(&[0; 4]);
+            font.extend_from_slice(&(offset as u32).to_be_bytes());
+            font.extend_from_slice(&(data.len() as u32).to_be_bytes());
+            offset += data.len();
+        }
+        tables.iter().for_each(|(_, data)| font.extend_from_slice(data));
+        font
+    }
+
+    #[test]
+    fn test_tables() {
+        let font = TrueTypeFont::from_bytes(test_font()).unwrap();
+        assert_eq!(1, font.glyph_index('A'));
+        assert_eq!(3, font.glyph_index('C'));
+        assert_eq!(0, font.glyph_index('z'));
+        assert_eq!(6.0, font.advance(1, 10.0));
+        assert_eq!(-1.0, font.kerning(1, 1, 10.0));
+        assert_eq!(0.0, font.kerning(1, 2, 10.0));
+        assert_eq!((8.0, 2.0), (font.line_metrics(10.0).ascent, font.line_metrics(10.0).descent));
+    }
+
+    #[test]
+    fn test_rasterize() {
+        let font = TrueTypeFont::from_bytes(test_font()).unwrap();
+        assert!(font.rasterize(0, 10.0, 0.0).unwrap().is_none());
+
+        /* the square covers whole pixels exactly */
+        let square = font.rasterize(1, 10.0, 0.0).unwrap().unwrap();
+        assert_eq!((0, -5, 6, 5), (square.left, square.top, square.coverage.width, square.coverage.height));
+        assert!(square.coverage.data.chunks_exact(6).all(|row| row == [255, 255, 255, 255, 255, 0]));
+
+        /* the composite is the same square, 5 pixels over */
+        let composite = font.rasterize(3, 10.0, 0.0).unwrap().unwrap();
+        assert_eq!((5, -5), (composite.left, composite.top));
+        assert_eq!(square.coverage.data, composite.coverage.data);
+
+        /* the curved diamond is anti-aliased: full in the middle, partial at the corners */
+        let diamond = font.rasterize(2, 20.0, 0.0).unwrap().unwrap();
+        let at = |x: u32, y: u32| diamond.coverage.data[(y * diamond.coverage.width + x) as usize];
+        assert_eq!(255, at(5, 5));
+        assert_eq!(0, at(0, 0));
+        assert!(at(1, 1) > 0 && at(1, 1) < 255);
+    }
+
+    #[test]
+    fn test_render_text() {
+        let font = TrueTypeFont::from_bytes(test_font()).unwrap();
+        let image = font.render_text("AA?", 10.0, Color::WHITE, Color::TRANSPARENT);
+        assert_eq!((16, 10), (image.width, image.height));                                                 // 6 - 1 (kerning) + 6 + 5 (missing glyph)
+        let alpha = |x: u32, y: u32| image.data[((y * image.width + x) * 4 + 3) as usize];
+        assert_eq!((0, 255, 255, 0), (alpha(0, 2), alpha(0, 3), alpha(9, 7), alpha(10, 7)));
+    }
+
+    #[test]
+    fn test_cmap_groups() {
+        /* a format 12 subtable: a group past the last glyph id, one that runs into it, and many that overlap */
+        let mut groups: Vec<[u32; 3]> = vec![[0x41, 0x43, 0xffff_ffff], [0x61, 0x63, 0xfffe]];
+        groups.extend(std::iter::repeat_n([0x100, 0x10ffff, 0], 20));
+        let mut cmap = vec![];
+        be16(&mut cmap, &[0, 1, 3, 10, 0, 12, 12, 0, 0, 0, 0, 0]);
+        cmap.extend_from_slice(&(groups.len() as u32).to_be_bytes());
+        groups.iter().flatten().for_each(|value| cmap.extend_from_slice(&value.to_be_bytes()));
+
+        let map = parse_cmap(&cmap).unwrap();
+        assert_eq!(2 + 0x10000, map.len());
+        assert_eq!((None, Some(&0xfffe), Some(&0xffff), None), (map.get(&0x41), map.get(&0x61), map.get(&0x62), map.get(&0x63)));
+        assert_eq!((Some(&0xffff), None), (map.get(&0x100ff), map.get(&0x10100)));
+    }
+
+    #[test]
+    fn test_cff_rejected() {
+        let mut font = test_font();
+        let glyf = font.windows(4).position(|w| w == b"glyf").unwrap();
+        font[glyf..glyf + 4].copy_from_slice(b"CFF ");
+        assert_eq!(Unsupported, TrueTypeFont::from_bytes(font).err().unwrap().kind());
+    }
+}
@@ -0,0 +1,202 @@
+use crate::support::text::truetype::{read_i16, read_u16, read_u8};
+use std::io::Error;
+use std::io::ErrorKind::InvalidData;
+
+/* composite glyph flags */
+static ARG_1_AND_2_ARE_WORDS: u16 = 0x0001;
+static ARGS_ARE_XY_VALUES: u16 = 0x0002;
+static WE_HAVE_A_SCALE: u16 = 0x0008;
+static MORE_COMPONENTS: u16 = 0x0020;
+static WE_HAVE_AN_X_AND_Y_SCALE: u16 = 0x0040;
+static WE_HAVE_A_TWO_BY_TWO: u16 = 0x0080;
+
+/* nesting limit for composite glyphs; real fonts rarely go past 2 or 3 */
+static MAX_DEPTH: usize = 8;
+
+pub type Point = (f32, f32);
+
+///
+/// one piece of a glyph outline, in font units (y up).
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Segment {
+    Line(Point, Point),
+    Quad(Point, Point, Point),
+}
+
+impl Segment {
+    pub(crate) fn map(&self, f: impl Fn(Point) -> Point) -> Segment {
+        match *self {
+            Segment::Line(a, b) => Segment::Line(f(a), f(b)),
+            Segment::Quad(a, b, c) => Segment::Quad(f(a), f(b), f(c)),
+        }
+    }
+
+    pub(crate) fn points(&self) -> Vec<Point> {
+        match *self {
+            Segment::Line(a, b) => vec![a, b],
+            Segment::Quad(a, b, c) => vec![a, b, c],
+        }
+    }
+}
+
+///
+/// parse glyph data (from the glyf table) into closed outlines; `lookup` fetches component glyphs.
+///
+pub(crate) fn parse_outline<'a>(data: &'a [u8], lookup: &dyn Fn(u16) -> std::io::Result<&'a [u8]>, depth: usize) -> std::io::Result<Vec<Segment>> {
+    if data.is_empty() {
+        return Ok(vec![]);
+    }
+    if depth > MAX_DEPTH {
+        return Err(Error::new(InvalidData, "composite glyphs nested too deeply"));
+    }
+
+    let contours = read_i16(data, 0)?;
+    if contours >= 0 {
+        parse_simple(data, contours as usize)
+    } else {
+        parse_composite(data, lookup, depth)
+    }
+}
+
+fn parse_simple(data: &[u8], contours: usize) -> std::io::Result<Vec<Segment>> {
+    /* contour end points, then skip the hinting instructions */
+    let mut end_points = Vec::with_capacity(contours);
+    for i in 0..contours {
+        end_points.push(read_u16(data, 10 + i * 2)? as usize);
+    }
+    let instruction_len = read_u16(data, 10 + contours * 2)? as usize;
+    let mut pos = 12 + contours * 2 + instruction_len;
+    let point_count = end_points.last().map(|last| last + 1).unwrap_or(0);
+
+    /* flags, with repeats */
+    let mut flags = Vec::with_capacity(point_count);
+    while flags.len() < point_count {
+        let flag = read_u8(data, pos)?;
+        pos += 1;
+        flags.push(flag);
+        if flag & 0x08 != 0 {
+            let repeat = read_u8(data, pos)?;
+            pos += 1;
+            (0..repeat).for_each(|_| flags.push(flag));
+        }
+    }
+    flags.truncate(point_count);
+
+    /* coordinates are deltas: short (with a sign flag) or i16, or "same as before" */
+    let mut read_coordinates = |short: u8, same_or_positive: u8| -> std::io::Result<Vec<f32>> {
+        let mut value = 0i32;
+        let mut values = Vec::with_capacity(point_count);
+        for flag in &flags {
+            if flag & short != 0 {
+                let delta = read_u8(data, pos)? as i32;
+                pos += 1;
+                value += if flag & same_or_positive != 0 { delta } else { -delta };
+            } else if flag & same_or_positive == 0 {
+                value += read_i16(data, pos)? as i32;
+                pos += 2;
+            }
+            values.push(value as f32);
+        }
+        Ok(values)
+    };
+    let xs = read_coordinates(0x02, 0x10)?;
+    let ys = read_coordinates(0x04, 0x20)?;
+
+    /* walk each contour */
+    let mut segments = vec![];
+    let mut start = 0;
+    for end in end_points {
+        if end < start || end >= point_count {
+            return Err(Error::new(InvalidData, "glyph contour end points out of order"));
+        }
+        let points: Vec<(Point, bool)> = (start..=end).map(|i| ((xs[i], ys[i]), flags[i] & 0x01 != 0)).collect();
+        contour_segments(&points, &mut segments);
+        start = end + 1;
+    }
+    Ok(segments)
+}
+
+///
+/// turn on/off-curve points into segments; two off-curve points in a row imply an on-curve point between them.
+///
+fn contour_segments(points: &[(Point, bool)], segments: &mut Vec<Segment>) {
+    if points.len() < 2 {
+        return;
+    }
+    let mid = |a: Point, b: Point| ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0);
+
+    /* start on an on-curve point (or an implied one), and finish back there */
+    let (start, sequence): (Point, Vec<(Point, bool)>) = match points.iter().position(|(_, on)| *on) {
+        Some(idx) => (points[idx].0, points[idx + 1..].iter().chain(points[..=idx].iter()).copied().collect()),
+        None => {
+            let start = mid(points[points.len() - 1].0, points[0].0);
+            (start, points.iter().copied().chain(std::iter::once((start, true))).collect())
+        }
+    };
+
+    let mut current = start;
+    let mut control: Option<Point> = None;
+    for (point, on_curve) in sequence {
+        match (on_curve, control) {
+            (true, Some(c)) => segments.push(Segment::Quad(current, c, point)),
+            (true, None) => segments.push(Segment::Line(current, point)),
+            (false, Some(c)) => {
+                let implied = mid(c, point);
+                segments.push(Segment::Quad(current, c, implied));
+                current = implied;
+            }
+            (false, None) => {}
+        }
+        if on_curve {
+            current = point;
+            control = None;
+        } else {
+            control = Some(point);
+        }
+    }
+}
+
+fn parse_composite<'a>(data: &'a [u8], lookup: &dyn Fn(u16) -> std::io::Result<&'a [u8]>, depth: usize) -> std::io::Result<Vec<Segment>> {
+    let mut segments = vec![];
+    let mut pos = 10;
+    loop {
+        let flags = read_u16(data, pos)?;
+        let glyph = read_u16(data, pos + 2)?;
+        pos += 4;
+
+        /* offset; point-matching placement isn't supported, and falls back to no offset */
+        let (arg1, arg2) = if flags & ARG_1_AND_2_ARE_WORDS != 0 {
+            pos += 4;
+            (read_i16(data, pos - 4)? as f32, read_i16(data, pos - 2)? as f32)
+        } else {
+            pos += 2;
+            (read_u8(data, pos - 2)? as i8 as f32, read_u8(data, pos - 1)? as i8 as f32)
+        };
+        let (dx, dy) = if flags & ARGS_ARE_XY_VALUES != 0 { (arg1, arg2) } else { (0.0, 0.0) };
+
+        /* transform, as 2.14 fixed point */
+        let f2dot14 = |offset: usize| -> std::io::Result<f32> { Ok(read_i16(data, offset)? as f32 / 16384.0) };
+        let (a, b, c, d) = if flags & WE_HAVE_A_SCALE != 0 {
+            pos += 2;
+            let scale = f2dot14(pos - 2)?;
+            (scale, 0.0, 0.0, scale)
+        } else if flags & WE_HAVE_AN_X_AND_Y_SCALE != 0 {
+            pos += 4;
+            (f2dot14(pos - 4)?, 0.0, 0.0, f2dot14(pos - 2)?)
+        } else if flags & WE_HAVE_A_TWO_BY_TWO != 0 {
+            pos += 8;
+            (f2dot14(pos - 8)?, f2dot14(pos - 6)?, f2dot14(pos - 4)?, f2dot14(pos - 2)?)
+        } else {
+            (1.0, 0.0, 0.0, 1.0)
+        };
+
+        let component = parse_outline(lookup(glyph)?, lookup, depth + 1)?;
+        segments.extend(component.iter().map(|s| s.map(|(x, y)| (a * x + c * y + dx, b * x + d * y + dy))));
+
+        if flags & MORE_COMPONENTS == 0 {
+            break;
+        }
+    }
+    Ok(segments)
+}
@@ -0,0 +1,524 @@
+//!
+//! zlib streams (rfc 1950) and the deflate format (rfc 1951).
+//!
+//! decompression supports all block types. compression either produces "stored" (uncompressed)
+//! blocks, or a single block of lz77 matches coded with the fixed huffman tables; the latter is
+//! much simpler than choosing per-block dynamic tables, and does well enough on image data.
+//!
+
+use crate::support::binary::bits::LsbBitReader;
+use std::io::Error;
+use std::io::ErrorKind::{InvalidData, Unsupported};
+
+const MAX_BITS: usize = 15;
+const MAX_STORED_BLOCK: usize = 65535;
+
+/* lz77 parameters for compression */
+const WINDOW_SIZE: usize = 32768;
+const HASH_BITS: usize = 15;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+const MAX_CHAIN: usize = 128;
+
+/* base lengths and extra bits for length codes 257..285 */
+const LENGTH_BASE: [u16; 29] = [3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258];
+const LENGTH_EXTRA: [u8; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+
+/* base distances and extra bits for distance codes 0..29 */
+const DIST_BASE: [u16; 30] = [1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577];
+const DIST_EXTRA: [u8; 30] = [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13];
+
+/* order in which code length code lengths are stored, for dynamic blocks */
+const CODE_LENGTH_ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+
+///
+/// decompress a zlib stream; the adler-32 checksum is verified.
+///
+pub fn decompress(bytes: &[u8]) -> std::io::Result<Vec<u8>> {
+    decompress_limited(bytes, usize::MAX)
+}
+
+///
+/// decompress a zlib stream, failing as soon as it makes more than `limit` bytes.
+///
+pub fn decompress_limited(bytes: &[u8], limit: usize) -> std::io::Result<Vec<u8>> {
+    /* header */
+    if bytes.len() < 6 {
+        return Err(Error::new(InvalidData, "zlib: stream too short"));
+    }
+    let cmf = bytes[0];
+    let flg = bytes[1];
+    if !(cmf as u16 * 256 + flg as u16).is_multiple_of(31) {
+        return Err(Error::new(InvalidData, "zlib: header check failed"));
+    }
+    if cmf & 0x0f != 8 {
+        return Err(Error::new(Unsupported, format!("zlib: unsupported compression method {}", cmf & 0x0f)));
+    }
+    if flg & 0x20 != 0 {
+        return Err(Error::new(Unsupported, "zlib: preset dictionaries aren't supported"));
+    }
+
+    /* body, followed by the checksum */
+    let (data, consumed) = inflate_with_length(&bytes[2..], limit)?;
+    let trailer = bytes.get(2 + consumed..2 + consumed + 4)
+        .ok_or_else(|| Error::new(InvalidData, "zlib: missing checksum"))?;
+    if u32::from_be_bytes(trailer.try_into().unwrap()) != adler32(&data) {
+        return Err(Error::new(InvalidData, "zlib: checksum mismatch"));
+    }
+
+    Ok(data)
+}
+
+///
+/// wrap the bytes in a zlib stream, using uncompressed deflate blocks.
+///
+pub fn compress_stored(bytes: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(bytes.len() + bytes.len() / MAX_STORED_BLOCK * 5 + 11);
+    out.extend_from_slice(&[0x78, 0x01]);                          // deflate, 32k window, no dictionary, fastest
+
+    let mut blocks = bytes.chunks(MAX_STORED_BLOCK).peekable();
+    if blocks.peek().is_none() {
+        out.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let len = block.len() as u16;
+        out.push(last as u8);                                       // bfinal, btype=00
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+
+    out.extend_from_slice(&adler32(bytes).to_be_bytes());
+    out
+}
+
+///
+/// compress the bytes into a zlib stream, using lz77 and the fixed huffman codes.
+///
+pub fn compress(bytes: &[u8]) -> Vec<u8> {
+    let mut writer = BitWriter::new(Vec::with_capacity(bytes.len() / 2 + 16));
+    writer.bits(0x9c78, 16);                                                                   // deflate, 32k window, default level
+    writer.bits(1, 1);                                                                          // bfinal
+    writer.bits(1, 2);                                                                          // btype=01, fixed huffman
+
+    /* hash chains: most recent position for each hash, then the previous position with the same hash */
+    let mut head = vec![usize::MAX; 1 << HASH_BITS];
+    let mut prev = vec![usize::MAX; WINDOW_SIZE];
+    let hash = |idx: usize| {
+        (((bytes[idx] as usize) << 10) ^ ((bytes[idx + 1] as usize) << 5) ^ bytes[idx + 2] as usize) & ((1 << HASH_BITS) - 1)
+    };
+    let insert = |idx: usize, head: &mut [usize], prev: &mut [usize]| {
+        if idx + MIN_MATCH <= bytes.len() {
+            let h = hash(idx);
+            prev[idx % WINDOW_SIZE] = head[h];
+            head[h] = idx;
+        }
+    };
+
+    let mut idx = 0;
+    while idx < bytes.len() {
+        /* longest match among recent positions with the same hash */
+        let (mut best_len, mut best_dist) = (0, 0);
+        if idx + MIN_MATCH <= bytes.len() {
+            let max_len = MAX_MATCH.min(bytes.len() - idx);
+            let mut candidate = head[hash(idx)];
+            for _ in 0..MAX_CHAIN {
+                if candidate == usize::MAX || idx - candidate > WINDOW_SIZE {
+                    break;
+                }
+                let len = bytes[candidate..].iter().zip(&bytes[idx..idx + max_len]).take_while(|(a, b)| a == b).count();
+                if len > best_len {
+                    (best_len, best_dist) = (len, idx - candidate);
+                    if len == max_len {
+                        break;
+                    }
+                }
+                let next = prev[candidate % WINDOW_SIZE];
+                if next == usize::MAX || next >= candidate {
+                    break;
+                }
+                candidate = next;
+            }
+        }
+
+        if best_len >= MIN_MATCH {
+            writer.length(best_len);
+            writer.distance(best_dist);
+            for k in idx..idx + best_len {
+                insert(k, &mut head, &mut prev);
+            }
+            idx += best_len;
+        } else {
+            writer.literal(bytes[idx] as u16);
+            insert(idx, &mut head, &mut prev);
+            idx += 1;
+        }
+    }
+    writer.literal(256);                                                                        // end of block
+
+    let mut out = writer.finish();
+    out.extend_from_slice(&adler32(bytes).to_be_bytes());
+    out
+}
+
+///
+/// decompress raw deflate data (no zlib header or checksum).
+///
+pub fn inflate(bytes: &[u8]) -> std::io::Result<Vec<u8>> {
+    inflate_limited(bytes, usize::MAX)
+}
+
+///
+/// decompress raw deflate data, failing as soon as it makes more than `limit` bytes.
+///
+pub fn inflate_limited(bytes: &[u8], limit: usize) -> std::io::Result<Vec<u8>> {
+    inflate_with_length(bytes, limit).map(|(data, _)| data)
+}
+
+///
+/// crc-32 checksum (ieee), as used by png and zip.
+///
+pub fn crc32(bytes: &[u8]) -> u32 {
+    !crc32_update(0xffff_ffff, bytes)
+}
+
+///
+/// feed more bytes into a running crc-32; start from 0xffffffff, and invert the result.
+///
+pub(crate) fn crc32_update(mut state: u32, bytes: &[u8]) -> u32 {
+    for byte in bytes {
+        state ^= *byte as u32;
+        for _ in 0..8 {
+            state = if state & 1 != 0 { 0xedb8_8320 ^ (state >> 1) } else { state >> 1 };
+        }
+    }
+    state
+}
+
+///
+/// adler-32 checksum, as used by zlib.
+///
+pub fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in bytes.chunks(5552) {
+        for byte in chunk {
+            a += *byte as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    (b << 16) | a
+}
+
+///
+/// inflate, also returning the number of input bytes consumed.
+///
+fn inflate_with_length(bytes: &[u8], limit: usize) -> std::io::Result<(Vec<u8>, usize)> {
+    let mut reader = LsbBitReader::new(bytes, "deflate");
+    let mut out = Vec::with_capacity(bytes.len().saturating_mul(4).min(limit));
+
+    loop {
+        let last = reader.bits(1)? == 1;
+        match reader.bits(2)? {
+            0 => stored_block(&mut reader, &mut out, limit)?,
+            1 => {
+                let (lengths, distances) = fixed_tables();
+                compressed_block(&mut reader, &mut out, limit, &lengths, &distances)?
+            }
+            2 => {
+                let (lengths, distances) = dynamic_tables(&mut reader)?;
+                compressed_block(&mut reader, &mut out, limit, &lengths, &distances)?
+            }
+            _ => return Err(Error::new(InvalidData, "deflate: invalid block type")),
+        }
+        if last {
+            break;
+        }
+    }
+
+    Ok((out, reader.position()))
+}
+
+fn stored_block(reader: &mut LsbBitReader, out: &mut Vec<u8>, limit: usize) -> std::io::Result<()> {
+    reader.align();
+    let header = reader.bytes(4)?;
+    let len = u16::from_le_bytes([header[0], header[1]]);
+    let nlen = u16::from_le_bytes([header[2], header[3]]);
+    if len != !nlen {
+        return Err(Error::new(InvalidData, "deflate: stored block length mismatch"));
+    }
+    check_limit(out, len as usize, limit)?;
+    out.extend_from_slice(reader.bytes(len as usize)?);
+    Ok(())
+}
+
+fn compressed_block(reader: &mut LsbBitReader, out: &mut Vec<u8>, limit: usize, lengths: &Huffman, distances: &Huffman) -> std::io::Result<()> {
+    loop {
+        let symbol = lengths.decode(reader)? as usize;
+        if symbol < 256 {
+            check_limit(out, 1, limit)?;
+            out.push(symbol as u8);
+        } else if symbol == 256 {
+            return Ok(());
+        } else {
+            /* length/distance pair */
+            let idx = symbol - 257;
+            if idx >= LENGTH_BASE.len() {
+                return Err(Error::new(InvalidData, "deflate: invalid length symbol"));
+            }
+            let length = LENGTH_BASE[idx] as usize + reader.bits(LENGTH_EXTRA[idx] as u32)? as usize;
+
+            let dsym = distances.decode(reader)? as usize;
+            if dsym >= DIST_BASE.len() {
+                return Err(Error::new(InvalidData, "deflate: invalid distance symbol"));
+            }
+            let distance = DIST_BASE[dsym] as usize + reader.bits(DIST_EXTRA[dsym] as u32)? as usize;
+            if distance > out.len() {
+                return Err(Error::new(InvalidData, "deflate: distance too far back"));
+            }
+
+            /* copy byte-by-byte; the source may overlap the destination */
+            check_limit(out, length, limit)?;
+            let start = out.len() - distance;
+            for k in 0..length {
+                out.push(out[start + k]);
+            }
+        }
+    }
+}
+
+fn check_limit(out: &[u8], more: usize, limit: usize) -> std::io::Result<()> {
+    if more > limit - out.len() {
+        return Err(Error::new(InvalidData, format!("deflate: output is more than {} bytes", limit)));
+    }
+    Ok(())
+}
+
+fn fixed_tables() -> (Huffman, Huffman) {
+    let mut lengths = [0u8; 288];
+    lengths[0..144].fill(8);
+    lengths[144..256].fill(9);
+    lengths[256..280].fill(7);
+    lengths[280..288].fill(8);
+    (Huffman::new(&lengths), Huffman::new(&[5u8; 30]))
+}
+
+fn dynamic_tables(reader: &mut LsbBitReader) -> std::io::Result<(Huffman, Huffman)> {
+    /* table sizes */
+    let hlit = reader.bits(5)? as usize + 257;
+    let hdist = reader.bits(5)? as usize + 1;
+    let hclen = reader.bits(4)? as usize + 4;
+
+    /* code lengths for the code length alphabet */
+    let mut cl_lengths = [0u8; 19];
+    for idx in CODE_LENGTH_ORDER.iter().take(hclen) {
+        cl_lengths[*idx] = reader.bits(3)? as u8;
+    }
+    let cl = Huffman::new(&cl_lengths);
+
+    /* literal/length and distance code lengths, run-length encoded together */
+    let mut lengths = vec![0u8; hlit + hdist];
+    let mut idx = 0;
+    while idx < lengths.len() {
+        let symbol = cl.decode(reader)?;
+        let (value, repeat) = match symbol {
+            0..=15 => (symbol as u8, 1),
+            16 => {
+                let previous = *lengths[..idx].last().ok_or_else(|| Error::new(InvalidData, "deflate: repeat with no previous length"))?;
+                (previous, 3 + reader.bits(2)? as usize)
+            }
+            17 => (0, 3 + reader.bits(3)? as usize),
+            18 => (0, 11 + reader.bits(7)? as usize),
+            _ => return Err(Error::new(InvalidData, "deflate: invalid code length symbol")),
+        };
+        if idx + repeat > lengths.len() {
+            return Err(Error::new(InvalidData, "deflate: too many code lengths"));
+        }
+        lengths[idx..idx + repeat].fill(value);
+        idx += repeat;
+    }
+
+    if lengths[256] == 0 {
+        return Err(Error::new(InvalidData, "deflate: missing end-of-block code"));
+    }
+    Ok((Huffman::new(&lengths[..hlit]), Huffman::new(&lengths[hlit..])))
+}
+
+///
+/// canonical huffman code, stored as counts per code length plus symbols in code order.
+///
+struct Huffman {
+    counts: [u16; MAX_BITS + 1],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Huffman {
+        let mut counts = [0u16; MAX_BITS + 1];
+        for length in lengths {
+            counts[*length as usize] += 1;
+        }
+        counts[0] = 0;
+
+        /* offsets of the first symbol of each length */
+        let mut offsets = [0u16; MAX_BITS + 2];
+        for len in 1..=MAX_BITS {
+            offsets[len + 1] = offsets[len] + counts[len];
+        }
+
+        let mut symbols = vec![0u16; offsets[MAX_BITS + 1] as usize];
+        for (symbol, length) in lengths.iter().enumerate() {
+            if *length != 0 {
+                symbols[offsets[*length as usize] as usize] = symbol as u16;
+                offsets[*length as usize] += 1;
+            }
+        }
+
+        Huffman { counts, symbols }
+    }
+
+    ///
+    /// decode one symbol, reading a bit at a time (codes are stored most-significant bit first).
+    ///
+    fn decode(&self, reader: &mut LsbBitReader) -> std::io::Result<u16> {
+        let mut code: i32 = 0;
+        let mut first: i32 = 0;
+        let mut index: i32 = 0;
+        for len in 1..=MAX_BITS {
+            code |= reader.bits(1)? as i32;
+            let count = self.counts[len] as i32;
+            if code - count < first {
+                return Ok(self.symbols[(index + (code - first)) as usize]);
+            }
+            index += count;
+            first += count;
+            first <<= 1;
+            code <<= 1;
+        }
+        Err(Error::new(InvalidData, "deflate: invalid huffman code"))
+    }
+}
+
+///
+/// writes bits least-significant first; huffman codes are written most-significant bit first.
+///
+struct BitWriter {
+    out: Vec<u8>,
+    buffer: u64,
+    count: u32,
+}
+
+impl BitWriter {
+    fn new(out: Vec<u8>) -> BitWriter {
+        BitWriter { out, buffer: 0, count: 0 }
+    }
+
+    fn bits(&mut self, value: u32, count: u32) {
+        self.buffer |= (value as u64) << self.count;
+        self.count += count;
+        while self.count >= 8 {
+            self.out.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    fn code(&mut self, code: u32, len: u32) {
+        self.bits(code.reverse_bits() >> (32 - len), len);
+    }
+
+    /* literal/length symbol, from the fixed table */
+    fn literal(&mut self, symbol: u16) {
+        let symbol = symbol as u32;
+        match symbol {
+            0..=143 => self.code(0x30 + symbol, 8),
+            144..=255 => self.code(0x190 + symbol - 144, 9),
+            256..=279 => self.code(symbol - 256, 7),
+            _ => self.code(0xc0 + symbol - 280, 8),
+        }
+    }
+
+    fn length(&mut self, length: usize) {
+        let idx = LENGTH_BASE.iter().rposition(|base| *base as usize <= length).unwrap();
+        self.literal(257 + idx as u16);
+        self.bits((length - LENGTH_BASE[idx] as usize) as u32, LENGTH_EXTRA[idx] as u32);
+    }
+
+    fn distance(&mut self, distance: usize) {
+        let idx = DIST_BASE.iter().rposition(|base| *base as usize <= distance).unwrap();
+        self.code(idx as u32, 5);
+        self.bits((distance - DIST_BASE[idx] as usize) as u32, DIST_EXTRA[idx] as u32);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.out.push(self.buffer as u8);
+        }
+        self.out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::support::zlib::{adler32, compress, compress_stored, decompress, decompress_limited, inflate};
+
+    #[test]
+    fn test_adler32() {
+        assert_eq!(0x091e_01de, adler32(b"123456789"));
+    }
+
+    #[test]
+    fn test_stored_round_trip() {
+        let data: Vec<u8> = (0..70000u32).map(|x| (x % 251) as u8).collect();
+        assert_eq!(data, decompress(&compress_stored(&data)).unwrap());
+        assert_eq!(Vec::<u8>::new(), decompress(&compress_stored(&[])).unwrap());
+    }
+
+    #[test]
+    fn test_compress_round_trip() {
+        let repetitive: Vec<u8> = (0..100_000u32).map(|x| ((x / 7) % 13) as u8).collect();
+        let compressed = compress(&repetitive);
+        assert!(compressed.len() < repetitive.len() / 20);
+        assert_eq!(repetitive, decompress(&compressed).unwrap());
+
+        let noisy: Vec<u8> = (0..50_000u32).map(|x| (x.wrapping_mul(2_654_435_761) >> 13) as u8).collect();
+        assert_eq!(noisy, decompress(&compress(&noisy)).unwrap());
+        assert_eq!(Vec::<u8>::new(), decompress(&compress(&[])).unwrap());
+    }
+
+    #[test]
+    fn test_fixed_huffman() {
+        /* python: zlib.compress(b"hello hello hello hello", 9) */
+        let stream = [0x78, 0xda, 0xcb, 0x48, 0xcd, 0xc9, 0xc9, 0x57, 0xc8, 0x40, 0x27, 0x01, 0x68, 0x03, 0x08, 0xb1];
+        assert_eq!(b"hello hello hello hello".to_vec(), decompress(&stream).unwrap());
+    }
+
+    #[test]
+    fn test_dynamic_huffman() {
+        /* python: zlib.compress(bytes(b"abcdefgh"[(i*i+i//7)%8] for i in range(2000)), 9) */
+        let stream = [
+            0x78, 0xda, 0xed, 0xcb, 0xb1, 0x01, 0x00, 0x30, 0x08, 0x02, 0xb0, 0x5b, 0x41, 0x51, 0xff, 0xbf,
+            0xa0, 0x9c, 0xd1, 0x81, 0x21, 0x63, 0x40, 0x11, 0x54, 0xb1, 0xc6, 0xb6, 0xab, 0x4d, 0xa7, 0x36,
+            0x0d, 0xc6, 0x76, 0x96, 0x56, 0xb7, 0x67, 0x68, 0x9c, 0x21, 0x2f, 0x2f, 0x2f, 0x2f, 0x2f, 0x2f,
+            0xef, 0x9b, 0xf7, 0x00, 0xd2, 0xe1, 0x10, 0xc1,
+        ];
+        let expected: Vec<u8> = (0..2000usize).map(|i| b"abcdefgh"[(i * i + i / 7) % 8]).collect();
+        assert_eq!(expected, decompress(&stream).unwrap());
+    }
+
+    #[test]
+    fn test_corrupt() {
+        assert!(decompress(&[0x78, 0xda, 0xff, 0xff, 0xff, 0xff, 0xff]).is_err());
+        assert!(inflate(&[0x07]).is_err());
+    }
+
+    #[test]
+    fn test_limit() {
+        let stream = compress(&[0; 100_000]);
+        assert_eq!(100_000, decompress_limited(&stream, 100_000).unwrap().len());
+        assert!(decompress_limited(&stream, 99_999).is_err());
+        assert!(decompress_limited(&compress_stored(&[0; 10]), 9).is_err());
+    }
+}
@@ -4,8 +4,12 @@ use crate::WorldController;
 
 pub mod error;
 pub mod api;
+pub mod headless;
+#[cfg(target_os="windows")]
 pub mod mswin;
 pub mod key;
+#[cfg(target_os="linux")]
+pub mod x11;
 
 ///
 /// shared definition of a screen that we render to; not specific to any host operating system.
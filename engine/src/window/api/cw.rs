@@ -1,20 +1,31 @@
 use std::error::Error;
+#[cfg(target_os="windows")]
 use std::panic;
+#[cfg(target_os="windows")]
 use std::panic::AssertUnwindSafe;
+use crate::config::window_config::WindowBackend;
 use crate::config::EngineConfig;
+#[cfg(target_os="windows")]
 use crate::window::mswin::MsWinWindow;
+use crate::window::headless::HeadlessWindow;
+#[cfg(target_os="linux")]
+use crate::window::x11::X11Window;
 use crate::window::Window;
+#[cfg(target_os="windows")]
 use crate::window::error::WindowingError;
 
 ///
 /// Create a window.
 ///
-/// Behavior changes depending on operating system.
+/// Behavior changes depending on operating system, unless a headless window is requested.
 ///
 /// See also: https://doc.rust-lang.org/reference/conditional-compilation.html
 ///
 pub fn create_window(request: &EngineConfig) -> Result<Box<dyn Window>, Box<dyn Error>> {
-    create_window_os(request)
+    match request.window.backend {
+        WindowBackend::Native => create_window_os(request),
+        WindowBackend::Headless { .. } => Ok(Box::new(HeadlessWindow::new(request)?)),
+    }
 }
 
 ///
@@ -29,17 +40,17 @@ fn create_window_os(request: &EngineConfig) -> Result<Box<dyn Window>, Box<dyn E
 }
 
 ///
-/// Create a window for Linux.
+/// Create a window for Linux (X11).
 ///
 #[cfg(target_os="linux")]
 fn create_window_os(request: &EngineConfig) -> Result<Box<dyn Window>, Box<dyn Error>> {
-    todo!("linux windowing not yet implemented")
+    Ok(Box::new(X11Window::new(request)?))
 }
 
 ///
 /// Create a window for MacOS.
 ///
 #[cfg(target_os="macos")]
-fn create_window_os(request: &EngineConfig) -> Result<Box<dyn Window>, Box<dyn Error>> {
+fn create_window_os(_request: &EngineConfig) -> Result<Box<dyn Window>, Box<dyn Error>> {
     todo!("macos windowing not yet implemented")
 }
\ No newline at end of file
@@ -1,5 +1,8 @@
 use crate::geometry::primitive::v2d::Vertex2D;
+#[cfg(target_os="windows")]
 use crate::window::mswin::winapi::set_cursor_pos;
+#[cfg(target_os="linux")]
+use crate::window::x11::active::warp_active_pointer;
 
 pub fn move_cursor(destination: &Vertex2D) {
     move_cursor_os(destination);
@@ -11,11 +14,11 @@ fn move_cursor_os(destination: &Vertex2D) {
 }
 
 #[cfg(target_os="linux")]
-fn move_cursor_os(destination: Vertex2D) {
-    todo!("linux windowing not yet implemented")
+fn move_cursor_os(destination: &Vertex2D) {
+    warp_active_pointer(destination.x as i32, destination.y as i32);
 }
 
 #[cfg(target_os="macos")]
-fn move_cursor_os(destination: Vertex2D) {
+fn move_cursor_os(_destination: &Vertex2D) {
     todo!("macos windowing not yet implemented")
 }
@@ -1,4 +1,7 @@
+#[cfg(target_os="windows")]
 use crate::window::mswin::winapi::show_cursor;
+#[cfg(target_os="linux")]
+use crate::window::x11::active::set_active_cursor_visible;
 
 #[allow(unused)]// todo
 pub fn show_mouse() {
@@ -22,12 +25,12 @@ fn hide_mouse_os() {
 
 #[cfg(target_os="linux")]
 fn show_mouse_os() {
-   todo!("linux")
+    set_active_cursor_visible(true);
 }
 
 #[cfg(target_os="linux")]
 fn hide_mouse_os() {
-    todo!("linux")
+    set_active_cursor_visible(false);
 }
 
 #[cfg(target_os="macos")]
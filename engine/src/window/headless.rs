@@ -0,0 +1,187 @@
+//!
+//! window implementation without any operating system resources.
+//!
+//! runs the world loop for a fixed number of frames, or until a predicate is met; useful for
+//! automated tests, and for rendering on machines without a display.
+//!
+
+use crate::config::window_config::{StopPredicate, WindowBackend, WindowDimensions};
+use crate::config::EngineConfig;
+use crate::input::screen::ScreenState;
+use crate::input::UserInput;
+use crate::support::logger::log;
+use crate::support::logger::log_level::LogLevel;
+use crate::window::error::WindowingError;
+use crate::window::Window;
+use crate::PainsawContext;
+use crate::WorldController;
+use std::sync::{Arc, Mutex};
+
+/* size used when fullscreen is requested, since there's no screen to measure */
+const FULLSCREEN_WIDTH: f32 = 1920.0;
+const FULLSCREEN_HEIGHT: f32 = 1080.0;
+
+pub struct HeadlessWindow {
+    pub input: Arc<Mutex<UserInput>>,
+    pub screen: ScreenState,
+
+    pub frames: Option<u128>,
+    pub until: Option<StopPredicate>,
+}
+
+impl Window for HeadlessWindow {
+    fn begin_event_handling(&mut self, wc: Arc<dyn WorldController>, config: EngineConfig) -> Result<(), Box<dyn std::error::Error>> {
+        log(LogLevel::Info, &|| String::from("begin headless event handling"));
+        let mut context = PainsawContext::new(&self.input, config, self.screen.clone());
+        let mut rendered: u128 = 0;
+
+        /* initialize client renderer, if necessary */
+        wc.initialize_world(&mut context);
+
+        /* no fps cap here; frames are produced as fast as possible */
+        while !self.is_done(&context, rendered) {
+            context.timing.begin_frame();
+
+            wc.update_world(&mut context);
+            wc.display_world_scene(&mut context);
+
+            context.timing.end_frame();
+            rendered += 1;
+        }
+
+        log(LogLevel::Info, &|| format!("headless loop finished; rendered {} frames", rendered));
+
+        Ok(())
+    }
+}
+
+impl HeadlessWindow {
+    ///
+    /// create a new instance.
+    ///
+    pub(crate) fn new(request: &EngineConfig) -> Result<HeadlessWindow, Box<dyn std::error::Error>> {
+        /* determine stop conditions */
+        let (frames, until) = match &request.window.backend {
+            WindowBackend::Headless { frames, until } => (*frames, until.clone()),
+            WindowBackend::Native => return Err(WindowingError(String::from("headless window requested for native backend")).into()),
+        };
+        if frames.is_none() && until.is_none() {
+            return Err(WindowingError(String::from("headless window requires a frame count or predicate")).into());
+        }
+
+        /* determine synthetic screen size */
+        let (width, height) = match request.window.dimensions {
+            WindowDimensions::Fullscreen => (FULLSCREEN_WIDTH, FULLSCREEN_HEIGHT),
+            WindowDimensions::Dimensional { width, height } => (width as f32, height as f32),
+        };
+
+        /* native windows receive a resize on creation; mimic that for the first frame */
+        let input = UserInput::new();
+        input.lock().unwrap().screen_resized = true;
+
+        Ok(HeadlessWindow {
+            input,
+            screen: ScreenState::from_dimensions(width, height),
+            frames,
+            until,
+        })
+    }
+
+    fn is_done(&self, context: &PainsawContext, rendered: u128) -> bool {
+        self.frames.is_some_and(|max| rendered >= max) || self.until.as_ref().is_some_and(|until| until(context))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::renderer_config::{CaptureConfig, RendererConfig};
+    use crate::config::window_config::{StopPredicate, WindowBackend, WindowConfig, WindowDimensions};
+    use crate::config::EngineConfig;
+    use crate::graphics::subsystem::GraphicsSubSystem;
+    use crate::input::keyboard::kc::KeyChange;
+    use crate::input::keyboard::kii::KeyInputInfo;
+    use crate::input::keyboard::kin::KeyInputName;
+    use crate::support::image::ImageFormat;
+    use crate::window::api::cw::create_window;
+    use crate::{PainsawContext, WorldController};
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    struct CountingWorld {
+        initialized: AtomicU32,
+        updated: AtomicU32,
+    }
+
+    impl WorldController for CountingWorld {
+        fn initialize_world_helper(&self, _context: &mut PainsawContext) {
+            self.initialized.fetch_add(1, Ordering::SeqCst);
+        }
+
+        fn update_world_helper(&self, context: &mut PainsawContext) {
+            self.updated.fetch_add(1, Ordering::SeqCst);
+            context.frame_count += 1;
+        }
+    }
+
+    struct PressingWorld {}
+
+    impl WorldController for PressingWorld {
+        fn initialize_world_helper(&self, context: &mut PainsawContext) {
+            context.input.lock().unwrap().record_keyboard_change(KeyInputName::KeyF12, KeyChange::Active { info: KeyInputInfo::unhandled() });
+        }
+
+        fn update_world_helper(&self, _context: &mut PainsawContext) {}
+    }
+
+    fn config(frames: Option<u128>, until: Option<StopPredicate>) -> EngineConfig {
+        EngineConfig {
+            window: WindowConfig {
+                dimensions: WindowDimensions::Dimensional { width: 64, height: 48 },
+                backend: WindowBackend::Headless { frames, until },
+                ..Default::default()
+            },
+            renderer: RendererConfig::new(GraphicsSubSystem::Software),
+            ..Default::default()
+        }
+    }
+
+    fn run(cfg: EngineConfig) -> Arc<CountingWorld> {
+        let world = Arc::new(CountingWorld { initialized: AtomicU32::new(0), updated: AtomicU32::new(0) });
+        let mut window = create_window(&cfg).unwrap();
+        window.begin_event_handling(world.clone(), cfg).unwrap();
+        world
+    }
+
+    #[test]
+    fn test_frame_count() {
+        let world = run(config(Some(3), None));
+        assert_eq!(1, world.initialized.load(Ordering::SeqCst));
+        assert_eq!(3, world.updated.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_predicate() {
+        let world = run(config(Some(100), Some(Arc::new(|context: &PainsawContext| context.frame_count >= 5 && context.screen.current_client_dimensions.width == 64.0))));
+        assert_eq!(5, world.updated.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_capture_key() {
+        let directory = std::env::temp_dir().join(format!("painsaw-capture-{}", std::process::id()));
+        let mut cfg = config(Some(2), None);
+        cfg.renderer.capture = Some(CaptureConfig::new(KeyInputName::KeyF12, &directory, ImageFormat::Targa));
+        let world = Arc::new(PressingWorld {});
+        let mut window = create_window(&cfg).unwrap();
+        window.begin_event_handling(world, cfg).unwrap();
+
+        let files: Vec<_> = std::fs::read_dir(&directory).unwrap().map(|x| x.unwrap().path()).collect();
+        std::fs::remove_dir_all(&directory).unwrap();
+        assert_eq!(1, files.len());
+        assert_eq!(Some("tga"), files[0].extension().and_then(|x| x.to_str()));
+    }
+
+    #[test]
+    fn test_no_stop_condition() {
+        assert!(create_window(&config(None, None)).is_err());
+    }
+}
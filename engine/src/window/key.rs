@@ -1,7 +1,17 @@
+#[cfg(target_os="windows")]
 use windows::Win32::Foundation::{HINSTANCE, HWND};
+#[cfg(target_os="windows")]
 use windows::Win32::Graphics::Gdi::HDC;
+#[cfg(target_os="windows")]
 use windows::Win32::Graphics::OpenGL::HGLRC;
+#[cfg(target_os="windows")]
 use windows::Win32::UI::WindowsAndMessaging::WNDCLASSW;
+#[cfg(target_os="linux")]
+use std::ffi::c_int;
+#[cfg(target_os="linux")]
+use std::rc::Rc;
+#[cfg(target_os="linux")]
+use x11_dl::xlib;
 
 ///
 /// uniquely identify a window, regardless what operating system.
@@ -20,4 +30,14 @@ pub struct WindowKey {
     pub hdc: HDC,
     #[cfg(target_os="windows")]
     pub hrc: HGLRC,
+
+    /* Linux (X11) */
+    #[cfg(target_os="linux")]
+    pub xlib: Rc<xlib::Xlib>,
+    #[cfg(target_os="linux")]
+    pub display: *mut xlib::Display,
+    #[cfg(target_os="linux")]
+    pub screen: c_int,
+    #[cfg(target_os="linux")]
+    pub window: xlib::Window,
 }
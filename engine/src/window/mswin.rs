@@ -59,8 +59,13 @@ impl Window for MsWinWindow {
                 /* timing */
                 context.timing.begin_frame();
 
+                /* refresh screen state, if the window was resized */
+                if self.input.lock().map(|uin| uin.screen_resized).unwrap_or(false) {
+                    context.screen.update(&self.key);
+                }
+
                 /* update world info; graphics scene */
-                wc.update_world(&mut context, &self.key);
+                wc.update_world(&mut context);
                 wc.display_world_scene(&mut context);
 
                 /* swap buffers after it's all done */
@@ -11,7 +11,7 @@ use crate::window::mswin::util::is_mouse_over_window;
 use crate::window::mswin::winapi::{default_window_proc, get_cursor_pos, get_raw_input_data, post_quit_message, screen_to_client};
 use std::sync::{Arc, Mutex};
 use windows::Win32::Foundation::{HWND, LPARAM, LRESULT, WPARAM};
-use windows::Win32::UI::Input::KeyboardAndMouse::{VIRTUAL_KEY, VK_A, VK_D, VK_ESCAPE, VK_G, VK_M, VK_S, VK_W};
+use windows::Win32::UI::Input::KeyboardAndMouse::{VIRTUAL_KEY, VK_A, VK_D, VK_ESCAPE, VK_F12, VK_G, VK_M, VK_S, VK_W};
 use windows::Win32::UI::Input::{HRAWINPUT, RAWINPUT, RAWINPUTHEADER, RID_INPUT, RIM_TYPEMOUSE};
 use windows::Win32::UI::WindowsAndMessaging::{WM_CLOSE, WM_CREATE, WM_DESTROY, WM_INPUT, WM_KEYDOWN, WM_KEYUP, WM_KILLFOCUS, WM_LBUTTONDOWN, WM_LBUTTONUP, WM_QUIT, WM_RBUTTONDOWN, WM_RBUTTONUP, WM_SETFOCUS, WM_SIZE};
 use crate::input::mouse::md::MouseDelta;
@@ -103,6 +103,7 @@ fn handle_message_if_applicable(input: &Arc<Mutex<UserInput>>, hwnd: HWND, messa
                 VK_M => { input.lock().expect("todo: m: down").record_keyboard_change(KeyInputName::KeyM, KeyChange::Active { info: KeyInputInfo::unhandled() }); HANDLED }
                 VK_S => { input.lock().expect("todo: s: down").record_keyboard_change(KeyInputName::KeyS, KeyChange::Active { info: KeyInputInfo::unhandled() }); HANDLED }
                 VK_W => { input.lock().expect("todo: w: down").record_keyboard_change(KeyInputName::KeyW, KeyChange::Active { info: KeyInputInfo::unhandled() }); HANDLED }
+                VK_F12 => { input.lock().expect("todo: f12: down").record_keyboard_change(KeyInputName::KeyF12, KeyChange::Active { info: KeyInputInfo::unhandled() }); HANDLED }
                 // todo: add remaining keys down
                 _ => NOT_HANDLED
             }
@@ -116,6 +117,7 @@ fn handle_message_if_applicable(input: &Arc<Mutex<UserInput>>, hwnd: HWND, messa
                 VK_M => { input.lock().expect("todo: m: up").record_keyboard_change(KeyInputName::KeyM, KeyChange::Inactive { info: KeyInputInfo::unhandled() }); HANDLED }
                 VK_S => { input.lock().expect("todo: s: up").record_keyboard_change(KeyInputName::KeyS, KeyChange::Inactive { info: KeyInputInfo::unhandled() }); HANDLED }
                 VK_W => { input.lock().expect("todo: w: up").record_keyboard_change(KeyInputName::KeyW, KeyChange::Inactive { info: KeyInputInfo::unhandled() }); HANDLED }
+                VK_F12 => { input.lock().expect("todo: f12: up").record_keyboard_change(KeyInputName::KeyF12, KeyChange::Inactive { info: KeyInputInfo::unhandled() }); HANDLED }
                 // todo: add remaining keys up
                 _ => NOT_HANDLED
             }
@@ -0,0 +1,240 @@
+use crate::config::window_config::WindowDimensions;
+use crate::config::EngineConfig;
+use crate::input::screen::ScreenState;
+use crate::input::UserInput;
+use crate::support::logger::log;
+use crate::support::logger::log_level::LogLevel;
+use crate::window::error::WindowingError;
+use crate::window::key::WindowKey;
+use crate::window::x11::active::{activate, deactivate, ActiveWindow};
+use crate::window::x11::events::handle_event;
+use crate::window::x11::xapi::{change_property_atoms, close_display, create_simple_window, default_depth, default_gc, default_screen, destroy_window, display_size, flush, intern_atom, map_window, open_display, poll_event, put_image_bgrx, root_window, select_input, set_class_hint, set_detectable_auto_repeat, set_wm_protocols, store_name};
+use crate::window::Window;
+use crate::PainsawContext;
+use crate::WorldController;
+use std::ffi::CString;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use x11_dl::xlib;
+use x11_dl::xlib::Xlib;
+
+pub mod active;
+pub mod events;
+pub mod xapi;
+
+///
+/// x11 window; frames are produced by the software rasterizer and copied into the window.
+///
+pub struct X11Window {
+    pub input: Arc<Mutex<UserInput>>,
+    pub quit: bool,
+
+    pub key: WindowKey,
+
+    gc: xlib::GC,
+    wm_delete: xlib::Atom,
+}
+
+impl Window for X11Window {
+    fn begin_event_handling(&mut self, wc: Arc<dyn WorldController>, config: EngineConfig) -> Result<(), Box<dyn std::error::Error>> {
+        log(LogLevel::Info, &|| String::from("begin event handling"));
+        let screen = ScreenState::from(&self.key);
+        let mut context = PainsawContext::new(&self.input, config, screen);
+
+        /* initialize client renderer, if necessary */
+        wc.initialize_world(&mut context);
+
+        while !self.quit {
+            if let Some(event) = poll_event(&self.key.xlib, self.key.display) {
+                self.quit = handle_event(&self.input, &self.key, self.wm_delete, event);
+            } else if context.timing.is_ok_to_render() {
+                /* timing */
+                context.timing.begin_frame();
+
+                /* refresh screen state, if the window was resized */
+                if self.input.lock().map(|uin| uin.screen_resized).unwrap_or(false) {
+                    context.screen.update(&self.key);
+                }
+
+                /* update world info; graphics scene */
+                wc.update_world(&mut context);
+                wc.display_world_scene(&mut context);
+
+                /* copy the frame into the window after it's all done */
+                self.present(&context);
+
+                /* timing */
+                context.timing.end_frame();
+            }
+        }
+
+        log(LogLevel::Info, &|| format!("after while(!quit); rendered {} frames", context.frame_count));
+
+        Ok(())
+    }
+}
+
+impl X11Window {
+    ///
+    /// create a new instance.
+    ///
+    pub(crate) fn new(request: &EngineConfig) -> Result<X11Window, Box<dyn std::error::Error>> {
+        /* make some variables */
+        let title = CString::new(request.window.title.clone().unwrap_or(String::from("WindowConfig: set title")))?;
+        let class = CString::new(request.window.window_id.clone().unwrap_or(String::from("WindowConfig: set window_id")))?;
+
+        /* connect to the x server */
+        let xlib = Rc::new(Xlib::open()?);
+        let display = open_display(&xlib).ok_or_else(|| WindowingError(String::from("XOpenDisplay failed; is DISPLAY set?")))?;
+        let screen = default_screen(&xlib, display);
+
+        /* frames are copied as 32-bit pixels; only truecolor displays are supported */
+        let depth = default_depth(&xlib, display, screen);
+        if depth != 24 && depth != 32 {
+            close_display(&xlib, display);
+            return Err(WindowingError(format!("unsupported display depth: {}", depth)).into());
+        }
+
+        /* determine size based on configuration */
+        let (width, height) = match request.window.dimensions {
+            WindowDimensions::Fullscreen => display_size(&xlib, display, screen),
+            WindowDimensions::Dimensional { width, height } => (width, height),
+        };
+
+        /* create the window */
+        let window = create_simple_window(&xlib, display, screen, width as u32, height as u32);
+        store_name(&xlib, display, window, &title);
+        set_class_hint(&xlib, display, window, &class);
+        select_input(&xlib, display, window, xlib::KeyPressMask | xlib::KeyReleaseMask | xlib::ButtonPressMask | xlib::ButtonReleaseMask
+            | xlib::PointerMotionMask | xlib::StructureNotifyMask | xlib::FocusChangeMask);
+
+        /* ask the window manager to send a message instead of killing the connection on close */
+        let mut wm_delete = [intern_atom(&xlib, display, c"WM_DELETE_WINDOW")];
+        set_wm_protocols(&xlib, display, window, &mut wm_delete);
+
+        /* ask the window manager for fullscreen, if configured */
+        if let WindowDimensions::Fullscreen = request.window.dimensions {
+            let state = intern_atom(&xlib, display, c"_NET_WM_STATE");
+            let fullscreen = intern_atom(&xlib, display, c"_NET_WM_STATE_FULLSCREEN");
+            change_property_atoms(&xlib, display, window, state, &[fullscreen]);
+        }
+
+        /* held keys should look like win32: repeated presses, without releases */
+        if !set_detectable_auto_repeat(&xlib, display) {
+            log(LogLevel::Warning, &|| String::from("detectable auto-repeat not supported; held keys will repeat"));
+        }
+
+        /* show the window */
+        map_window(&xlib, display, window);
+        flush(&xlib, display);
+
+        /* register for the cursor api */
+        activate(ActiveWindow {
+            xlib: xlib.clone(),
+            display,
+            window,
+            root: root_window(&xlib, display, screen),
+            blank_cursor: None,
+            pointer: None,
+        });
+
+        /* done; returning handles to window */
+        Ok(X11Window {
+            input: UserInput::new(),
+            quit: false,
+
+            gc: default_gc(&xlib, display, screen),
+            wm_delete: wm_delete[0],
+
+            key: WindowKey {
+                xlib,
+                display,
+                screen,
+                window,
+            },
+        })
+    }
+
+    ///
+    /// copy the rendered frame into the window.
+    ///
+    fn present(&self, context: &PainsawContext) {
+        let Some(frame) = context.capture_frame() else { return };
+        if frame.width == 0 || frame.height == 0 {
+            return;
+        }
+
+        /* rgba to bgrx; x ignores the last byte */
+        let mut pixels: Vec<u8> = frame.data
+            .chunks_exact(4)
+            .flat_map(|p| [p[2], p[1], p[0], p[3]])
+            .collect();
+
+        if put_image_bgrx(&self.key, self.gc, &mut pixels, frame.width, frame.height) {
+            flush(&self.key.xlib, self.key.display);
+        } else {
+            log(LogLevel::Warning, &|| String::from("XCreateImage failed; frame not presented"));
+        }
+    }
+}
+
+impl Drop for X11Window {
+    fn drop(&mut self) {
+        deactivate();
+        destroy_window(&self.key.xlib, self.key.display, self.key.window);
+        close_display(&self.key.xlib, self.key.display);
+    }
+}
+
+///
+/// these need an x server (for example, `xvfb-run cargo test`); they're skipped when DISPLAY isn't set.
+///
+#[cfg(test)]
+mod tests {
+    use crate::config::window_config::{WindowConfig, WindowDimensions};
+    use crate::config::EngineConfig;
+    use crate::input::screen::ScreenState;
+    use crate::window::x11::active::{pointer_delta, warp_active_pointer};
+    use crate::window::x11::X11Window;
+
+    fn has_display() -> bool {
+        std::env::var_os("DISPLAY").is_some()
+    }
+
+    fn config() -> EngineConfig {
+        EngineConfig {
+            window: WindowConfig {
+                dimensions: WindowDimensions::Dimensional { width: 64, height: 48 },
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_screen_state() {
+        if !has_display() {
+            return;
+        }
+
+        let window = X11Window::new(&config()).unwrap();
+        let screen = ScreenState::from(&window.key);
+        assert_eq!(64.0, screen.current_client_dimensions.width);
+        assert_eq!(48.0, screen.current_client_dimensions.height);
+        assert_eq!(32.0, screen.client_center.x);
+    }
+
+    #[test]
+    fn test_pointer_delta() {
+        if !has_display() {
+            return;
+        }
+
+        let _window = X11Window::new(&config()).unwrap();
+        warp_active_pointer(10, 10);
+
+        assert!(pointer_delta(10, 10).is_none());
+        let delta = pointer_delta(13, 8).unwrap();
+        assert_eq!((3.0, -2.0), (delta.dx, delta.dy));
+    }
+}
@@ -0,0 +1,83 @@
+//!
+//! the x11 window that currently owns the pointer.
+//!
+//! cursor functions in the public window api (move, show, hide) have no window handle,
+//! so the window registers itself here when created.  xlib isn't thread safe; this is
+//! stored per thread, matching the thread that runs the event loop.
+//!
+
+use crate::input::mouse::md::MouseDelta;
+use crate::window::x11::xapi::{create_blank_cursor, define_cursor, flush, free_cursor, undefine_cursor, warp_pointer};
+use std::cell::RefCell;
+use std::rc::Rc;
+use x11_dl::xlib;
+use x11_dl::xlib::{Display, Xlib};
+
+pub(crate) struct ActiveWindow {
+    pub xlib: Rc<Xlib>,
+    pub display: *mut Display,
+    pub window: xlib::Window,
+    pub root: xlib::Window,
+
+    /* created on first use */
+    pub blank_cursor: Option<xlib::Cursor>,
+
+    /* last known pointer position, in root coordinates */
+    pub pointer: Option<(i32, i32)>,
+}
+
+thread_local! {
+    static ACTIVE: RefCell<Option<ActiveWindow>> = const { RefCell::new(None) };
+}
+
+pub(crate) fn activate(window: ActiveWindow) {
+    ACTIVE.with(|active| *active.borrow_mut() = Some(window));
+}
+
+pub(crate) fn deactivate() {
+    ACTIVE.with(|active| {
+        if let Some(window) = active.borrow_mut().take() && let Some(cursor) = window.blank_cursor {
+            free_cursor(&window.xlib, window.display, cursor);
+        }
+    });
+}
+
+fn with_active<R>(f: impl FnOnce(&mut ActiveWindow) -> R) -> Option<R> {
+    ACTIVE.with(|active| active.borrow_mut().as_mut().map(f))
+}
+
+///
+/// move the pointer to the given root (screen) coordinates.
+///
+/// the pointer position is updated immediately, so that the motion event caused by the
+/// warp doesn't get reported as a mouse delta.
+///
+pub(crate) fn warp_active_pointer(x: i32, y: i32) {
+    with_active(|active| {
+        warp_pointer(&active.xlib, active.display, active.root, x, y);
+        flush(&active.xlib, active.display);
+        active.pointer = Some((x, y));
+    });
+}
+
+pub(crate) fn set_active_cursor_visible(visible: bool) {
+    with_active(|active| {
+        if visible {
+            undefine_cursor(&active.xlib, active.display, active.window);
+        } else {
+            let cursor = *active.blank_cursor.get_or_insert_with(|| create_blank_cursor(&active.xlib, active.display, active.window));
+            define_cursor(&active.xlib, active.display, active.window, cursor);
+        }
+        flush(&active.xlib, active.display);
+    });
+}
+
+///
+/// record a new pointer position (root coordinates), returning the movement since the last one.
+///
+pub(crate) fn pointer_delta(x: i32, y: i32) -> Option<MouseDelta> {
+    with_active(|active| active.pointer.replace((x, y)))
+        .flatten()
+        .filter(|(px, py)| *px != x || *py != y)
+        .map(|(px, py)| MouseDelta::new((x - px) as f32, (y - py) as f32))
+}
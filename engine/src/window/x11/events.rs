@@ -0,0 +1,94 @@
+use crate::input::keyboard::kc::KeyChange;
+use crate::input::keyboard::kii::KeyInputInfo;
+use crate::input::keyboard::kin::KeyInputName;
+use crate::input::mouse::mfs::MouseFunctionStatus;
+use crate::input::mouse::min::MouseInputName;
+use crate::input::UserInput;
+use crate::support::logger::log;
+use crate::support::logger::log_level::LogLevel;
+use crate::window::key::WindowKey;
+use crate::window::x11::active::pointer_delta;
+use crate::window::x11::xapi::lookup_keysym;
+use std::sync::{Arc, Mutex};
+use x11_dl::keysym::{XK_Escape, XK_F12, XK_a, XK_d, XK_g, XK_m, XK_s, XK_w};
+use x11_dl::xlib;
+
+///
+/// handle a single x11 event; the x11 equivalent of the win32 window procedure.
+///
+/// returns true when the window has been asked to close.
+///
+pub(crate) fn handle_event(input: &Arc<Mutex<UserInput>>, key: &WindowKey, wm_delete: xlib::Atom, event: xlib::XEvent) -> bool {
+    match event.get_type() {
+        xlib::KeyPress => {
+            if let Some(name) = key_name(key, event) {
+                input.lock().expect("todo: key: down").record_keyboard_change(name, KeyChange::Active { info: KeyInputInfo::unhandled() });
+            }
+        }
+        xlib::KeyRelease => {
+            if let Some(name) = key_name(key, event) {
+                input.lock().expect("todo: key: up").record_keyboard_change(name, KeyChange::Inactive { info: KeyInputInfo::unhandled() });
+            }
+        }
+        xlib::MotionNotify => {
+            let motion = xlib::XMotionEvent::from(event);
+            if let Ok(mut uin) = input.try_lock() {
+                /* add a delta */
+                if let Some(md) = pointer_delta(motion.x_root, motion.y_root) {
+                    uin.mouse_deltas.push(md);
+                }
+
+                /* send position, relative to the window */
+                uin.record_mouse_change(MouseInputName::MouseMove, motion.x, motion.y, &MouseFunctionStatus::Active);
+            }
+        }
+        xlib::ButtonPress | xlib::ButtonRelease => {
+            let button = xlib::XButtonEvent::from(event);
+            let status = if event.get_type() == xlib::ButtonPress { MouseFunctionStatus::Active } else { MouseFunctionStatus::Inactive };
+            let name = match button.button {
+                xlib::Button1 => Some(MouseInputName::MouseLeftButton),
+                xlib::Button3 => Some(MouseInputName::MouseRightButton),
+                _ => None,// todo: add mouse scroll (buttons 4, 5)
+            };
+            if let Some(name) = name {
+                input.lock().expect("todo: button").record_mouse_change(name, button.x, button.y, &status);
+            }
+        }
+        xlib::ConfigureNotify => {
+            input.lock().expect("todo: configure").screen_resized = true;
+        }
+        xlib::FocusIn => {
+            input.lock().expect("todo: focus-in").focus.update(KeyChange::Active { info: KeyInputInfo::unhandled() });
+        }
+        xlib::FocusOut => {
+            input.lock().expect("todo: focus-out").focus.update(KeyChange::Inactive { info: KeyInputInfo::unhandled() });
+        }
+        xlib::ClientMessage => {
+            let message = xlib::XClientMessageEvent::from(event);
+            if message.data.get_long(0) as xlib::Atom == wm_delete {
+                log(LogLevel::Debug, &|| String::from("WM_DELETE_WINDOW"));
+                return true;
+            }
+        }
+        _ => {}
+    }
+
+    false
+}
+
+#[allow(non_upper_case_globals)]// keysym names come from x11
+fn key_name(key: &WindowKey, event: xlib::XEvent) -> Option<KeyInputName> {
+    let mut key_event = xlib::XKeyEvent::from(event);
+    match lookup_keysym(&key.xlib, &mut key_event) as u32 {
+        XK_Escape => Some(KeyInputName::KeyEscape),
+        XK_a => Some(KeyInputName::KeyA),
+        XK_d => Some(KeyInputName::KeyD),
+        XK_g => Some(KeyInputName::KeyG),
+        XK_m => Some(KeyInputName::KeyM),
+        XK_s => Some(KeyInputName::KeyS),
+        XK_w => Some(KeyInputName::KeyW),
+        XK_F12 => Some(KeyInputName::KeyF12),
+        // todo: add remaining keys
+        _ => None,
+    }
+}
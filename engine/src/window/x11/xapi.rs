@@ -0,0 +1,246 @@
+use crate::window::key::WindowKey;
+use std::ffi::{c_char, c_int, c_long, c_uint, CStr};
+use std::ptr::null;
+use x11_dl::xlib;
+use x11_dl::xlib::{Display, Xlib};
+
+///
+/// XOpenDisplay(); uses the DISPLAY environment variable.
+///
+pub(crate) fn open_display(xlib: &Xlib) -> Option<*mut Display> {
+    let display = unsafe { (xlib.XOpenDisplay)(null()) };
+    if display.is_null() { None } else { Some(display) }
+}
+
+///
+/// XCloseDisplay()
+///
+pub(crate) fn close_display(xlib: &Xlib, display: *mut Display) {
+    unsafe { (xlib.XCloseDisplay)(display); }
+}
+
+///
+/// XDefaultScreen()
+///
+pub(crate) fn default_screen(xlib: &Xlib, display: *mut Display) -> c_int {
+    unsafe { (xlib.XDefaultScreen)(display) }
+}
+
+///
+/// XRootWindow()
+///
+pub(crate) fn root_window(xlib: &Xlib, display: *mut Display, screen: c_int) -> xlib::Window {
+    unsafe { (xlib.XRootWindow)(display, screen) }
+}
+
+///
+/// XDefaultDepth()
+///
+pub(crate) fn default_depth(xlib: &Xlib, display: *mut Display, screen: c_int) -> c_int {
+    unsafe { (xlib.XDefaultDepth)(display, screen) }
+}
+
+///
+/// XDefaultGC()
+///
+pub(crate) fn default_gc(xlib: &Xlib, display: *mut Display, screen: c_int) -> xlib::GC {
+    unsafe { (xlib.XDefaultGC)(display, screen) }
+}
+
+///
+/// XDisplayWidth() and XDisplayHeight()
+///
+pub(crate) fn display_size(xlib: &Xlib, display: *mut Display, screen: c_int) -> (c_int, c_int) {
+    unsafe { ((xlib.XDisplayWidth)(display, screen), (xlib.XDisplayHeight)(display, screen)) }
+}
+
+///
+/// XCreateSimpleWindow(), with a black border and background.
+///
+pub(crate) fn create_simple_window(xlib: &Xlib, display: *mut Display, screen: c_int, width: c_uint, height: c_uint) -> xlib::Window {
+    unsafe {
+        let black = (xlib.XBlackPixel)(display, screen);
+        (xlib.XCreateSimpleWindow)(display, root_window(xlib, display, screen), 0, 0, width, height, 0, black, black)
+    }
+}
+
+///
+/// XDestroyWindow()
+///
+pub(crate) fn destroy_window(xlib: &Xlib, display: *mut Display, window: xlib::Window) {
+    unsafe { (xlib.XDestroyWindow)(display, window); }
+}
+
+///
+/// XStoreName()
+///
+pub(crate) fn store_name(xlib: &Xlib, display: *mut Display, window: xlib::Window, name: &CStr) {
+    unsafe { (xlib.XStoreName)(display, window, name.as_ptr()); }
+}
+
+///
+/// XSetClassHint(); name and class are both set to the given value.
+///
+pub(crate) fn set_class_hint(xlib: &Xlib, display: *mut Display, window: xlib::Window, class: &CStr) {
+    let mut hint = xlib::XClassHint {
+        res_name: class.as_ptr() as *mut c_char,
+        res_class: class.as_ptr() as *mut c_char,
+    };
+    unsafe { (xlib.XSetClassHint)(display, window, &mut hint); }
+}
+
+///
+/// XSelectInput()
+///
+pub(crate) fn select_input(xlib: &Xlib, display: *mut Display, window: xlib::Window, mask: c_long) {
+    unsafe { (xlib.XSelectInput)(display, window, mask); }
+}
+
+///
+/// XInternAtom()
+///
+pub(crate) fn intern_atom(xlib: &Xlib, display: *mut Display, name: &CStr) -> xlib::Atom {
+    unsafe { (xlib.XInternAtom)(display, name.as_ptr(), xlib::False) }
+}
+
+///
+/// XSetWMProtocols()
+///
+pub(crate) fn set_wm_protocols(xlib: &Xlib, display: *mut Display, window: xlib::Window, protocols: &mut [xlib::Atom]) {
+    unsafe { (xlib.XSetWMProtocols)(display, window, protocols.as_mut_ptr(), protocols.len() as c_int); }
+}
+
+///
+/// XChangeProperty(), replacing the property with a list of atoms.
+///
+pub(crate) fn change_property_atoms(xlib: &Xlib, display: *mut Display, window: xlib::Window, property: xlib::Atom, atoms: &[xlib::Atom]) {
+    unsafe { (xlib.XChangeProperty)(display, window, property, xlib::XA_ATOM, 32, xlib::PropModeReplace, atoms.as_ptr() as *const u8, atoms.len() as c_int); }
+}
+
+///
+/// XkbSetDetectableAutoRepeat(); when supported, held keys no longer produce release/press pairs.
+///
+pub(crate) fn set_detectable_auto_repeat(xlib: &Xlib, display: *mut Display) -> bool {
+    unsafe { (xlib.XkbSetDetectableAutoRepeat)(display, xlib::True, std::ptr::null_mut()) != 0 }
+}
+
+///
+/// XMapWindow()
+///
+pub(crate) fn map_window(xlib: &Xlib, display: *mut Display, window: xlib::Window) {
+    unsafe { (xlib.XMapWindow)(display, window); }
+}
+
+///
+/// XFlush()
+///
+pub(crate) fn flush(xlib: &Xlib, display: *mut Display) {
+    unsafe { (xlib.XFlush)(display); }
+}
+
+///
+/// XPending() and XNextEvent(); returns the next event, if one is queued.
+///
+pub(crate) fn poll_event(xlib: &Xlib, display: *mut Display) -> Option<xlib::XEvent> {
+    unsafe {
+        if (xlib.XPending)(display) > 0 {
+            let mut event: xlib::XEvent = std::mem::zeroed();
+            (xlib.XNextEvent)(display, &mut event);
+            Some(event)
+        } else {
+            None
+        }
+    }
+}
+
+///
+/// XLookupKeysym(), for the unshifted keysym.
+///
+pub(crate) fn lookup_keysym(xlib: &Xlib, event: &mut xlib::XKeyEvent) -> xlib::KeySym {
+    unsafe { (xlib.XLookupKeysym)(event, 0) }
+}
+
+///
+/// XGetWindowAttributes()
+///
+pub(crate) fn get_window_attributes(xlib: &Xlib, display: *mut Display, window: xlib::Window) -> xlib::XWindowAttributes {
+    unsafe {
+        let mut attributes: xlib::XWindowAttributes = std::mem::zeroed();
+        (xlib.XGetWindowAttributes)(display, window, &mut attributes);
+        attributes
+    }
+}
+
+///
+/// XTranslateCoordinates(); converts a point from one window's coordinates into another's.
+///
+pub(crate) fn translate_coordinates(xlib: &Xlib, display: *mut Display, from: xlib::Window, to: xlib::Window, x: c_int, y: c_int) -> (c_int, c_int) {
+    let (mut dx, mut dy, mut child) = (0, 0, 0);
+    unsafe { (xlib.XTranslateCoordinates)(display, from, to, x, y, &mut dx, &mut dy, &mut child); }
+    (dx, dy)
+}
+
+///
+/// XWarpPointer(), to an absolute position on the given window.
+///
+pub(crate) fn warp_pointer(xlib: &Xlib, display: *mut Display, window: xlib::Window, x: c_int, y: c_int) {
+    unsafe { (xlib.XWarpPointer)(display, 0, window, 0, 0, 0, 0, x, y); }
+}
+
+///
+/// XDefineCursor()
+///
+pub(crate) fn define_cursor(xlib: &Xlib, display: *mut Display, window: xlib::Window, cursor: xlib::Cursor) {
+    unsafe { (xlib.XDefineCursor)(display, window, cursor); }
+}
+
+///
+/// XUndefineCursor()
+///
+pub(crate) fn undefine_cursor(xlib: &Xlib, display: *mut Display, window: xlib::Window) {
+    unsafe { (xlib.XUndefineCursor)(display, window); }
+}
+
+///
+/// create a fully transparent cursor, from an empty 8x8 bitmap.
+///
+pub(crate) fn create_blank_cursor(xlib: &Xlib, display: *mut Display, window: xlib::Window) -> xlib::Cursor {
+    let bits = [0 as c_char; 8];
+    unsafe {
+        let mut black: xlib::XColor = std::mem::zeroed();
+        let pixmap = (xlib.XCreateBitmapFromData)(display, window, bits.as_ptr(), 8, 8);
+        let cursor = (xlib.XCreatePixmapCursor)(display, pixmap, pixmap, &mut black, &mut black, 0, 0);
+        (xlib.XFreePixmap)(display, pixmap);
+        cursor
+    }
+}
+
+///
+/// XFreeCursor()
+///
+pub(crate) fn free_cursor(xlib: &Xlib, display: *mut Display, cursor: xlib::Cursor) {
+    unsafe { (xlib.XFreeCursor)(display, cursor); }
+}
+
+///
+/// XCreateImage() + XPutImage(); draws 32-bit BGRX pixels into the window.
+///
+/// the pixel buffer remains owned by the caller; it's detached before XDestroyImage() so
+/// that xlib doesn't try to free it.
+///
+pub(crate) fn put_image_bgrx(key: &WindowKey, gc: xlib::GC, pixels: &mut [u8], width: c_uint, height: c_uint) -> bool {
+    let (xlib, display, screen, window) = (&key.xlib, key.display, key.screen, key.window);
+    unsafe {
+        let visual = (xlib.XDefaultVisual)(display, screen);
+        let depth = (xlib.XDefaultDepth)(display, screen) as c_uint;
+        let image = (xlib.XCreateImage)(display, visual, depth, xlib::ZPixmap, 0, pixels.as_mut_ptr() as *mut c_char, width, height, 32, 0);
+        if image.is_null() {
+            return false;
+        }
+
+        (xlib.XPutImage)(display, window, gc, image, 0, 0, 0, 0, width, height);
+        (*image).data = std::ptr::null_mut();
+        (xlib.XDestroyImage)(image);
+        true
+    }
+}